@@ -0,0 +1,341 @@
+use anyhow::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::analysis::model::{ExportModel, StepModel};
+use crate::analysis::sarif::{SarifFinding, SarifRule};
+use crate::symbols::{id_attribute, SymbolKind, SymbolTable};
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::ReportFormat;
+
+/// Kinds of references checked against the catalogs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    Script,
+    Layout,
+    TableOccurrence,
+    Field,
+    ValueList,
+}
+
+impl ReferenceKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Script => "script",
+            Self::Layout => "layout",
+            Self::TableOccurrence => "table occurrence",
+            Self::Field => "field",
+            Self::ValueList => "value list",
+        }
+    }
+}
+
+/// A step referring to an element that no longer exists
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BrokenReference {
+    pub db: String,
+    pub script_id: u32,
+    pub script: String,
+    /// Step number as shown in FileMaker's script workspace, starting at 1
+    pub step: usize,
+    pub step_name: String,
+    pub kind: ReferenceKind,
+    pub id: u32,
+    /// Name the reference had when the step was last saved, if the export kept it
+    pub name: String,
+}
+
+/// References of all steps of all scripts to scripts, layouts, table occurrences, fields and
+/// value lists missing in the catalogs of their export.
+/// References to elements of other files and empty references with id 0 are not checked.
+pub fn broken_references(model: &ExportModel) -> Vec<BrokenReference> {
+    let mut broken = Vec::new();
+    for script in &model.scripts {
+        for step in &script.steps {
+            for (kind, id, name) in missing_references(step, &model.symbols) {
+                broken.push(BrokenReference {
+                    db: model.db_name.clone(),
+                    script_id: script.id,
+                    script: script.name.clone(),
+                    step: step.number,
+                    step_name: step.name.clone(),
+                    kind,
+                    id,
+                    name,
+                });
+            }
+        }
+    }
+    broken
+}
+
+/// A reference of a step to a script, layout, table occurrence, field or value list
+#[derive(Debug, PartialEq)]
+pub struct StepReference {
+    pub kind: ReferenceKind,
+    pub id: u32,
+    /// Name the reference had when the step was last saved, if the export kept it
+    pub name: String,
+    /// Table occurrence of a field reference
+    pub table_occurrence_id: Option<u32>,
+}
+
+/// All references of a step, in order. A field comes right after the table occurrence it is
+/// read through. Empty references with id 0 are left out.
+pub fn step_references(step: &StepModel) -> Vec<StepReference> {
+    let mut references = Vec::new();
+    // The field whose table occurrence is read next
+    let mut field: Option<(u32, String)> = None;
+
+    let mut reader = Reader::from_str(&step.xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(_) | Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => {
+                let id = id_attribute(&e).filter(|id| *id != 0);
+                let name = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                let kind = match e.name().as_ref() {
+                    b"FieldReference" => {
+                        field = id.map(|id| (id, name.clone()));
+                        None
+                    }
+                    b"TableOccurrenceReference" => Some(ReferenceKind::TableOccurrence),
+                    b"ScriptReference" => Some(ReferenceKind::Script),
+                    b"LayoutReference" => Some(ReferenceKind::Layout),
+                    b"ValueListReference" => Some(ReferenceKind::ValueList),
+                    _ => None,
+                };
+                let (Some(kind), Some(id)) = (kind, id) else {
+                    buf.clear();
+                    continue;
+                };
+                references.push(StepReference {
+                    kind,
+                    id,
+                    name,
+                    table_occurrence_id: None,
+                });
+                if kind == ReferenceKind::TableOccurrence {
+                    if let Some((field_id, field_name)) = field.take() {
+                        references.push(StepReference {
+                            kind: ReferenceKind::Field,
+                            id: field_id,
+                            name: field_name,
+                            table_occurrence_id: Some(id),
+                        });
+                    }
+                }
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"FieldReference" => field = None,
+            _ => {}
+        }
+        buf.clear();
+    }
+    references
+}
+
+fn missing_references(
+    step: &StepModel,
+    symbols: &SymbolTable,
+) -> Vec<(ReferenceKind, u32, String)> {
+    let references = step_references(step);
+    // Script and layout references of a step using another file refer to that file's catalogs
+    let uses_other_file = step.xml.contains("<DataSourceReference")
+        || references.iter().any(|reference| {
+            reference.kind == ReferenceKind::TableOccurrence
+                && symbols.is_external_occurrence(reference.id)
+        });
+
+    references
+        .into_iter()
+        .filter(|reference| match reference.kind {
+            ReferenceKind::Script | ReferenceKind::Layout if uses_other_file => false,
+            ReferenceKind::Script => !symbols.contains(SymbolKind::Script, reference.id),
+            ReferenceKind::Layout => !symbols.contains(SymbolKind::Layout, reference.id),
+            ReferenceKind::ValueList => !symbols.contains(SymbolKind::ValueList, reference.id),
+            ReferenceKind::TableOccurrence => {
+                !symbols.contains(SymbolKind::TableOccurrence, reference.id)
+            }
+            ReferenceKind::Field => reference.table_occurrence_id.is_some_and(|occurrence| {
+                symbols.contains(SymbolKind::TableOccurrence, occurrence)
+                    && symbols.contains_field(occurrence, reference.id) == Some(false)
+            }),
+        })
+        .map(|reference| (reference.kind, reference.id, reference.name))
+        .collect()
+}
+
+impl BrokenReference {
+    /// E.g. `missing layout "Invoice" (ID 3)`
+    fn missing(&self) -> String {
+        let name = match self.name.is_empty() {
+            true => String::new(),
+            false => format!("\"{}\" ", self.name),
+        };
+        format!("missing {} {name}(ID {})", self.kind.label(), self.id)
+    }
+}
+
+pub const SARIF_RULES: [SarifRule; 1] = [SarifRule {
+    id: "broken-reference",
+    description: "Script step referring to an element missing in the catalogs",
+}];
+
+pub fn sarif_findings(broken: &[BrokenReference]) -> Vec<SarifFinding> {
+    broken
+        .iter()
+        .map(|reference| SarifFinding {
+            rule: "broken-reference",
+            level: "error",
+            message: format!(
+                "{} refers to a {}",
+                reference.step_name,
+                reference.missing()
+            ),
+            db: reference.db.clone(),
+            script_id: reference.script_id,
+            script: reference.script.clone(),
+            step: Some(reference.step),
+        })
+        .collect()
+}
+
+/// Render the broken references grouped by script
+pub fn render(broken: &[BrokenReference], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            let mut previous_script = None;
+            for reference in broken {
+                let script = (&reference.db, reference.script_id);
+                if previous_script != Some(script) {
+                    text.push_str(&format!(
+                        "{}: {} (ID {})\n",
+                        reference.db, reference.script, reference.script_id
+                    ));
+                    previous_script = Some(script);
+                }
+                text.push_str(&format!(
+                    "\tstep {} {}: {}\n",
+                    reference.step,
+                    reference.step_name,
+                    reference.missing()
+                ));
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(broken)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+        </TableOccurrenceCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total"></Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice"></Layout>
+        </LayoutCatalog>
+        <ScriptCatalog>
+            <Script id="1" name="Main"></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="6" name="Go to Layout" enable="True">
+                        <LayoutReferenceContainer value="1">
+                            <LayoutReference id="2" name="Deleted layout"></LayoutReference>
+                        </LayoutReferenceContainer>
+                    </Step>
+                    <Step id="76" name="Set Field" enable="True">
+                        <FieldReference id="1" name="Total">
+                            <TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference>
+                        </FieldReference>
+                        <Calculation>
+                            <ChunkList>
+                                <Chunk type="FieldRef">
+                                    <FieldReference id="2" name="">
+                                        <TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference>
+                                    </FieldReference>
+                                </Chunk>
+                            </ChunkList>
+                        </Calculation>
+                    </Step>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <List name="From list" value="1">
+                            <ScriptReference id="9" name="Gone"></ScriptReference>
+                        </List>
+                    </Step>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <List name="From list" value="1">
+                            <DataSourceReference id="1" name="Archive"></DataSourceReference>
+                            <ScriptReference id="9" name="Archive"></ScriptReference>
+                        </List>
+                    </Step>
+                    <Step id="76" name="Set Field" enable="True">
+                        <FieldReference id="0" name="">
+                            <TableOccurrenceReference id="1065099" name="Old"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    fn broken() -> Vec<BrokenReference> {
+        let model = read_model(&export(CATALOGS));
+        broken_references(&model)
+    }
+
+    #[test]
+    fn test_broken_references() {
+        let broken: Vec<_> = broken()
+            .into_iter()
+            .map(|reference| (reference.step, reference.kind, reference.id))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                (1, ReferenceKind::Layout, 2),
+                (2, ReferenceKind::Field, 2),
+                (3, ReferenceKind::Script, 9),
+                (5, ReferenceKind::TableOccurrence, 1065099),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_text() {
+        let expected = "Invoices: Main (ID 1)
+\tstep 1 Go to Layout: missing layout \"Deleted layout\" (ID 2)
+\tstep 2 Set Field: missing field (ID 2)
+\tstep 3 Perform Script: missing script \"Gone\" (ID 9)
+\tstep 5 Set Field: missing table occurrence \"Old\" (ID 1065099)
+";
+        assert_eq!(render(&broken(), ReportFormat::Text).unwrap(), expected);
+    }
+}
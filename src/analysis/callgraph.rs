@@ -0,0 +1,494 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::analysis::graph_xml::{render_gexf, render_graphml, Graph, GraphEdge, GraphNode};
+use crate::analysis::model::{ExportModel, StepModel};
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::calculation::Calculation;
+use crate::symbols::id_attribute;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::GraphFormat;
+
+/// How a script is called by a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    /// Perform Script
+    Perform,
+    /// Perform Script on Server, with or without callback
+    Server,
+    /// The callback of Perform Script on Server with Callback
+    Callback,
+    /// Installed as a handler, e.g. by Install OnTimer Script
+    Trigger,
+}
+
+/// A step calling a script
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Call {
+    pub caller: u32,
+    /// Step number in the calling script
+    pub step: usize,
+    pub kind: CallKind,
+    pub enabled: bool,
+    /// Id of the called script, in `file` if given; `None` for a script name calculated at runtime
+    pub script_id: Option<u32>,
+    /// Name of the called script, or the calculation of its name
+    pub script: String,
+    /// External data source of a script in another file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScriptNode {
+    pub id: u32,
+    pub name: String,
+    /// Number of calls of this script from scripts of the same file
+    pub callers: usize,
+}
+
+/// Scripts of one export and the calls between them
+#[derive(Debug, Serialize)]
+pub struct CallGraph {
+    pub db: String,
+    pub scripts: Vec<ScriptNode>,
+    pub calls: Vec<Call>,
+}
+
+impl CallGraph {
+    pub fn build(model: &ExportModel) -> Self {
+        let calls: Vec<Call> = model
+            .scripts
+            .iter()
+            .flat_map(|script| {
+                script
+                    .steps
+                    .iter()
+                    .flat_map(|step| step_calls(script.id, step))
+            })
+            .collect();
+
+        let mut scripts: Vec<ScriptNode> = model
+            .scripts
+            .iter()
+            .map(|script| ScriptNode {
+                id: script.id,
+                name: script.name.clone(),
+                callers: calls
+                    .iter()
+                    .filter(|call| call.file.is_none() && call.script_id == Some(script.id))
+                    .count(),
+            })
+            .collect();
+        scripts.sort_by_key(|script| script.id);
+
+        Self {
+            db: model.db_name.clone(),
+            scripts,
+            calls,
+        }
+    }
+}
+
+/// The scripts called by a step, in the order of the step's parameters
+pub fn step_calls(caller: u32, step: &StepModel) -> Vec<Call> {
+    let mut calls = Vec::new();
+    let mut file = None;
+    let mut in_callback = false;
+
+    let mut reader = Reader::from_str(&step.xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(_) | Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"Parameter" => {
+                    if let Some(parameter_type) = parse_unescaped_attribute(&e, "type") {
+                        in_callback = parameter_type.starts_with("Callback");
+                    }
+                }
+                b"DataSourceReference" => file = parse_unescaped_attribute(&e, "name"),
+                b"List" if parse_unescaped_attribute(&e, "value").as_deref() == Some("2") => {
+                    let calculation =
+                        Calculation::from_xml(&mut reader, &e, &mut Diagnostics::default());
+                    if let Some(script) = calculation.ok().and_then(|calc| calc.display()) {
+                        calls.push(call(caller, step, in_callback, None, script));
+                    }
+                }
+                b"ScriptReference" => {
+                    let script = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                    calls.push(call(caller, step, in_callback, id_attribute(&e), script));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    // The file applies to the script performed, not to the callback in this file
+    for call in &mut calls {
+        if call.kind != CallKind::Callback {
+            call.file = file.clone();
+        }
+    }
+    calls
+}
+
+fn call(
+    caller: u32,
+    step: &StepModel,
+    in_callback: bool,
+    script_id: Option<u32>,
+    script: String,
+) -> Call {
+    let kind = match (step.id, in_callback) {
+        (_, true) => CallKind::Callback,
+        (1, _) => CallKind::Perform,
+        (164 | 210, _) => CallKind::Server,
+        _ => CallKind::Trigger,
+    };
+    Call {
+        caller,
+        step: step.number,
+        kind,
+        enabled: step.enabled,
+        script_id,
+        script,
+        file: None,
+    }
+}
+
+pub fn render(graphs: &[CallGraph], format: GraphFormat) -> Result<String, Error> {
+    match format {
+        GraphFormat::Dot => Ok(render_dot(graphs)),
+        GraphFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(graphs)?)),
+        GraphFormat::Graphml => Ok(render_graphml(&graph(graphs))),
+        GraphFormat::Gexf => Ok(render_gexf(&graph(graphs))),
+    }
+}
+
+/// A call target that isn't a script of the exports
+enum OtherNode {
+    /// A script of another file, or one missing in the catalog
+    Script(String),
+    /// A script name calculated at runtime
+    Calculated(String),
+}
+
+/// The node a call points to, and the node to add for it if it isn't a script of the exports.
+/// Calls of scripts in another of the given exports are linked to that export's script.
+fn call_target(
+    graph: &CallGraph,
+    call: &Call,
+    db_names: &HashSet<&str>,
+    script_ids: &HashSet<u32>,
+) -> (String, Option<OtherNode>) {
+    match (&call.file, call.script_id) {
+        (Some(file), Some(id)) if db_names.contains(file.as_str()) => (node_id(file, id), None),
+        (None, Some(id)) if script_ids.contains(&id) => (node_id(&graph.db, id), None),
+        (file, Some(id)) => {
+            // Data source names are only unique within the calling file
+            let target = match file {
+                Some(file) => node_id(&format!("{}/{file}", graph.db), id),
+                None => node_id(&graph.db, id),
+            };
+            let label = match file {
+                Some(file) => format!("{file}: {}", call.script),
+                None => call.script.clone(),
+            };
+            (target, Some(OtherNode::Script(label)))
+        }
+        (_, None) => (
+            format!("{}#{}:{}", graph.db, call.caller, call.step),
+            Some(OtherNode::Calculated(call.script.clone())),
+        ),
+    }
+}
+
+/// Render the call graphs as Graphviz DOT, with one cluster per export.
+/// Calls of scripts in another of the given exports are linked to that export's script.
+pub fn render_dot(graphs: &[CallGraph]) -> String {
+    let db_names: HashSet<&str> = graphs.iter().map(|graph| graph.db.as_str()).collect();
+    let mut lines = vec![
+        "digraph callgraph {".to_string(),
+        "\trankdir=LR;".to_string(),
+        "\tnode [shape=box];".to_string(),
+    ];
+    // Targets outside the scripts of the exports, e.g. scripts in other files or calculated names
+    let mut other_nodes: BTreeMap<String, String> = BTreeMap::new();
+    let mut edges = Vec::new();
+
+    for graph in graphs {
+        let script_ids: HashSet<u32> = graph.scripts.iter().map(|script| script.id).collect();
+        lines.push(format!(
+            "\tsubgraph {} {{",
+            quote(&format!("cluster_{}", graph.db))
+        ));
+        lines.push(format!("\t\tlabel={};", quote(&graph.db)));
+        for script in &graph.scripts {
+            lines.push(format!(
+                "\t\t{} [label={}];",
+                quote(&node_id(&graph.db, script.id)),
+                quote(&script.name)
+            ));
+        }
+        lines.push("\t}".to_string());
+
+        for call in &graph.calls {
+            let (target, other_node) = call_target(graph, call, &db_names, &script_ids);
+            match other_node {
+                Some(OtherNode::Script(label)) => {
+                    other_nodes.insert(
+                        target.clone(),
+                        format!("label={}, style=dashed", quote(&label)),
+                    );
+                }
+                Some(OtherNode::Calculated(label)) => {
+                    other_nodes.insert(
+                        target.clone(),
+                        format!("label={}, shape=note", quote(&label)),
+                    );
+                }
+                None => {}
+            }
+            let mut attributes = vec![format!("label=\"{}\"", call.step)];
+            match call.kind {
+                CallKind::Perform => {}
+                CallKind::Server => attributes.push("style=dashed".to_string()),
+                CallKind::Callback | CallKind::Trigger => {
+                    attributes.push("style=dotted".to_string())
+                }
+            }
+            if !call.enabled {
+                attributes.push("color=gray".to_string());
+            }
+            edges.push(format!(
+                "\t{} -> {} [{}];",
+                quote(&node_id(&graph.db, call.caller)),
+                quote(&target),
+                attributes.join(", ")
+            ));
+        }
+    }
+
+    for (node, attributes) in other_nodes {
+        lines.push(format!("\t{} [{attributes}];", quote(&node)));
+    }
+    lines.extend(edges);
+    lines.push("}".to_string());
+    format!("{}\n", lines.join("\n"))
+}
+
+/// The call graphs as one graph for GraphML and GEXF, grouped by export. Edges are labeled
+/// with the step number, and `disabled` for disabled steps.
+fn graph(graphs: &[CallGraph]) -> Graph {
+    let db_names: HashSet<&str> = graphs.iter().map(|graph| graph.db.as_str()).collect();
+    let mut nodes = Vec::new();
+    let mut other_nodes: BTreeMap<String, GraphNode> = BTreeMap::new();
+    let mut edges = Vec::new();
+    for graph in graphs {
+        let script_ids: HashSet<u32> = graph.scripts.iter().map(|script| script.id).collect();
+        for script in &graph.scripts {
+            nodes.push(GraphNode {
+                id: node_id(&graph.db, script.id),
+                label: script.name.clone(),
+                group: graph.db.clone(),
+                category: "script".to_string(),
+            });
+        }
+        for call in &graph.calls {
+            let (target, other_node) = call_target(graph, call, &db_names, &script_ids);
+            let (label, category) = match other_node {
+                Some(OtherNode::Script(label)) => (label, "other script"),
+                Some(OtherNode::Calculated(label)) => (label, "calculated"),
+                None => (String::new(), ""),
+            };
+            if !category.is_empty() {
+                other_nodes.insert(
+                    target.clone(),
+                    GraphNode {
+                        id: target.clone(),
+                        label,
+                        group: graph.db.clone(),
+                        category: category.to_string(),
+                    },
+                );
+            }
+            edges.push(GraphEdge {
+                source: node_id(&graph.db, call.caller),
+                target,
+                label: match call.enabled {
+                    true => call.step.to_string(),
+                    false => format!("{} disabled", call.step),
+                },
+                kind: serde_json::to_value(call.kind)
+                    .ok()
+                    .and_then(|kind| kind.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    nodes.extend(other_nodes.into_values());
+    Graph {
+        name: "callgraph",
+        directed: true,
+        nodes,
+        edges,
+    }
+}
+
+fn node_id(db: &str, script_id: u32) -> String {
+    format!("{db}#{script_id}")
+}
+
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="2" name="Print &quot;A&quot;"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="1" name="Perform Script" enable="False">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="By name" value="2">
+                                    <Calculation><Text><![CDATA["Print " & $kind]]></Text></Calculation>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="210" name="Perform Script on Server with Callback" enable="True">
+                        <ParameterValues>
+                            <Parameter type="CallbackScriptParameter">
+                                <ScriptReference id="2" name="Print &quot;A&quot;"></ScriptReference>
+                            </Parameter>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <DataSourceReference id="1" name="Archive"></DataSourceReference>
+                                    <ScriptReference id="7" name="Archive invoices"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Print &quot;A&quot;"></ScriptReference>
+                <ObjectList></ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    fn graph() -> CallGraph {
+        let model = read_model(&export(CATALOGS));
+        CallGraph::build(&model)
+    }
+
+    #[test]
+    fn test_calls() {
+        let graph = graph();
+        let calls: Vec<_> = graph
+            .calls
+            .iter()
+            .map(|call| {
+                (
+                    call.step,
+                    call.kind,
+                    call.script_id,
+                    call.script.as_str(),
+                    call.file.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                (1, CallKind::Perform, Some(2), "Print \"A\"", None),
+                (2, CallKind::Perform, None, "\"Print \" & $kind", None),
+                (3, CallKind::Callback, Some(2), "Print \"A\"", None),
+                (
+                    3,
+                    CallKind::Server,
+                    Some(7),
+                    "Archive invoices",
+                    Some("Archive")
+                ),
+            ]
+        );
+        assert!(!graph.calls[1].enabled);
+        assert_eq!(graph.scripts[0].callers, 0);
+        assert_eq!(graph.scripts[1].callers, 2);
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let expected = r#"digraph callgraph {
+	rankdir=LR;
+	node [shape=box];
+	subgraph "cluster_Invoices" {
+		label="Invoices";
+		"Invoices#1" [label="Main"];
+		"Invoices#2" [label="Print \"A\""];
+	}
+	"Invoices#1:2" [label="\"Print \" & $kind", shape=note];
+	"Invoices/Archive#7" [label="Archive: Archive invoices", style=dashed];
+	"Invoices#1" -> "Invoices#2" [label="1"];
+	"Invoices#1" -> "Invoices#1:2" [label="2", color=gray];
+	"Invoices#1" -> "Invoices#2" [label="3", style=dotted];
+	"Invoices#1" -> "Invoices/Archive#7" [label="3", style=dashed];
+}
+"#;
+        assert_eq!(render_dot(&[graph()]), expected);
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = super::graph(&[graph()]);
+        let nodes: Vec<_> = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node.category.as_str()))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("Invoices#1", "script"),
+                ("Invoices#2", "script"),
+                ("Invoices#1:2", "calculated"),
+                ("Invoices/Archive#7", "other script"),
+            ]
+        );
+        let edge = &graph.edges[1];
+        assert_eq!(
+            (edge.label.as_str(), edge.kind.as_str()),
+            ("2 disabled", "perform")
+        );
+    }
+}
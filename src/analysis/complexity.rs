@@ -0,0 +1,174 @@
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::callgraph::{step_calls, CallKind};
+use crate::analysis::model::{ExportModel, ScriptModel};
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::{ComplexityMetric, TableFormat};
+
+/// Step id of comments, which aren't counted as steps
+const COMMENT: u32 = 89;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ScriptMetrics {
+    pub db: String,
+    pub id: u32,
+    pub name: String,
+    /// Number of steps, without comments
+    pub steps: usize,
+    /// Deepest nesting of If and Loop blocks, 0 for a script without blocks
+    pub max_depth: usize,
+    /// Number of scripts performed, on the client or the server
+    pub perform_script_calls: usize,
+    pub calculations: usize,
+    /// Total number of characters of all calculations
+    pub calculation_length: usize,
+}
+
+impl ScriptMetrics {
+    pub fn value(&self, metric: ComplexityMetric) -> usize {
+        match metric {
+            ComplexityMetric::Steps => self.steps,
+            ComplexityMetric::Depth => self.max_depth,
+            ComplexityMetric::Calls => self.perform_script_calls,
+            ComplexityMetric::CalcLength => self.calculation_length,
+        }
+    }
+}
+
+/// Metrics of all scripts of an export, in script order
+pub fn script_metrics(model: &ExportModel) -> Vec<ScriptMetrics> {
+    model
+        .scripts
+        .iter()
+        .map(|script| metrics(&model.db_name, script))
+        .collect()
+}
+
+fn metrics(db: &str, script: &ScriptModel) -> ScriptMetrics {
+    let mut metrics = ScriptMetrics {
+        db: db.to_string(),
+        id: script.id,
+        name: script.name.clone(),
+        steps: 0,
+        max_depth: 0,
+        perform_script_calls: 0,
+        calculations: 0,
+        calculation_length: 0,
+    };
+    // Blocks are nested like the indentation of the sanitized scripts, ignoring disabled steps
+    let mut depth: usize = 0;
+    for step in &script.steps {
+        if step.id != COMMENT {
+            metrics.steps += 1;
+        }
+        if step.enabled {
+            match id_to_script_step(&step.id) {
+                ScriptStep::IfStart | ScriptStep::LoopStart => {
+                    depth += 1;
+                    metrics.max_depth = metrics.max_depth.max(depth);
+                }
+                ScriptStep::IfEnd | ScriptStep::LoopEnd => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        metrics.perform_script_calls += step_calls(script.id, step)
+            .iter()
+            .filter(|call| matches!(call.kind, CallKind::Perform | CallKind::Server))
+            .count();
+        for calculation in step.calculations() {
+            metrics.calculations += 1;
+            metrics.calculation_length += calculation.chars().count();
+        }
+    }
+    metrics
+}
+
+pub fn render(metrics: &[ScriptMetrics], format: TableFormat) -> Result<String, Error> {
+    match format {
+        TableFormat::Csv => {
+            let mut csv = String::from(
+                "db,id,name,steps,max_depth,perform_script_calls,calculations,calculation_length\n",
+            );
+            for script in metrics {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    csv_field(&script.db),
+                    script.id,
+                    csv_field(&script.name),
+                    script.steps,
+                    script.max_depth,
+                    script.perform_script_calls,
+                    script.calculations,
+                    script.calculation_length
+                ));
+            }
+            Ok(csv)
+        }
+        TableFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(metrics)?)),
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Print, then send"></ScriptReference>
+                <ObjectList>
+                    <Step id="89" name="# (comment)" enable="True"></Step>
+                    <Step id="71" name="Loop" enable="True"></Step>
+                    <Step id="68" name="If" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Calculation">
+                                <Calculation><Text><![CDATA[$i > 3]]></Text></Calculation>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="2" name="Send"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="70" name="End If" enable="True"></Step>
+                    <Step id="68" name="If" enable="False"></Step>
+                    <Step id="73" name="End Loop" enable="True"></Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Send"></ScriptReference>
+                <ObjectList></ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_render_csv() {
+        let model = read_model(&export(CATALOGS));
+        let expected =
+            "db,id,name,steps,max_depth,perform_script_calls,calculations,calculation_length
+Invoices,1,\"Print, then send\",6,2,1,1,6
+Invoices,2,Send,0,0,0,0,0
+";
+        assert_eq!(
+            render(&script_metrics(&model), TableFormat::Csv).unwrap(),
+            expected
+        );
+    }
+}
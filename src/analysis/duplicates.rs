@@ -0,0 +1,214 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{ExportModel, ScriptModel};
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::sanitizer::sanitize;
+use crate::ReportFormat;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScriptRef {
+    pub db: String,
+    pub id: u32,
+    pub name: String,
+}
+
+/// Scripts with the same steps, or two scripts sharing most of their steps
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Duplicate {
+    /// Percentage of the steps the scripts share, 100 for identical scripts
+    pub similarity: u32,
+    pub steps: usize,
+    pub scripts: Vec<ScriptRef>,
+}
+
+/// Scripts with the same normalized steps
+struct Sequence {
+    scripts: Vec<ScriptRef>,
+    steps: usize,
+    /// Number of occurrences of each step hash
+    counts: HashMap<u64, usize>,
+}
+
+/// Hashes of the enabled steps of a script, rendered like the exploded scripts with runs of
+/// whitespace collapsed. Comments and disabled steps don't change what a script does.
+fn step_hashes(script: &ScriptModel) -> Vec<u64> {
+    script
+        .steps
+        .iter()
+        .filter(|step| step.enabled && id_to_script_step(&step.id) != ScriptStep::Comment)
+        .map(|step| {
+            let rendered = sanitize(&step.id, &step.xml, &mut Diagnostics::default())
+                .unwrap_or_else(|| step.name.clone());
+            let normalized = rendered.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut hasher = DefaultHasher::new();
+            normalized.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Groups of identical scripts followed by pairs of scripts whose share of common steps is at
+/// least `threshold` percent, most similar first. Scripts with fewer than `min_steps` steps are
+/// ignored, and of identical scripts only the first is compared with the others.
+pub fn duplicates(models: &[ExportModel], threshold: u32, min_steps: usize) -> Vec<Duplicate> {
+    let mut sequences: Vec<Sequence> = Vec::new();
+    let mut by_hashes: HashMap<Vec<u64>, usize> = HashMap::new();
+    for model in models {
+        for script in &model.scripts {
+            let hashes = step_hashes(script);
+            if hashes.is_empty() || hashes.len() < min_steps {
+                continue;
+            }
+            let script = ScriptRef {
+                db: model.db_name.clone(),
+                id: script.id,
+                name: script.name.clone(),
+            };
+            match by_hashes.get(&hashes) {
+                Some(&index) => sequences[index].scripts.push(script),
+                None => {
+                    let mut counts = HashMap::new();
+                    for hash in &hashes {
+                        *counts.entry(*hash).or_insert(0) += 1;
+                    }
+                    by_hashes.insert(hashes.clone(), sequences.len());
+                    sequences.push(Sequence {
+                        scripts: vec![script],
+                        steps: hashes.len(),
+                        counts,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut duplicates: Vec<Duplicate> = sequences
+        .iter()
+        .filter(|sequence| sequence.scripts.len() > 1)
+        .map(|sequence| Duplicate {
+            similarity: 100,
+            steps: sequence.steps,
+            scripts: sequence.scripts.clone(),
+        })
+        .collect();
+
+    let mut similar = Vec::new();
+    for (i, a) in sequences.iter().enumerate() {
+        for b in &sequences[i + 1..] {
+            // Even if all steps of the shorter script are shared the scripts can't be similar
+            if 200 * a.steps.min(b.steps) < threshold as usize * (a.steps + b.steps) {
+                continue;
+            }
+            let (small, large) = match a.counts.len() <= b.counts.len() {
+                true => (&a.counts, &b.counts),
+                false => (&b.counts, &a.counts),
+            };
+            let common: usize = small
+                .iter()
+                .map(|(hash, count)| (*count).min(large.get(hash).copied().unwrap_or(0)))
+                .sum();
+            let similarity = (200 * common / (a.steps + b.steps)) as u32;
+            if similarity >= threshold && similarity < 100 {
+                similar.push(Duplicate {
+                    similarity,
+                    steps: common,
+                    scripts: vec![a.scripts[0].clone(), b.scripts[0].clone()],
+                });
+            }
+        }
+    }
+    // Stable, so pairs with the same similarity stay in script order
+    similar.sort_by_key(|duplicate| std::cmp::Reverse(duplicate.similarity));
+    duplicates.extend(similar);
+    duplicates
+}
+
+pub fn render(duplicates: &[Duplicate], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for duplicate in duplicates {
+                match duplicate.similarity {
+                    100 => text.push_str(&format!("identical, {} steps\n", duplicate.steps)),
+                    similarity => text.push_str(&format!(
+                        "{similarity}% similar, {} common steps\n",
+                        duplicate.steps
+                    )),
+                }
+                for script in &duplicate.scripts {
+                    text.push_str(&format!(
+                        "\t{}: {} (ID {})\n",
+                        script.db, script.name, script.id
+                    ));
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(duplicates)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    fn script(id: u32, name: &str, steps: &[&str]) -> String {
+        let steps: String = steps
+            .iter()
+            .map(|step| match *step {
+                "#" => r##"<Step id="89" name="# (comment)" enable="True"></Step>"##.to_string(),
+                "Beep" => r#"<Step id="93" name="Beep" enable="True"></Step>"#.to_string(),
+                window => format!(
+                    r#"<Step id="85" name="Adjust Window" enable="True"><ParameterValues><Parameter type="List"><List name="{window}" value="1"></List></Parameter></ParameterValues></Step>"#
+                ),
+            })
+            .collect();
+        format!(
+            r#"<Script><ScriptReference id="{id}" name="{name}"></ScriptReference><ObjectList>{steps}</ObjectList></Script>"#
+        )
+    }
+
+    #[test]
+    fn test_render_text() {
+        let catalogs = format!(
+            "<StepsForScripts>{}{}{}{}{}</StepsForScripts>",
+            script(
+                1,
+                "Print",
+                &["Beep", "Maximize", "Beep", "Minimize", "Beep"]
+            ),
+            script(
+                2,
+                "Print copy",
+                &["#", "Beep", "Maximize", "Beep", "Minimize", "Beep"]
+            ),
+            script(
+                3,
+                "Print other",
+                &["Beep", "Maximize", "Beep", "Hide", "Beep"]
+            ),
+            script(4, "Short", &["Beep"]),
+            script(5, "Short copy", &["Beep"]),
+        );
+        let models = vec![read_model(&export(&catalogs))];
+        let expected = "identical, 5 steps
+\tInvoices: Print (ID 1)
+\tInvoices: Print copy (ID 2)
+80% similar, 4 common steps
+\tInvoices: Print (ID 1)
+\tInvoices: Print other (ID 3)
+";
+        assert_eq!(
+            render(&duplicates(&models, 80, 2), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
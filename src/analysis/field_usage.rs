@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::analysis::model::{ExportModel, StepModel};
+use crate::symbols::{id_attribute, SymbolKind};
+use crate::ReportFormat;
+
+/// The scripts and layout objects referring to a field
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct FieldUsage {
+    pub db: String,
+    pub table_id: u32,
+    pub table: String,
+    pub field_id: u32,
+    pub field: String,
+    pub scripts: Vec<ScriptFieldUse>,
+    pub layouts: Vec<LayoutObjectUse>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ScriptFieldUse {
+    pub id: u32,
+    pub name: String,
+    /// Numbers of the steps referring to the field, as target or in a calculation
+    pub steps: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct LayoutObjectUse {
+    pub layout: String,
+    pub object: String,
+}
+
+/// Usage of every field of the export, by base table and field id.
+/// References through table occurrences of other files are not resolved.
+pub fn field_usage(model: &ExportModel) -> Vec<FieldUsage> {
+    let mut usages: BTreeMap<(u32, u32), FieldUsage> = model
+        .symbols
+        .fields()
+        .map(|(table_id, field_id, field)| {
+            let usage = FieldUsage {
+                db: model.db_name.clone(),
+                table_id,
+                table: model
+                    .symbols
+                    .name(SymbolKind::BaseTable, table_id)
+                    .unwrap_or_default(),
+                field_id,
+                field: field.name.clone(),
+                ..Default::default()
+            };
+            ((table_id, field_id), usage)
+        })
+        .collect();
+    let field_key = |table_occurrence_id: u32, field_id: u32| {
+        let table_id = model.symbols.base_table_of(table_occurrence_id)?;
+        Some((table_id, field_id))
+    };
+
+    for script in &model.scripts {
+        let mut steps_by_field: BTreeMap<(u32, u32), Vec<usize>> = BTreeMap::new();
+        for step in &script.steps {
+            for (table_occurrence_id, field_id) in step_field_references(step) {
+                if let Some(key) = field_key(table_occurrence_id, field_id) {
+                    let steps = steps_by_field.entry(key).or_default();
+                    if steps.last() != Some(&step.number) {
+                        steps.push(step.number);
+                    }
+                }
+            }
+        }
+        for (key, steps) in steps_by_field {
+            if let Some(usage) = usages.get_mut(&key) {
+                usage.scripts.push(ScriptFieldUse {
+                    id: script.id,
+                    name: script.name.clone(),
+                    steps,
+                });
+            }
+        }
+    }
+
+    // Layouts are listed once per action of the export, e.g. added and modified
+    let mut layout_uses: BTreeSet<((u32, u32), LayoutObjectUse)> = BTreeSet::new();
+    for field_use in &model.layout_field_uses {
+        if let Some(key) = field_key(field_use.table_occurrence_id, field_use.field_id) {
+            layout_uses.insert((
+                key,
+                LayoutObjectUse {
+                    layout: field_use.layout.clone(),
+                    object: field_use.object.clone(),
+                },
+            ));
+        }
+    }
+    for (key, layout_use) in layout_uses {
+        if let Some(usage) = usages.get_mut(&key) {
+            usage.layouts.push(layout_use);
+        }
+    }
+
+    usages.into_values().collect()
+}
+
+/// Table occurrence and field id of all field references of a step, including its calculations
+fn step_field_references(step: &StepModel) -> Vec<(u32, u32)> {
+    let mut references = Vec::new();
+    let mut field_id = None;
+
+    let mut reader = Reader::from_str(&step.xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(_) | Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"FieldReference" => field_id = id_attribute(&e),
+                b"TableOccurrenceReference" => {
+                    if let (Some(field_id), Some(table_occurrence_id)) =
+                        (field_id.take(), id_attribute(&e))
+                    {
+                        references.push((table_occurrence_id, field_id));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) if e.name().as_ref() == b"FieldReference" => field_id = None,
+            _ => {}
+        }
+        buf.clear();
+    }
+    references
+}
+
+pub fn render(usages: &[FieldUsage], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for usage in usages {
+                text.push_str(&format!(
+                    "{}: {}::{} (ID {})\n",
+                    usage.db, usage.table, usage.field, usage.field_id
+                ));
+                for script in &usage.scripts {
+                    let steps: Vec<String> = script.steps.iter().map(usize::to_string).collect();
+                    text.push_str(&format!(
+                        "\tscript {} (ID {}): step {}\n",
+                        script.name,
+                        script.id,
+                        steps.join(", ")
+                    ));
+                }
+                for layout in &usage.layouts {
+                    text.push_str(&format!("\tlayout {}: {}\n", layout.layout, layout.object));
+                }
+                if usage.scripts.is_empty() && usage.layouts.is_empty() {
+                    text.push_str("\tnot referenced by scripts or layouts\n");
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(usages)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+            <TableOccurrence id="1065090" name="Invoices_Open" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+        </TableOccurrenceCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total"></Field>
+                    <Field id="2" name="Notes"></Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice">
+                <PartsList>
+                    <Part>
+                        <ObjectList>
+                            <LayoutObject id="7" type="Edit Box" name="">
+                                <Field>
+                                    <FieldReference id="1" name="Total">
+                                        <TableOccurrenceReference id="1065090" name="Invoices_Open"></TableOccurrenceReference>
+                                    </FieldReference>
+                                </Field>
+                            </LayoutObject>
+                            <LayoutObject id="8" type="Button" name="Recalculate">
+                                <Conditions><Hide><Calculation><ChunkList><Chunk type="FieldRef">
+                                    <FieldReference id="1" name="Total">
+                                        <TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference>
+                                    </FieldReference>
+                                </Chunk></ChunkList></Calculation></Hide></Conditions>
+                            </LayoutObject>
+                        </ObjectList>
+                    </Part>
+                </PartsList>
+            </Layout>
+        </LayoutCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="76" name="Set Field" enable="True">
+                        <FieldReference id="1" name="Total">
+                            <TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference>
+                        </FieldReference>
+                        <Calculation><ChunkList><Chunk type="FieldRef">
+                            <FieldReference id="1" name="Total">
+                                <TableOccurrenceReference id="1065090" name="Invoices_Open"></TableOccurrenceReference>
+                            </FieldReference>
+                        </Chunk></ChunkList></Calculation>
+                    </Step>
+                    <Step id="89" name="# (comment)" enable="True"></Step>
+                    <Step id="76" name="Set Field" enable="True">
+                        <FieldReference id="1" name="Total">
+                            <TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    fn usages() -> Vec<FieldUsage> {
+        let model = read_model(&export(CATALOGS));
+        field_usage(&model)
+    }
+
+    #[test]
+    fn test_render_text() {
+        let expected = "Invoices: Invoices::Total (ID 1)
+\tscript Main (ID 1): step 1, 3
+\tlayout Invoice: Edit Box (ID 7)
+\tlayout Invoice: Recalculate
+Invoices: Invoices::Notes (ID 2)
+\tnot referenced by scripts or layouts
+";
+        assert_eq!(render(&usages(), ReportFormat::Text).unwrap(), expected);
+    }
+}
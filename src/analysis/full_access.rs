@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::callgraph::CallGraph;
+use crate::analysis::model::ExportModel;
+use crate::analysis::sarif::{SarifFinding, SarifRule};
+use crate::ReportFormat;
+
+/// A script running with full access privileges and where users can start it
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FullAccessScript {
+    pub db: String,
+    pub id: u32,
+    pub name: String,
+    pub entry_points: Vec<EntryPoint>,
+}
+
+/// A layout, custom menu or the file options starting a script that is or performs a privileged one
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EntryPoint {
+    /// E.g. `layout Invoice` for a button or script trigger of the layout or its objects
+    pub user: String,
+    /// Names of the scripts from the started one to the privileged one
+    pub path: Vec<String>,
+}
+
+/// All scripts with full access privileges and the layouts, custom menus and file options from
+/// which they are reached, directly or through scripts performing them in the same file
+pub fn full_access_scripts(model: &ExportModel, graph: &CallGraph) -> Vec<FullAccessScript> {
+    let names: HashMap<u32, &str> = model
+        .scripts
+        .iter()
+        .map(|script| (script.id, script.name.as_str()))
+        .collect();
+    let mut callers: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for call in graph.calls.iter().filter(|call| call.file.is_none()) {
+        if let Some(script_id) = call.script_id {
+            let script_callers = callers.entry(script_id).or_default();
+            if !script_callers.contains(&call.caller) {
+                script_callers.push(call.caller);
+            }
+        }
+    }
+
+    let mut privileged = Vec::new();
+    for &id in &model.full_access_scripts {
+        // Shortest path from each script reaching the privileged one, found breadth first
+        let mut paths: BTreeMap<u32, Vec<u32>> = BTreeMap::from([(id, vec![id])]);
+        let mut queue = VecDeque::from([id]);
+        while let Some(script_id) = queue.pop_front() {
+            for caller in callers.get(&script_id).into_iter().flatten() {
+                if !paths.contains_key(caller) {
+                    let mut path = vec![*caller];
+                    path.extend(&paths[&script_id]);
+                    paths.insert(*caller, path);
+                    queue.push_back(*caller);
+                }
+            }
+        }
+
+        let mut entry_points = Vec::new();
+        for script_use in &model.script_uses {
+            let Some(path) = paths.get(&script_use.script_id) else {
+                continue;
+            };
+            let entry_point = EntryPoint {
+                user: script_use.user.to_string(),
+                path: path
+                    .iter()
+                    .map(|id| names.get(id).copied().unwrap_or_default().to_string())
+                    .collect(),
+            };
+            // Users are listed once per action of the export, e.g. added and modified
+            if !entry_points.contains(&entry_point) {
+                entry_points.push(entry_point);
+            }
+        }
+        privileged.push(FullAccessScript {
+            db: model.db_name.clone(),
+            id,
+            name: names.get(&id).copied().unwrap_or_default().to_string(),
+            entry_points,
+        });
+    }
+    privileged
+}
+
+pub const SARIF_RULES: [SarifRule; 1] = [SarifRule {
+    id: "full-access-script",
+    description: "Script running with full access privileges",
+}];
+
+pub fn sarif_findings(scripts: &[FullAccessScript]) -> Vec<SarifFinding> {
+    scripts
+        .iter()
+        .map(|script| {
+            let started_by: Vec<String> = script
+                .entry_points
+                .iter()
+                .map(|entry_point| {
+                    format!("{}: {}", entry_point.user, entry_point.path.join(" > "))
+                })
+                .collect();
+            let message = match started_by.is_empty() {
+                true => {
+                    "Runs with full access, not started by layouts, custom menus or file options"
+                        .to_string()
+                }
+                false => format!(
+                    "Runs with full access, started by {}",
+                    started_by.join("; ")
+                ),
+            };
+            SarifFinding {
+                rule: "full-access-script",
+                level: "warning",
+                message,
+                db: script.db.clone(),
+                script_id: script.id,
+                script: script.name.clone(),
+                step: None,
+            }
+        })
+        .collect()
+}
+
+pub fn render(scripts: &[FullAccessScript], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for script in scripts {
+                text.push_str(&format!(
+                    "{}: {} (ID {}) runs with full access\n",
+                    script.db, script.name, script.id
+                ));
+                for entry_point in &script.entry_points {
+                    text.push_str(&format!(
+                        "\t{}: {}\n",
+                        entry_point.user,
+                        entry_point.path.join(" > ")
+                    ));
+                }
+                if script.entry_points.is_empty() {
+                    text.push_str("\tnot started by layouts, custom menus or file options\n");
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(scripts)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice">
+                <ScriptTriggers>
+                    <ScriptTrigger type="OnRecordLoad">
+                        <ScriptReference id="1" name="Load"></ScriptReference>
+                    </ScriptTrigger>
+                </ScriptTriggers>
+            </Layout>
+        </LayoutCatalog>
+        <CustomMenuCatalog>
+            <CustomMenu id="1" name="Admin">
+                <ScriptReference id="3" name="Delete all"></ScriptReference>
+            </CustomMenu>
+        </CustomMenuCatalog>
+        <ScriptCatalog>
+            <Script id="1" name="Load"><Options runwithfullaccess="False">0</Options></Script>
+            <Script id="2" name="Privileged"><Options runwithfullaccess="True">0</Options></Script>
+            <Script id="3" name="Delete all"><Options runwithfullaccess="True">0</Options></Script>
+            <Script id="4" name="Cleanup"><Options runwithfullaccess="True">0</Options></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Load"></ScriptReference>
+                <ObjectList>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="2" name="Privileged"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+            <Script><ScriptReference id="2" name="Privileged"></ScriptReference><ObjectList></ObjectList></Script>
+            <Script><ScriptReference id="3" name="Delete all"></ScriptReference><ObjectList></ObjectList></Script>
+            <Script><ScriptReference id="4" name="Cleanup"></ScriptReference><ObjectList></ObjectList></Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let graph = CallGraph::build(&model);
+        let expected = "Invoices: Privileged (ID 2) runs with full access
+\tlayout Invoice: Load > Privileged
+Invoices: Delete all (ID 3) runs with full access
+\tcustom menu Admin: Delete all
+Invoices: Cleanup (ID 4) runs with full access
+\tnot started by layouts, custom menus or file options
+";
+        assert_eq!(
+            render(&full_access_scripts(&model, &graph), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use quick_xml::escape::escape;
+
+/// A graph of an analysis for the XML graph formats of tools like Gephi and yEd
+pub struct Graph {
+    pub name: &'static str,
+    pub directed: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    /// The export the node belongs to
+    pub group: String,
+    /// E.g. `script`, or the base table of a table occurrence
+    pub category: String,
+}
+
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub label: String,
+    /// E.g. the call kind of a call graph edge
+    pub kind: String,
+}
+
+/// Render the graph as GraphML, with the labels, groups and kinds as data
+pub fn render_graphml(graph: &Graph) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#.to_string(),
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#.to_string(),
+        r#"  <key id="group" for="node" attr.name="group" attr.type="string"/>"#.to_string(),
+        r#"  <key id="category" for="node" attr.name="category" attr.type="string"/>"#.to_string(),
+        r#"  <key id="edge_label" for="edge" attr.name="label" attr.type="string"/>"#.to_string(),
+        r#"  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>"#.to_string(),
+        format!(
+            r#"  <graph id="{}" edgedefault="{}">"#,
+            graph.name,
+            match graph.directed {
+                true => "directed",
+                false => "undirected",
+            }
+        ),
+    ];
+    for node in &graph.nodes {
+        lines.push(format!(r#"    <node id="{}">"#, xml_text(&node.id)));
+        lines.push(format!(
+            r#"      <data key="label">{}</data>"#,
+            xml_text(&node.label)
+        ));
+        lines.push(format!(
+            r#"      <data key="group">{}</data>"#,
+            xml_text(&node.group)
+        ));
+        lines.push(format!(
+            r#"      <data key="category">{}</data>"#,
+            xml_text(&node.category)
+        ));
+        lines.push("    </node>".to_string());
+    }
+    for (index, edge) in graph.edges.iter().enumerate() {
+        lines.push(format!(
+            r#"    <edge id="e{index}" source="{}" target="{}">"#,
+            xml_text(&edge.source),
+            xml_text(&edge.target)
+        ));
+        lines.push(format!(
+            r#"      <data key="edge_label">{}</data>"#,
+            xml_text(&edge.label)
+        ));
+        lines.push(format!(
+            r#"      <data key="kind">{}</data>"#,
+            xml_text(&edge.kind)
+        ));
+        lines.push("    </edge>".to_string());
+    }
+    lines.push("  </graph>".to_string());
+    lines.push("</graphml>".to_string());
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Render the graph as GEXF 1.3, with the groups and kinds as attributes
+pub fn render_gexf(graph: &Graph) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#.to_string(),
+        format!(
+            "  <meta><creator>{} {}</creator></meta>",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ),
+        format!(
+            r#"  <graph mode="static" defaultedgetype="{}">"#,
+            match graph.directed {
+                true => "directed",
+                false => "undirected",
+            }
+        ),
+        r#"    <attributes class="node">"#.to_string(),
+        r#"      <attribute id="group" title="group" type="string"/>"#.to_string(),
+        r#"      <attribute id="category" title="category" type="string"/>"#.to_string(),
+        "    </attributes>".to_string(),
+        r#"    <attributes class="edge">"#.to_string(),
+        r#"      <attribute id="kind" title="kind" type="string"/>"#.to_string(),
+        "    </attributes>".to_string(),
+        "    <nodes>".to_string(),
+    ];
+    for node in &graph.nodes {
+        lines.push(format!(
+            r#"      <node id="{}" label="{}"><attvalues><attvalue for="group" value="{}"/><attvalue for="category" value="{}"/></attvalues></node>"#,
+            xml_text(&node.id),
+            xml_text(&node.label),
+            xml_text(&node.group),
+            xml_text(&node.category)
+        ));
+    }
+    lines.push("    </nodes>".to_string());
+    lines.push("    <edges>".to_string());
+    for (index, edge) in graph.edges.iter().enumerate() {
+        lines.push(format!(
+            r#"      <edge id="{index}" source="{}" target="{}" label="{}"><attvalues><attvalue for="kind" value="{}"/></attvalues></edge>"#,
+            xml_text(&edge.source),
+            xml_text(&edge.target),
+            xml_text(&edge.label),
+            xml_text(&edge.kind)
+        ));
+    }
+    lines.push("    </edges>".to_string());
+    lines.push("  </graph>".to_string());
+    lines.push("</gexf>".to_string());
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Escape text for XML content and attributes, keeping line breaks of calculations
+fn xml_text(text: &str) -> String {
+    escape(text).replace('\r', "&#13;").replace('\n', "&#10;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_gexf() {
+        let graph = Graph {
+            name: "callgraph",
+            directed: true,
+            nodes: vec![GraphNode {
+                id: "Invoices#1".to_string(),
+                label: "Print & send".to_string(),
+                group: "Invoices".to_string(),
+                category: "script".to_string(),
+            }],
+            edges: vec![GraphEdge {
+                source: "Invoices#1".to_string(),
+                target: "Invoices#1".to_string(),
+                label: "3".to_string(),
+                kind: "perform".to_string(),
+            }],
+        };
+        let gexf = render_gexf(&graph);
+        assert!(gexf.contains(
+            r#"<node id="Invoices#1" label="Print &amp; send"><attvalues><attvalue for="group" value="Invoices"/>"#
+        ));
+        assert!(gexf.contains(r#"<edge id="0" source="Invoices#1" target="Invoices#1" label="3">"#));
+    }
+}
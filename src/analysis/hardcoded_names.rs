@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::calc_formatter::calculation_texts;
+use crate::symbols::SymbolKind;
+use crate::ReportFormat;
+
+/// Kinds of elements whose names are matched against text literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    Layout,
+    Script,
+    /// A field qualified by a table occurrence, e.g. `Invoices::Total`
+    Field,
+}
+
+impl NameKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Layout => "layout",
+            Self::Script => "script",
+            Self::Field => "field",
+        }
+    }
+}
+
+/// A text literal of a calculation naming a layout, script or field, which breaks when the
+/// element is renamed, e.g. in `GetLayoutObjectAttribute`, `GetField` or `ExecuteSQL`
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HardcodedName {
+    pub db: String,
+    pub owner: CalculationOwner,
+    pub kind: NameKind,
+    /// The name as defined in the catalog
+    pub name: String,
+}
+
+/// Text literals of all calculations equal to the name of a layout, a script or a field qualified
+/// by one of its table occurrences, ignoring case as FileMaker does
+pub fn hardcoded_names(model: &ExportModel) -> Vec<HardcodedName> {
+    let mut names: HashMap<String, Vec<(NameKind, String)>> = HashMap::new();
+    let mut add_name = |kind: NameKind, name: String| {
+        if !name.is_empty() {
+            names
+                .entry(name.to_lowercase())
+                .or_default()
+                .push((kind, name));
+        }
+    };
+
+    let mut fields_by_table: HashMap<u32, Vec<&str>> = HashMap::new();
+    for (table_id, _, field) in model.symbols.fields() {
+        fields_by_table
+            .entry(table_id)
+            .or_default()
+            .push(&field.name);
+    }
+    for (kind, id, symbol) in model.symbols.symbols() {
+        match kind {
+            SymbolKind::Layout => add_name(NameKind::Layout, symbol.name.clone()),
+            SymbolKind::Script => add_name(NameKind::Script, symbol.name.clone()),
+            SymbolKind::TableOccurrence => {
+                let fields = model
+                    .symbols
+                    .base_table_of(id)
+                    .and_then(|table_id| fields_by_table.get(&table_id));
+                for field in fields.into_iter().flatten() {
+                    add_name(NameKind::Field, format!("{}::{field}", symbol.name));
+                }
+            }
+            SymbolKind::BaseTable | SymbolKind::ValueList => {}
+        }
+    }
+    // Symbols are unordered; elements of different kinds may have the same name
+    for named in names.values_mut() {
+        named.sort();
+    }
+
+    let mut hardcoded = Vec::new();
+    for calculation in model.all_calculations() {
+        let mut found: Vec<&(NameKind, String)> = Vec::new();
+        for text in calculation_texts(&calculation.text) {
+            for named in names.get(&text.to_lowercase()).into_iter().flatten() {
+                if !found.contains(&named) {
+                    found.push(named);
+                }
+            }
+        }
+        hardcoded.extend(found.into_iter().map(|(kind, name)| HardcodedName {
+            db: model.db_name.clone(),
+            owner: calculation.owner.clone(),
+            kind: *kind,
+            name: name.clone(),
+        }));
+    }
+    hardcoded
+}
+
+pub fn render(hardcoded: &[HardcodedName], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => Ok(hardcoded
+            .iter()
+            .map(|name| {
+                format!(
+                    "{}: {}: {} \"{}\"\n",
+                    name.db,
+                    name.owner,
+                    name.kind.label(),
+                    name.name
+                )
+            })
+            .collect()),
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(hardcoded)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+        </TableOccurrenceCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total"></Field>
+                    <Field id="2" name="Label">
+                        <Calculation><Text><![CDATA[GetField ( "invoices::total" ) & "Total"]]></Text></Calculation>
+                    </Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice">
+                <PartsList>
+                    <Part>
+                        <ObjectList>
+                            <LayoutObject id="7" type="Text" name="">
+                                <Conditions><Hide><Calculation><Text><![CDATA[Get ( LayoutName ) = "Invoice"]]></Text></Calculation></Hide></Conditions>
+                            </LayoutObject>
+                        </ObjectList>
+                    </Part>
+                </PartsList>
+            </Layout>
+        </LayoutCatalog>
+        <ScriptCatalog>
+            <Script id="1" name="Main"></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[GetLayoutObjectAttribute ( "Invoice" ; "content" ) & "Main" & "Invoice"]]></Text></Calculation></value>
+                                <Name value="$x"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let expected = "Invoices: script Main (ID 1) step 1: layout \"Invoice\"
+Invoices: script Main (ID 1) step 1: script \"Main\"
+Invoices: field Invoices::Label: field \"Invoices::Total\"
+Invoices: layout Invoice: Text (ID 7): layout \"Invoice\"
+";
+        assert_eq!(
+            render(&hardcoded_names(&model), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use anyhow::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::analysis::model::{ExportModel, StepModel};
+use crate::analysis::sarif::{SarifFinding, SarifRule};
+use crate::calc_formatter::{calculation_variables, VariableUse};
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::ReportFormat;
+
+/// Step id of Set Variable
+const SET_VARIABLE: u32 = 141;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// A variable assigned but never read, by its script for `$` or by any script for `$$`
+    UnusedVariable,
+    /// A `$` variable read by a step before any step of its script assigns it
+    ReadBeforeSet,
+}
+
+impl LintRule {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused-variable",
+            Self::ReadBeforeSet => "read-before-set",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LintFinding {
+    pub db: String,
+    pub script_id: u32,
+    pub script: String,
+    /// Step number as shown in FileMaker's script workspace, starting at 1
+    pub step: usize,
+    pub rule: LintRule,
+    pub variable: String,
+}
+
+/// Variable findings of all scripts of an export. Steps are checked in script order, so a
+/// variable assigned at the end of a loop and read at its start counts as read before set.
+/// Disabled steps are ignored. Variables set by custom functions or `Evaluate` and `$$` variables
+/// read by layouts or fields aren't seen.
+pub fn lint(model: &ExportModel) -> Vec<LintFinding> {
+    // Findings with the index of their script
+    let mut findings: Vec<(usize, LintFinding)> = Vec::new();
+    let finding = |script_index: usize, step: usize, rule: LintRule, variable: String| {
+        let script = &model.scripts[script_index];
+        let finding = LintFinding {
+            db: model.db_name.clone(),
+            script_id: script.id,
+            script: script.name.clone(),
+            step,
+            rule,
+            variable,
+        };
+        (script_index, finding)
+    };
+    // First assignment of each `$$` variable, and whether any script reads it
+    let mut globals: HashMap<String, (usize, usize, String, bool)> = HashMap::new();
+
+    for (script_index, script) in model.scripts.iter().enumerate() {
+        // First assignment of each `$` variable, and whether the script reads it
+        let mut locals: HashMap<String, (usize, String, bool)> = HashMap::new();
+        for step in script.steps.iter().filter(|step| step.enabled) {
+            for variable in step_variables(step) {
+                // Variable names are case-insensitive
+                let key = variable.name.to_lowercase();
+                if key.starts_with("$$") {
+                    let global = globals.entry(key).or_insert_with(|| {
+                        (script_index, step.number, variable.name.clone(), false)
+                    });
+                    global.3 |= !variable.assigned;
+                    continue;
+                }
+                match (locals.get_mut(&key), variable.assigned) {
+                    (Some(local), false) => local.2 = true,
+                    (Some(_), true) => {}
+                    (None, true) => {
+                        locals.insert(key, (step.number, variable.name, false));
+                    }
+                    (None, false) => {
+                        let name = variable.name.clone();
+                        findings.push(finding(
+                            script_index,
+                            step.number,
+                            LintRule::ReadBeforeSet,
+                            name,
+                        ));
+                        // Reported once
+                        locals.insert(key, (step.number, variable.name, true));
+                    }
+                }
+            }
+        }
+        for (step, name, read) in locals.into_values() {
+            if !read {
+                findings.push(finding(script_index, step, LintRule::UnusedVariable, name));
+            }
+        }
+    }
+    for (script_index, step, name, read) in globals.into_values() {
+        if !read {
+            findings.push(finding(script_index, step, LintRule::UnusedVariable, name));
+        }
+    }
+
+    findings.sort_by(|(a_index, a), (b_index, b)| {
+        (a_index, a.step, &a.variable).cmp(&(b_index, b.step, &b.variable))
+    });
+    findings.into_iter().map(|(_, finding)| finding).collect()
+}
+
+/// The variables read by the calculations of a step and assigned by the step,
+/// e.g. by Set Variable or a target variable of Insert from URL
+pub fn step_variables(step: &StepModel) -> Vec<VariableUse> {
+    let mut variables: Vec<VariableUse> = step
+        .calculations()
+        .iter()
+        .flat_map(|calculation| calculation_variables(calculation))
+        .collect();
+
+    let mut reader = Reader::from_str(&step.xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(_) | Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => {
+                let assigned = match e.name().as_ref() {
+                    b"Name" if step.id == SET_VARIABLE => parse_unescaped_attribute(&e, "value"),
+                    b"Variable" => parse_unescaped_attribute(&e, "value"),
+                    _ => None,
+                };
+                // The step assigns its variable after evaluating its calculations
+                if let Some(name) = assigned.filter(|name| name.starts_with('$')) {
+                    variables.push(VariableUse {
+                        name,
+                        assigned: true,
+                    });
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    variables
+}
+
+pub const SARIF_RULES: [SarifRule; 2] = [
+    SarifRule {
+        id: "unused-variable",
+        description: "Variable assigned but never read",
+    },
+    SarifRule {
+        id: "read-before-set",
+        description: "Local variable read before its script assigns it",
+    },
+];
+
+pub fn sarif_findings(findings: &[LintFinding]) -> Vec<SarifFinding> {
+    findings
+        .iter()
+        .map(|finding| SarifFinding {
+            rule: finding.rule.label(),
+            level: "warning",
+            message: match finding.rule {
+                LintRule::UnusedVariable => {
+                    format!("{} is assigned but never read", finding.variable)
+                }
+                LintRule::ReadBeforeSet => format!("{} is read before it is set", finding.variable),
+            },
+            db: finding.db.clone(),
+            script_id: finding.script_id,
+            script: finding.script.clone(),
+            step: Some(finding.step),
+        })
+        .collect()
+}
+
+pub fn render(findings: &[LintFinding], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            let mut previous_script = None;
+            for finding in findings {
+                let script = (&finding.db, finding.script_id);
+                if previous_script != Some(script) {
+                    text.push_str(&format!(
+                        "{}: {} (ID {})\n",
+                        finding.db, finding.script, finding.script_id
+                    ));
+                    previous_script = Some(script);
+                }
+                text.push_str(&format!(
+                    "\tstep {}: {} {}\n",
+                    finding.step,
+                    finding.rule.label(),
+                    finding.variable
+                ));
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(findings)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[$count + 1]]></Text></Calculation></value>
+                                <Name value="$Count"></Name>
+                                <repetition><Calculation><Text><![CDATA[1]]></Text></Calculation></repetition>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[Let ( $total = 0 ; $total )]]></Text></Calculation></value>
+                                <Name value="$unused"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="False">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[$disabled]]></Text></Calculation></value>
+                                <Name value="$$flag"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="160" name="Insert from URL" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Target">
+                                <Variable value="$$result"></Variable>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[Get ( AccountName )]]></Text></Calculation></value>
+                                <Name value="$$session"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Print"></ScriptReference>
+                <ObjectList>
+                    <Step id="68" name="If" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Calculation">
+                                <Calculation><Text><![CDATA[$$RESULT = "" or $count]]></Text></Calculation>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    fn findings() -> Vec<LintFinding> {
+        let model = read_model(&export(CATALOGS));
+        lint(&model)
+    }
+
+    #[test]
+    fn test_lint() {
+        let findings: Vec<_> = findings()
+            .into_iter()
+            .map(|finding| {
+                (
+                    finding.script_id,
+                    finding.step,
+                    finding.rule,
+                    finding.variable,
+                )
+            })
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                (1, 1, LintRule::ReadBeforeSet, "$count".to_string()),
+                (1, 2, LintRule::UnusedVariable, "$unused".to_string()),
+                (1, 5, LintRule::UnusedVariable, "$$session".to_string()),
+                (2, 1, LintRule::ReadBeforeSet, "$count".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_text() {
+        let expected = "Invoices: Main (ID 1)
+\tstep 1: read-before-set $count
+\tstep 2: unused-variable $unused
+\tstep 5: unused-variable $$session
+Invoices: Print (ID 2)
+\tstep 1: read-before-set $count
+";
+        assert_eq!(render(&findings(), ReportFormat::Text).unwrap(), expected);
+    }
+}
@@ -0,0 +1,280 @@
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use rayon::prelude::*;
+
+use crate::analysis::broken_refs::broken_references;
+use crate::analysis::callgraph::CallGraph;
+use crate::analysis::complexity::script_metrics;
+use crate::analysis::duplicates::duplicates;
+use crate::analysis::field_usage::field_usage;
+use crate::analysis::full_access::full_access_scripts;
+use crate::analysis::hardcoded_names::hardcoded_names;
+use crate::analysis::lint::lint;
+use crate::analysis::model::ExportModel;
+use crate::analysis::plugins::plugin_usage;
+use crate::analysis::relationship_graph::RelationshipGraph;
+use crate::analysis::sql::sql_queries;
+use crate::analysis::todos::{marker_regex, tasks};
+use crate::analysis::unreferenced::unreferenced;
+use crate::analysis::unused::{calculated_calls, unused_scripts, Allowlist};
+use crate::exit_status::ExitStatus;
+use crate::inputs::{check_unique_db_names, resolve_inputs};
+use crate::{Analysis, AnalysisInput};
+
+pub mod broken_refs;
+pub mod callgraph;
+pub mod complexity;
+pub mod duplicates;
+pub mod field_usage;
+pub mod full_access;
+pub mod graph_xml;
+pub mod hardcoded_names;
+pub mod lint;
+pub mod model;
+pub mod plugins;
+pub mod relationship_graph;
+pub mod sarif;
+pub mod sql;
+#[cfg(test)]
+pub mod test_fixtures;
+pub mod todos;
+pub mod unreferenced;
+pub mod unused;
+
+/// Run an `analyze` subcommand
+pub fn run(analysis: &Analysis) -> Result<ExitStatus, Error> {
+    match analysis {
+        Analysis::Callgraph { input, format } => {
+            let graphs = read_models(input)?
+                .iter()
+                .map(CallGraph::build)
+                .collect::<Vec<_>>();
+            write_output(input, &callgraph::render(&graphs, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::RelationshipGraph { input, format } => {
+            let graphs = read_models(input)?
+                .iter()
+                .map(RelationshipGraph::build)
+                .collect::<Vec<_>>();
+            write_output(input, &relationship_graph::render(&graphs, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::UnusedScripts {
+            input,
+            allowlist,
+            format,
+            exploded,
+        } => {
+            let allowlist = match allowlist {
+                Some(path) => Allowlist::from_file(path)?,
+                None => Allowlist::default(),
+            };
+            let models = read_models(input)?;
+            let graphs = models.iter().map(CallGraph::build).collect::<Vec<_>>();
+            let unused = unused_scripts(&models, &graphs, &allowlist);
+            let calculated = calculated_calls(&graphs);
+            if calculated > 0 {
+                eprintln!(
+                    "Warning: {calculated} steps perform a script by a calculated name, which may use any of these scripts"
+                );
+            }
+            let report = match format.report_format() {
+                Some(format) => unused::render(&unused, format)?,
+                None => sarif::render(
+                    &unused::SARIF_RULES,
+                    &unused::sarif_findings(&unused),
+                    &models,
+                    exploded.as_deref(),
+                )?,
+            };
+            write_output(input, &report)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::BrokenRefs {
+            input,
+            format,
+            exploded,
+            strict,
+        } => {
+            let models = read_models(input)?;
+            let broken = models
+                .iter()
+                .flat_map(broken_references)
+                .collect::<Vec<_>>();
+            let report = match format.report_format() {
+                Some(format) => broken_refs::render(&broken, format)?,
+                None => sarif::render(
+                    &broken_refs::SARIF_RULES,
+                    &broken_refs::sarif_findings(&broken),
+                    &models,
+                    exploded.as_deref(),
+                )?,
+            };
+            write_output(input, &report)?;
+            match *strict && !broken.is_empty() {
+                true => Ok(ExitStatus::Findings),
+                false => Ok(ExitStatus::Clean),
+            }
+        }
+        Analysis::FieldUsage { input, format } => {
+            let usages = read_models(input)?
+                .iter()
+                .flat_map(field_usage)
+                .collect::<Vec<_>>();
+            write_output(input, &field_usage::render(&usages, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::HardcodedNames { input, format } => {
+            let hardcoded = read_models(input)?
+                .iter()
+                .flat_map(hardcoded_names)
+                .collect::<Vec<_>>();
+            write_output(input, &hardcoded_names::render(&hardcoded, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Sql {
+            input,
+            format,
+            strict,
+        } => {
+            let queries = read_models(input)?
+                .iter()
+                .flat_map(sql_queries)
+                .collect::<Vec<_>>();
+            write_output(input, &sql::render(&queries, *format)?)?;
+            match *strict && queries.iter().any(|query| !query.missing.is_empty()) {
+                true => Ok(ExitStatus::Findings),
+                false => Ok(ExitStatus::Clean),
+            }
+        }
+        Analysis::Complexity {
+            input,
+            format,
+            sort,
+        } => {
+            let mut metrics = read_models(input)?
+                .iter()
+                .flat_map(script_metrics)
+                .collect::<Vec<_>>();
+            if let Some(metric) = sort {
+                // Stable, so scripts with the same value stay in script order
+                metrics.sort_by_key(|script| std::cmp::Reverse(script.value(*metric)));
+            }
+            write_output(input, &complexity::render(&metrics, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::FullAccess {
+            input,
+            format,
+            exploded,
+        } => {
+            let models = read_models(input)?;
+            let scripts = models
+                .iter()
+                .flat_map(|model| full_access_scripts(model, &CallGraph::build(model)))
+                .collect::<Vec<_>>();
+            let report = match format.report_format() {
+                Some(format) => full_access::render(&scripts, format)?,
+                None => sarif::render(
+                    &full_access::SARIF_RULES,
+                    &full_access::sarif_findings(&scripts),
+                    &models,
+                    exploded.as_deref(),
+                )?,
+            };
+            write_output(input, &report)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Plugins { input, format } => {
+            let plugins = plugin_usage(&read_models(input)?);
+            write_output(input, &plugins::render(&plugins, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Todos {
+            input,
+            markers,
+            format,
+        } => {
+            let markers = marker_regex(markers)?;
+            let tasks = read_models(input)?
+                .iter()
+                .flat_map(|model| tasks(model, &markers))
+                .collect::<Vec<_>>();
+            write_output(input, &todos::render(&tasks, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Duplicates {
+            input,
+            threshold,
+            min_steps,
+            format,
+        } => {
+            let duplicates = duplicates(&read_models(input)?, *threshold, *min_steps);
+            write_output(input, &duplicates::render(&duplicates, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Unreferenced { input, format } => {
+            let found = read_models(input)?
+                .iter()
+                .flat_map(unreferenced)
+                .collect::<Vec<_>>();
+            write_output(input, &unreferenced::render(&found, *format)?)?;
+            Ok(ExitStatus::Clean)
+        }
+        Analysis::Lint {
+            input,
+            format,
+            exploded,
+            strict,
+        } => {
+            let models = read_models(input)?;
+            let findings = models.iter().flat_map(lint).collect::<Vec<_>>();
+            let report = match format.report_format() {
+                Some(format) => lint::render(&findings, format)?,
+                None => sarif::render(
+                    &lint::SARIF_RULES,
+                    &lint::sarif_findings(&findings),
+                    &models,
+                    exploded.as_deref(),
+                )?,
+            };
+            write_output(input, &report)?;
+            match *strict && !findings.is_empty() {
+                true => Ok(ExitStatus::Findings),
+                false => Ok(ExitStatus::Clean),
+            }
+        }
+    }
+}
+
+/// Read all exports of the input, sorted by path
+fn read_models(input: &AnalysisInput) -> Result<Vec<ExportModel>, Error> {
+    read_exports(&input.inputs)
+}
+
+/// Read all exports of the directories, files or glob patterns, sorted by path
+pub fn read_exports(inputs: &[PathBuf]) -> Result<Vec<ExportModel>, Error> {
+    let paths = resolve_inputs(inputs)?;
+    check_unique_db_names(&paths)?;
+    paths
+        .par_iter()
+        .map(|path| ExportModel::from_export(path))
+        .collect()
+}
+
+/// Write the report to the output file, or to stdout
+pub fn write_output(input: &AnalysisInput, report: &str) -> Result<(), Error> {
+    match &input.output {
+        Some(output_path) if output_path != Path::new("-") => fs::write(output_path, report)
+            .with_context(|| format!("Error writing file {}", output_path.display())),
+        // A closed pipe, e.g. `| head`, is not an error
+        _ => match io::stdout().lock().write_all(report.as_bytes()) {
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+            result => result.context("Error writing to stdout"),
+        },
+    }
+}
@@ -0,0 +1,702 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Error, Result};
+use encoding_rs_io::DecodeReaderBytes;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::config::Flags;
+use crate::inputs::read_db_name;
+use crate::symbols::{id_attribute, SymbolTable};
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::xml_utils::{
+    cdata_element_to_string, end_element_to_string, general_ref_to_string, start_element_to_string,
+    text_element_to_string,
+};
+
+/// The scripts of one export with the raw XML of their steps, read for analysis without exploding it
+#[derive(Debug, Default)]
+pub struct ExportModel {
+    pub db_name: String,
+    pub symbols: SymbolTable,
+    pub scripts: Vec<ScriptModel>,
+    /// References to scripts outside of script steps
+    pub script_uses: Vec<ScriptUse>,
+    pub layout_field_uses: Vec<LayoutFieldUse>,
+    /// Calculations outside of the steps of scripts
+    pub calculations: Vec<CalculationModel>,
+    /// Ids of the scripts with "Run script with full access privileges"
+    pub full_access_scripts: Vec<u32>,
+    /// Ids of the value lists used by layouts and fields; see [`StepModel::value_list_references`]
+    pub value_list_references: Vec<u32>,
+    /// The relationships of the relationship graph
+    pub relationships: Vec<RelationshipModel>,
+    /// The fields of all tables, in the order of the export
+    pub fields: Vec<FieldModel>,
+    pub value_lists: Vec<ValueListModel>,
+}
+
+/// Where a script is used other than by a script step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptUser {
+    /// A button or script trigger of the layout or one of its objects
+    Layout(String),
+    CustomMenu(String),
+    /// A script trigger of the file options
+    FileOptions,
+}
+
+impl fmt::Display for ScriptUser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Layout(layout) => write!(f, "layout {layout}"),
+            Self::CustomMenu(menu) => write!(f, "custom menu {menu}"),
+            Self::FileOptions => write!(f, "file options"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptUse {
+    pub script_id: u32,
+    pub user: ScriptUser,
+}
+
+/// A layout object referring to a field, e.g. a field object or a calculation of a button
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutFieldUse {
+    pub layout: String,
+    /// The object's name, or its type and id
+    pub object: String,
+    pub table_occurrence_id: u32,
+    pub field_id: u32,
+}
+
+/// Where a calculation is defined
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CalculationOwner {
+    ScriptStep {
+        script_id: u32,
+        script: String,
+        step: usize,
+    },
+    /// A calculation, auto-enter or validation calculation of a field
+    Field {
+        table: String,
+        field: String,
+    },
+    /// A layout object, or the layout itself if `object` is empty
+    Layout {
+        layout: String,
+        object: String,
+    },
+    CustomFunction {
+        name: String,
+    },
+    CustomMenu {
+        name: String,
+    },
+}
+
+impl fmt::Display for CalculationOwner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ScriptStep {
+                script_id,
+                script,
+                step,
+            } => write!(f, "script {script} (ID {script_id}) step {step}"),
+            Self::Field { table, field } => write!(f, "field {table}::{field}"),
+            Self::Layout { layout, object } if object.is_empty() => write!(f, "layout {layout}"),
+            Self::Layout { layout, object } => write!(f, "layout {layout}: {object}"),
+            Self::CustomFunction { name } => write!(f, "custom function {name}"),
+            Self::CustomMenu { name } => write!(f, "custom menu {name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalculationModel {
+    pub owner: CalculationOwner,
+    /// The calculation as exported
+    pub text: String,
+}
+
+/// The definition of a field
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FieldModel {
+    /// Name of the base table
+    pub table: String,
+    pub id: u32,
+    pub name: String,
+    /// `Normal`, `Calculated` or `Summary`
+    pub field_type: String,
+    /// E.g. `Text`, `Number` or `Container`
+    pub data_type: String,
+    pub comment: String,
+    pub global: bool,
+    pub repetitions: u32,
+}
+
+/// The definition of a value list
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ValueListModel {
+    pub id: u32,
+    pub name: String,
+    /// `Custom` or `FromField`
+    pub source: String,
+    /// The values of a custom value list
+    pub values: Vec<String>,
+    /// The field of a value list from a field, as `table occurrence::field`
+    pub field: String,
+}
+
+/// A relationship between two table occurrences
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct RelationshipModel {
+    pub id: u32,
+    /// Names of the table occurrences
+    pub left: String,
+    pub right: String,
+    pub predicates: Vec<JoinPredicateModel>,
+}
+
+/// A match condition of a relationship, e.g. `Equal` of two fields
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct JoinPredicateModel {
+    /// The operator as exported, e.g. `Equal`, `Less` or `CartesianProduct`
+    pub operator: String,
+    pub left_field: String,
+    pub right_field: String,
+}
+
+impl fmt::Display for JoinPredicateModel {
+    /// E.g. `ID = ID_Invoice`, with the operator as shown in the relationship graph
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = match self.operator.as_str() {
+            "Equal" => "=",
+            "NotEqual" => "≠",
+            "Less" => "<",
+            "LessOrEqual" => "≤",
+            "Greater" => ">",
+            "GreaterOrEqual" => "≥",
+            "CartesianProduct" => "×",
+            other => other,
+        };
+        write!(f, "{} {operator} {}", self.left_field, self.right_field)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScriptModel {
+    pub id: u32,
+    pub name: String,
+    pub steps: Vec<StepModel>,
+}
+
+#[derive(Debug, Default)]
+pub struct StepModel {
+    /// Step number as shown in FileMaker's script workspace, starting at 1
+    pub number: usize,
+    pub id: u32,
+    pub name: String,
+    pub enabled: bool,
+    /// The `<Step>` element, as passed to the step renderers
+    pub xml: String,
+}
+
+impl ExportModel {
+    pub fn from_export(path: &Path) -> Result<ExportModel, Error> {
+        let db_name = read_db_name(path)
+            .ok_or_else(|| anyhow!("Unsupported XML-format in {}", path.display()))?;
+        let file =
+            File::open(path).with_context(|| format!("Error opening file {}", path.display()))?;
+        let mut reader = Reader::from_reader(BufReader::new(DecodeReaderBytes::new(file)));
+        let mut model = ExportModel::from_reader(&db_name, &mut reader)
+            .with_context(|| format!("Error parsing {}", path.display()))?;
+        model.symbols = SymbolTable::from_export(path)?;
+        Ok(model)
+    }
+
+    /// Read the steps of all scripts and the uses of scripts elsewhere; the symbols are read by a separate pass
+    pub fn from_reader<R: BufRead>(
+        db_name: &str,
+        reader: &mut Reader<R>,
+    ) -> Result<ExportModel, Error> {
+        let mut model = ExportModel {
+            db_name: db_name.to_string(),
+            ..Default::default()
+        };
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut script: Option<ScriptModel> = None;
+        let mut layout_name = String::new();
+        let mut menu_name = String::new();
+        // Labels of the layout objects being read by depth, objects can be nested in groups
+        let mut layout_objects: Vec<(usize, String)> = Vec::new();
+        let mut layout_field: Option<u32> = None;
+        let mut table_name = String::new();
+        let mut field_name = String::new();
+        let mut custom_function_name = String::new();
+        let mut catalog_script_id = None;
+        // Value lists are defined in the catalog, and in newer exports detailed separately
+        let mut value_list: Option<usize> = None;
+
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Eof => break,
+                Event::Start(e) => {
+                    let parent = path.last().map(Vec::as_slice).unwrap_or_default();
+                    match (e.name().as_ref(), parent) {
+                        (b"Script", b"StepsForScripts") => script = Some(ScriptModel::default()),
+                        (b"Script", b"ScriptCatalog" | b"Script") => {
+                            catalog_script_id = id_attribute(&e);
+                        }
+                        (b"Options", b"Script") if path.iter().any(|n| n == b"ScriptCatalog") => {
+                            if let (Some(id), Some("True")) = (
+                                catalog_script_id,
+                                parse_unescaped_attribute(&e, "runwithfullaccess").as_deref(),
+                            ) {
+                                model.full_access_scripts.push(id);
+                            }
+                        }
+                        (b"ScriptReference", b"Script") if script.is_some() => {
+                            let script = script.as_mut().unwrap();
+                            script.id = id_attribute(&e).unwrap_or_default();
+                            script.name = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                        }
+                        (b"ScriptReference", _) => {
+                            let user = if path.iter().any(|name| name == b"LayoutCatalog") {
+                                Some(ScriptUser::Layout(layout_name.clone()))
+                            } else if path.iter().any(|name| name == b"CustomMenuCatalog") {
+                                Some(ScriptUser::CustomMenu(menu_name.clone()))
+                            } else if path.get(1).map(Vec::as_slice) == Some(b"Metadata") {
+                                Some(ScriptUser::FileOptions)
+                            } else {
+                                // E.g. the script privileges of a privilege set
+                                None
+                            };
+                            if let (Some(user), Some(script_id)) = (user, id_attribute(&e)) {
+                                model.script_uses.push(ScriptUse { script_id, user });
+                            }
+                        }
+                        (b"Layout", b"LayoutCatalog" | b"Layout") => {
+                            layout_name = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                        }
+                        (b"LayoutObject", _) => {
+                            let label = match parse_unescaped_attribute(&e, "name") {
+                                Some(name) if !name.is_empty() => name,
+                                _ => format!(
+                                    "{} (ID {})",
+                                    parse_unescaped_attribute(&e, "type").unwrap_or_default(),
+                                    id_attribute(&e).unwrap_or_default()
+                                ),
+                            };
+                            layout_objects.push((path.len(), label));
+                        }
+                        (b"FieldReference", _) if !layout_objects.is_empty() => {
+                            layout_field = id_attribute(&e);
+                        }
+                        (b"TableOccurrenceReference", b"FieldReference")
+                            if !path.iter().any(|name| name == b"PrimaryField") =>
+                        {
+                            if let (Some(field_id), Some(table_occurrence_id), Some((_, object))) =
+                                (layout_field.take(), id_attribute(&e), layout_objects.last())
+                            {
+                                model.layout_field_uses.push(LayoutFieldUse {
+                                    layout: layout_name.clone(),
+                                    object: object.clone(),
+                                    table_occurrence_id,
+                                    field_id,
+                                });
+                            }
+                        }
+                        (b"BaseTableReference", b"FieldCatalog") => {
+                            table_name = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                        }
+                        (b"Field", b"ObjectList") | (b"FieldReference", b"Field")
+                            if path.iter().any(|name| name == b"FieldsForTables") =>
+                        {
+                            if let Some(name) = parse_unescaped_attribute(&e, "name") {
+                                field_name = name;
+                            }
+                            if e.name().as_ref() == b"Field" {
+                                model.fields.push(FieldModel {
+                                    table: table_name.clone(),
+                                    id: id_attribute(&e).unwrap_or_default(),
+                                    name: field_name.clone(),
+                                    field_type: parse_unescaped_attribute(&e, "fieldtype")
+                                        .unwrap_or_default(),
+                                    data_type: parse_unescaped_attribute(&e, "datatype")
+                                        .unwrap_or_default(),
+                                    comment: parse_unescaped_attribute(&e, "comment")
+                                        .unwrap_or_default(),
+                                    global: false,
+                                    repetitions: 1,
+                                });
+                            }
+                        }
+                        (b"Storage", b"Field")
+                            if path.iter().any(|name| name == b"FieldsForTables") =>
+                        {
+                            if let Some(field) = model.fields.last_mut() {
+                                field.global = parse_unescaped_attribute(&e, "global").as_deref()
+                                    == Some("True");
+                                field.repetitions = parse_unescaped_attribute(&e, "maxRepetitions")
+                                    .and_then(|repetitions| repetitions.parse().ok())
+                                    .unwrap_or(1);
+                            }
+                        }
+                        (b"ValueList", b"ValueListCatalog") => {
+                            value_list = Some(model.value_list(
+                                id_attribute(&e).unwrap_or_default(),
+                                parse_unescaped_attribute(&e, "name").unwrap_or_default(),
+                            ));
+                        }
+                        (b"ValueListReference", b"ValueList")
+                            if path.iter().any(|name| name == b"OptionsForValueLists") =>
+                        {
+                            value_list = Some(model.value_list(
+                                id_attribute(&e).unwrap_or_default(),
+                                parse_unescaped_attribute(&e, "name").unwrap_or_default(),
+                            ));
+                        }
+                        (b"Source", b"ValueList") => {
+                            if let Some(index) = value_list {
+                                model.value_lists[index].source =
+                                    parse_unescaped_attribute(&e, "value").unwrap_or_default();
+                            }
+                        }
+                        (b"FieldReference", b"PrimaryField") => {
+                            if let Some(index) = value_list {
+                                model.value_lists[index].field =
+                                    parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                            }
+                        }
+                        (b"TableOccurrenceReference", b"FieldReference")
+                            if path.iter().any(|name| name == b"PrimaryField") =>
+                        {
+                            if let Some(index) = value_list {
+                                let occurrence =
+                                    parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                                let field = &mut model.value_lists[index].field;
+                                *field = format!("{occurrence}::{field}");
+                            }
+                        }
+                        (b"CustomFunctionReference", b"CustomFunctionCalc") => {
+                            custom_function_name =
+                                parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                        }
+                        // Not the definition of a value list or its privileges
+                        (b"ValueListReference", _)
+                            if !path.iter().any(|name| {
+                                name == b"OptionsForValueLists" || name == b"PrivilegeSetsCatalog"
+                            }) =>
+                        {
+                            model.value_list_references.extend(id_attribute(&e));
+                        }
+                        (b"Relationship", b"RelationshipCatalog") => {
+                            model.relationships.push(RelationshipModel {
+                                id: id_attribute(&e).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        }
+                        (b"TableOccurrenceReference", b"LeftTable" | b"RightTable")
+                            if path.iter().any(|name| name == b"RelationshipCatalog") =>
+                        {
+                            if let Some(relationship) = model.relationships.last_mut() {
+                                let name =
+                                    parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                                match parent {
+                                    b"LeftTable" => relationship.left = name,
+                                    _ => relationship.right = name,
+                                }
+                            }
+                        }
+                        (b"JoinPredicate", b"JoinPredicateList") => {
+                            if let Some(relationship) = model.relationships.last_mut() {
+                                relationship.predicates.push(JoinPredicateModel {
+                                    operator: parse_unescaped_attribute(&e, "type")
+                                        .unwrap_or_default(),
+                                    ..Default::default()
+                                });
+                            }
+                        }
+                        (b"FieldReference", b"LeftField" | b"RightField") => {
+                            let predicate = model
+                                .relationships
+                                .last_mut()
+                                .and_then(|relationship| relationship.predicates.last_mut());
+                            if let Some(predicate) = predicate {
+                                let name =
+                                    parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                                match parent {
+                                    b"LeftField" => predicate.left_field = name,
+                                    _ => predicate.right_field = name,
+                                }
+                            }
+                        }
+                        (b"CustomMenu", b"CustomMenuCatalog") => {
+                            menu_name = parse_unescaped_attribute(&e, "name").unwrap_or_default();
+                        }
+                        (b"Step", b"ObjectList") if script.is_some() => {
+                            let script = script.as_mut().unwrap();
+                            script.steps.push(StepModel {
+                                number: script.steps.len() + 1,
+                                id: id_attribute(&e).unwrap_or_default(),
+                                name: parse_unescaped_attribute(&e, "name").unwrap_or_default(),
+                                enabled: parse_unescaped_attribute(&e, "enable").as_deref()
+                                    != Some("False"),
+                                xml: element_xml(reader, &e)?,
+                            });
+                            buf.clear();
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    path.push(e.name().as_ref().to_vec());
+                }
+                Event::CData(e)
+                    if path.ends_with(&[b"CustomValues".to_vec(), b"Text".to_vec()]) =>
+                {
+                    if let Some(index) = value_list {
+                        model.value_lists[index].values = String::from_utf8_lossy(&e)
+                            .split('\r')
+                            .map(str::to_string)
+                            .collect();
+                    }
+                }
+                Event::CData(e) if path.ends_with(&[b"Calculation".to_vec(), b"Text".to_vec()]) => {
+                    let in_catalog = |catalog: &[u8]| path.iter().any(|name| name == catalog);
+                    let owner = if in_catalog(b"LayoutCatalog") {
+                        Some(CalculationOwner::Layout {
+                            layout: layout_name.clone(),
+                            object: layout_objects
+                                .last()
+                                .map(|(_, label)| label.clone())
+                                .unwrap_or_default(),
+                        })
+                    } else if in_catalog(b"FieldsForTables") {
+                        Some(CalculationOwner::Field {
+                            table: table_name.clone(),
+                            field: field_name.clone(),
+                        })
+                    } else if in_catalog(b"CalcsForCustomFunctions") {
+                        Some(CalculationOwner::CustomFunction {
+                            name: custom_function_name.clone(),
+                        })
+                    } else if in_catalog(b"CustomMenuCatalog") {
+                        Some(CalculationOwner::CustomMenu {
+                            name: menu_name.clone(),
+                        })
+                    } else {
+                        // E.g. the record access of a privilege set
+                        None
+                    };
+                    if let Some(owner) = owner {
+                        model.calculations.push(CalculationModel {
+                            owner,
+                            text: String::from_utf8_lossy(&e).to_string(),
+                        });
+                    }
+                }
+                Event::End(e) => {
+                    path.pop();
+                    if layout_objects.last().map(|(depth, _)| *depth) == Some(path.len()) {
+                        layout_objects.pop();
+                    }
+                    if e.name().as_ref() == b"Script"
+                        && path.last().map(Vec::as_slice) == Some(b"StepsForScripts")
+                    {
+                        model.scripts.extend(script.take());
+                    }
+                    if e.name().as_ref() == b"ValueList"
+                        && matches!(
+                            path.last().map(Vec::as_slice),
+                            Some(b"ValueListCatalog" | b"OptionsForValueLists")
+                        )
+                    {
+                        value_list = None;
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(model)
+    }
+
+    /// Index of the value list with the id, added if it wasn't read yet
+    fn value_list(&mut self, id: u32, name: String) -> usize {
+        match self.value_lists.iter().position(|list| list.id == id) {
+            Some(index) => index,
+            None => {
+                self.value_lists.push(ValueListModel {
+                    id,
+                    name,
+                    ..Default::default()
+                });
+                self.value_lists.len() - 1
+            }
+        }
+    }
+
+    /// The calculations of all script steps followed by all other calculations
+    pub fn all_calculations(&self) -> Vec<CalculationModel> {
+        let step_calculations = self.scripts.iter().flat_map(|script| {
+            script.steps.iter().flat_map(|step| {
+                step.calculations()
+                    .into_iter()
+                    .map(|text| CalculationModel {
+                        owner: CalculationOwner::ScriptStep {
+                            script_id: script.id,
+                            script: script.name.clone(),
+                            step: step.number,
+                        },
+                        text,
+                    })
+            })
+        });
+        step_calculations
+            .chain(self.calculations.iter().cloned())
+            .collect()
+    }
+}
+
+impl StepModel {
+    /// The calculations of the step's parameters, in order
+    pub fn calculations(&self) -> Vec<String> {
+        let mut calculations = Vec::new();
+        let mut reader = Reader::from_str(&self.xml);
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(_) | Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => path.push(e.name().as_ref().to_vec()),
+                Ok(Event::End(_)) => {
+                    path.pop();
+                }
+                Ok(Event::CData(e))
+                    if path.ends_with(&[b"Calculation".to_vec(), b"Text".to_vec()]) =>
+                {
+                    calculations.push(String::from_utf8_lossy(&e).to_string());
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+        calculations
+    }
+
+    /// Ids of the value lists the step's parameters refer to, e.g. of a sort order
+    pub fn value_list_references(&self) -> Vec<u32> {
+        let mut references = Vec::new();
+        let mut reader = Reader::from_str(&self.xml);
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(_) | Ok(Event::Eof) => break,
+                Ok(Event::Start(e) | Event::Empty(e))
+                    if e.name().as_ref() == b"ValueListReference" =>
+                {
+                    references.extend(id_attribute(&e));
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+        references
+    }
+}
+
+/// The rest of an element as XML, with the attributes the exploded script files keep
+fn element_xml<R: BufRead>(reader: &mut Reader<R>, start: &BytesStart) -> Result<String, Error> {
+    let flags = Flags::default();
+    let mut content = start_element_to_string(start, &flags);
+    let mut depth = 1;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Start(e) => {
+                depth += 1;
+                content.push_str(&start_element_to_string(&e, &flags));
+            }
+            Event::CData(e) => content.push_str(&cdata_element_to_string(&e)),
+            Event::Text(e) => content.push_str(&text_element_to_string(&e, true)),
+            Event::GeneralRef(e) => content.push_str(&general_ref_to_string(&e, true)),
+            Event::End(e) => {
+                content.push_str(&end_element_to_string(&e));
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::export;
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <ScriptCatalog>
+            <Script id="1" name="Main"></Script>
+            <Script id="2" name="Print">
+                <Options runwithfullaccess="True">0</Options>
+            </Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList membercount="2">
+                    <Step index="0" id="89" name="# (comment)" enable="True"></Step>
+                    <Step index="1" id="1" name="Perform Script" enable="False">
+                        <ParameterValues membercount="1">
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="2" name="Print &amp; send"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Print"></ScriptReference>
+                <ObjectList membercount="0"></ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_from_reader() {
+        let model =
+            ExportModel::from_reader("Invoices", &mut Reader::from_str(&export(CATALOGS))).unwrap();
+        assert_eq!(model.scripts.len(), 2);
+        let main = &model.scripts[0];
+        assert_eq!(main.name, "Main");
+        assert_eq!(main.steps.len(), 2);
+        let step = &main.steps[1];
+        assert_eq!((step.number, step.id, step.enabled), (2, 1, false));
+        assert!(step
+            .xml
+            .starts_with(r#"<Step id="1" name="Perform Script" enable="False">"#));
+        assert!(step.xml.contains(r#"name="Print &amp; send""#));
+        assert!(step.xml.ends_with("</Step>"));
+        assert!(model.scripts[1].steps.is_empty());
+        assert_eq!(model.full_access_scripts, vec![2]);
+    }
+}
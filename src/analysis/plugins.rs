@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::calc_formatter::calculation_functions;
+use crate::ReportFormat;
+
+/// Function name prefixes of common plugins; other plugin functions are grouped by the part of
+/// their name before the first `_`
+const KNOWN_PLUGINS: [(&str, &str); 12] = [
+    ("MBS", "MBS Plugin"),
+    ("BE_", "BaseElements"),
+    ("bBox_", "bBox"),
+    ("zippScript_", "zippScript"),
+    ("TrFile_", "Troi File"),
+    ("TrDialog_", "Troi Dialog"),
+    ("TrText_", "Troi Text"),
+    ("TrPrint_", "Troi Activator"),
+    ("TURL_", "Troi URL"),
+    ("TrSerial_", "Troi Serial"),
+    ("mFMb_", "myFMbutler"),
+    ("SMTP_", "360Works Email"),
+];
+
+/// The functions of one plugin used by the exports
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PluginUsage {
+    pub plugin: String,
+    pub functions: Vec<FunctionUsage>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FunctionUsage {
+    pub name: String,
+    pub calls: usize,
+    /// Where the function is called, e.g. `Invoices: field Invoices::Total`
+    pub used_by: Vec<String>,
+}
+
+/// The plugin a function belongs to, `None` for built-in functions. Built-in functions never
+/// contain `_`, custom functions are told apart by their catalog
+fn plugin_of(function: &str) -> Option<String> {
+    let known = KNOWN_PLUGINS.iter().find(|(prefix, _)| {
+        function.len() >= prefix.len()
+            && function.is_char_boundary(prefix.len())
+            && function[..prefix.len()].eq_ignore_ascii_case(prefix)
+    });
+    match known {
+        Some((_, plugin)) => Some(plugin.to_string()),
+        None => function
+            .split_once('_')
+            .filter(|(prefix, _)| !prefix.is_empty())
+            .map(|(prefix, _)| prefix.to_string()),
+    }
+}
+
+/// Calls of plugin functions in all calculations of the exports, by plugin and function
+pub fn plugin_usage(models: &[ExportModel]) -> Vec<PluginUsage> {
+    let mut plugins: BTreeMap<String, BTreeMap<String, FunctionUsage>> = BTreeMap::new();
+    for model in models {
+        let custom_functions: HashSet<String> = model
+            .calculations
+            .iter()
+            .filter_map(|calculation| match &calculation.owner {
+                CalculationOwner::CustomFunction { name } => Some(name.to_lowercase()),
+                _ => None,
+            })
+            .collect();
+        for calculation in model.all_calculations() {
+            for function in calculation_functions(&calculation.text) {
+                if custom_functions.contains(&function.to_lowercase()) {
+                    continue;
+                }
+                let Some(plugin) = plugin_of(&function) else {
+                    continue;
+                };
+                // Function names are case insensitive, the first spelling found is listed
+                let usage = plugins
+                    .entry(plugin)
+                    .or_default()
+                    .entry(function.to_lowercase())
+                    .or_insert_with(|| FunctionUsage {
+                        name: function,
+                        calls: 0,
+                        used_by: Vec::new(),
+                    });
+                usage.calls += 1;
+                let user = format!("{}: {}", model.db_name, calculation.owner);
+                if !usage.used_by.contains(&user) {
+                    usage.used_by.push(user);
+                }
+            }
+        }
+    }
+
+    plugins
+        .into_iter()
+        .map(|(plugin, functions)| PluginUsage {
+            plugin,
+            functions: functions.into_values().collect(),
+        })
+        .collect()
+}
+
+pub fn render(plugins: &[PluginUsage], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for plugin in plugins {
+                text.push_str(&format!("{}\n", plugin.plugin));
+                for function in &plugin.functions {
+                    let calls = match function.calls {
+                        1 => "1 call".to_string(),
+                        calls => format!("{calls} calls"),
+                    };
+                    text.push_str(&format!("\t{}: {calls}\n", function.name));
+                    for user in &function.used_by {
+                        text.push_str(&format!("\t\t{user}\n"));
+                    }
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(plugins)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <CalcsForCustomFunctions>
+            <ObjectList>
+                <CustomFunctionCalc>
+                    <CustomFunctionReference id="1" name="Format_Date"></CustomFunctionReference>
+                    <Calculation><Text><![CDATA[BE_Version ( ) & MBS ( "Version" )]]></Text></Calculation>
+                </CustomFunctionCalc>
+            </ObjectList>
+        </CalcsForCustomFunctions>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[MBS ( "Text.Upper" ; Format_Date ( 1 ) ) & Acme_Ping ( 1 ) & mbs ( "Version" )]]></Text></Calculation></value>
+                                <Name value="$x"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_plugin_of() {
+        assert_eq!(plugin_of("MBS").as_deref(), Some("MBS Plugin"));
+        assert_eq!(plugin_of("be_FileExists").as_deref(), Some("BaseElements"));
+        assert_eq!(plugin_of("Acme_Ping").as_deref(), Some("Acme"));
+        assert_eq!(plugin_of("JSONGetElement"), None);
+        assert_eq!(plugin_of("_Private"), None);
+    }
+
+    #[test]
+    fn test_render_text() {
+        let models = vec![read_model(&export(CATALOGS))];
+        let expected = "Acme
+\tAcme_Ping: 1 call
+\t\tInvoices: script Main (ID 1) step 1
+BaseElements
+\tBE_Version: 1 call
+\t\tInvoices: custom function Format_Date
+MBS Plugin
+\tMBS: 3 calls
+\t\tInvoices: script Main (ID 1) step 1
+\t\tInvoices: custom function Format_Date
+";
+        assert_eq!(
+            render(&plugin_usage(&models), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::graph_xml::{render_gexf, render_graphml, Graph, GraphEdge, GraphNode};
+use crate::analysis::model::{ExportModel, RelationshipModel};
+use crate::symbols::SymbolKind;
+use crate::GraphFormat;
+
+#[derive(Debug, Serialize)]
+pub struct TableOccurrenceNode {
+    pub name: String,
+    /// Name of the base table, empty for tables of other files
+    pub base_table: String,
+}
+
+/// Table occurrences of one export and the relationships between them
+#[derive(Debug, Serialize)]
+pub struct RelationshipGraph {
+    pub db: String,
+    pub table_occurrences: Vec<TableOccurrenceNode>,
+    pub relationships: Vec<RelationshipModel>,
+}
+
+impl RelationshipGraph {
+    pub fn build(model: &ExportModel) -> Self {
+        let mut table_occurrences: Vec<(u32, TableOccurrenceNode)> = model
+            .symbols
+            .symbols()
+            .filter(|(kind, _, _)| *kind == SymbolKind::TableOccurrence)
+            .map(|(_, id, symbol)| {
+                let base_table = model
+                    .symbols
+                    .base_table_of(id)
+                    .and_then(|table_id| model.symbols.name(SymbolKind::BaseTable, table_id))
+                    .unwrap_or_default();
+                let node = TableOccurrenceNode {
+                    name: symbol.name.clone(),
+                    base_table,
+                };
+                (id, node)
+            })
+            .collect();
+        table_occurrences.sort_by_key(|(id, _)| *id);
+        Self {
+            db: model.db_name.clone(),
+            table_occurrences: table_occurrences
+                .into_iter()
+                .map(|(_, node)| node)
+                .collect(),
+            relationships: model.relationships.clone(),
+        }
+    }
+}
+
+pub fn render(graphs: &[RelationshipGraph], format: GraphFormat) -> Result<String, Error> {
+    match format {
+        GraphFormat::Dot => Ok(render_dot(graphs)),
+        GraphFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(graphs)?)),
+        GraphFormat::Graphml => Ok(render_graphml(&graph(graphs))),
+        GraphFormat::Gexf => Ok(render_gexf(&graph(graphs))),
+    }
+}
+
+/// Render the relationship graphs as undirected Graphviz DOT, with one cluster per export and
+/// the match fields of each relationship as its label
+fn render_dot(graphs: &[RelationshipGraph]) -> String {
+    let mut lines = vec![
+        "graph relationships {".to_string(),
+        "\tnode [shape=box];".to_string(),
+    ];
+    let mut edges = Vec::new();
+    for graph in graphs {
+        lines.push(format!(
+            "\tsubgraph {} {{",
+            quote(&format!("cluster_{}", graph.db))
+        ));
+        lines.push(format!("\t\tlabel={};", quote(&graph.db)));
+        for occurrence in &graph.table_occurrences {
+            lines.push(format!(
+                "\t\t{} [label={}];",
+                quote(&node_id(&graph.db, &occurrence.name)),
+                quote(&occurrence.name)
+            ));
+        }
+        lines.push("\t}".to_string());
+        for relationship in &graph.relationships {
+            edges.push(format!(
+                "\t{} -- {} [label={}];",
+                quote(&node_id(&graph.db, &relationship.left)),
+                quote(&node_id(&graph.db, &relationship.right)),
+                quote(&predicates(relationship))
+            ));
+        }
+    }
+    lines.extend(edges);
+    lines.push("}".to_string());
+    format!("{}\n", lines.join("\n"))
+}
+
+/// The relationship graphs as one graph for GraphML and GEXF, grouped by export, with the base
+/// table of each table occurrence as its category
+fn graph(graphs: &[RelationshipGraph]) -> Graph {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for graph in graphs {
+        for occurrence in &graph.table_occurrences {
+            nodes.push(GraphNode {
+                id: node_id(&graph.db, &occurrence.name),
+                label: occurrence.name.clone(),
+                group: graph.db.clone(),
+                category: occurrence.base_table.clone(),
+            });
+        }
+        for relationship in &graph.relationships {
+            edges.push(GraphEdge {
+                source: node_id(&graph.db, &relationship.left),
+                target: node_id(&graph.db, &relationship.right),
+                label: predicates(relationship),
+                kind: "relationship".to_string(),
+            });
+        }
+    }
+    Graph {
+        name: "relationships",
+        directed: false,
+        nodes,
+        edges,
+    }
+}
+
+/// E.g. `ID = ID_Invoice AND Date < Shipped`
+fn predicates(relationship: &RelationshipModel) -> String {
+    relationship
+        .predicates
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Table occurrence names are unique within their file
+fn node_id(db: &str, table_occurrence: &str) -> String {
+    format!("{db}::{table_occurrence}")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices"></TableOccurrence>
+            <TableOccurrence id="1065090" name="Invoices_Lines"></TableOccurrence>
+        </TableOccurrenceCatalog>
+        <RelationshipCatalog>
+            <Relationship id="5">
+                <LeftTable><TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference></LeftTable>
+                <RightTable><TableOccurrenceReference id="1065090" name="Invoices_Lines"></TableOccurrenceReference></RightTable>
+                <JoinPredicateList>
+                    <JoinPredicate type="Equal">
+                        <LeftField><FieldReference id="1" name="ID"></FieldReference></LeftField>
+                        <RightField><FieldReference id="2" name="ID_Invoice"></FieldReference></RightField>
+                    </JoinPredicate>
+                </JoinPredicateList>
+            </Relationship>
+        </RelationshipCatalog>
+    "#;
+
+    #[test]
+    fn test_render_dot() {
+        let model = read_model(&export(CATALOGS));
+        let expected = r#"graph relationships {
+	node [shape=box];
+	subgraph "cluster_Invoices" {
+		label="Invoices";
+		"Invoices::Invoices" [label="Invoices"];
+		"Invoices::Invoices_Lines" [label="Invoices_Lines"];
+	}
+	"Invoices::Invoices" -- "Invoices::Invoices_Lines" [label="ID = ID_Invoice"];
+}
+"#;
+        assert_eq!(render_dot(&[RelationshipGraph::build(&model)]), expected);
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use serde_json::{json, Value};
+
+use crate::analysis::model::{ExportModel, ScriptModel};
+use crate::config::Flags;
+use crate::diagnostics::Diagnostics;
+use crate::index::element_paths;
+use crate::script_steps::sanitizer::sanitize;
+use crate::symbols::SymbolKind;
+use crate::OutputTree;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A rule of the findings, listed with the tool
+pub struct SarifRule {
+    pub id: &'static str,
+    pub description: &'static str,
+}
+
+/// A finding in a script, reported as a SARIF result
+#[derive(Debug, PartialEq)]
+pub struct SarifFinding {
+    pub rule: &'static str,
+    /// `error`, `warning` or `note`
+    pub level: &'static str,
+    pub message: String,
+    pub db: String,
+    pub script_id: u32,
+    pub script: String,
+    /// Step number, `None` for findings about the whole script
+    pub step: Option<usize>,
+}
+
+/// A SARIF 2.1.0 log of the findings. With the output folder of an explode run of the same
+/// exports, results point to the line of their step in the sanitized script, so code scanning
+/// tools can annotate it; otherwise they only name the database and script.
+pub fn render(
+    rules: &[SarifRule],
+    findings: &[SarifFinding],
+    models: &[ExportModel],
+    exploded: Option<&Path>,
+) -> Result<String, Error> {
+    let mut paths: HashMap<&str, HashMap<u32, String>> = HashMap::new();
+    if let Some(exploded) = exploded {
+        for model in models {
+            paths.insert(
+                &model.db_name,
+                sanitized_script_paths(exploded, &model.db_name),
+            );
+        }
+    }
+    let scripts: HashMap<(&str, u32), &ScriptModel> = models
+        .iter()
+        .flat_map(|model| {
+            model
+                .scripts
+                .iter()
+                .map(|script| ((model.db_name.as_str(), script.id), script))
+        })
+        .collect();
+
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let mut location = json!({
+                "logicalLocations": [{
+                    "fullyQualifiedName": format!("{}/{}", finding.db, finding.script),
+                    "kind": "function",
+                }],
+            });
+            let path = paths
+                .get(finding.db.as_str())
+                .and_then(|paths| paths.get(&finding.script_id));
+            if let Some(path) = path {
+                let line = match (finding.step, scripts.get(&(&finding.db, finding.script_id))) {
+                    (Some(step), Some(script)) => step_line(script, step),
+                    _ => 1,
+                };
+                location["physicalLocation"] = json!({
+                    "artifactLocation": { "uri": path },
+                    "region": { "startLine": line },
+                });
+            }
+            json!({
+                "ruleId": finding.rule,
+                "level": finding.level,
+                "message": { "text": finding.message },
+                "locations": [location],
+            })
+        })
+        .collect();
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+            })
+        })
+        .collect();
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    Ok(format!("{}\n", serde_json::to_string_pretty(&log)?))
+}
+
+/// Paths of the sanitized scripts of a database by script id, joined to the output folder and
+/// separated by `/`. The output tree of the explode run is detected from the folders.
+fn sanitized_script_paths(exploded: &Path, db_name: &str) -> HashMap<u32, String> {
+    for output_tree in [OutputTree::Db, OutputTree::Domain] {
+        let flags = Flags {
+            output_tree,
+            ..Default::default()
+        };
+        let paths: HashMap<u32, String> = element_paths(exploded, db_name, &flags)
+            .into_iter()
+            .filter(|((kind, _), _)| *kind == SymbolKind::Script)
+            .filter_map(|((_, id), paths)| {
+                let path = paths
+                    .into_iter()
+                    .find(|path| path.split('/').any(|folder| folder == "scripts_sanitized"))?;
+                let root = exploded.to_string_lossy().replace('\\', "/");
+                Some((id, format!("{}/{path}", root.trim_end_matches('/'))))
+            })
+            .collect();
+        if !paths.is_empty() {
+            return paths;
+        }
+    }
+    HashMap::new()
+}
+
+/// Line of the sanitized script on which a step starts, counting the lines rendered for the
+/// steps before it
+fn step_line(script: &ScriptModel, step: usize) -> usize {
+    1 + script
+        .steps
+        .iter()
+        .filter(|previous| previous.number < step)
+        .map(|previous| {
+            sanitize(&previous.id, &previous.xml, &mut Diagnostics::default())
+                .map_or(0, |text| text.split('\r').count())
+        })
+        .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="7" name="Print"></ScriptReference>
+                <ObjectList>
+                    <Step id="89" name="# (comment)" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Comment"><Comment value="First&#13;Second"></Comment></Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_render() {
+        let exploded = std::env::temp_dir().join("fm_exploder_sarif_test");
+        let folder = exploded.join("Invoices/scripts_sanitized/Reports - ID 3");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("Print - ID 7.txt"), "").unwrap();
+        let models = vec![read_model(&export(CATALOGS))];
+        let finding = SarifFinding {
+            rule: "unused-variable",
+            level: "warning",
+            message: "$x is assigned but never read".to_string(),
+            db: "Invoices".to_string(),
+            script_id: 7,
+            script: "Print".to_string(),
+            step: Some(3),
+        };
+        let rules = [SarifRule {
+            id: "unused-variable",
+            description: "Variable assigned but never read",
+        }];
+
+        let log: Value =
+            serde_json::from_str(&render(&rules, &[finding], &models, Some(&exploded)).unwrap())
+                .unwrap();
+        fs::remove_dir_all(&exploded).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "unused-variable");
+        let location = &run["results"][0]["locations"][0];
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            format!(
+                "{}/Invoices/scripts_sanitized/Reports - ID 3/Print - ID 7.txt",
+                exploded.to_string_lossy()
+            )
+        );
+        // The comment takes two lines
+        assert_eq!(location["physicalLocation"]["region"]["startLine"], 4);
+        assert_eq!(
+            location["logicalLocations"][0]["fullyQualifiedName"],
+            "Invoices/Print"
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::calc_formatter::first_text_arguments;
+use crate::symbols::SymbolKind;
+use crate::ReportFormat;
+
+/// `ExecuteSQL` in English and German
+const SQL_FUNCTIONS: [&str; 2] = ["executesql", "sqlausführen"];
+
+/// Words of FileMaker's SQL that are neither tables nor columns
+const SQL_KEYWORDS: [&str; 62] = [
+    "all",
+    "and",
+    "any",
+    "as",
+    "asc",
+    "between",
+    "by",
+    "case",
+    "char",
+    "cross",
+    "date",
+    "decimal",
+    "delete",
+    "desc",
+    "distinct",
+    "else",
+    "end",
+    "exists",
+    "false",
+    "fetch",
+    "first",
+    "from",
+    "full",
+    "group",
+    "having",
+    "in",
+    "inner",
+    "insert",
+    "int",
+    "integer",
+    "into",
+    "is",
+    "join",
+    "left",
+    "like",
+    "next",
+    "not",
+    "null",
+    "numeric",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "percent",
+    "right",
+    "row",
+    "rows",
+    "select",
+    "set",
+    "some",
+    "then",
+    "time",
+    "timestamp",
+    "true",
+    "union",
+    "update",
+    "values",
+    "varchar",
+    "when",
+    "where",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlReferenceKind {
+    Table,
+    Field,
+}
+
+/// A table occurrence or field named by a query that doesn't exist
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MissingSqlReference {
+    pub kind: SqlReferenceKind,
+    /// As written in the query, qualified fields with their table
+    pub name: String,
+}
+
+/// An `ExecuteSQL` call and the references of its query missing in the schema
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SqlQuery {
+    pub db: String,
+    pub owner: CalculationOwner,
+    /// The query, `None` if it is calculated and can't be checked
+    pub sql: Option<String>,
+    pub missing: Vec<MissingSqlReference>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SqlToken {
+    /// A name, unquoted or in double quotes
+    Identifier(String),
+    /// Punctuation, operators, numbers, strings and parameters
+    Other(String),
+}
+
+/// The tables and columns named by a query
+#[derive(Debug, Default, PartialEq)]
+struct SqlReferences {
+    tables: Vec<String>,
+    /// Table aliases in lower case with their table
+    aliases: HashMap<String, String>,
+    /// Columns with the table or alias qualifying them
+    columns: Vec<(Option<String>, String)>,
+    /// Names given to result columns by `AS`
+    column_aliases: HashSet<String>,
+}
+
+/// Table occurrences and fields of an export by lower case name
+struct Schema {
+    /// Fields of the base table of each local table occurrence, `None` for other files
+    tables: HashMap<String, Option<HashSet<String>>>,
+}
+
+impl Schema {
+    fn new(model: &ExportModel) -> Self {
+        let mut fields_by_table: HashMap<u32, HashSet<String>> = HashMap::new();
+        for (table_id, _, field) in model.symbols.fields() {
+            fields_by_table
+                .entry(table_id)
+                .or_default()
+                .insert(field.name.to_lowercase());
+        }
+        let tables = model
+            .symbols
+            .symbols()
+            .filter(|(kind, _, _)| *kind == SymbolKind::TableOccurrence)
+            .map(|(_, id, symbol)| {
+                let fields = match model.symbols.is_external_occurrence(id) {
+                    true => None,
+                    false => model.symbols.base_table_of(id).map(|table_id| {
+                        fields_by_table.get(&table_id).cloned().unwrap_or_default()
+                    }),
+                };
+                (symbol.name.to_lowercase(), fields)
+            })
+            .collect();
+        Self { tables }
+    }
+
+    /// The fields of a table; `None` if the table is missing, `Some(None)` if they're unknown
+    fn fields(&self, table: &str) -> Option<Option<&HashSet<String>>> {
+        if is_system_table(table) {
+            return Some(None);
+        }
+        self.tables.get(&table.to_lowercase()).map(Option::as_ref)
+    }
+}
+
+/// The `ExecuteSQL` queries of all calculations of an export, with the table occurrences and
+/// fields they name that don't exist. Calculated queries are listed but not checked.
+pub fn sql_queries(model: &ExportModel) -> Vec<SqlQuery> {
+    let schema = Schema::new(model);
+    let mut queries = Vec::new();
+    for calculation in model.all_calculations() {
+        for sql in first_text_arguments(&calculation.text, &SQL_FUNCTIONS) {
+            let missing = match &sql {
+                Some(sql) => missing_references(&references(&tokenize(sql)), &schema),
+                None => Vec::new(),
+            };
+            queries.push(SqlQuery {
+                db: model.db_name.clone(),
+                owner: calculation.owner.clone(),
+                sql,
+                missing,
+            });
+        }
+    }
+    queries
+}
+
+/// FileMaker's tables describing the schema, e.g. `FileMaker_Fields`
+fn is_system_table(table: &str) -> bool {
+    table.to_lowercase().starts_with("filemaker_")
+}
+
+fn is_keyword(word: &str) -> bool {
+    SQL_KEYWORDS.contains(&word.to_lowercase().as_str())
+}
+
+fn tokenize(sql: &str) -> Vec<SqlToken> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '"' | '\'' => {
+                // Quotes are escaped by doubling them
+                i += 1;
+                while i < chars.len() {
+                    if chars[i] == c && chars.get(i + 1) == Some(&c) {
+                        i += 2;
+                    } else if chars[i] == c {
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+                let content: String = chars[start + 1..i.min(chars.len())].iter().collect();
+                i += 1;
+                tokens.push(match c {
+                    '"' => SqlToken::Identifier(content.replace("\"\"", "\"")),
+                    _ => SqlToken::Other(format!("'{content}'")),
+                });
+            }
+            _ if c.is_alphanumeric() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match c.is_ascii_digit() {
+                    true => SqlToken::Other(word),
+                    false => SqlToken::Identifier(word),
+                });
+            }
+            _ => {
+                i += 1;
+                tokens.push(SqlToken::Other(c.to_string()));
+            }
+        }
+    }
+    tokens
+}
+
+fn references(tokens: &[SqlToken]) -> SqlReferences {
+    let mut references = SqlReferences::default();
+    let identifier = |i: usize| match tokens.get(i) {
+        Some(SqlToken::Identifier(name)) if !is_keyword(name) => Some(name.clone()),
+        _ => None,
+    };
+    let is_other = |i: usize, text: &str| tokens.get(i) == Some(&SqlToken::Other(text.to_string()));
+    let is_word = |i: usize, word: &str| matches!(tokens.get(i), Some(SqlToken::Identifier(name)) if name.eq_ignore_ascii_case(word));
+
+    let mut i = 0;
+    while i < tokens.len() {
+        // Lists of tables
+        if ["from", "join", "update", "into"]
+            .iter()
+            .any(|keyword| is_word(i, keyword))
+        {
+            i += 1;
+            while let Some(table) = identifier(i) {
+                i += 1;
+                if is_word(i, "as") {
+                    i += 1;
+                }
+                if let Some(alias) = identifier(i) {
+                    references
+                        .aliases
+                        .insert(alias.to_lowercase(), table.clone());
+                    i += 1;
+                }
+                references.tables.push(table);
+                if !is_other(i, ",") {
+                    break;
+                }
+                i += 1;
+            }
+            continue;
+        }
+        if is_word(i, "as") {
+            if let Some(alias) = identifier(i + 1) {
+                references.column_aliases.insert(alias.to_lowercase());
+            }
+            i += 2;
+            continue;
+        }
+        let Some(name) = identifier(i) else {
+            i += 1;
+            continue;
+        };
+        if is_other(i + 1, "(") {
+            // A function
+            i += 1;
+        } else if is_other(i + 1, ".") {
+            if let Some(column) = identifier(i + 2) {
+                references.columns.push((Some(name), column));
+            }
+            i += 3;
+        } else {
+            references.columns.push((None, name));
+            i += 1;
+        }
+    }
+    references
+}
+
+fn missing_references(references: &SqlReferences, schema: &Schema) -> Vec<MissingSqlReference> {
+    let mut missing = Vec::new();
+    for table in &references.tables {
+        if schema.fields(table).is_none() {
+            missing.push(MissingSqlReference {
+                kind: SqlReferenceKind::Table,
+                name: table.clone(),
+            });
+        }
+    }
+
+    let table_fields: Vec<Option<Option<&HashSet<String>>>> = references
+        .tables
+        .iter()
+        .map(|table| schema.fields(table))
+        .collect();
+    for (qualifier, column) in &references.columns {
+        let key = column.to_lowercase();
+        let exists = match qualifier {
+            Some(qualifier) => {
+                let table = references
+                    .aliases
+                    .get(&qualifier.to_lowercase())
+                    .unwrap_or(qualifier);
+                match schema.fields(table) {
+                    Some(Some(fields)) => fields.contains(&key),
+                    // A missing table is reported once, and fields of other files are unknown
+                    _ => true,
+                }
+            }
+            None => {
+                references.column_aliases.contains(&key)
+                    || table_fields.iter().any(|fields| match fields {
+                        Some(Some(fields)) => fields.contains(&key),
+                        _ => true,
+                    })
+                    || table_fields.is_empty()
+            }
+        };
+        if !exists {
+            let name = match qualifier {
+                Some(qualifier) => format!("{qualifier}.{column}"),
+                None => column.clone(),
+            };
+            missing.push(MissingSqlReference {
+                kind: SqlReferenceKind::Field,
+                name,
+            });
+        }
+    }
+    missing
+}
+
+pub fn render(queries: &[SqlQuery], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for query in queries {
+                let sql = match &query.sql {
+                    Some(sql) => sql.replace(['\r', '\n'], " "),
+                    None => "(calculated query, not checked)".to_string(),
+                };
+                text.push_str(&format!("{}: {}: {sql}\n", query.db, query.owner));
+                for missing in &query.missing {
+                    let kind = match missing.kind {
+                        SqlReferenceKind::Table => "table",
+                        SqlReferenceKind::Field => "field",
+                    };
+                    text.push_str(&format!("\tmissing {kind} \"{}\"\n", missing.name));
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(queries)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+            <TableOccurrence id="1065090" name="Invoices Open" type="Local">
+                <BaseTableSourceReference type="BaseTableReference">
+                    <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                </BaseTableSourceReference>
+            </TableOccurrence>
+        </TableOccurrenceCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total"></Field>
+                    <Field id="2" name="Customer ID"></Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[ExecuteSQL ( "SELECT SUM(i.Total) AS sum, \"Customer ID\" FROM \"Invoices Open\" i WHERE i.Totl > ? ORDER BY sum" ; "" ; "" ; 0 )]]></Text></Calculation></value>
+                                <Name value="$sum"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[ExecuteSQL ( "SELECT Name FROM Customers, Invoices WHERE Invoices.Total = 'x''y'" ; "" ; "" ) & ExecuteSQL ( $sql ; "" ; "" )]]></Text></Calculation></value>
+                                <Name value="$names"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[SQLAusführen ( "SELECT FieldName FROM FileMaker_Fields WHERE TableName = ?" ; "" ; "" ; "Invoices" )]]></Text></Calculation></value>
+                                <Name value="$fields"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_references() {
+        let references = references(&tokenize(
+            "SELECT a.x, COUNT(*) AS n FROM A a JOIN \"B c\" AS b ON a.id = b.id WHERE y = 'it''s'",
+        ));
+        assert_eq!(references.tables, vec!["A", "B c"]);
+        assert_eq!(references.aliases.get("b").map(String::as_str), Some("B c"));
+        assert_eq!(
+            references.columns,
+            vec![
+                (Some("a".to_string()), "x".to_string()),
+                (Some("a".to_string()), "id".to_string()),
+                (Some("b".to_string()), "id".to_string()),
+                (None, "y".to_string()),
+            ]
+        );
+        assert!(references.column_aliases.contains("n"));
+    }
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let expected = "Invoices: script Main (ID 1) step 1: SELECT SUM(i.Total) AS sum, \"Customer ID\" FROM \"Invoices Open\" i WHERE i.Totl > ? ORDER BY sum
+\tmissing field \"i.Totl\"
+Invoices: script Main (ID 1) step 2: SELECT Name FROM Customers, Invoices WHERE Invoices.Total = 'x''y'
+\tmissing table \"Customers\"
+Invoices: script Main (ID 1) step 2: (calculated query, not checked)
+Invoices: script Main (ID 1) step 3: SELECT FieldName FROM FileMaker_Fields WHERE TableName = ?
+";
+        assert_eq!(
+            render(&sql_queries(&model), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use quick_xml::Reader;
+
+use crate::analysis::model::ExportModel;
+use crate::symbols::SymbolTable;
+
+/// An export of `Invoices.fmp12` adding the given catalogs to its structure
+pub fn export(catalogs: &str) -> String {
+    export_with_metadata(catalogs, "")
+}
+
+/// An export of `Invoices.fmp12` adding the given catalogs and file metadata, e.g. its script triggers
+pub fn export_with_metadata(catalogs: &str, metadata: &str) -> String {
+    format!(
+        r#"<FMSaveAsXML File="Invoices.fmp12">
+            <Structure><AddAction>{catalogs}</AddAction></Structure>
+            <Metadata><AddAction>{metadata}</AddAction></Metadata>
+        </FMSaveAsXML>"#
+    )
+}
+
+/// The model of an export with its symbols, read like `ExportModel::from_export` does
+pub fn read_model(export: &str) -> ExportModel {
+    let mut model = ExportModel::from_reader("Invoices", &mut Reader::from_str(export)).unwrap();
+    model.symbols = SymbolTable::from_reader(&mut Reader::from_str(export));
+    model
+}
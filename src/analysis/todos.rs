@@ -0,0 +1,149 @@
+use anyhow::{Context, Error, Result};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::calc_formatter::calculation_comments;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::sanitizer::sanitize;
+use crate::ReportFormat;
+
+/// A comment line containing one of the markers
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Task {
+    pub db: String,
+    pub owner: CalculationOwner,
+    pub marker: String,
+    /// The line of the comment, without comment delimiters
+    pub text: String,
+}
+
+/// Regex finding any of the markers as a whole word, case sensitive
+pub fn marker_regex(markers: &[String]) -> Result<Regex, Error> {
+    let alternatives: Vec<String> = markers
+        .iter()
+        .filter(|marker| !marker.is_empty())
+        .map(|marker| regex::escape(marker))
+        .collect();
+    let pattern = format!(r"\b({})\b", alternatives.join("|"));
+    Regex::new(&pattern).with_context(|| format!("Invalid markers '{}'", markers.join(",")))
+}
+
+/// Lines of comment steps and of calculation comments containing a marker, in script and step
+/// order followed by the comments of other calculations
+pub fn tasks(model: &ExportModel, markers: &Regex) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut push_tasks = |owner: &CalculationOwner, comment: &str| {
+        for line in comment.split(['\r', '\n']) {
+            let text = line
+                .trim()
+                .trim_start_matches("//")
+                .trim_start_matches("/*")
+                .trim_end_matches("*/")
+                .trim();
+            if let Some(marker) = markers.find(text) {
+                tasks.push(Task {
+                    db: model.db_name.clone(),
+                    owner: owner.clone(),
+                    marker: marker.as_str().to_string(),
+                    text: text.to_string(),
+                });
+            }
+        }
+    };
+
+    for script in &model.scripts {
+        for step in &script.steps {
+            let owner = CalculationOwner::ScriptStep {
+                script_id: script.id,
+                script: script.name.clone(),
+                step: step.number,
+            };
+            if id_to_script_step(&step.id) == ScriptStep::Comment {
+                let rendered = sanitize(&step.id, &step.xml, &mut Diagnostics::default());
+                let comment = rendered.unwrap_or_default();
+                push_tasks(&owner, comment.strip_prefix("# ").unwrap_or(&comment));
+                continue;
+            }
+            for calculation in step.calculations() {
+                for comment in calculation_comments(&calculation) {
+                    push_tasks(&owner, &comment);
+                }
+            }
+        }
+    }
+    for calculation in &model.calculations {
+        for comment in calculation_comments(&calculation.text) {
+            push_tasks(&calculation.owner, &comment);
+        }
+    }
+    tasks
+}
+
+pub fn render(tasks: &[Task], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => Ok(tasks
+            .iter()
+            .map(|task| format!("{}: {}: {}\n", task.db, task.owner, task.text))
+            .collect()),
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(tasks)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total">
+                        <Calculation><Text><![CDATA[Sum ( Lines::Amount ) /* FIXME: rounding */]]></Text></Calculation>
+                    </Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="89" name="# (comment)" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Comment">
+                                <Comment value="Sends the invoice&#13;TODO handle errors&#13;No todos here, TODOS neither"></Comment>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[Get ( ScriptParameter ) // HACK until 2.0]]></Text></Calculation></value>
+                                <Name value="$x"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let markers =
+            marker_regex(&["TODO".to_string(), "FIXME".to_string(), "HACK".to_string()]).unwrap();
+        let expected = "Invoices: script Main (ID 1) step 1: TODO handle errors
+Invoices: script Main (ID 1) step 2: HACK until 2.0
+Invoices: field Invoices::Total: FIXME: rounding
+";
+        assert_eq!(
+            render(&tasks(&model, &markers), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
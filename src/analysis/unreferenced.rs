@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::calc_formatter::{calculation_words, text_arguments};
+use crate::symbols::SymbolKind;
+use crate::ReportFormat;
+
+/// Functions naming a value list by their second argument
+const VALUE_LIST_FUNCTIONS: [&str; 2] = ["valuelistitems", "valuelistids"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnreferencedKind {
+    CustomFunction,
+    ValueList,
+}
+
+impl UnreferencedKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::CustomFunction => "custom function",
+            Self::ValueList => "value list",
+        }
+    }
+}
+
+/// A custom function or value list of an export that nothing uses
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Unreferenced {
+    pub db: String,
+    pub kind: UnreferencedKind,
+    pub name: String,
+}
+
+/// Custom functions not called by any calculation, directly or through other used custom
+/// functions, followed by value lists not used by layouts, fields, script steps or
+/// `ValueListItems` and `ValueListIDs`, both in catalog order
+pub fn unreferenced(model: &ExportModel) -> Vec<Unreferenced> {
+    let mut found = Vec::new();
+    let all_calculations = model.all_calculations();
+
+    let custom_functions: Vec<(&str, &str)> = model
+        .calculations
+        .iter()
+        .filter_map(|calculation| match &calculation.owner {
+            CalculationOwner::CustomFunction { name } => {
+                Some((name.as_str(), calculation.text.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let by_name: HashMap<String, usize> = custom_functions
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| (name.to_lowercase(), index))
+        .collect();
+    // Custom functions without parameters are called without parentheses, so any name counts
+    let called = |text: &str| -> Vec<usize> {
+        calculation_words(text)
+            .iter()
+            .filter_map(|word| by_name.get(&word.to_lowercase()).copied())
+            .collect()
+    };
+    let mut used = vec![false; custom_functions.len()];
+    let mut queue: VecDeque<usize> = all_calculations
+        .iter()
+        .filter(|calculation| !matches!(calculation.owner, CalculationOwner::CustomFunction { .. }))
+        .flat_map(|calculation| called(&calculation.text))
+        .collect();
+    while let Some(index) = queue.pop_front() {
+        if !used[index] {
+            used[index] = true;
+            queue.extend(called(custom_functions[index].1));
+        }
+    }
+    for ((name, _), used) in custom_functions.iter().zip(used) {
+        if !used {
+            found.push(Unreferenced {
+                db: model.db_name.clone(),
+                kind: UnreferencedKind::CustomFunction,
+                name: name.to_string(),
+            });
+        }
+    }
+
+    let mut used_ids: HashSet<u32> = model.value_list_references.iter().copied().collect();
+    for script in &model.scripts {
+        for step in &script.steps {
+            used_ids.extend(step.value_list_references());
+        }
+    }
+    let used_names: HashSet<String> = all_calculations
+        .iter()
+        .flat_map(|calculation| text_arguments(&calculation.text, &VALUE_LIST_FUNCTIONS, 1))
+        .flatten()
+        .map(|name| name.to_lowercase())
+        .collect();
+    for (kind, id, symbol) in model.symbols.symbols() {
+        if kind == SymbolKind::ValueList
+            && !used_ids.contains(&id)
+            && !used_names.contains(&symbol.name.to_lowercase())
+        {
+            found.push(Unreferenced {
+                db: model.db_name.clone(),
+                kind: UnreferencedKind::ValueList,
+                name: symbol.name.clone(),
+            });
+        }
+    }
+    found
+}
+
+pub fn render(found: &[Unreferenced], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => Ok(found
+            .iter()
+            .map(|item| format!("{}: {} {}\n", item.db, item.kind.label(), item.name))
+            .collect()),
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(found)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <ValueListCatalog>
+            <ValueList id="1" name="Status"></ValueList>
+            <ValueList id="2" name="Countries"></ValueList>
+            <ValueList id="3" name="Sort order"></ValueList>
+            <ValueList id="4" name="Unused"></ValueList>
+        </ValueListCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Status">
+                        <Validation><ValueListReference id="1" name="Status"></ValueListReference></Validation>
+                        <Calculation><Text><![CDATA[_kTaxRate * 2]]></Text></Calculation>
+                    </Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <OptionsForValueLists>
+            <ValueList><ValueListReference id="4" name="Unused"></ValueListReference></ValueList>
+        </OptionsForValueLists>
+        <CalcsForCustomFunctions>
+            <ObjectList>
+                <CustomFunctionCalc>
+                    <CustomFunctionReference id="1" name="_kTaxRate"></CustomFunctionReference>
+                    <Calculation><Text><![CDATA[Round ( 0.19 ; 2 )]]></Text></Calculation>
+                </CustomFunctionCalc>
+                <CustomFunctionCalc>
+                    <CustomFunctionReference id="2" name="Countries"></CustomFunctionReference>
+                    <Calculation><Text><![CDATA[ValueListItems ( "" ; "Countries" )]]></Text></Calculation>
+                </CustomFunctionCalc>
+                <CustomFunctionCalc>
+                    <CustomFunctionReference id="3" name="Recursive"></CustomFunctionReference>
+                    <Calculation><Text><![CDATA[Recursive ( n - 1 )]]></Text></Calculation>
+                </CustomFunctionCalc>
+            </ObjectList>
+        </CalcsForCustomFunctions>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Sort"></ScriptReference>
+                <ObjectList>
+                    <Step id="39" name="Sort Records" enable="True">
+                        <ParameterValues>
+                            <Parameter type="SortSpecification">
+                                <SortSpecification><SortList><Sort type="Custom">
+                                    <ValueListReference id="3" name="Sort order"></ValueListReference>
+                                </Sort></SortList></SortSpecification>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let expected = "Invoices: custom function Countries
+Invoices: custom function Recursive
+Invoices: value list Unused
+";
+        assert_eq!(
+            render(&unreferenced(&model), ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
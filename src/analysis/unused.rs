@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Error, Result};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::analysis::callgraph::CallGraph;
+use crate::analysis::model::ExportModel;
+use crate::analysis::sarif::{SarifFinding, SarifRule};
+use crate::ReportFormat;
+
+/// Scripts called from outside the analyzed exports, e.g. via the Data API or by
+/// Perform Script on Server from other files.
+///
+/// One script per line: a name, a name pattern with `*`, or `ID <id>`.
+/// Lines after a `[Database]` header only apply to that database, `#` starts a comment.
+#[derive(Debug, Default)]
+pub struct Allowlist {
+    entries: Vec<AllowlistEntry>,
+}
+
+#[derive(Debug)]
+struct AllowlistEntry {
+    db: Option<String>,
+    pattern: ScriptPattern,
+}
+
+#[derive(Debug)]
+enum ScriptPattern {
+    Id(u32),
+    Name(Regex),
+}
+
+impl Allowlist {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Error reading file {}", path.display()))?;
+        Ok(Self::parse(&text))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let id_pattern = Regex::new(r"^ID (\d+)$").unwrap();
+        let mut db = None;
+        let mut entries = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                db = Some(header.to_string());
+                continue;
+            }
+            let pattern = match id_pattern.captures(line) {
+                Some(captures) => ScriptPattern::Id(captures[1].parse().unwrap()),
+                None => {
+                    let parts: Vec<String> = line.split('*').map(regex::escape).collect();
+                    ScriptPattern::Name(Regex::new(&format!("^{}$", parts.join(".*"))).unwrap())
+                }
+            };
+            entries.push(AllowlistEntry {
+                db: db.clone(),
+                pattern,
+            });
+        }
+        Self { entries }
+    }
+
+    pub fn contains(&self, db: &str, id: u32, name: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.db.as_deref().is_none_or(|entry_db| entry_db == db)
+                && match &entry.pattern {
+                    ScriptPattern::Id(entry_id) => *entry_id == id,
+                    ScriptPattern::Name(regex) => regex.is_match(name),
+                }
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct UnusedScript {
+    pub db: String,
+    pub id: u32,
+    pub name: String,
+}
+
+/// Scripts that are neither called by a script step of another script nor used by a layout,
+/// custom menu or the file options of the exports, and not in the allowlist
+pub fn unused_scripts(
+    models: &[ExportModel],
+    graphs: &[CallGraph],
+    allowlist: &Allowlist,
+) -> Vec<UnusedScript> {
+    let mut used: HashSet<(&str, u32)> = HashSet::new();
+    for (model, graph) in models.iter().zip(graphs) {
+        for call in &graph.calls {
+            let Some(script_id) = call.script_id else {
+                continue;
+            };
+            match &call.file {
+                Some(file) => {
+                    used.insert((file.as_str(), script_id));
+                }
+                None if script_id != call.caller => {
+                    used.insert((model.db_name.as_str(), script_id));
+                }
+                None => {} // Recursion
+            }
+        }
+        for script_use in &model.script_uses {
+            used.insert((model.db_name.as_str(), script_use.script_id));
+        }
+    }
+
+    models
+        .iter()
+        .flat_map(|model| {
+            model
+                .scripts
+                .iter()
+                .filter(|script| !used.contains(&(model.db_name.as_str(), script.id)))
+                .filter(|script| !allowlist.contains(&model.db_name, script.id, &script.name))
+                .map(|script| UnusedScript {
+                    db: model.db_name.clone(),
+                    id: script.id,
+                    name: script.name.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Number of Perform Script steps calling a script by a calculated name, which may use any script
+pub fn calculated_calls(graphs: &[CallGraph]) -> usize {
+    graphs
+        .iter()
+        .flat_map(|graph| &graph.calls)
+        .filter(|call| call.script_id.is_none())
+        .count()
+}
+
+pub const SARIF_RULES: [SarifRule; 1] = [SarifRule {
+    id: "unused-script",
+    description: "Script neither performed by other scripts nor used by layouts, custom menus or the file options",
+}];
+
+pub fn sarif_findings(unused: &[UnusedScript]) -> Vec<SarifFinding> {
+    unused
+        .iter()
+        .map(|script| SarifFinding {
+            rule: "unused-script",
+            level: "note",
+            message: format!("Script {} is not used", script.name),
+            db: script.db.clone(),
+            script_id: script.id,
+            script: script.name.clone(),
+            step: None,
+        })
+        .collect()
+}
+
+pub fn render(unused: &[UnusedScript], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => Ok(unused
+            .iter()
+            .map(|script| format!("{}: {} (ID {})\n", script.db, script.name, script.id))
+            .collect()),
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(unused)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export_with_metadata, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice">
+                <ScriptTriggers>
+                    <ScriptTrigger type="OnRecordLoad">
+                        <ScriptReference id="4" name="Load"></ScriptReference>
+                    </ScriptTrigger>
+                </ScriptTriggers>
+            </Layout>
+        </LayoutCatalog>
+        <PrivilegeSetsCatalog>
+            <Custom><ObjectList><Script><ScriptReference id="5" name="Unused"></ScriptReference></Script></ObjectList></Custom>
+        </PrivilegeSetsCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="2" name="Print"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="1" name="Perform Script" enable="True">
+                        <ParameterValues>
+                            <Parameter type="List">
+                                <List name="From list" value="1">
+                                    <ScriptReference id="1" name="Main"></ScriptReference>
+                                </List>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+            <Script><ScriptReference id="2" name="Print"></ScriptReference><ObjectList></ObjectList></Script>
+            <Script><ScriptReference id="3" name="API - Create"></ScriptReference><ObjectList></ObjectList></Script>
+            <Script><ScriptReference id="4" name="Load"></ScriptReference><ObjectList></ObjectList></Script>
+            <Script><ScriptReference id="5" name="Unused"></ScriptReference><ObjectList></ObjectList></Script>
+        </StepsForScripts>
+    "#;
+
+    const METADATA: &str = r#"
+        <ScriptTriggers>
+            <ScriptTrigger type="OnFirstWindowOpen">
+                <ScriptReference id="1" name="Main"></ScriptReference>
+            </ScriptTrigger>
+        </ScriptTriggers>
+    "#;
+
+    fn unused(allowlist: &Allowlist) -> Vec<String> {
+        let models = vec![read_model(&export_with_metadata(CATALOGS, METADATA))];
+        let graphs: Vec<CallGraph> = models.iter().map(CallGraph::build).collect();
+        unused_scripts(&models, &graphs, allowlist)
+            .into_iter()
+            .map(|script| script.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_scripts() {
+        assert_eq!(
+            unused(&Allowlist::default()),
+            vec!["API - Create", "Unused"]
+        );
+        let allowlist = Allowlist::parse("# Data API\n[Invoices]\nAPI - *\n[Other]\nUnused\n");
+        assert_eq!(unused(&allowlist), vec!["Unused"]);
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let allowlist = Allowlist::parse("ID 12\nPrint (*)\n[Archive]\nCleanup\n");
+        assert!(allowlist.contains("Invoices", 12, "Anything"));
+        assert!(allowlist.contains("Invoices", 1, "Print (PDF)"));
+        assert!(!allowlist.contains("Invoices", 1, "Print PDF"));
+        assert!(allowlist.contains("Archive", 3, "Cleanup"));
+        assert!(!allowlist.contains("Invoices", 3, "Cleanup"));
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Error, Result};
+use serde::Serialize;
+
+use crate::exit_status::ExitStatus;
+use crate::manifest::element_id_from_path;
+use crate::utils::file_utils::{collect_files, escape_filename};
+use crate::ReportFormat;
+
+/// Folder of the rendered scripts in both output trees
+const SANITIZED_SCRIPTS_FOLDER: &str = "scripts_sanitized";
+
+const UNCOMMITTED_AUTHOR: &str = "Not Committed Yet";
+
+/// A line of a rendered script with the commit that last changed it
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AnnotatedLine {
+    pub line: usize,
+    /// `None` for lines not committed yet
+    pub commit: Option<CommitInfo>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    /// Author date as `YYYY-MM-DD`
+    pub date: String,
+    /// First line of the commit message, e.g. the export of a `--git-commit` run
+    pub summary: String,
+}
+
+/// Run the `annotate` subcommand: blame the rendered text of a script in a git repository
+pub fn run(
+    script: &str,
+    out_dir: &Path,
+    db_name: Option<&str>,
+    format: ReportFormat,
+) -> Result<ExitStatus, Error> {
+    let path = find_script(out_dir, script, db_name)?;
+    let lines = annotate(&path)?;
+    let report = render(&lines, format)?;
+    // A closed pipe, e.g. `| head`, is not an error
+    match io::stdout().lock().write_all(report.as_bytes()) {
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => {}
+        result => result.context("Error writing to stdout")?,
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// The rendered text file of a script given by name or as `ID <id>`, in the `scripts_sanitized`
+/// folder of any database of the output folder, or only of the given database
+fn find_script(out_dir: &Path, script: &str, db_name: Option<&str>) -> Result<PathBuf, Error> {
+    let mut files = Vec::new();
+    collect_files(out_dir, &mut files);
+    let id = script.strip_prefix("ID ").map(str::trim);
+    let file_name = escape_filename(script);
+    let mut matches: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("txt"))
+        .filter(|path| {
+            let components = path
+                .strip_prefix(out_dir)
+                .unwrap_or(path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            components.iter().any(|c| c == SANITIZED_SCRIPTS_FOLDER)
+                && db_name.is_none_or(|db_name| components.iter().any(|c| c == db_name))
+        })
+        .filter(|path| match id {
+            Some(id) => element_id_from_path(path).as_deref() == Some(id),
+            None => path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.rsplit_once(" - ID "))
+                .is_some_and(|(name, _)| name == file_name),
+        })
+        .collect();
+    matches.sort();
+    match matches.len() {
+        0 => Err(anyhow!(
+            "No rendered script '{script}' found in {}",
+            out_dir.display()
+        )),
+        1 => Ok(matches.remove(0)),
+        _ => Err(anyhow!(
+            "Script '{script}' is ambiguous, pass --db or the script's ID:\n{}",
+            matches
+                .iter()
+                .map(|path| format!("  {}", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+/// Each line of the file with the commit that last changed it, from `git blame`
+fn annotate(path: &Path) -> Result<Vec<AnnotatedLine>, Error> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let file_name = path.file_name().unwrap_or_default();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--porcelain", "--"])
+        .arg(file_name)
+        .output()
+        .context("Error running git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git blame failed: {}; annotate needs an output folder in a git repository, e.g. exploded with --git-commit",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let blamed = parse_porcelain(&String::from_utf8_lossy(&output.stdout));
+
+    let mut hashes = blamed
+        .iter()
+        .map(|(hash, _)| hash.as_str())
+        .filter(|hash| !is_uncommitted(hash))
+        .collect::<Vec<_>>();
+    hashes.sort();
+    hashes.dedup();
+    let commits = commit_infos(dir, &hashes)?;
+
+    Ok(blamed
+        .iter()
+        .enumerate()
+        .map(|(index, (hash, text))| AnnotatedLine {
+            line: index + 1,
+            commit: commits.get(hash).cloned(),
+            text: text.clone(),
+        })
+        .collect())
+}
+
+/// The commit hash and text of each line of the output of `git blame --porcelain`
+fn parse_porcelain(output: &str) -> Vec<(String, String)> {
+    let mut lines = Vec::new();
+    let mut hash = "";
+    for line in output.lines() {
+        match line.strip_prefix('\t') {
+            Some(text) => lines.push((hash.to_string(), text.to_string())),
+            // The header of each line starts with the hash and the line numbers
+            None => {
+                let mut words = line.split(' ');
+                if let (Some(first), Some(second)) = (words.next(), words.next()) {
+                    if first.len() >= 40 && second.parse::<usize>().is_ok() {
+                        hash = first;
+                    }
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Lines changed in the working tree are blamed on the all-zero hash
+fn is_uncommitted(hash: &str) -> bool {
+    hash.bytes().all(|byte| byte == b'0')
+}
+
+fn commit_infos(dir: &Path, hashes: &[&str]) -> Result<HashMap<String, CommitInfo>, Error> {
+    if hashes.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "show",
+            "--no-patch",
+            "--date=short",
+            "--format=%H%x09%h%x09%an%x09%ad%x09%s",
+        ])
+        .args(hashes)
+        .output()
+        .context("Error running git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git show failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\t');
+            let full_hash = fields.next()?.to_string();
+            let info = CommitInfo {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                summary: fields.next().unwrap_or_default().to_string(),
+            };
+            Some((full_hash, info))
+        })
+        .collect())
+}
+
+/// Lines like `git blame`: hash, date, author and line number, then the text
+pub fn render(lines: &[AnnotatedLine], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let commits = lines.iter().filter_map(|line| line.commit.as_ref());
+            let hash_width = commits
+                .clone()
+                .map(|commit| commit.hash.len())
+                .max()
+                .unwrap_or(7);
+            let author_width = commits
+                .map(|commit| commit.author.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(UNCOMMITTED_AUTHOR.len());
+            let number_width = lines.len().to_string().len();
+            Ok(lines
+                .iter()
+                .map(|line| {
+                    let zeros = "0".repeat(hash_width);
+                    // Like git blame, lines not committed yet get a hash of zeros
+                    let (hash, date, author) = match &line.commit {
+                        Some(commit) => (
+                            commit.hash.as_str(),
+                            commit.date.as_str(),
+                            commit.author.as_str(),
+                        ),
+                        None => (zeros.as_str(), "", UNCOMMITTED_AUTHOR),
+                    };
+                    format!(
+                        "{hash} {date:10} {author:author_width$} {:>number_width$}) {}\n",
+                        line.line, line.text
+                    )
+                })
+                .collect())
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(lines)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output = "1111111111111111111111111111111111111111 1 1 2
+author Jo
+author-time 1718000000
+summary Explode Invoices.xml
+filename Print - ID 1.txt
+\tBeep
+1111111111111111111111111111111111111111 2 2
+filename Print - ID 1.txt
+\tExit Script []
+0000000000000000000000000000000000000000 3 3 1
+author Not Committed Yet
+filename Print - ID 1.txt
+\t# 1 done
+";
+        let lines = parse_porcelain(output);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].0, "1".repeat(40));
+        assert_eq!(lines[1].1, "Exit Script []");
+        assert!(is_uncommitted(&lines[2].0));
+    }
+
+    #[test]
+    fn test_render_text() {
+        let commit = CommitInfo {
+            hash: "1a2b3c4".to_string(),
+            author: "Jo".to_string(),
+            date: "2025-06-09".to_string(),
+            summary: "Explode Invoices.xml".to_string(),
+        };
+        let lines = vec![
+            AnnotatedLine {
+                line: 1,
+                commit: Some(commit),
+                text: "Beep".to_string(),
+            },
+            AnnotatedLine {
+                line: 2,
+                commit: None,
+                text: "Exit Script []".to_string(),
+            },
+        ];
+        assert_eq!(
+            render(&lines, ReportFormat::Text).unwrap(),
+            "1a2b3c4 2025-06-09 Jo                1) Beep\n\
+             0000000            Not Committed Yet 2) Exit Script []\n"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Error, Result};
+use clap::Parser;
+
+use crate::exit_status::ExitStatus;
+use crate::manifest::relative_path_string;
+use crate::utils::file_utils::{collect_files, ScratchDir};
+use crate::{run, Args};
+
+/// Explode an XML export held in memory with options of the command line, e.g. `["--lossless"]`,
+/// returning the written files by their path relative to the output root, separated by `/`
+pub fn explode_bytes(xml: &[u8], options: &[String]) -> Result<BTreeMap<String, Vec<u8>>, Error> {
+    let scratch = ScratchDir::create("api")?;
+    let out_dir = scratch.path.join("out");
+    explode_scratch(&scratch, xml, &out_dir, options)?;
+
+    let mut file_paths = Vec::new();
+    collect_files(&out_dir, &mut file_paths);
+    file_paths
+        .into_iter()
+        .map(|file_path| {
+            let content = fs::read(&file_path)
+                .with_context(|| format!("Error reading file {}", file_path.display()))?;
+            Ok((relative_path_string(&out_dir, &file_path), content))
+        })
+        .collect()
+}
+
+/// The script steps of an XML export held in memory as the records of `--format jsonl`,
+/// with their typed parameters. The format is set here, so `--format` is not an option of it.
+pub fn steps(xml: &[u8], options: &[String]) -> Result<Vec<serde_json::Value>, Error> {
+    if options
+        .iter()
+        .any(|option| option == "--format" || option.starts_with("--format="))
+    {
+        bail!("The steps are always read as --format jsonl, --format can't be given");
+    }
+    let scratch = ScratchDir::create("api")?;
+    let jsonl_path = scratch.path.join("steps.jsonl");
+    let mut options = options.to_vec();
+    options.extend(["--format".to_string(), "jsonl".to_string()]);
+    explode_scratch(&scratch, xml, &jsonl_path, &options)?;
+
+    let jsonl = fs::read_to_string(&jsonl_path)
+        .with_context(|| format!("Error reading file {}", jsonl_path.display()))?;
+    jsonl
+        .lines()
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Run the explode of the export written to the scratch directory into the target
+fn explode_scratch(
+    scratch: &ScratchDir,
+    xml: &[u8],
+    target: &Path,
+    options: &[String],
+) -> Result<(), Error> {
+    let input_path = scratch.path.join("export.xml");
+    fs::write(&input_path, xml)
+        .with_context(|| format!("Error writing file {}", input_path.display()))?;
+
+    let mut command_line = vec![
+        env!("CARGO_PKG_NAME").into(),
+        input_path.into_os_string(),
+        target.as_os_str().to_owned(),
+        "--quiet".into(),
+    ];
+    command_line.extend(options.iter().map(Into::into));
+    let args = Args::try_parse_from(command_line)?;
+    args.validate()?;
+    match run(args)? {
+        ExitStatus::ParseError | ExitStatus::IoError => bail!("Error exploding the export"),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = "tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml";
+
+    #[test]
+    fn test_explode_bytes() {
+        let xml = fs::read(EXPORT).unwrap();
+        let files = explode_bytes(&xml, &["--only".to_string(), "scripts".to_string()]).unwrap();
+        assert!(files.keys().all(|path| path.starts_with("Ooe/script")));
+        let control = files
+            .iter()
+            .find(|(path, _)| {
+                path.starts_with("Ooe/scripts_sanitized/") && path.contains("Control")
+            })
+            .map(|(_, content)| String::from_utf8_lossy(content))
+            .unwrap();
+        assert!(control.contains("Set Revert Transaction on Error [ OFF ]"));
+    }
+
+    #[test]
+    fn test_steps() {
+        let xml = fs::read(EXPORT).unwrap();
+        let records = steps(&xml, &[]).unwrap();
+        let step = records
+            .iter()
+            .find(|step| step["text"] == "Set Revert Transaction on Error [ OFF ]")
+            .unwrap();
+        assert_eq!(step["parameters"][0]["type"], "boolean");
+        assert_eq!(step["parameters"][0]["value"], false);
+
+        let err = steps(&xml, &["--format=text".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("--format"));
+    }
+}
@@ -0,0 +1,912 @@
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Function calls longer than this are broken into one argument per line
+const MAX_INLINE_WIDTH: usize = 80;
+
+/// Functions whose arguments are put on separate lines when the call doesn't fit on one line,
+/// in English and German
+const BLOCK_FUNCTIONS: [&str; 8] = [
+    "let", "case", "if", "while", "setzevar", "falls", "wenn", "solange",
+];
+
+/// Logical operators, in English and German
+const KEYWORD_OPERATORS: [&str; 8] = ["and", "or", "xor", "not", "und", "oder", "xoder", "nicht"];
+
+/// Characters that end a word, e.g. a field reference or function name
+const SPECIAL_CHARS: &str = "\"()[];+-*/^&=≠<>≤≥";
+
+/// How rendered calculations are laid out
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcStyle {
+    /// Re-indented with this indentation per level
+    Formatted(String),
+    /// Collapsed to a single line
+    Inline,
+    /// Kept as exported, with `\n` for line breaks
+    EscapedNewlines,
+}
+
+thread_local! {
+    static STYLE: RefCell<Option<CalcStyle>> = const { RefCell::new(None) };
+    static COMMENTS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Lay out all calculations rendered by `f` on this thread in the given style,
+/// or keep them as exported with `None`
+pub fn with_calc_style<T>(style: Option<CalcStyle>, f: impl FnOnce() -> T) -> T {
+    let previous = STYLE.with(|cell| cell.replace(style));
+    let result = f();
+    STYLE.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+/// Run `f` and return the comments of all calculations it rendered on this thread
+pub fn collect_calc_comments<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let previous = COMMENTS.with(|cell| cell.replace(Some(Vec::new())));
+    let result = f();
+    let comments = COMMENTS.with(|cell| cell.replace(previous));
+    (result, comments.unwrap_or_default())
+}
+
+/// The calculation laid out as configured by `with_calc_style`.
+/// Its comments are recorded for `collect_calc_comments`.
+pub fn format_rendered_calculation(calculation: String) -> String {
+    COMMENTS.with(|cell| {
+        if let Some(comments) = cell.borrow_mut().as_mut() {
+            comments.extend(calculation_comments(&calculation));
+        }
+    });
+    STYLE.with(|cell| match cell.borrow().as_ref() {
+        Some(style) => apply_calc_style(&calculation, style),
+        None => calculation,
+    })
+}
+
+pub fn apply_calc_style(calculation: &str, style: &CalcStyle) -> String {
+    match style {
+        CalcStyle::Formatted(indent) => {
+            format_calculation(calculation, indent).unwrap_or(calculation.to_string())
+        }
+        CalcStyle::Inline => inline_calculation(calculation),
+        CalcStyle::EscapedNewlines => escape_line_breaks(calculation),
+    }
+}
+
+/// Re-indent `Let`, `Case`, `If` and `While` and put single spaces around operators.
+/// Lines are separated by `\r` like in the export; `None` if the calculation can't be tokenized,
+/// e.g. because of unbalanced brackets.
+pub fn format_calculation(calculation: &str, indent: &str) -> Option<String> {
+    render(
+        calculation,
+        &Formatter {
+            indent,
+            inline: false,
+        },
+    )
+}
+
+/// Collapse the calculation to a single line: line breaks in text become `¶` and `//` comments
+/// become `/* */` comments. Line breaks of calculations that can't be tokenized become `\n`.
+pub fn inline_calculation(calculation: &str) -> String {
+    render(
+        calculation,
+        &Formatter {
+            indent: "",
+            inline: true,
+        },
+    )
+    .unwrap_or_else(|| escape_line_breaks(calculation))
+}
+
+/// The calculation on a single line, with `\n` for its line breaks
+pub fn escape_line_breaks(calculation: &str) -> String {
+    line_breaks().replace_all(calculation, "\\n").to_string()
+}
+
+/// The `/* */` and `//` comments of the calculation, verbatim;
+/// empty if the calculation can't be tokenized
+pub fn calculation_comments(calculation: &str) -> Vec<String> {
+    tokenize(calculation)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Comment(comment) | Token::LineComment(comment) => Some(comment),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The contents of the text literals of the calculation, without quotes and escapes;
+/// empty if the calculation can't be tokenized
+pub fn calculation_texts(calculation: &str) -> Vec<String> {
+    tokenize(calculation)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(unquote)
+        .collect()
+}
+
+/// The first argument of every call of one of the functions, e.g. the query of `ExecuteSQL`,
+/// if it is a text literal or literals joined by `&`; `None` if it is calculated otherwise
+pub fn first_text_arguments(calculation: &str, functions: &[&str]) -> Vec<Option<String>> {
+    text_arguments(calculation, functions, 0)
+}
+
+/// The argument at `position` of every call of one of the functions, like
+/// [`first_text_arguments`]; `None` for calls with fewer arguments
+pub fn text_arguments(
+    calculation: &str,
+    functions: &[&str],
+    position: usize,
+) -> Vec<Option<String>> {
+    let tokens: Vec<Token> = tokenize(calculation)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Comment(_) | Token::LineComment(_)))
+        .collect();
+    let mut arguments = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Word(word) = token else {
+            continue;
+        };
+        if tokens.get(i + 1) != Some(&Token::Open('('))
+            || !functions.contains(&word.to_lowercase().as_str())
+        {
+            continue;
+        }
+        let mut argument = 0;
+        let mut start = 0;
+        let mut text = None;
+        let mut depth = 0;
+        for (j, token) in tokens[i + 2..].iter().enumerate() {
+            match token {
+                Token::Close(_) if depth == 0 => break,
+                Token::Separator if depth == 0 => {
+                    if argument == position {
+                        break;
+                    }
+                    argument += 1;
+                    start = j + 1;
+                    continue;
+                }
+                Token::Open(_) => depth += 1,
+                Token::Close(_) => depth -= 1,
+                _ => {}
+            }
+            if argument != position {
+                continue;
+            }
+            if j == start {
+                text = Some(String::new());
+            }
+            // Text literals at even positions, joined by `&`
+            text = match ((j - start) % 2, token) {
+                (0, Token::Text(_)) => text.zip(unquote(token)).map(|(text, part)| text + &part),
+                (1, Token::Operator(operator)) if operator == "&" => text,
+                _ => None,
+            };
+        }
+        arguments.push(text.filter(|text| !text.is_empty()));
+    }
+    arguments
+}
+
+/// The names of all functions called by the calculation, in order and as written;
+/// empty if the calculation can't be tokenized
+pub fn calculation_functions(calculation: &str) -> Vec<String> {
+    let tokens = tokenize(calculation).unwrap_or_default();
+    tokens
+        .iter()
+        .zip(tokens.iter().skip(1))
+        .filter_map(|pair| match pair {
+            (Token::Word(word), Token::Open('(')) => Some(word.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// All names of the calculation, e.g. of functions, fields and variables, in order and as
+/// written; empty if the calculation can't be tokenized
+pub fn calculation_words(calculation: &str) -> Vec<String> {
+    tokenize(calculation)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The content of a text literal, without quotes and escapes
+fn unquote(token: &Token) -> Option<String> {
+    let Token::Text(text) = token else {
+        return None;
+    };
+    let quoted = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+/// A `$` or `$$` variable read or assigned by a calculation
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableUse {
+    pub name: String,
+    /// Assigned by a definition of `Let`, rather than read
+    pub assigned: bool,
+}
+
+/// Where the tokens between brackets are
+enum Frame {
+    /// The arguments of `Let`, with the index of the current one
+    Let(usize),
+    /// The `[ ]` list of definitions of `Let`
+    Definitions,
+    Other,
+}
+
+/// The variables used by the calculation in order of evaluation; the assignment of a `Let`
+/// definition follows the reads of its value. Empty if the calculation can't be tokenized
+pub fn calculation_variables(calculation: &str) -> Vec<VariableUse> {
+    let tokens = tokenize(calculation).unwrap_or_default();
+    let mut uses = Vec::new();
+    // The open brackets with the variable being defined by the current definition
+    let mut frames: Vec<(Frame, Option<String>)> = Vec::new();
+    let mut definition_start = false;
+    let mut previous: Option<&Token> = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Comment(_) | Token::LineComment(_) => continue,
+            Token::Open(c) => {
+                let frame = match (previous, c, frames.last()) {
+                    (Some(Token::Word(word)), '(', _)
+                        if ["let", "setzevar"].contains(&word.to_lowercase().as_str()) =>
+                    {
+                        Frame::Let(0)
+                    }
+                    (Some(Token::Open('(')), '[', Some((Frame::Let(0), _))) => Frame::Definitions,
+                    _ => Frame::Other,
+                };
+                frames.push((frame, None));
+                definition_start = true;
+            }
+            Token::Close(_) => {
+                if let Some((_, Some(name))) = frames.pop() {
+                    uses.push(VariableUse {
+                        name,
+                        assigned: true,
+                    });
+                }
+                definition_start = false;
+            }
+            Token::Separator => {
+                if let Some((frame, defined)) = frames.last_mut() {
+                    if let Some(name) = defined.take() {
+                        uses.push(VariableUse {
+                            name,
+                            assigned: true,
+                        });
+                    }
+                    if let Frame::Let(argument) = frame {
+                        *argument += 1;
+                    }
+                }
+                definition_start = true;
+            }
+            Token::Word(word) if word.starts_with('$') && !word.starts_with("${") => {
+                let name = word.trim_start_matches('$');
+                if !name.is_empty() {
+                    let defining = definition_start
+                        && matches!(frames.last(), Some((Frame::Let(0) | Frame::Definitions, _)))
+                        && tokens.get(i + 1) == Some(&Token::Operator("=".to_string()));
+                    match defining {
+                        true => frames.last_mut().unwrap().1 = Some(word.clone()),
+                        false => uses.push(VariableUse {
+                            name: word.clone(),
+                            assigned: false,
+                        }),
+                    }
+                }
+                definition_start = false;
+            }
+            _ => definition_start = false,
+        }
+        previous = Some(token);
+    }
+    uses
+}
+
+fn line_breaks() -> &'static Regex {
+    static LINE_BREAKS: OnceLock<Regex> = OnceLock::new();
+    LINE_BREAKS.get_or_init(|| Regex::new(r"\r\n|\n|\r").unwrap())
+}
+
+fn render(calculation: &str, formatter: &Formatter) -> Option<String> {
+    let tokens = tokenize(calculation)?;
+    let mut pos = 0;
+    let mut args = parse_args(&tokens, &mut pos, None)?;
+    if args.len() != 1 {
+        return None;
+    }
+    Some(
+        formatter
+            .expression(&args.remove(0), 0)
+            .trim_end()
+            .to_string(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Function and variable names, field references, numbers and keywords like `and`
+    Word(String),
+    Text(String),
+    Comment(String),
+    /// A `//` comment, which ends the line
+    LineComment(String),
+    Operator(String),
+    Open(char),
+    Close(char),
+    Separator,
+}
+
+fn tokenize(calculation: &str) -> Option<Vec<Token>> {
+    let chars = calculation.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let start = i;
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '"' => {
+                i += 1;
+                loop {
+                    match chars.get(i)? {
+                        '\\' => i += 2,
+                        '"' => break,
+                        _ => i += 1,
+                    }
+                }
+                i += 1;
+                tokens.push(Token::Text(chars[start..i].iter().collect()));
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && !matches!(chars[i], '\r' | '\n') {
+                    i += 1;
+                }
+                let comment = chars[start..i].iter().collect::<String>();
+                tokens.push(Token::LineComment(comment.trim_end().to_string()));
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                while !(chars.get(i)? == &'*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                tokens.push(Token::Comment(chars[start..i].iter().collect()));
+            }
+            '(' | '[' => {
+                i += 1;
+                tokens.push(Token::Open(c));
+            }
+            ')' | ']' => {
+                i += 1;
+                tokens.push(Token::Close(c));
+            }
+            ';' => {
+                i += 1;
+                tokens.push(Token::Separator);
+            }
+            '<' if matches!(next, Some('>' | '=')) => {
+                i += 2;
+                tokens.push(Token::Operator(chars[start..i].iter().collect()));
+            }
+            '>' if next == Some('=') => {
+                i += 2;
+                tokens.push(Token::Operator(chars[start..i].iter().collect()));
+            }
+            _ if SPECIAL_CHARS.contains(c) => {
+                i += 1;
+                tokens.push(Token::Operator(c.to_string()));
+            }
+            _ => {
+                i = word_end(&chars, i)?;
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// End of the word starting at `start`; words separated by spaces on the same line
+/// are kept together, as field names may contain spaces
+fn word_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut segment_start = start;
+    let mut end = segment_end(chars, start)?;
+    loop {
+        let mut next = end;
+        while next < chars.len() && matches!(chars[next], ' ' | '\t') {
+            next += 1;
+        }
+        match chars.get(next) {
+            Some(c) if next > end && !c.is_whitespace() && !SPECIAL_CHARS.contains(*c) => {}
+            _ => return Some(end),
+        }
+        let next_end = segment_end(chars, next)?;
+        let segment = chars[segment_start..end].iter().collect::<String>();
+        let next_segment = chars[next..next_end].iter().collect::<String>();
+        if is_keyword_operator(&segment) || is_keyword_operator(&next_segment) {
+            return Some(end);
+        }
+        segment_start = next;
+        end = next_end;
+    }
+}
+
+/// End of the word without spaces starting at `start`
+fn segment_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start;
+    loop {
+        match chars.get(i) {
+            Some('$') if chars.get(i + 1) == Some(&'{') => {
+                // ${Table::Field name} may contain any character
+                while chars.get(i)? != &'}' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            Some(c) if !c.is_whitespace() && !SPECIAL_CHARS.contains(*c) => i += 1,
+            _ => return Some(i),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Token(Token),
+    Call { name: String, args: Vec<Vec<Node>> },
+    Group { open: char, args: Vec<Vec<Node>> },
+}
+
+/// Parse `;` separated arguments up to the `closing` bracket or the end of the calculation
+fn parse_args(tokens: &[Token], pos: &mut usize, closing: Option<char>) -> Option<Vec<Vec<Node>>> {
+    let mut args = vec![Vec::new()];
+    while let Some(token) = tokens.get(*pos) {
+        *pos += 1;
+        match token {
+            Token::Open(open) => {
+                let close = match open {
+                    '(' => ')',
+                    _ => ']',
+                };
+                let inner = parse_args(tokens, pos, Some(close))?;
+                let current = args.last_mut().unwrap();
+                match current.pop() {
+                    Some(Node::Token(Token::Word(name)))
+                        if *open == '(' && !is_keyword_operator(&name) =>
+                    {
+                        current.push(Node::Call { name, args: inner });
+                    }
+                    last => {
+                        current.extend(last);
+                        current.push(Node::Group {
+                            open: *open,
+                            args: inner,
+                        });
+                    }
+                }
+            }
+            Token::Close(close) => {
+                return match Some(*close) == closing {
+                    true => Some(args),
+                    false => None,
+                };
+            }
+            Token::Separator if closing.is_some() => args.push(Vec::new()),
+            Token::Separator => return None,
+            _ => args.last_mut().unwrap().push(Node::Token(token.clone())),
+        }
+    }
+    match closing {
+        None => Some(args),
+        Some(_) => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Previous {
+    Operand,
+    Operator,
+    Unary,
+    LineComment,
+}
+
+struct Formatter<'a> {
+    indent: &'a str,
+    /// Never break lines
+    inline: bool,
+}
+
+impl Formatter<'_> {
+    fn line_break(&self, level: usize) -> String {
+        format!("\r{}", self.indent.repeat(level))
+    }
+
+    fn expression(&self, nodes: &[Node], level: usize) -> String {
+        let mut text = String::new();
+        let mut previous: Option<Previous> = None;
+        for node in nodes {
+            let (rendered, current) = match node {
+                Node::Token(Token::Operator(op)) => {
+                    let is_unary = matches!(op.as_str(), "-" | "+")
+                        && !matches!(previous, Some(Previous::Operand));
+                    match is_unary {
+                        true => (op.clone(), Previous::Unary),
+                        false => (op.clone(), Previous::Operator),
+                    }
+                }
+                Node::Token(Token::Word(word)) if is_keyword_operator(word) => {
+                    (word.clone(), Previous::Operator)
+                }
+                Node::Token(Token::LineComment(comment)) if self.inline => {
+                    let comment = comment.trim_start_matches('/').trim();
+                    (format!("/* {comment} */"), Previous::Operand)
+                }
+                Node::Token(Token::LineComment(comment)) => {
+                    (comment.clone(), Previous::LineComment)
+                }
+                Node::Token(Token::Text(text)) if self.inline => (
+                    line_breaks().replace_all(text, "¶").to_string(),
+                    Previous::Operand,
+                ),
+                Node::Token(Token::Comment(text)) if self.inline => (
+                    text.split_whitespace().collect::<Vec<_>>().join(" "),
+                    Previous::Operand,
+                ),
+                Node::Token(Token::Word(text))
+                | Node::Token(Token::Text(text))
+                | Node::Token(Token::Comment(text)) => (text.clone(), Previous::Operand),
+                Node::Call { name, args } => (self.call(name, args, level), Previous::Operand),
+                Node::Group { open, args } => {
+                    (self.group(*open, args, level, false), Previous::Operand)
+                }
+                Node::Token(Token::Open(_) | Token::Close(_) | Token::Separator) => continue,
+            };
+            match previous {
+                None | Some(Previous::Unary) => {}
+                Some(Previous::LineComment) => text.push_str(&self.line_break(level)),
+                Some(_) => text.push(' '),
+            }
+            text.push_str(&rendered);
+            previous = Some(current);
+        }
+        text
+    }
+
+    fn call(&self, name: &str, args: &[Vec<Node>], level: usize) -> String {
+        if args.len() == 1 && args[0].is_empty() {
+            return format!("{name} ()");
+        }
+        let function = name.rsplit(' ').next().unwrap_or(name).to_lowercase();
+        let is_block = BLOCK_FUNCTIONS.contains(&function.as_str());
+
+        let inline = format!("{name} ( {} )", self.inline_args(args, level));
+        let too_long = inline.chars().count() > MAX_INLINE_WIDTH || inline.contains('\r');
+        let must_expand = args.iter().any(|arg| ends_with_line_comment(arg));
+        let expand = must_expand || (is_block && too_long);
+        if self.inline || !expand {
+            return inline;
+        }
+
+        // Case pairs each condition with its result on one line
+        let per_line = match function.as_str() {
+            "case" | "falls" => 2,
+            _ => 1,
+        };
+        let lines = args
+            .chunks(per_line)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|arg| self.block_arg(arg, level + 1))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{name} ({}{}{})",
+            self.line_break(level + 1),
+            self.join_lines(lines, level + 1),
+            self.line_break(level)
+        )
+    }
+
+    /// An argument of an expanded block function; its bracket lists are expanded as well
+    fn block_arg(&self, arg: &[Node], level: usize) -> (String, bool) {
+        let rendered = match arg {
+            [Node::Group { open: '[', args }] => self.group('[', args, level, true),
+            _ => self.expression(arg, level),
+        };
+        (rendered, ends_with_line_comment(arg))
+    }
+
+    fn group(&self, open: char, args: &[Vec<Node>], level: usize, expand: bool) -> String {
+        let close = match open {
+            '(' => ')',
+            _ => ']',
+        };
+        let must_expand = args.iter().any(|arg| ends_with_line_comment(arg));
+        if self.inline || (!expand && !must_expand) {
+            return format!("{open} {} {close}", self.inline_args(args, level));
+        }
+        let lines = args
+            .iter()
+            .map(|arg| vec![(self.expression(arg, level + 1), ends_with_line_comment(arg))])
+            .collect();
+        format!(
+            "{open}{}{}{}{close}",
+            self.line_break(level + 1),
+            self.join_lines(lines, level + 1),
+            self.line_break(level)
+        )
+    }
+
+    fn inline_args(&self, args: &[Vec<Node>], level: usize) -> String {
+        args.iter()
+            .map(|arg| self.expression(arg, level))
+            .collect::<Vec<_>>()
+            .join(" ; ")
+    }
+
+    /// Join lines of arguments with `;`, which goes on its own line after a line comment
+    fn join_lines(&self, lines: Vec<Vec<(String, bool)>>, level: usize) -> String {
+        let separator = |ends_with_comment: bool| match ends_with_comment {
+            true => format!("{};", self.line_break(level)),
+            false => " ;".to_string(),
+        };
+        let count = lines.len();
+        let mut text = String::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            let parts = line.len();
+            for (j, (arg, ends_with_comment)) in line.into_iter().enumerate() {
+                text.push_str(&arg);
+                if j + 1 < parts {
+                    text.push_str(&separator(ends_with_comment));
+                    text.push(' ');
+                } else if i + 1 < count {
+                    text.push_str(&separator(ends_with_comment));
+                    text.push_str(&self.line_break(level));
+                }
+            }
+        }
+        text
+    }
+}
+
+fn is_keyword_operator(word: &str) -> bool {
+    KEYWORD_OPERATORS
+        .iter()
+        .any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+fn ends_with_line_comment(nodes: &[Node]) -> bool {
+    matches!(nodes.last(), Some(Node::Token(Token::LineComment(_))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(calculation: &str) -> String {
+        format_calculation(calculation, "    ")
+            .unwrap()
+            .replace('\r', "\n")
+    }
+
+    #[test]
+    fn test_operator_spacing() {
+        assert_eq!(format("$a+1"), "$a + 1");
+        assert_eq!(format("-$a*(2-x)"), "-$a * ( 2 - x )");
+        assert_eq!(
+            format("a<>b and not IsEmpty(c)"),
+            "a <> b and not IsEmpty ( c )"
+        );
+        assert_eq!(format("Get(CurrentDate)>=x"), "Get ( CurrentDate ) >= x");
+        assert_eq!(
+            format("Customers::First Name&\" \""),
+            "Customers::First Name & \" \""
+        );
+        assert_eq!(format("x = -1"), "x = -1");
+        assert_eq!(format("Random"), "Random");
+        assert_eq!(format("x and -1"), "x and -1");
+    }
+
+    #[test]
+    fn test_short_blocks_stay_inline() {
+        assert_eq!(format("If($a;\"x\";\"y\")"), "If ( $a ; \"x\" ; \"y\" )");
+    }
+
+    #[test]
+    fn test_let() {
+        let calculation = "Let([~first=Customers::First Name;~last=Customers::Last Name];If(IsEmpty(~last);~first;~last&\", \"&~first))";
+        assert_eq!(
+            format(calculation),
+            r#"Let (
+    [
+        ~first = Customers::First Name ;
+        ~last = Customers::Last Name
+    ] ;
+    If ( IsEmpty ( ~last ) ; ~first ; ~last & ", " & ~first )
+)"#
+        );
+    }
+
+    #[test]
+    fn test_case_with_comments() {
+        let calculation = "Case(\r$x=1;\"one\"; // first\r$x=2;\"two\";\r/* other */ \"many\")";
+        assert_eq!(
+            format(calculation),
+            r#"Case (
+    $x = 1 ; "one" ;
+    // first
+    $x = 2 ; "two" ;
+    /* other */ "many"
+)"#
+        );
+        assert_eq!(
+            format("If ( $a // why\r ; 1 ; 2 )"),
+            r#"If (
+    $a // why
+    ;
+    1 ;
+    2
+)"#
+        );
+    }
+
+    #[test]
+    fn test_nested_blocks() {
+        let calculation = "Case ( Get ( WindowMode ) = 0 ; Let ( [ a = 1 ; b = Get ( ScriptParameter ) ] ; a + b ) ; \"Find\" )";
+        assert_eq!(
+            format(calculation),
+            r#"Case (
+    Get ( WindowMode ) = 0 ; Let ( [ a = 1 ; b = Get ( ScriptParameter ) ] ; a + b ) ;
+    "Find"
+)"#
+        );
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert_eq!(format_calculation("If ( a ; b", "    "), None);
+        assert_eq!(format_calculation("\"open", "    "), None);
+        assert_eq!(format_calculation("a ) b", "    "), None);
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            inline_calculation("Let ( [\r    ~a = 1 ; // first\r    ~b = \"x\ry\"\r] ;\r    ~a\r)"),
+            "Let ( [ ~a = 1 ; /* first */ ~b = \"x¶y\" ] ; ~a )"
+        );
+        assert_eq!(inline_calculation("/* a\r   b */ 1"), "/* a b */ 1");
+        assert_eq!(inline_calculation("If ( a ;\rb"), "If ( a ;\\nb");
+    }
+
+    #[test]
+    fn test_calculation_texts() {
+        assert_eq!(
+            calculation_texts(
+                "GetLayoutObjectAttribute ( \"tab\" ; \"content\" ) & \"say \\\"hi\\\"\" // \"no\""
+            ),
+            vec!["tab", "content", "say \"hi\""]
+        );
+    }
+
+    #[test]
+    fn test_first_text_arguments() {
+        let calculation = "ExecuteSQL ( \"SELECT a \" & \"FROM t\" ; \"\" ; \"\" ) & executesql ( $sql ; \"\" ; \"\" ) & ExecuteSQL ( \"SELECT \" & Upper ( \"b\" ) ; \"\" ; \"\" )";
+        assert_eq!(
+            first_text_arguments(calculation, &["executesql"]),
+            vec![Some("SELECT a FROM t".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn test_text_arguments() {
+        let calculation = "ValueListItems ( Get ( FileName ) ; \"Status\" ) & ValueListItems ( \"\" ; $name ) & ValueListIDs ( \"\" )";
+        assert_eq!(
+            text_arguments(calculation, &["valuelistitems", "valuelistids"], 1),
+            vec![Some("Status".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn test_calculation_words() {
+        assert_eq!(
+            calculation_words("Let ( x = Table::Field ; _kMax + x ) /* Other */"),
+            vec!["Let", "x", "Table::Field", "_kMax", "x"]
+        );
+    }
+
+    #[test]
+    fn test_calculation_functions() {
+        assert_eq!(
+            calculation_functions(
+                "If ( not IsEmpty ( $a ) ; MBS ( \"Text.Upper\" ; BE_Version ) ) // Ignored ( )"
+            ),
+            vec!["If", "IsEmpty", "MBS"]
+        );
+    }
+
+    #[test]
+    fn test_calculation_variables() {
+        let uses = |calculation: &str| -> Vec<(String, bool)> {
+            calculation_variables(calculation)
+                .into_iter()
+                .map(|variable| (variable.name, variable.assigned))
+                .collect()
+        };
+        assert_eq!(
+            uses("Let ( [ $a = $a + 1 ; ~b = $$c[2] ] ; $a & ${Table::$Field} ) // $comment"),
+            vec![
+                ("$a".to_string(), false),
+                ("$a".to_string(), true),
+                ("$$c".to_string(), false),
+                ("$a".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            uses("SetzeVar ( $x = 1 ; $x = 2 ) & \"$text\""),
+            vec![("$x".to_string(), true), ("$x".to_string(), false)]
+        );
+        assert_eq!(uses("If ( $a = 1 ; $b )").len(), 2);
+    }
+
+    #[test]
+    fn test_calculation_comments() {
+        assert_eq!(
+            calculation_comments(
+                "Let ( ~a = 1 ; // one\r/* a\r   b */ ~a & \"/* no comment */\" )"
+            ),
+            vec!["// one", "/* a\r   b */"]
+        );
+        assert!(calculation_comments("\"open /* */").is_empty());
+
+        let (formatted, comments) = collect_calc_comments(|| {
+            format_rendered_calculation("1 /* first */".to_string())
+                + &format_rendered_calculation("2 // second".to_string())
+        });
+        assert_eq!(formatted, "1 /* first */2 // second");
+        assert_eq!(comments, vec!["/* first */", "// second"]);
+        assert_eq!(
+            format_rendered_calculation("3 // third".to_string()),
+            "3 // third"
+        );
+    }
+
+    #[test]
+    fn test_scoped_indent() {
+        let calculation = "1+2".to_string();
+        assert_eq!(format_rendered_calculation(calculation.clone()), "1+2");
+        let formatted = with_calc_style(Some(CalcStyle::Formatted("\t".to_string())), || {
+            format_rendered_calculation(calculation.clone())
+        });
+        assert_eq!(formatted, "1 + 2");
+        assert_eq!(format_rendered_calculation(calculation), "1+2");
+    }
+}
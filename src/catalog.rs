@@ -1,27 +1,29 @@
+use std::fs;
 use std::io::{BufRead, Read};
 use std::path::{Path, PathBuf};
 
 use anyhow::Error;
-use quick_xml::escape::unescape;
 use quick_xml::events::{BytesStart, Event};
 
-use crate::utils::attributes::get_attributes;
+use crate::since::is_modified_since;
+use crate::utils::attributes::{get_attribute, get_attributes};
 use crate::utils::file_utils::{escape_filename, join_scope_id_and_name};
 use crate::utils::xml_utils::{
     end_element_to_string, end_element_to_string_from_start_element, extract_values_from_xml_paths,
-    push_rest_of_element_to_skeleton, skip_rest_of_element, start_element_to_string, XmlEventType,
+    push_rest_of_element_to_skeleton, skip_rest_of_element, start_element_to_string,
+    unescape_entities, XmlEventType,
 };
 use crate::utils::{
     build_out_dir_path, create_dir, move_to_subfolder, write_rest_of_element_to_file,
     FolderStructure,
 };
 use crate::utils::{push_line_to_skeleton, rename_file_if_necessary};
-use crate::xml_processor::ProcessingContext;
+use crate::xml_processor::{record_parse_error, ProcessingContext};
 
 /// Parse and explode a generic catalog, handling both wrapped and unwrapped formats
 pub fn xml_explode_catalog<R: Read + BufRead>(
     context: &mut ProcessingContext<'_, R>,
-    _start_tag: &BytesStart,
+    start_tag: &BytesStart,
     folder_structure: Option<&FolderStructure>,
     // catalog_config: &CatalogConfig,
 ) -> Result<Option<FolderStructure>, Error> {
@@ -40,6 +42,10 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
 
     let mut buf = Vec::new(); // buffer for reading xml events
 
+    // Progress is reported as items processed out of the catalog's membercount
+    let member_count = get_attribute(start_tag, "membercount");
+    let mut item_count = 0;
+
     // Adjust depth based on whether items are wrapped in ObjectList
     let base_depth = context.path_stack.len(); // depth of the catalog start tag, e.g., 4 for BaseDirectoryCatalog, if the path is FMSaveAsXML/Structure/AddAction/BaseDirectoryCatalog
     let mut rel_depth = 1; // tracks depth as we traverse the xml; depth 1 is the catalog start tag, e.g., <BaseDirectoryCatalog>
@@ -60,7 +66,14 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
     loop {
         match context.reader.read_event_into(&mut buf) {
             Err(e) => {
-                println!("Error {e}");
+                let enclosing = match current_name.is_empty() {
+                    true => None,
+                    false => Some(format!(
+                        "{} '{current_name}'",
+                        String::from_utf8_lossy(&catalog_item_name)
+                    )),
+                };
+                record_parse_error(context, enclosing, &e);
                 break;
             }
             Ok(Event::Eof) => break,
@@ -100,11 +113,21 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
                     continue;
                 }
 
+                item_count += 1;
+                context.progress.set_message(match &member_count {
+                    Some(total) => {
+                        format!("{} {item_count}/{total}", catalog_config.out_folder_name)
+                    }
+                    None => format!("{} {item_count}", catalog_config.out_folder_name),
+                });
+
                 // Handle catalog items (e.g. <BaseDirectory>)
                 // Parse attributes needed for folder tracking
+                let mut is_folder_item = false;
                 if uses_folders {
                     let (id, name, is_folder, is_marker, is_separator) =
                         parse_folder_attributes(&e);
+                    is_folder_item = is_folder || is_marker || is_separator;
                     current_id = id;
                     current_name = name;
 
@@ -118,7 +141,7 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
                         current_path.pop();
                     }
 
-                    if (is_folder || is_marker || is_separator) && !context.flags.lossless {
+                    if is_folder_item && !context.flags.lossless {
                         skip_rest_of_element(context.reader, &e);
                         rel_depth -= 1;
                         continue;
@@ -129,6 +152,10 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
                     current_name.clear();
                 }
 
+                if !is_folder_item {
+                    context.run.stats.record_catalog_item(catalog_type);
+                }
+
                 // Adjust indentation level based on wrapping
                 let indentation_level = if wrapped_in_object_list { 5 } else { 4 };
                 let file_path = write_rest_of_element_to_file(
@@ -138,22 +165,31 @@ pub fn xml_explode_catalog<R: Read + BufRead>(
                     base_depth + rel_depth - 1,
                     id_path,
                 );
-                rename_file_if_necessary(&file_path, context.path_stack, &catalog_item_name);
 
-                // Move to subfolder if necessary
-                let subfolder_dir_path = determine_subfolder_path(
-                    &out_dir_path_base,
-                    folder_structure,
-                    &file_path,
-                    id_path,
-                    uses_folders,
-                    &current_path,
-                );
-                if let Some(subfolder_dir_path) = subfolder_dir_path {
-                    if subfolder_dir_path != out_dir_path_base
-                        && !subfolder_dir_path.to_string_lossy().is_empty()
-                    {
-                        let _ = move_to_subfolder(&file_path, &subfolder_dir_path);
+                // Items not modified since --since are dropped again
+                let is_included = context.flags.since.as_deref().is_none_or(|since| {
+                    is_modified_since(&file_path, catalog_type, context.symbols.as_deref(), since)
+                });
+                if !is_included {
+                    let _ = fs::remove_file(&file_path);
+                } else {
+                    rename_file_if_necessary(&file_path, context.path_stack, &catalog_item_name);
+
+                    // Move to subfolder if necessary
+                    let subfolder_dir_path = determine_subfolder_path(
+                        &out_dir_path_base,
+                        folder_structure,
+                        &file_path,
+                        id_path,
+                        uses_folders,
+                        &current_path,
+                    );
+                    if let Some(subfolder_dir_path) = subfolder_dir_path {
+                        if subfolder_dir_path != out_dir_path_base
+                            && !subfolder_dir_path.to_string_lossy().is_empty()
+                        {
+                            let _ = move_to_subfolder(&file_path, &subfolder_dir_path);
+                        }
                     }
                 }
 
@@ -247,17 +283,13 @@ fn parse_folder_attributes(e: &BytesStart) -> (String, String, bool, bool, bool)
     for attr in get_attributes(e).unwrap() {
         match attr.0.as_str() {
             "id" => current_id = attr.1.to_string(),
-            "name" => current_name = unescape(attr.1.as_str()).unwrap().to_string(),
+            "name" => current_name = unescape_entities(&attr.1),
             "isFolder" => match attr.1.as_str() {
                 "True" => is_folder = true,
                 "Marker" => is_marker = true,
                 _ => {}
             },
-            "isSeparatorItem" => {
-                if attr.1.as_str() == "True" {
-                    is_separator = true
-                }
-            }
+            "isSeparatorItem" if attr.1.as_str() == "True" => is_separator = true,
             _ => {}
         }
     }
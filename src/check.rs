@@ -0,0 +1,570 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Error, Result};
+use quick_xml::escape::escape;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::analysis::broken_refs::{self, broken_references};
+use crate::analysis::lint::{self, lint, step_variables};
+use crate::analysis::model::ExportModel;
+use crate::analysis::read_exports;
+use crate::analysis::sarif::SarifFinding;
+use crate::analysis::write_output;
+use crate::diagnostics::Diagnostics;
+use crate::exit_status::ExitStatus;
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::sanitizer::sanitize;
+use crate::symbols::SymbolKind;
+use crate::AnalysisInput;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckRule {
+    BrokenReference,
+    UnusedVariable,
+    ReadBeforeSet,
+    /// Script steps the exploder can't render, e.g. of a newer FileMaker version
+    UnknownStep,
+    /// Script names not matching `naming.scripts`
+    ScriptName,
+    /// Field names not matching `naming.fields`
+    FieldName,
+    /// Variables assigned by script steps whose name doesn't match `naming.variables`
+    VariableName,
+    LayoutName,
+    TableName,
+    TableOccurrenceName,
+    ValueListName,
+}
+
+impl CheckRule {
+    const ALL: [CheckRule; 11] = [
+        Self::BrokenReference,
+        Self::UnusedVariable,
+        Self::ReadBeforeSet,
+        Self::UnknownStep,
+        Self::ScriptName,
+        Self::FieldName,
+        Self::VariableName,
+        Self::LayoutName,
+        Self::TableName,
+        Self::TableOccurrenceName,
+        Self::ValueListName,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::BrokenReference => "broken-reference",
+            Self::UnusedVariable => "unused-variable",
+            Self::ReadBeforeSet => "read-before-set",
+            Self::UnknownStep => "unknown-step",
+            Self::ScriptName => "script-name",
+            Self::FieldName => "field-name",
+            Self::VariableName => "variable-name",
+            Self::LayoutName => "layout-name",
+            Self::TableName => "table-name",
+            Self::TableOccurrenceName => "table-occurrence-name",
+            Self::ValueListName => "value-list-name",
+        }
+    }
+
+    /// The kind of catalog element whose names a naming rule checks
+    fn symbol_kind(self) -> Option<SymbolKind> {
+        match self {
+            Self::LayoutName => Some(SymbolKind::Layout),
+            Self::TableName => Some(SymbolKind::BaseTable),
+            Self::TableOccurrenceName => Some(SymbolKind::TableOccurrence),
+            Self::ValueListName => Some(SymbolKind::ValueList),
+            _ => None,
+        }
+    }
+}
+
+/// Settings of `check`, read from the JSON file given with `--config`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckConfig {
+    /// Rules to run; empty means all
+    pub rules: Vec<CheckRule>,
+    pub naming: NamingConfig,
+}
+
+/// Regular expressions names must match, by element type; naming rules without one are skipped
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingConfig {
+    pub scripts: Option<String>,
+    pub fields: Option<String>,
+    /// Matched against the name with its `$` or `$$`
+    pub variables: Option<String>,
+    pub layouts: Option<String>,
+    pub tables: Option<String>,
+    pub table_occurrences: Option<String>,
+    pub value_lists: Option<String>,
+}
+
+impl NamingConfig {
+    fn patterns(&self) -> [(CheckRule, &Option<String>); 7] {
+        [
+            (CheckRule::ScriptName, &self.scripts),
+            (CheckRule::FieldName, &self.fields),
+            (CheckRule::VariableName, &self.variables),
+            (CheckRule::LayoutName, &self.layouts),
+            (CheckRule::TableName, &self.tables),
+            (CheckRule::TableOccurrenceName, &self.table_occurrences),
+            (CheckRule::ValueListName, &self.value_lists),
+        ]
+    }
+}
+
+impl CheckConfig {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Error reading check config {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid check config {}", path.display()))
+    }
+}
+
+/// A rule violation of a script, one of its steps or a field
+#[derive(Debug, PartialEq)]
+pub struct CheckFinding {
+    pub rule: CheckRule,
+    pub db: String,
+    /// E.g. `Print (ID 7)` for a script, `field Invoices::Total (ID 3)` or `layout Invoice (ID 2)`
+    pub subject: String,
+    pub step: Option<usize>,
+    pub message: String,
+}
+
+impl CheckFinding {
+    fn from_script_finding(rule: CheckRule, finding: SarifFinding) -> Self {
+        Self {
+            rule,
+            db: finding.db,
+            subject: script_subject(&finding.script, finding.script_id),
+            step: finding.step,
+            message: finding.message,
+        }
+    }
+}
+
+fn script_subject(name: &str, id: u32) -> String {
+    format!("{name} (ID {id})")
+}
+
+/// The rules and their compiled naming patterns
+pub struct Checks {
+    rules: Vec<CheckRule>,
+    naming: Vec<(CheckRule, Regex)>,
+}
+
+impl Checks {
+    pub fn new(config: &CheckConfig) -> Result<Self, Error> {
+        let mut naming = Vec::new();
+        for (rule, pattern) in config.naming.patterns() {
+            if let Some(pattern) = pattern {
+                let regex = Regex::new(pattern).with_context(|| {
+                    format!("Invalid naming pattern '{pattern}' of {}", rule.id())
+                })?;
+                naming.push((rule, regex));
+            }
+        }
+        let rules = match config.rules.is_empty() {
+            true => CheckRule::ALL.to_vec(),
+            false => config.rules.clone(),
+        };
+        // Naming rules without a pattern don't apply
+        let rules = rules
+            .into_iter()
+            .filter(|rule| {
+                config
+                    .naming
+                    .patterns()
+                    .iter()
+                    .all(|(naming_rule, pattern)| naming_rule != rule || pattern.is_some())
+            })
+            .collect();
+        Ok(Self { rules, naming })
+    }
+
+    fn pattern(&self, rule: CheckRule) -> Option<&Regex> {
+        self.naming
+            .iter()
+            .find(|(naming_rule, _)| *naming_rule == rule)
+            .map(|(_, pattern)| pattern)
+    }
+
+    /// Findings of all rules, by rule and in script or field order
+    pub fn findings(&self, models: &[ExportModel]) -> Vec<CheckFinding> {
+        let mut findings = Vec::new();
+        for &rule in &self.rules {
+            match rule {
+                CheckRule::BrokenReference => {
+                    let broken = models
+                        .iter()
+                        .flat_map(broken_references)
+                        .collect::<Vec<_>>();
+                    findings.extend(
+                        broken_refs::sarif_findings(&broken)
+                            .into_iter()
+                            .map(|finding| CheckFinding::from_script_finding(rule, finding)),
+                    );
+                }
+                CheckRule::UnusedVariable | CheckRule::ReadBeforeSet => {
+                    let lint_findings = models.iter().flat_map(lint).collect::<Vec<_>>();
+                    findings.extend(
+                        lint::sarif_findings(&lint_findings)
+                            .into_iter()
+                            .filter(|finding| finding.rule == rule.id())
+                            .map(|finding| CheckFinding::from_script_finding(rule, finding)),
+                    );
+                }
+                CheckRule::UnknownStep => {
+                    for model in models {
+                        for script in &model.scripts {
+                            for step in &script.steps {
+                                let known = id_to_script_step(&step.id) != ScriptStep::Unknown;
+                                let mut diagnostics = Diagnostics::default();
+                                sanitize(&step.id, &step.xml, &mut diagnostics);
+                                if known && diagnostics.entries.is_empty() {
+                                    continue;
+                                }
+                                findings.push(CheckFinding {
+                                    rule,
+                                    db: model.db_name.clone(),
+                                    subject: script_subject(&script.name, script.id),
+                                    step: Some(step.number),
+                                    message: match known {
+                                        true => format!(
+                                            "{} (ID {}) can't be fully rendered",
+                                            step.name, step.id
+                                        ),
+                                        false => format!(
+                                            "{} (ID {}) is an unknown step",
+                                            step.name, step.id
+                                        ),
+                                    },
+                                });
+                            }
+                        }
+                    }
+                }
+                CheckRule::VariableName => {
+                    let Some(pattern) = self.pattern(rule) else {
+                        continue;
+                    };
+                    for model in models {
+                        for script in &model.scripts {
+                            // Each variable is reported once per script, where it's first assigned
+                            let mut seen = HashSet::new();
+                            for step in &script.steps {
+                                for variable in step_variables(step) {
+                                    if !variable.assigned
+                                        || pattern.is_match(&variable.name)
+                                        || !seen.insert(variable.name.clone())
+                                    {
+                                        continue;
+                                    }
+                                    findings.push(CheckFinding {
+                                        rule,
+                                        db: model.db_name.clone(),
+                                        subject: script_subject(&script.name, script.id),
+                                        step: Some(step.number),
+                                        message: format!(
+                                            "{} doesn't match {pattern}",
+                                            variable.name
+                                        ),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let Some(pattern) = self.pattern(rule) else {
+                        continue;
+                    };
+                    for model in models {
+                        for (subject, name) in subjects(rule, model) {
+                            if !pattern.is_match(&name) {
+                                findings.push(CheckFinding {
+                                    rule,
+                                    db: model.db_name.clone(),
+                                    subject,
+                                    step: None,
+                                    message: format!("Name doesn't match {pattern}"),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        findings
+    }
+
+    /// A JUnit XML report with a test suite per rule and a test case per script, or per element
+    /// for naming rules of other elements, failed with the findings of the rule
+    pub fn junit(&self, models: &[ExportModel], findings: &[CheckFinding]) -> String {
+        let mut suites = String::new();
+        let mut total_tests = 0;
+        let mut total_failures = 0;
+        for &rule in &self.rules {
+            let subjects: Vec<(&str, String)> = models
+                .iter()
+                .flat_map(|model| {
+                    let db = model.db_name.as_str();
+                    subjects(rule, model)
+                        .into_iter()
+                        .map(move |(subject, _)| (db, subject))
+                })
+                .collect();
+
+            let mut cases = String::new();
+            let mut failures = 0;
+            for (db, subject) in &subjects {
+                let subject_findings: Vec<&CheckFinding> = findings
+                    .iter()
+                    .filter(|finding| {
+                        finding.rule == rule && finding.db == *db && finding.subject == *subject
+                    })
+                    .collect();
+                let case_name = escape(format!("{db}: {subject}")).to_string();
+                if subject_findings.is_empty() {
+                    cases.push_str(&format!(
+                        "    <testcase classname=\"{}\" name=\"{case_name}\"/>\n",
+                        rule.id()
+                    ));
+                    continue;
+                }
+                failures += 1;
+                let lines: Vec<String> = subject_findings
+                    .iter()
+                    .map(|finding| match finding.step {
+                        Some(step) => format!("step {step}: {}", finding.message),
+                        None => finding.message.clone(),
+                    })
+                    .collect();
+                cases.push_str(&format!(
+                    "    <testcase classname=\"{}\" name=\"{case_name}\">\n      <failure type=\"{}\" message=\"{} finding(s)\">{}</failure>\n    </testcase>\n",
+                    rule.id(),
+                    rule.id(),
+                    subject_findings.len(),
+                    escape(lines.join("\n"))
+                ));
+            }
+            total_tests += subjects.len();
+            total_failures += failures;
+            suites.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\">\n{cases}  </testsuite>\n",
+                rule.id(),
+                subjects.len()
+            ));
+        }
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{total_tests}\" failures=\"{total_failures}\">\n{suites}</testsuites>\n",
+            env!("CARGO_PKG_NAME"),
+        )
+    }
+}
+
+/// The elements a rule checks, with their names: the fields or catalog elements of naming rules
+/// for them, otherwise the scripts
+fn subjects(rule: CheckRule, model: &ExportModel) -> Vec<(String, String)> {
+    if rule == CheckRule::FieldName {
+        return model
+            .symbols
+            .fields()
+            .map(|(table_id, id, field)| {
+                let table = model
+                    .symbols
+                    .name(SymbolKind::BaseTable, table_id)
+                    .unwrap_or_default();
+                let subject = format!("field {table}::{} (ID {id})", field.name);
+                (subject, field.name.clone())
+            })
+            .collect();
+    }
+    if let Some(kind) = rule.symbol_kind() {
+        let label = match kind {
+            SymbolKind::Layout => "layout",
+            SymbolKind::BaseTable => "table",
+            SymbolKind::TableOccurrence => "table occurrence",
+            _ => "value list",
+        };
+        return model
+            .symbols
+            .symbols()
+            .filter(|(symbol_kind, _, _)| *symbol_kind == kind)
+            .map(|(_, id, symbol)| {
+                let subject = format!("{label} {} (ID {id})", symbol.name);
+                (subject, symbol.name.clone())
+            })
+            .collect();
+    }
+    model
+        .scripts
+        .iter()
+        .map(|script| (script_subject(&script.name, script.id), script.name.clone()))
+        .collect()
+}
+
+/// Run the `check` subcommand: report the findings of all rules and fail if there are any
+pub fn run(
+    input: &AnalysisInput,
+    config: Option<&Path>,
+    report: &[String],
+) -> Result<ExitStatus, Error> {
+    let config = match config {
+        Some(path) => CheckConfig::from_file(path)?,
+        None => CheckConfig::default(),
+    };
+    let checks = Checks::new(&config)?;
+    let models = read_exports(&input.inputs)?;
+    let findings = checks.findings(&models);
+    if let [format, path] = report {
+        match format.as_str() {
+            "junit" => {
+                fs::write(path, checks.junit(&models, &findings))
+                    .with_context(|| format!("Error writing file {path}"))?;
+            }
+            _ => return Err(anyhow!("Unknown report format '{format}', expected junit")),
+        }
+    }
+    write_output(input, &render(&findings))?;
+    match findings.is_empty() {
+        true => Ok(ExitStatus::Clean),
+        false => Ok(ExitStatus::Findings),
+    }
+}
+
+/// One line per finding and the number of findings
+pub fn render(findings: &[CheckFinding]) -> String {
+    let mut text = String::new();
+    for finding in findings {
+        let step = match finding.step {
+            Some(step) => format!(" step {step}"),
+            None => String::new(),
+        };
+        text.push_str(&format!(
+            "{}: {}{step}: {}: {}\n",
+            finding.db,
+            finding.subject,
+            finding.rule.id(),
+            finding.message
+        ));
+    }
+    let subjects: HashSet<(&str, &str)> = findings
+        .iter()
+        .map(|finding| (finding.db.as_str(), finding.subject.as_str()))
+        .collect();
+    match findings.len() {
+        0 => text.push_str("No problems found\n"),
+        count => text.push_str(&format!(
+            "{count} problem(s) in {} element(s)\n",
+            subjects.len()
+        )),
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="total"></Field>
+                    <Field id="2" name="Date"></Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <LayoutCatalog>
+            <Layout id="1" name="invoice"></Layout>
+        </LayoutCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Print &amp; Send"></ScriptReference>
+                <ObjectList>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[1]]></Text></Calculation></value>
+                                <Name value="$unused"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="9999" name="Future Step" enable="True"></Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="INV - Clean"></ScriptReference>
+                <ObjectList></ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    fn models() -> Vec<ExportModel> {
+        let model = read_model(&export(CATALOGS));
+        vec![model]
+    }
+
+    #[test]
+    fn test_render() {
+        let config: CheckConfig = serde_json::from_str(
+            r#"{"naming": {
+                "scripts": "^[A-Z]{3} - ",
+                "fields": "^[a-z]",
+                "variables": "^\\$_",
+                "layouts": "^[A-Z]"
+            }}"#,
+        )
+        .unwrap();
+        let checks = Checks::new(&config).unwrap();
+        let expected = "Invoices: Print & Send (ID 1) step 1: unused-variable: $unused is assigned but never read
+Invoices: Print & Send (ID 1) step 2: unknown-step: Future Step (ID 9999) is an unknown step
+Invoices: Print & Send (ID 1): script-name: Name doesn't match ^[A-Z]{3} - 
+Invoices: field Invoices::Date (ID 2): field-name: Name doesn't match ^[a-z]
+Invoices: Print & Send (ID 1) step 1: variable-name: $unused doesn't match ^\\$_
+Invoices: layout invoice (ID 1): layout-name: Name doesn't match ^[A-Z]
+6 problem(s) in 3 element(s)
+";
+        assert_eq!(render(&checks.findings(&models())), expected);
+    }
+
+    #[test]
+    fn test_junit() {
+        let config: CheckConfig =
+            serde_json::from_str(r#"{"rules": ["unused-variable"]}"#).unwrap();
+        let checks = Checks::new(&config).unwrap();
+        let models = models();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="fm-xml-export-exploder" tests="2" failures="1">
+  <testsuite name="unused-variable" tests="2" failures="1">
+    <testcase classname="unused-variable" name="Invoices: Print &amp; Send (ID 1)">
+      <failure type="unused-variable" message="1 finding(s)">step 1: $unused is assigned but never read</failure>
+    </testcase>
+    <testcase classname="unused-variable" name="Invoices: INV - Clean (ID 2)"/>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(checks.junit(&models, &checks.findings(&models)), expected);
+    }
+
+    #[test]
+    fn test_unknown_rule() {
+        assert!(serde_json::from_str::<CheckConfig>(r#"{"rules": ["typo"]}"#).is_err());
+    }
+}
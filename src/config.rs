@@ -1,4 +1,5 @@
-use crate::OutputTree;
+use crate::calc_formatter::CalcStyle;
+use crate::{BoolStyle, FieldNames, LineEnding, Locale, OutputTree, Section};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CatalogType {
@@ -26,6 +27,30 @@ pub enum CatalogType {
 }
 
 impl CatalogType {
+    pub const ALL: [CatalogType; 21] = [
+        Self::Accounts,
+        Self::BaseDirectory,
+        Self::BaseTable,
+        Self::CalcsForCustomFunctions,
+        Self::CustomFunctions,
+        Self::CustomMenu,
+        Self::CustomMenuSet,
+        Self::ExternalDataSource,
+        Self::ExtendedPrivileges,
+        Self::FieldsForTables,
+        Self::FileAccess,
+        Self::Layout,
+        Self::Library,
+        Self::OptionsForValueLists,
+        Self::PrivilegeSets,
+        Self::Relationship,
+        Self::Script,
+        Self::StepsForScripts,
+        Self::TableOccurrence,
+        Self::Theme,
+        Self::ValueList,
+    ];
+
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
         match bytes {
             b"AccountsCatalog" => Some(Self::Accounts),
@@ -53,6 +78,27 @@ impl CatalogType {
         }
     }
 
+    /// The section selectable via `--only` this catalog belongs to
+    pub fn section(&self) -> Section {
+        match self {
+            Self::Script | Self::StepsForScripts => Section::Scripts,
+            Self::Layout => Section::Layouts,
+            Self::BaseTable
+            | Self::FieldsForTables
+            | Self::TableOccurrence
+            | Self::Relationship => Section::Schema,
+            Self::Accounts | Self::PrivilegeSets | Self::ExtendedPrivileges | Self::FileAccess => {
+                Section::Security
+            }
+            Self::CustomFunctions | Self::CalcsForCustomFunctions => Section::CustomFunctions,
+            Self::ValueList | Self::OptionsForValueLists => Section::ValueLists,
+            Self::CustomMenu | Self::CustomMenuSet => Section::Menus,
+            Self::Theme => Section::Themes,
+            Self::Library => Section::Libraries,
+            Self::ExternalDataSource | Self::BaseDirectory => Section::DataSources,
+        }
+    }
+
     pub fn get_config(&self) -> CatalogConfig {
         match self {
             Self::Accounts => CatalogConfig {
@@ -215,8 +261,82 @@ pub struct CatalogConfig {
     pub id_path: String,
 }
 
+#[derive(Debug)]
 pub struct Flags {
     pub parse_all_lines: bool,
     pub lossless: bool,
     pub output_tree: OutputTree,
+    pub line_endings: LineEnding,
+    pub trailing_newline: bool,
+    /// Sections to explode; empty means all
+    pub only: Vec<Section>,
+    /// Only explode catalog items modified on or after this ISO date or time
+    pub since: Option<String>,
+    /// Prefix each rendered script step with its step number
+    pub step_numbers: bool,
+    /// Append the raw XML of unrecognized script steps beneath the rendered step
+    pub debug_unknown: bool,
+    /// Language of rendered step names; `None` keeps the names of the export
+    pub locale: Option<Locale>,
+    /// Spaces per indentation level of formatted calculations, `0` for tabs; `None` keeps calculations as exported
+    pub format_calcs: Option<usize>,
+    /// Collapse rendered calculations to a single line
+    pub inline_calcs: bool,
+    /// Keep rendered calculations as exported, with `\n` for their line breaks
+    pub escape_calc_newlines: bool,
+    /// List the comments of all calculations per script in `calc_comments`
+    pub calc_comments: bool,
+    /// Append the layouts, scripts, fields and variables used by each script to its rendered text
+    pub dependencies: bool,
+    /// Whether field targets of script steps include their table occurrence
+    pub field_names: FieldNames,
+    /// Whether ON/OFF options of script steps are rendered as words or as symbols
+    pub bool_style: BoolStyle,
+    /// Mask credentials in script steps and literal API keys in all output files
+    pub redact: bool,
+}
+
+impl Flags {
+    /// How rendered calculations are laid out; `None` keeps them as exported
+    pub fn calc_style(&self) -> Option<CalcStyle> {
+        if self.inline_calcs {
+            return Some(CalcStyle::Inline);
+        }
+        if self.escape_calc_newlines {
+            return Some(CalcStyle::EscapedNewlines);
+        }
+        self.format_calcs.map(|width| match width {
+            0 => CalcStyle::Formatted("\t".to_string()),
+            _ => CalcStyle::Formatted(" ".repeat(width)),
+        })
+    }
+
+    pub fn includes(&self, section: Section) -> bool {
+        self.only.is_empty() || self.only.contains(&section)
+    }
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        Self {
+            parse_all_lines: false,
+            lossless: false,
+            output_tree: OutputTree::Db,
+            line_endings: LineEnding::Lf,
+            trailing_newline: true,
+            only: Vec::new(),
+            since: None,
+            step_numbers: false,
+            debug_unknown: false,
+            locale: None,
+            format_calcs: None,
+            inline_calcs: false,
+            escape_calc_newlines: false,
+            calc_comments: false,
+            dependencies: false,
+            field_names: FieldNames::Qualified,
+            bool_style: BoolStyle::Words,
+            redact: false,
+        }
+    }
 }
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+
+use crate::analysis::complexity::csv_field;
+use crate::analysis::model::ExportModel;
+use crate::analysis::read_exports;
+use crate::exit_status::ExitStatus;
+
+/// Run `--format csv`: write one CSV file per schema catalog to the output folder
+pub fn run(inputs: &[PathBuf], out_dir: &Path) -> Result<ExitStatus, Error> {
+    let models = read_exports(inputs)?;
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Error creating directory {}", out_dir.display()))?;
+    for (file_name, csv) in [
+        ("fields.csv", fields_csv(&models)),
+        ("value_lists.csv", value_lists_csv(&models)),
+        ("relationships.csv", relationships_csv(&models)),
+    ] {
+        let path = out_dir.join(file_name);
+        fs::write(&path, csv).with_context(|| format!("Error writing file {}", path.display()))?;
+    }
+    Ok(ExitStatus::Clean)
+}
+
+fn fields_csv(models: &[ExportModel]) -> String {
+    let mut csv =
+        String::from("db,table,id,name,field_type,data_type,global,repetitions,comment\n");
+    for model in models {
+        for field in &model.fields {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&model.db_name),
+                csv_field(&field.table),
+                field.id,
+                csv_field(&field.name),
+                field.field_type,
+                field.data_type,
+                field.global,
+                field.repetitions,
+                csv_field(&field.comment)
+            ));
+        }
+    }
+    csv
+}
+
+/// The values of custom value lists are listed one per line in their cell
+fn value_lists_csv(models: &[ExportModel]) -> String {
+    let mut csv = String::from("db,id,name,source,field,values\n");
+    for model in models {
+        for value_list in &model.value_lists {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                csv_field(&model.db_name),
+                value_list.id,
+                csv_field(&value_list.name),
+                value_list.source,
+                csv_field(&value_list.field),
+                csv_field(&value_list.values.join("\n"))
+            ));
+        }
+    }
+    csv
+}
+
+fn relationships_csv(models: &[ExportModel]) -> String {
+    let mut csv = String::from("db,id,left_table_occurrence,right_table_occurrence,predicates\n");
+    for model in models {
+        for relationship in &model.relationships {
+            let predicates = relationship
+                .predicates
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv_field(&model.db_name),
+                relationship.id,
+                csv_field(&relationship.left),
+                csv_field(&relationship.right),
+                csv_field(&predicates)
+            ));
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <ValueListCatalog>
+            <ValueList id="1" name="Status">
+                <Source value="Custom"></Source>
+                <CustomValues><Text><![CDATA[Open&#13;Paid]]></Text></CustomValues>
+            </ValueList>
+            <ValueList id="2" name="Customers">
+                <Source value="FromField"></Source>
+            </ValueList>
+        </ValueListCatalog>
+        <RelationshipCatalog>
+            <Relationship id="5">
+                <LeftTable><TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference></LeftTable>
+                <RightTable><TableOccurrenceReference id="1065090" name="Lines"></TableOccurrenceReference></RightTable>
+                <JoinPredicateList>
+                    <JoinPredicate type="Equal">
+                        <LeftField><FieldReference id="1" name="ID"></FieldReference></LeftField>
+                        <RightField><FieldReference id="2" name="ID_Invoice"></FieldReference></RightField>
+                    </JoinPredicate>
+                    <JoinPredicate type="Less">
+                        <LeftField><FieldReference id="3" name="Date"></FieldReference></LeftField>
+                        <RightField><FieldReference id="4" name="Shipped"></FieldReference></RightField>
+                    </JoinPredicate>
+                </JoinPredicateList>
+            </Relationship>
+        </RelationshipCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="ID" fieldtype="Normal" datatype="Number" comment="Primary key, serial">
+                        <Storage global="False" maxRepetitions="1"></Storage>
+                    </Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <OptionsForValueLists>
+            <ValueList>
+                <ValueListReference id="2" name="Customers"></ValueListReference>
+                <Field>
+                    <PrimaryField>
+                        <FieldReference id="2" name="Name"><TableOccurrenceReference id="1065091" name="Customers"></TableOccurrenceReference></FieldReference>
+                    </PrimaryField>
+                </Field>
+            </ValueList>
+        </OptionsForValueLists>
+    "#;
+
+    #[test]
+    fn test_catalog_csv() {
+        let models = vec![read_model(&export(CATALOGS).replace("&#13;", "\r"))];
+        assert_eq!(
+            fields_csv(&models),
+            "db,table,id,name,field_type,data_type,global,repetitions,comment\n\
+             Invoices,Invoices,1,ID,Normal,Number,false,1,\"Primary key, serial\"\n"
+        );
+        assert_eq!(
+            value_lists_csv(&models),
+            "db,id,name,source,field,values\n\
+             Invoices,1,Status,Custom,,\"Open\nPaid\"\n\
+             Invoices,2,Customers,FromField,Customers::Name,\n"
+        );
+        assert_eq!(
+            relationships_csv(&models),
+            "db,id,left_table_occurrence,right_table_occurrence,predicates\n\
+             Invoices,5,Invoices,Lines,ID = ID_Invoice AND Date < Shipped\n"
+        );
+    }
+}
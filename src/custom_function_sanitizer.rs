@@ -4,8 +4,12 @@ use std::path::Path;
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 
+use crate::calc_formatter::apply_calc_style;
+use crate::config::Flags;
+use crate::diagnostics::{line_column_at, Diagnostics};
 use crate::utils::write_text_file;
 use crate::utils::xml_utils::cdata_to_string;
+use crate::xml_processor::RunContext;
 
 #[derive(Debug, Default)]
 struct CfInfo {
@@ -16,37 +20,66 @@ struct CfInfo {
 
 /// Process all XML files in the cf directory and create sanitized text versions
 /// This function mirrors the folder structure of the XML files
-pub fn create_sanitized_custom_functions(cf_xml_out_dir_path: &Path, cf_text_out_dir_path: &Path) {
+pub fn create_sanitized_custom_functions(
+    db_name: &str,
+    cf_xml_out_dir_path: &Path,
+    cf_text_out_dir_path: &Path,
+    flags: &Flags,
+    run: &RunContext,
+) {
     // Recursively process all XML files in the cf directory
     process_directory_recursively(
+        db_name,
         cf_xml_out_dir_path,
         cf_xml_out_dir_path,
         cf_text_out_dir_path,
+        flags,
+        run,
     );
 }
 
 fn process_directory_recursively(
+    db_name: &str,
     current_dir: &Path,
     cf_xml_out_dir_path: &Path,
     cf_text_out_dir_path: &Path,
+    flags: &Flags,
+    run: &RunContext,
 ) {
     if let Ok(entries) = fs::read_dir(current_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("xml") {
-                process_cf_xml_file(&path, cf_xml_out_dir_path, cf_text_out_dir_path);
+                process_cf_xml_file(
+                    db_name,
+                    &path,
+                    cf_xml_out_dir_path,
+                    cf_text_out_dir_path,
+                    flags,
+                    run,
+                );
             } else if path.is_dir() {
                 // Recursively process subdirectories
-                process_directory_recursively(&path, cf_xml_out_dir_path, cf_text_out_dir_path);
+                process_directory_recursively(
+                    db_name,
+                    &path,
+                    cf_xml_out_dir_path,
+                    cf_text_out_dir_path,
+                    flags,
+                    run,
+                );
             }
         }
     }
 }
 
 fn process_cf_xml_file(
+    db_name: &str,
     xml_file_path: &Path,
     cf_xml_out_dir_path: &Path,
     cf_text_out_dir_path: &Path,
+    flags: &Flags,
+    run: &RunContext,
 ) {
     // Read the XML file content
     let xml_content = match fs::read_to_string(xml_file_path) {
@@ -58,8 +91,24 @@ fn process_cf_xml_file(
     };
 
     // Parse the script and create sanitized text
-    let cf_info = parse_cf_xml(&xml_content);
-    if let Some(cf_info) = cf_info {
+    let mut diagnostics = Diagnostics::default();
+    let cf_info = parse_cf_xml(&xml_content, &mut diagnostics);
+    let file_name = xml_file_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    for diagnostic in &mut diagnostics.entries {
+        run.stats.record_parse_warning();
+        if let Ok((line, column)) = line_column_at(xml_content.as_bytes(), diagnostic.offset) {
+            diagnostic.location = Some(format!("{file_name}:{line}:{column}"));
+        }
+    }
+    run.diagnostics
+        .extend(&format!("{db_name} / custom functions"), diagnostics);
+    if let Some(mut cf_info) = cf_info {
+        if let Some(style) = flags.calc_style() {
+            cf_info.text = apply_calc_style(&cf_info.text, &style);
+        }
         // Determine the relative path from the XML file to maintain folder structure
         let relative_path = xml_file_path
             .strip_prefix(cf_xml_out_dir_path)
@@ -75,11 +124,12 @@ fn process_cf_xml_file(
 
         // Change extension to .txt
         let output_file_path = output_file_path.with_extension("txt");
-        write_text_file(&output_file_path, &cf_info.text);
+        let result = write_text_file(&output_file_path, &cf_info.text, flags, run.writer.as_ref());
+        run.stats.record_write_result(result);
     }
 }
 
-fn parse_cf_xml(xml_content: &str) -> Option<CfInfo> {
+fn parse_cf_xml(xml_content: &str, diagnostics: &mut Diagnostics) -> Option<CfInfo> {
     let mut cf_info = CfInfo::default();
 
     let mut reader = Reader::from_str(xml_content);
@@ -89,7 +139,7 @@ fn parse_cf_xml(xml_content: &str) -> Option<CfInfo> {
     loop {
         match reader.read_event_into(&mut buf) {
             Err(e) => {
-                println!("Error parsing XML: {e}");
+                diagnostics.warn(reader.error_position(), e);
                 break;
             }
             Ok(Event::Eof) => break,
@@ -106,13 +156,17 @@ fn parse_cf_xml(xml_content: &str) -> Option<CfInfo> {
                     }
                 }
             }
+            // A calculation containing `]]>`, e.g. in a comment, is split into several CDATA sections
             Ok(Event::CData(e)) => {
-                cf_info.text = cdata_to_string(&e);
-                break;
+                cf_info.text.push_str(&cdata_to_string(&e));
             }
-            Ok(Event::End(_e)) => {
+            Ok(Event::End(e)) => {
                 depth -= 1;
 
+                if e.name().as_ref() == b"Text" && !cf_info.text.is_empty() {
+                    break;
+                }
+
                 if depth == 0 {
                     break;
                 }
@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::analysis::callgraph::step_calls;
+use crate::analysis::lint::step_variables;
+use crate::analysis::model::StepModel;
+use crate::utils::attributes::parse_unescaped_attribute;
+
+/// What the enabled steps of a script refer to, for the section appended by `--dependencies`
+#[derive(Debug, Default, PartialEq)]
+pub struct Dependencies {
+    pub layouts: BTreeSet<String>,
+    /// Scripts performed or installed, with their file if it's another one
+    pub scripts: BTreeSet<String>,
+    /// Fields set, targeted or used by calculations, qualified by their table occurrence
+    pub fields: BTreeSet<String>,
+    pub variables: BTreeSet<String>,
+}
+
+impl Dependencies {
+    pub fn add_step(&mut self, step: &StepModel) {
+        if !step.enabled {
+            return;
+        }
+        for call in step_calls(0, step) {
+            // Scripts named by a calculation can't be listed
+            if call.script_id.is_none() {
+                continue;
+            }
+            self.scripts.insert(match call.file {
+                Some(file) => format!("{} ({file})", call.script),
+                None => call.script,
+            });
+        }
+        self.variables.extend(
+            step_variables(step)
+                .into_iter()
+                .map(|variable| variable.name),
+        );
+
+        let mut field = None;
+        let mut reader = Reader::from_str(&step.xml);
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(_) | Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => match e.name().as_ref() {
+                    b"LayoutReference" => {
+                        self.layouts.extend(
+                            parse_unescaped_attribute(&e, "name").filter(|name| !name.is_empty()),
+                        );
+                    }
+                    b"FieldReference" => field = parse_unescaped_attribute(&e, "name"),
+                    b"TableOccurrenceReference" => {
+                        if let (Some(field), Some(table)) =
+                            (field.take(), parse_unescaped_attribute(&e, "name"))
+                        {
+                            self.fields.insert(format!("{table}::{field}"));
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::End(e)) if e.name().as_ref() == b"FieldReference" => field = None,
+                _ => {}
+            }
+            buf.clear();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layouts.is_empty()
+            && self.scripts.is_empty()
+            && self.fields.is_empty()
+            && self.variables.is_empty()
+    }
+
+    /// A `Dependencies` heading and one line per kind of dependency, empty if there are none
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut text = String::from("\n--- Dependencies ---\n");
+        for (label, names) in [
+            ("Layouts", &self.layouts),
+            ("Scripts", &self.scripts),
+            ("Fields", &self.fields),
+            ("Variables", &self.variables),
+        ] {
+            if !names.is_empty() {
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                text.push_str(&format!("{label}: {}\n", names.join(", ")));
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: u32, enabled: bool, xml: &str) -> StepModel {
+        StepModel {
+            number: 1,
+            id,
+            name: String::new(),
+            enabled,
+            xml: xml.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut dependencies = Dependencies::default();
+        dependencies.add_step(&step(
+            6,
+            true,
+            r#"<Step id="6" name="Go to Layout" enable="True"><ParameterValues><Parameter type="LayoutReferenceContainer"><LayoutReferenceContainer value="5"><LayoutReference id="2" name="Contacts"></LayoutReference></LayoutReferenceContainer></Parameter></ParameterValues></Step>"#,
+        ));
+        dependencies.add_step(&step(
+            76,
+            true,
+            r#"<Step id="76" name="Set Field" enable="True"><ParameterValues><Parameter type="FieldReference"><FieldReference id="1" name="Total"><TableOccurrenceReference id="1" name="Invoices"></TableOccurrenceReference></FieldReference></Parameter><Parameter type="Calculation"><Calculation><Calculation><Text><![CDATA[$sum + $$rate]]></Text></Calculation></Calculation></Parameter></ParameterValues></Step>"#,
+        ));
+        dependencies.add_step(&step(
+            1,
+            true,
+            r#"<Step id="1" name="Perform Script" enable="True"><ParameterValues><Parameter type="List"><List name="From list" value="1"><ScriptReference id="2" name="Print"></ScriptReference></List></Parameter></ParameterValues></Step>"#,
+        ));
+        dependencies.add_step(&step(
+            6,
+            false,
+            r#"<Step id="6" name="Go to Layout" enable="False"><ParameterValues><Parameter type="LayoutReferenceContainer"><LayoutReferenceContainer value="5"><LayoutReference id="3" name="Disabled"></LayoutReference></LayoutReferenceContainer></Parameter></ParameterValues></Step>"#,
+        ));
+        assert_eq!(
+            dependencies.render(),
+            "
+--- Dependencies ---
+Layouts: Contacts
+Scripts: Print
+Fields: Invoices::Total
+Variables: $$rate, $sum
+"
+        );
+        assert_eq!(Dependencies::default().render(), "");
+    }
+}
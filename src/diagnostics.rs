@@ -0,0 +1,145 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::Mutex;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A problem found while parsing, e.g. malformed XML that was skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnostic {
+    /// Where the problem occurred, e.g. the database and script
+    pub context: String,
+    /// Byte offset in the parsed XML
+    pub offset: u64,
+    pub message: String,
+    /// The offset as `file:line:column`, if known
+    pub location: Option<String>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.context.is_empty() {
+            write!(f, "{}: ", self.context)?;
+        }
+        match &self.location {
+            Some(location) => write!(f, "{} ({location})", self.message),
+            None => write!(f, "{} (at byte {})", self.message, self.offset),
+        }
+    }
+}
+
+/// Translate a byte offset into a 1-based line and column, counting columns in characters
+pub fn line_column_at<R: Read>(source: R, offset: u64) -> io::Result<(u64, u64)> {
+    let mut reader = BufReader::new(source.take(offset));
+    let (mut line, mut column) = (1, 1);
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok((line, column));
+        }
+        for &byte in chunk {
+            match byte {
+                b'\n' => {
+                    line += 1;
+                    column = 1;
+                }
+                // Continuation bytes of multi-byte UTF-8 characters
+                0x80..=0xBF => {}
+                _ => column += 1,
+            }
+        }
+        let len = chunk.len();
+        reader.consume(len);
+    }
+}
+
+/// Warnings collected while parsing one XML fragment, e.g. a script step
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn warn(&mut self, offset: u64, message: impl Display) {
+        self.entries.push(Diagnostic {
+            context: String::new(),
+            offset,
+            message: message.to_string(),
+            location: None,
+        });
+    }
+
+    /// Take over the diagnostics of a nested fragment, prefixed with where the fragment belongs to
+    pub fn append(&mut self, context: &str, other: Diagnostics) {
+        self.entries
+            .extend(other.entries.into_iter().map(|mut diagnostic| {
+                diagnostic.context = match diagnostic.context.is_empty() {
+                    true => context.to_string(),
+                    false => format!("{context}: {}", diagnostic.context),
+                };
+                diagnostic
+            }));
+    }
+}
+
+/// Diagnostics of all exports processed in parallel, reported at the end of the run
+#[derive(Debug, Default)]
+pub struct DiagnosticsCollector {
+    entries: Mutex<Vec<Diagnostic>>,
+}
+
+impl DiagnosticsCollector {
+    /// Add the diagnostics of a fragment, prefixed with where the fragment belongs to
+    pub fn extend(&self, context: &str, diagnostics: Diagnostics) {
+        let mut prefixed = Diagnostics::default();
+        prefixed.append(context, diagnostics);
+        self.entries.lock().unwrap().extend(prefixed.entries);
+    }
+
+    pub fn push(&self, diagnostic: Diagnostic) {
+        self.entries.lock().unwrap().push(diagnostic);
+    }
+
+    /// All diagnostics in a stable order, independent of the order exports were processed in
+    pub fn sorted(&self) -> Vec<Diagnostic> {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.sort_by(|a, b| a.context.cmp(&b.context).then(a.offset.cmp(&b.offset)));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let collector = DiagnosticsCollector::default();
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.warn(42, "ill-formed document");
+        collector.extend("Admin / script 'B'", diagnostics);
+        let mut step_diagnostics = Diagnostics::default();
+        step_diagnostics.warn(7, "unexpected end");
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.append("step 1", step_diagnostics);
+        collector.extend("Admin / script 'A'", diagnostics);
+
+        let sorted = collector.sorted();
+        assert_eq!(sorted.len(), 2);
+        assert_eq!(
+            sorted[0].to_string(),
+            "Admin / script 'A': step 1: unexpected end (at byte 7)"
+        );
+        assert_eq!(sorted[1].offset, 42);
+    }
+
+    #[test]
+    fn test_line_column_at() {
+        let text = "<a>\n  <ä>\n\t<b/>";
+        assert_eq!(line_column_at(text.as_bytes(), 0).unwrap(), (1, 1));
+        assert_eq!(line_column_at(text.as_bytes(), 6).unwrap(), (2, 3));
+        assert_eq!(line_column_at(text.as_bytes(), 10).unwrap(), (2, 6));
+        assert_eq!(line_column_at(text.as_bytes(), 13).unwrap(), (3, 3));
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::ExportModel;
+use crate::analysis::{read_exports, write_output};
+use crate::diagnostics::Diagnostics;
+use crate::exit_status::ExitStatus;
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::sanitizer::sanitize;
+use crate::symbols::SymbolKind;
+use crate::{AnalysisInput, DiffFormat};
+
+/// Unchanged lines shown around the changes of a hunk of `--format unified`
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    Script,
+    Layout,
+    Table,
+    TableOccurrence,
+    Field,
+    ValueList,
+}
+
+impl ElementKind {
+    const ALL: [ElementKind; 6] = [
+        Self::Script,
+        Self::Layout,
+        Self::Table,
+        Self::TableOccurrence,
+        Self::Field,
+        Self::ValueList,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Script => "script",
+            Self::Layout => "layout",
+            Self::Table => "table",
+            Self::TableOccurrence => "table occurrence",
+            Self::Field => "field",
+            Self::ValueList => "value list",
+        }
+    }
+
+    fn symbol_kind(self) -> Option<SymbolKind> {
+        match self {
+            Self::Script => Some(SymbolKind::Script),
+            Self::Layout => Some(SymbolKind::Layout),
+            Self::Table => Some(SymbolKind::BaseTable),
+            Self::TableOccurrence => Some(SymbolKind::TableOccurrence),
+            Self::ValueList => Some(SymbolKind::ValueList),
+            Self::Field => None,
+        }
+    }
+}
+
+/// What the steps of scripts are compared by
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffOptions {
+    /// Leave out comment steps
+    pub ignore_comments: bool,
+    /// Consider scripts whose steps were only reordered unchanged
+    pub ignore_order: bool,
+}
+
+/// An element added, removed, renamed or, for scripts, with changed steps between two exports.
+/// Elements are the same if they have the same UUID, or the same id if one of them has no UUID.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ElementChange {
+    pub db: String,
+    pub kind: ElementKind,
+    /// The id in the newer export, or in the older one if the element was removed
+    pub id: u32,
+    /// `None` if the element was added
+    pub old_name: Option<String>,
+    /// `None` if the element was removed
+    pub new_name: Option<String>,
+    /// Removed and added lines of the rendered steps of a script, prefixed with `- ` and `+ `
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<String>,
+    /// All step lines of a script with how they changed, for `--format unified`
+    #[serde(skip)]
+    pub edits: Vec<(Edit, String)>,
+}
+
+/// How a line changed from the older to the newer export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// An element of one export; fields are named with their table, e.g. `Invoices::Total`
+struct Element<'a> {
+    /// The base table of a field and the id of the element
+    key: (u32, u32),
+    uuid: Option<&'a str>,
+    name: String,
+}
+
+/// Run the `diff` subcommand on an older and a newer export, or directories of exports
+pub fn run(
+    old: &Path,
+    new: &Path,
+    output: Option<&Path>,
+    format: DiffFormat,
+    options: DiffOptions,
+) -> Result<ExitStatus, Error> {
+    let old_models = read_exports(&[old.to_path_buf()])?;
+    let new_models = read_exports(&[new.to_path_buf()])?;
+    let changes = pair_exports(old_models, new_models)
+        .iter()
+        .flat_map(|(old, new)| diff(old, new, options))
+        .collect::<Vec<_>>();
+
+    let input = AnalysisInput {
+        inputs: vec![PathBuf::from(old), PathBuf::from(new)],
+        output: output.map(Path::to_path_buf),
+    };
+    write_output(&input, &render(&changes, format)?)?;
+    Ok(ExitStatus::Clean)
+}
+
+/// Pair the exports of the same database by name; a single export on each side is paired even if
+/// the file was renamed. Exports without a partner are compared with an empty export.
+fn pair_exports(old: Vec<ExportModel>, new: Vec<ExportModel>) -> Vec<(ExportModel, ExportModel)> {
+    if old.len() == 1 && new.len() == 1 {
+        return old.into_iter().zip(new).collect();
+    }
+    let mut new_by_name: HashMap<String, ExportModel> = new
+        .into_iter()
+        .map(|model| (model.db_name.clone(), model))
+        .collect();
+    let mut pairs = Vec::new();
+    for old_model in old {
+        let new_model = new_by_name
+            .remove(&old_model.db_name)
+            .unwrap_or_else(|| ExportModel {
+                db_name: old_model.db_name.clone(),
+                ..Default::default()
+            });
+        pairs.push((old_model, new_model));
+    }
+    let mut added = new_by_name.into_values().collect::<Vec<_>>();
+    added.sort_by(|a, b| a.db_name.cmp(&b.db_name));
+    for new_model in added {
+        let old_model = ExportModel {
+            db_name: new_model.db_name.clone(),
+            ..Default::default()
+        };
+        pairs.push((old_model, new_model));
+    }
+    pairs
+}
+
+/// The changes between two exports of a database, by kind of element: changed and added elements
+/// in the order of their ids, followed by the removed ones
+pub fn diff(old: &ExportModel, new: &ExportModel, options: DiffOptions) -> Vec<ElementChange> {
+    let mut changes = Vec::new();
+    for kind in ElementKind::ALL {
+        let old_elements = elements(old, kind);
+        let new_elements = elements(new, kind);
+        let old_by_uuid: HashMap<&str, usize> = old_elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| Some((element.uuid?, index)))
+            .collect();
+        let old_by_key: HashMap<(u32, u32), usize> = old_elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (element.key, index))
+            .collect();
+
+        let mut matched = HashSet::new();
+        for element in &new_elements {
+            let old_index = match element.uuid.and_then(|uuid| old_by_uuid.get(uuid)) {
+                Some(index) => Some(*index),
+                None => old_by_key
+                    .get(&element.key)
+                    .copied()
+                    .filter(|index| element.uuid.is_none() || old_elements[*index].uuid.is_none()),
+            };
+            let Some(old_index) = old_index else {
+                changes.push(change(old, new, kind, None, Some(element), options));
+                continue;
+            };
+            matched.insert(old_index);
+            let old_element = &old_elements[old_index];
+            let change = change(old, new, kind, Some(old_element), Some(element), options);
+            if old_element.name != element.name || !change.steps.is_empty() {
+                changes.push(change);
+            }
+        }
+        for (index, element) in old_elements.iter().enumerate() {
+            if !matched.contains(&index) {
+                changes.push(change(old, new, kind, Some(element), None, options));
+            }
+        }
+    }
+    changes
+}
+
+fn change(
+    old_model: &ExportModel,
+    new_model: &ExportModel,
+    kind: ElementKind,
+    old: Option<&Element>,
+    new: Option<&Element>,
+    options: DiffOptions,
+) -> ElementChange {
+    let edits = match kind {
+        ElementKind::Script => {
+            let lines = |model, element: Option<&Element>| {
+                element.map_or_else(Vec::new, |element| {
+                    script_lines(model, element.key.1, options)
+                })
+            };
+            let old_lines = lines(old_model, old);
+            let new_lines = lines(new_model, new);
+            let reordered = options.ignore_order
+                && old.is_some()
+                && new.is_some()
+                && is_permutation(&old_lines, &new_lines);
+            match reordered {
+                true => Vec::new(),
+                false => diff_lines(&old_lines, &new_lines),
+            }
+        }
+        _ => Vec::new(),
+    };
+    // Only the changed lines of scripts in both exports are listed, not all lines of added ones
+    let steps = match (old, new) {
+        (Some(_), Some(_)) => edits
+            .iter()
+            .filter_map(|(edit, line)| match edit {
+                Edit::Keep => None,
+                Edit::Remove => Some(format!("- {line}")),
+                Edit::Add => Some(format!("+ {line}")),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    ElementChange {
+        db: new_model.db_name.clone(),
+        kind,
+        id: new.or(old).map_or(0, |element| element.key.1),
+        old_name: old.map(|element| element.name.clone()),
+        new_name: new.map(|element| element.name.clone()),
+        steps,
+        edits,
+    }
+}
+
+/// Whether two texts have the same lines, in any order
+fn is_permutation(old: &[String], new: &[String]) -> bool {
+    let mut old = old.iter().collect::<Vec<_>>();
+    let mut new = new.iter().collect::<Vec<_>>();
+    old.sort();
+    new.sort();
+    old == new
+}
+
+/// The elements of a kind in the order of their ids
+fn elements(model: &ExportModel, kind: ElementKind) -> Vec<Element<'_>> {
+    let mut elements: Vec<Element> = match kind.symbol_kind() {
+        Some(symbol_kind) => model
+            .symbols
+            .symbols()
+            .filter(|(symbol_kind_of, _, _)| *symbol_kind_of == symbol_kind)
+            .map(|(_, id, symbol)| Element {
+                key: (0, id),
+                uuid: symbol.uuid.as_deref(),
+                name: symbol.name.clone(),
+            })
+            .collect(),
+        None => model
+            .symbols
+            .fields()
+            .map(|(table_id, id, field)| Element {
+                key: (table_id, id),
+                uuid: field.uuid.as_deref(),
+                name: format!(
+                    "{}::{}",
+                    model
+                        .symbols
+                        .name(SymbolKind::BaseTable, table_id)
+                        .unwrap_or_default(),
+                    field.name
+                ),
+            })
+            .collect(),
+    };
+    elements.sort_by_key(|element| element.key);
+    elements
+}
+
+/// The steps of a script rendered like the exploded scripts, one entry per line. The rendered text
+/// leaves out ids, UUIDs and other attributes that change without changing what a step does;
+/// trailing whitespace is dropped as well.
+fn script_lines(model: &ExportModel, script_id: u32, options: DiffOptions) -> Vec<String> {
+    let Some(script) = model.scripts.iter().find(|script| script.id == script_id) else {
+        return Vec::new();
+    };
+    script
+        .steps
+        .iter()
+        .filter(|step| {
+            !options.ignore_comments || id_to_script_step(&step.id) != ScriptStep::Comment
+        })
+        .flat_map(|step| {
+            let rendered = sanitize(&step.id, &step.xml, &mut Diagnostics::default())
+                .unwrap_or_else(|| step.name.clone());
+            rendered
+                .replace('\r', "\n")
+                .lines()
+                .map(|line| line.trim_end().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// All lines of two texts, kept if in their longest common subsequence, else removed or added
+fn diff_lines(old: &[String], new: &[String]) -> Vec<(Edit, String)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_prefix, old_suffix) = (&old[..prefix], &old[old.len() - suffix..]);
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    // Length of the common subsequence of the remaining lines from each position on
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut edits: Vec<(Edit, String)> = old_prefix
+        .iter()
+        .map(|line| (Edit::Keep, line.clone()))
+        .collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push((Edit::Keep, old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            edits.push((Edit::Remove, old[i].clone()));
+            i += 1;
+        } else {
+            edits.push((Edit::Add, new[j].clone()));
+            j += 1;
+        }
+    }
+    edits.extend(old_suffix.iter().map(|line| (Edit::Keep, line.clone())));
+    // Nothing changed
+    if edits.iter().all(|(edit, _)| *edit == Edit::Keep) {
+        edits.clear();
+    }
+    edits
+}
+
+/// The hunks of a unified diff, with up to [`CONTEXT_LINES`] unchanged lines around the changes
+fn unified_hunks(edits: &[(Edit, String)]) -> String {
+    // Ranges of edits to show, merged where their context overlaps
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, _) in edits
+        .iter()
+        .enumerate()
+        .filter(|(_, (edit, _))| *edit != Edit::Keep)
+    {
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let mut text = String::new();
+    for (start, end) in ranges {
+        // Lines of the older and of the newer text among the edits
+        let old_lines =
+            |edits: &[(Edit, String)]| edits.iter().filter(|(edit, _)| *edit != Edit::Add).count();
+        let new_lines = |edits: &[(Edit, String)]| {
+            edits
+                .iter()
+                .filter(|(edit, _)| *edit != Edit::Remove)
+                .count()
+        };
+        let hunk = &edits[start..end];
+        let (old_before, new_before) = (old_lines(&edits[..start]), new_lines(&edits[..start]));
+        let (old_count, new_count) = (old_lines(hunk), new_lines(hunk));
+        // An empty side starts at the line before it, like in the output of diff -u
+        let line = |before: usize, count: usize| match count {
+            0 => format!("{before},0"),
+            _ => format!("{},{count}", before + 1),
+        };
+        text.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            line(old_before, old_count),
+            line(new_before, new_count)
+        ));
+        for (edit, line) in hunk {
+            let prefix = match edit {
+                Edit::Keep => ' ',
+                Edit::Remove => '-',
+                Edit::Add => '+',
+            };
+            text.push_str(&format!("{prefix}{line}\n"));
+        }
+    }
+    text
+}
+
+/// The name of an element in the headers of `--format unified`, e.g. `Invoices/script/Print (ID 1)`
+fn unified_name(change: &ElementChange, name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!(
+            "{}/{}/{name} (ID {})",
+            change.db,
+            change.kind.label(),
+            change.id
+        ),
+        None => "/dev/null".to_string(),
+    }
+}
+
+pub fn render(changes: &[ElementChange], format: DiffFormat) -> Result<String, Error> {
+    match format {
+        DiffFormat::Text => Ok(changes
+            .iter()
+            .map(|change| {
+                let label = change.kind.label();
+                let summary = match (&change.old_name, &change.new_name) {
+                    (Some(old), Some(new)) if old != new => {
+                        format!("{label} renamed from \"{old}\" to \"{new}\"")
+                    }
+                    (Some(name), Some(_)) => format!("{label} \"{name}\" changed"),
+                    (None, Some(name)) => format!("{label} \"{name}\" added"),
+                    (Some(name), None) => format!("{label} \"{name}\" removed"),
+                    (None, None) => label.to_string(),
+                };
+                let mut text = format!("{}: {summary} (ID {})\n", change.db, change.id);
+                for line in &change.steps {
+                    text.push_str(&format!("    {line}\n"));
+                }
+                text
+            })
+            .collect()),
+        DiffFormat::Unified => Ok(changes
+            .iter()
+            .map(|change| {
+                format!(
+                    "--- {}\n+++ {}\n{}",
+                    unified_name(change, &change.old_name),
+                    unified_name(change, &change.new_name),
+                    unified_hunks(&change.edits)
+                )
+            })
+            .collect()),
+        DiffFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(changes)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const OLD: &str = r#"
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice"><UUID>LAYOUT-1</UUID></Layout>
+            <Layout id="2" name="Old list"><UUID>LAYOUT-2</UUID></Layout>
+        </LayoutCatalog>
+        <ScriptCatalog>
+            <Script id="1" name="Print"><UUID>SCRIPT-1</UUID></Script>
+            <Script id="2" name="Unchanged"><UUID>SCRIPT-2</UUID></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Print"></ScriptReference>
+                <ObjectList>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                    <Step id="103" name="Exit Script" enable="True"></Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Unchanged"></ScriptReference>
+                <ObjectList>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    const NEW: &str = r#"
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice"><UUID>LAYOUT-1</UUID></Layout>
+            <Layout id="2" name="New list"><UUID>LAYOUT-3</UUID></Layout>
+        </LayoutCatalog>
+        <ScriptCatalog>
+            <Script id="1" name="Print invoice"><UUID>SCRIPT-1</UUID></Script>
+            <Script id="2" name="Unchanged"><UUID>SCRIPT-2</UUID></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Print invoice"></ScriptReference>
+                <ObjectList>
+                    <Step id="93" name="Beep" enable="False"></Step>
+                    <Step id="103" name="Exit Script" enable="True"></Step>
+                </ObjectList>
+            </Script>
+            <Script>
+                <ScriptReference id="2" name="Unchanged"></ScriptReference>
+                <ObjectList>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_render_text() {
+        let changes = diff(
+            &read_model(&export(OLD)),
+            &read_model(&export(NEW)),
+            DiffOptions::default(),
+        );
+        let expected = r#"Invoices: script renamed from "Print" to "Print invoice" (ID 1)
+    - Beep
+    + // Beep
+Invoices: layout "New list" added (ID 2)
+Invoices: layout "Old list" removed (ID 2)
+"#;
+        assert_eq!(render(&changes, DiffFormat::Text).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_render_unified() {
+        let changes = diff(
+            &read_model(&export(OLD)),
+            &read_model(&export(NEW)),
+            DiffOptions::default(),
+        );
+        let expected = r#"--- Invoices/script/Print (ID 1)
++++ Invoices/script/Print invoice (ID 1)
+@@ -1,2 +1,2 @@
+-Beep
++// Beep
+ Exit Script []
+--- /dev/null
++++ Invoices/layout/New list (ID 2)
+--- Invoices/layout/Old list (ID 2)
++++ /dev/null
+"#;
+        assert_eq!(render(&changes, DiffFormat::Unified).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unified_hunks() {
+        let lines = |text: &str| text.split(' ').map(str::to_string).collect::<Vec<_>>();
+        let edits = diff_lines(&lines("a b c d e f g h i j"), &lines("a c d e f g h i x j"));
+        assert_eq!(
+            unified_hunks(&edits),
+            "@@ -1,5 +1,4 @@\n a\n-b\n c\n d\n e\n@@ -7,4 +6,5 @@\n g\n h\n i\n+x\n j\n"
+        );
+        assert_eq!(
+            unified_hunks(&diff_lines(&[], &lines("a"))),
+            "@@ -0,0 +1,1 @@\n+a\n"
+        );
+    }
+
+    #[test]
+    fn test_ignore_comments_and_order() {
+        let script = |steps: &str| {
+            export(&format!(
+                r#"
+                    <ScriptCatalog><Script id="1" name="Print"><UUID>SCRIPT-1</UUID></Script></ScriptCatalog>
+                    <StepsForScripts><Script>
+                        <ScriptReference id="1" name="Print"></ScriptReference>
+                        <ObjectList>{steps}</ObjectList>
+                    </Script></StepsForScripts>
+                "#
+            ))
+        };
+        let comment = |text: &str| {
+            format!(
+                r##"<Step id="89" name="# (comment)" enable="True"><ParameterValues><Parameter type="Comment"><Comment value="{text}"></Comment></Parameter></ParameterValues></Step>"##
+            )
+        };
+        let beep = r#"<Step id="93" name="Beep" enable="True"></Step>"#;
+        let exit = r#"<Step id="103" name="Exit Script" enable="True"></Step>"#;
+        let old = read_model(&script(&format!("{}{beep}{exit}", comment("Old"))));
+        let new = read_model(&script(&format!("{}{exit}{beep}", comment("New"))));
+
+        assert_eq!(diff(&old, &new, DiffOptions::default()).len(), 1);
+        let ignore_comments = DiffOptions {
+            ignore_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            diff(&old, &new, ignore_comments)[0].steps,
+            vec!["- Beep", "+ Beep"]
+        );
+        let ignore_both = DiffOptions {
+            ignore_comments: true,
+            ignore_order: true,
+        };
+        assert_eq!(diff(&old, &new, ignore_both), Vec::new());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+
+/// Credentials sent with the requests of inputs given as URLs
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlAuth {
+    None,
+    Basic { user: String, password: String },
+    Bearer(String),
+}
+
+impl UrlAuth {
+    fn header(&self) -> Option<String> {
+        match self {
+            UrlAuth::None => None,
+            UrlAuth::Basic { user, password } => Some(format!(
+                "Basic {}",
+                STANDARD.encode(format!("{user}:{password}"))
+            )),
+            UrlAuth::Bearer(token) => Some(format!("Bearer {token}")),
+        }
+    }
+}
+
+/// Whether an input is an HTTP(S) URL instead of a path
+pub fn is_url(input: &Path) -> bool {
+    let input = input.to_string_lossy().to_ascii_lowercase();
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Download the inputs that are URLs into the directory, one folder each, and return the inputs
+/// with each URL replaced by its downloaded file
+pub fn download_url_inputs(
+    inputs: &[PathBuf],
+    dir: &Path,
+    auth: &UrlAuth,
+) -> Result<Vec<PathBuf>, Error> {
+    let agent = ureq::Agent::new_with_defaults();
+    let mut local_inputs = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        if !is_url(input) {
+            local_inputs.push(input.clone());
+            continue;
+        }
+        let url = input.to_string_lossy();
+        let file_dir = dir.join(index.to_string());
+        fs::create_dir_all(&file_dir)
+            .with_context(|| format!("Error creating directory {}", file_dir.display()))?;
+        let file_path = file_dir.join(file_name(&url));
+
+        let mut request = agent.get(url.as_ref());
+        if let Some(header) = auth.header() {
+            request = request.header("Authorization", header);
+        }
+        let mut response = request
+            .call()
+            .with_context(|| format!("Error downloading {url}"))?;
+        let mut file = File::create(&file_path)
+            .with_context(|| format!("Error creating file {}", file_path.display()))?;
+        io::copy(&mut response.body_mut().as_reader(), &mut file)
+            .with_context(|| format!("Error downloading {url}"))?;
+        local_inputs.push(file_path);
+    }
+    Ok(local_inputs)
+}
+
+/// The last path segment of the URL as file name of its download, `export.xml` if it has none
+fn file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let segment = path
+        .split_once("://")
+        .and_then(|(_, rest)| rest.split_once('/'))
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .unwrap_or_default();
+    let name = percent_decode_str(segment).decode_utf8_lossy();
+    // A decoded segment must not lead out of the download folder
+    let name = name.replace(['/', '\\'], "_");
+    match name.as_str() {
+        "" | "." | ".." => "export.xml".to_string(),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("https://artifacts.example.com/fm/Invoices%202026.xml?token=1"),
+            "Invoices 2026.xml"
+        );
+        assert_eq!(file_name("https://artifacts.example.com"), "export.xml");
+        assert_eq!(
+            file_name("https://artifacts.example.com/exports/"),
+            "export.xml"
+        );
+        assert_eq!(
+            file_name("https://example.com/a%2F..%2Fb.xml"),
+            "a_.._b.xml"
+        );
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url(Path::new("HTTPS://example.com/export.xml")));
+        assert!(!is_url(Path::new("exports/http.xml")));
+    }
+
+    #[test]
+    fn test_download_url_inputs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut authorization = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("authorization") {
+                        authorization = value.trim().to_string();
+                    }
+                }
+            }
+            let body = "<FMSaveAsXML/>";
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            authorization
+        });
+
+        let dir = ScratchDir::create("download-test").unwrap();
+        let inputs = vec![
+            PathBuf::from("exports/Local.xml"),
+            PathBuf::from(format!("http://127.0.0.1:{port}/Invoices.xml")),
+        ];
+        let auth = UrlAuth::Bearer("secret".to_string());
+        let local_inputs = download_url_inputs(&inputs, &dir.path, &auth).unwrap();
+
+        assert_eq!(server.join().unwrap(), "Bearer secret");
+        assert_eq!(local_inputs[0], inputs[0]);
+        assert_eq!(local_inputs[1], dir.path.join("1").join("Invoices.xml"));
+        assert_eq!(
+            fs::read_to_string(&local_inputs[1]).unwrap(),
+            "<FMSaveAsXML/>"
+        );
+    }
+}
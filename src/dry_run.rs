@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Flags;
+use crate::manifest::{db_output_dirs, relative_path_string};
+use crate::utils::file_utils::collect_files;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedChange {
+    Create,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for PlannedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create => write!(f, "create"),
+            Self::Update => write!(f, "update"),
+            Self::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// Compare the output exploded into the staging directory with the target directory
+/// Returns the changes a real run would apply to the target, sorted by path; unchanged files are omitted
+pub fn plan_changes(
+    staging_dir: &Path,
+    target_dir: &Path,
+    db_names: &[String],
+    flags: &Flags,
+) -> Vec<(PlannedChange, String)> {
+    let planned = files_by_relative_path(staging_dir, db_names, flags);
+    let existing = files_by_relative_path(target_dir, db_names, flags);
+
+    let mut changes = Vec::new();
+    for (relative_path, planned_path) in &planned {
+        match existing.get(relative_path) {
+            None => changes.push((PlannedChange::Create, relative_path.clone())),
+            Some(existing_path) => {
+                if fs::read(planned_path).ok() != fs::read(existing_path).ok() {
+                    changes.push((PlannedChange::Update, relative_path.clone()));
+                }
+            }
+        }
+    }
+    for relative_path in existing.keys() {
+        if !planned.contains_key(relative_path) {
+            changes.push((PlannedChange::Delete, relative_path.clone()));
+        }
+    }
+    changes.sort_by(|a, b| a.1.cmp(&b.1));
+    changes
+}
+
+fn files_by_relative_path(
+    root_out_dir: &Path,
+    db_names: &[String],
+    flags: &Flags,
+) -> BTreeMap<String, PathBuf> {
+    let mut file_paths = Vec::new();
+    for db_dir in db_output_dirs(root_out_dir, db_names, flags) {
+        collect_files(&db_dir, &mut file_paths);
+    }
+    file_paths
+        .into_iter()
+        .map(|path| (relative_path_string(root_out_dir, &path), path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_changes() {
+        let root = std::env::temp_dir().join(format!("fm-dry-run-test-{}", std::process::id()));
+        let staging = root.join("staging");
+        let target = root.join("target");
+        for dir in [&staging, &target] {
+            fs::create_dir_all(dir.join("db/scripts")).unwrap();
+        }
+        fs::write(staging.join("db/scripts/new.txt"), "new").unwrap();
+        fs::write(staging.join("db/scripts/changed.txt"), "after").unwrap();
+        fs::write(target.join("db/scripts/changed.txt"), "before").unwrap();
+        fs::write(staging.join("db/scripts/same.txt"), "same").unwrap();
+        fs::write(target.join("db/scripts/same.txt"), "same").unwrap();
+        fs::write(target.join("db/scripts/stale.txt"), "stale").unwrap();
+        fs::write(target.join("unrelated.txt"), "keep").unwrap();
+
+        let changes = plan_changes(&staging, &target, &["db".to_string()], &Flags::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                (PlannedChange::Update, "db/scripts/changed.txt".to_string()),
+                (PlannedChange::Create, "db/scripts/new.txt".to_string()),
+                (PlannedChange::Delete, "db/scripts/stale.txt".to_string()),
+            ]
+        );
+    }
+}
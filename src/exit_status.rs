@@ -0,0 +1,79 @@
+use std::process::ExitCode;
+
+use anyhow::Error;
+
+/// Exit code contract of a run, so automation can react without grepping stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// Everything was exploded without warnings
+    Clean = 0,
+    /// Invalid command line arguments
+    Usage = 1,
+    /// The run completed, but with parse warnings or unknown script steps
+    Warnings = 2,
+    /// The run completed, but at least one export ended prematurely, e.g. because it was interrupted
+    Truncated = 5,
+    /// At least one export could not be parsed
+    ParseError = 3,
+    /// Reading the input or writing the output failed
+    IoError = 4,
+    /// An analysis run with `--strict` found problems
+    Findings = 6,
+}
+
+impl ExitStatus {
+    /// How bad the outcome is, independent of the exit code numbers, which only ever get appended
+    pub fn severity(self) -> u8 {
+        match self {
+            Self::Clean => 0,
+            Self::Warnings => 1,
+            Self::Findings => 2,
+            Self::Truncated => 3,
+            Self::ParseError => 4,
+            Self::IoError => 5,
+            Self::Usage => 6,
+        }
+    }
+
+    /// Classify an error by whether it was caused by an IO failure
+    pub fn from_error(error: &Error) -> Self {
+        if error
+            .chain()
+            .any(|cause| cause.is::<std::io::Error>() || cause.is::<zip::result::ZipError>())
+        {
+            Self::IoError
+        } else {
+            Self::ParseError
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn test_from_error() {
+        let io_error: Result<(), _> = Err(std::io::Error::other("disk full"));
+        let io_error = io_error.context("Error writing file").unwrap_err();
+        assert_eq!(ExitStatus::from_error(&io_error), ExitStatus::IoError);
+
+        let parse_error = anyhow!("Unsupported XML-format");
+        assert_eq!(ExitStatus::from_error(&parse_error), ExitStatus::ParseError);
+    }
+
+    #[test]
+    fn test_severity() {
+        assert!(ExitStatus::Clean.severity() < ExitStatus::Warnings.severity());
+        assert!(ExitStatus::Warnings.severity() < ExitStatus::Truncated.severity());
+        assert!(ExitStatus::Truncated.severity() < ExitStatus::ParseError.severity());
+        assert!(ExitStatus::ParseError.severity() < ExitStatus::IoError.severity());
+    }
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, Context, Error, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::Value;
+
+use crate::FetchArgs;
+
+/// Pause between looks into the share while waiting for an export
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Run the export script on the server if one is given, then return the newest XML export of
+/// the share, waiting for the one saved by the script
+pub fn fetch(args: &FetchArgs) -> Result<PathBuf, Error> {
+    let Some(server) = &args.server else {
+        return newest_export(&args.share, None)?
+            .with_context(|| format!("No XML export found in {}", args.share.display()));
+    };
+    // File times of a share can lag behind the clock of this machine a little
+    let started = SystemTime::now() - Duration::from_secs(5);
+    run_script(server, args)?;
+
+    let deadline = Instant::now() + Duration::from_secs(args.wait);
+    let mut previous: Option<(PathBuf, u64)> = None;
+    loop {
+        if let Some(path) = newest_export(&args.share, Some(started))? {
+            // The export is complete once its size stops changing
+            let size = fs::metadata(&path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            if previous.as_ref() == Some(&(path.clone(), size)) {
+                return Ok(path);
+            }
+            previous = Some((path, size));
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "No new XML export in {} after {} seconds",
+                args.share.display(),
+                args.wait
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The most recently modified `.xml` file directly in the folder, if modified after `since`
+fn newest_export(share: &Path, since: Option<SystemTime>) -> Result<Option<PathBuf>, Error> {
+    let entries =
+        fs::read_dir(share).with_context(|| format!("Error reading folder {}", share.display()))?;
+    let newest = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+        })
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+            modified.ok().map(|modified| (modified, path))
+        })
+        .filter(|(modified, _)| since.is_none_or(|since| *modified >= since))
+        .max();
+    Ok(newest.map(|(_, path)| path))
+}
+
+/// Perform the script with the FileMaker OData API, which waits for the script to finish
+fn run_script(server: &str, args: &FetchArgs) -> Result<(), Error> {
+    let (Some(database), Some(script)) = (&args.database, &args.script) else {
+        return Err(anyhow!("--server requires --database and --script"));
+    };
+    let url = format!(
+        "{}/fmi/odata/v4/{}/Script.{}",
+        server.trim_end_matches('/'),
+        encode(database),
+        encode(script)
+    );
+    let credentials = format!(
+        "{}:{}",
+        args.user.as_deref().unwrap_or_default(),
+        args.password.as_deref().unwrap_or_default()
+    );
+
+    let mut response = ureq::post(url)
+        .header(
+            "Authorization",
+            format!("Basic {}", STANDARD.encode(credentials)),
+        )
+        .header("Content-Type", "application/json")
+        .send("{}")
+        .with_context(|| format!("Error performing script {script} of {database} on {server}"))?;
+    let body: Value = serde_json::from_str(&response.body_mut().read_to_string()?)?;
+    match body["scriptResult"]["code"].as_i64() {
+        None | Some(0) => Ok(()),
+        Some(code) => Err(anyhow!(
+            "Script {script} failed with FileMaker error {code}"
+        )),
+    }
+}
+
+/// Percent-encode a name for a path segment of an OData API URL
+fn encode(name: &str) -> String {
+    utf8_percent_encode(name, NON_ALPHANUMERIC).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    #[test]
+    fn test_newest_export() {
+        let share = ScratchDir::create("fetch-test").unwrap();
+        let now = SystemTime::now();
+        let touch = |name: &str, age: u64| {
+            let file = fs::File::create(share.path.join(name)).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+        };
+        touch("Invoices.xml", 60);
+        touch("Contacts.XML", 30);
+        touch("notes.txt", 0);
+
+        assert_eq!(
+            newest_export(&share.path, None).unwrap(),
+            Some(share.path.join("Contacts.XML"))
+        );
+        assert_eq!(
+            newest_export(&share.path, Some(now - Duration::from_secs(10))).unwrap(),
+            None
+        );
+        assert_eq!(encode("Print & Send"), "Print%20%26%20Send");
+    }
+}
@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::config::Flags;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::write_text_file;
+use crate::OutputTree;
+
+pub const FOLDER_INDEX_FILE_NAME: &str = "_index.md";
+
+const COMMENT_STEP_ID: &str = "89";
+
+/// Write an `_index.md` into each folder of the scripts, sanitized scripts and layouts of the given
+/// databases, listing its subfolders and files with a one-line summary to browse the output on GitHub
+pub fn write_folder_indexes(
+    root_out_dir: &Path,
+    db_names: &[String],
+    flags: &Flags,
+) -> Result<usize, Error> {
+    let mut count = 0;
+    for db_name in db_names {
+        let domain_dir = |domain: &str| match flags.output_tree {
+            OutputTree::Db => root_out_dir.join(db_name).join(domain),
+            OutputTree::Domain => root_out_dir.join(domain).join(db_name),
+        };
+        let scripts_dir = domain_dir("scripts");
+        count += write_indexes(root_out_dir, &scripts_dir, "xml", flags, &|path| {
+            script_summary(path)
+        })?;
+        count += write_indexes(
+            root_out_dir,
+            &domain_dir("scripts_sanitized"),
+            "txt",
+            flags,
+            &|path| {
+                // The summary is read from the script the text was rendered from
+                let relative_path = path.strip_prefix(domain_dir("scripts_sanitized")).ok()?;
+                script_summary(&scripts_dir.join(relative_path).with_extension("xml"))
+            },
+        )?;
+        count += write_indexes(
+            root_out_dir,
+            &domain_dir("layouts"),
+            "xml",
+            flags,
+            &|path| layout_summary(path),
+        )?;
+    }
+    Ok(count)
+}
+
+/// Write the index of `dir` and of all folders below it, returning the number of indexes written
+fn write_indexes(
+    root_out_dir: &Path,
+    dir: &Path,
+    extension: &str,
+    flags: &Flags,
+    summary: &dyn Fn(&Path) -> Option<String>,
+) -> Result<usize, Error> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(0);
+    };
+    let mut folders = Vec::new();
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            folders.push(path);
+        } else if path.extension().and_then(|s| s.to_str()) == Some(extension) {
+            files.push(path);
+        }
+    }
+    folders.sort();
+    files.sort();
+
+    let mut count = 0;
+    for folder in &folders {
+        count += write_indexes(root_out_dir, folder, extension, flags, summary)?;
+    }
+
+    let title = dir
+        .strip_prefix(root_out_dir)
+        .unwrap_or(dir)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" / ");
+    let mut content = format!("# {}\n", escape_markdown(&title));
+    if !folders.is_empty() {
+        content.push('\n');
+        for folder in &folders {
+            let name = file_name(folder);
+            content.push_str(&format!(
+                "- [{}/](<{}/{FOLDER_INDEX_FILE_NAME}>)\n",
+                escape_markdown(&name),
+                escape_destination(&name)
+            ));
+        }
+    }
+    if !files.is_empty() {
+        content.push('\n');
+        for file in &files {
+            let name = file_name(file);
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            content.push_str(&format!(
+                "- [{}](<{}>)",
+                escape_markdown(&stem),
+                escape_destination(&name)
+            ));
+            if let Some(summary) = summary(file) {
+                content.push_str(&format!(" — {}", escape_markdown(&summary)));
+            }
+            content.push('\n');
+        }
+    }
+
+    write_text_file(
+        &dir.join(FOLDER_INDEX_FILE_NAME),
+        content.trim_end_matches('\n'),
+        flags,
+        None,
+    )?;
+    Ok(count + 1)
+}
+
+/// Escape characters Markdown would take as emphasis, code, links or HTML tags
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape the characters ending a link destination in angle brackets
+fn escape_destination(path: &str) -> String {
+    path.replace('<', "\\<").replace('>', "\\>")
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The number of steps of an exploded script and the first line of its first comment,
+/// e.g. `12 steps — Creates an invoice`
+fn script_summary(script_path: &Path) -> Option<String> {
+    let xml = fs::read_to_string(script_path).ok()?;
+    let mut reader = Reader::from_str(&xml);
+    let mut steps = 0;
+    let mut in_comment_step = false;
+    let mut comment = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"Step" => {
+                    steps += 1;
+                    in_comment_step = comment.is_none()
+                        && parse_unescaped_attribute(&e, "id").as_deref() == Some(COMMENT_STEP_ID);
+                }
+                b"Comment" if in_comment_step => {
+                    comment = parse_unescaped_attribute(&e, "value")
+                        .and_then(|value| {
+                            value
+                                .split(['\r', '\n'])
+                                .next()
+                                .map(|line| line.trim().to_string())
+                        })
+                        .filter(|line| !line.is_empty());
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let steps = match steps {
+        1 => "1 step".to_string(),
+        _ => format!("{steps} steps"),
+    };
+    match comment {
+        Some(comment) => Some(format!("{steps} — {comment}")),
+        None => Some(steps),
+    }
+}
+
+/// The table occurrence a layout shows records from, e.g. `Invoices`
+fn layout_summary(layout_path: &Path) -> Option<String> {
+    let xml = fs::read_to_string(layout_path).ok()?;
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.name().as_ref() == b"TableOccurrenceReference" =>
+            {
+                return parse_unescaped_attribute(&e, "name").filter(|name| !name.is_empty());
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_folder_indexes() {
+        let out_dir = std::env::temp_dir().join("fm_exploder_test_folder_index");
+        let _ = fs::remove_dir_all(&out_dir);
+        let scripts_dir = out_dir.join("Db").join("scripts");
+        let sanitized_dir = out_dir.join("Db").join("scripts_sanitized");
+        fs::create_dir_all(scripts_dir.join("Folder - ID 3")).unwrap();
+        fs::create_dir_all(sanitized_dir.join("Folder - ID 3")).unwrap();
+        fs::write(
+            scripts_dir.join("Folder - ID 3").join("A - ID 1.xml"),
+            r##"<Script>
+                <ObjectList membercount="3">
+                    <Step id="89" name="# (comment)" enable="True">
+                        <ParameterValues membercount="1">
+                            <Parameter type="Comment"><Comment></Comment></Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="89" name="# (comment)" enable="True">
+                        <ParameterValues membercount="1">
+                            <Parameter type="Comment"><Comment value="Creates an invoice&#13;for the order"></Comment></Parameter>
+                        </ParameterValues>
+                    </Step>
+                    <Step id="103" name="Exit Script" enable="True"></Step>
+                </ObjectList>
+            </Script>"##,
+        )
+        .unwrap();
+        fs::write(
+            sanitized_dir.join("Folder - ID 3").join("A - ID 1.txt"),
+            "Exit Script\n",
+        )
+        .unwrap();
+
+        let count = write_folder_indexes(&out_dir, &["Db".to_string()], &Flags::default()).unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(
+            fs::read_to_string(scripts_dir.join(FOLDER_INDEX_FILE_NAME)).unwrap(),
+            "# Db / scripts\n\n- [Folder - ID 3/](<Folder - ID 3/_index.md>)\n"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                sanitized_dir
+                    .join("Folder - ID 3")
+                    .join(FOLDER_INDEX_FILE_NAME)
+            )
+            .unwrap(),
+            "# Db / scripts_sanitized / Folder - ID 3\n\n\
+             - [A - ID 1](<A - ID 1.txt>) — 3 steps — Creates an invoice\n"
+        );
+
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use anyhow::{Context, Error, Result};
+
+use crate::exit_status::ExitStatus;
+use crate::SampleArgs;
+
+/// Scripts per script folder of the sample
+const SCRIPTS_PER_FOLDER: u32 = 100;
+
+/// Id FileMaker gives the first table occurrence of a file
+const FIRST_TABLE_OCCURRENCE_ID: u32 = 1065089;
+
+/// Steps of a script, repeated until its step count is reached. The `End If` closing the `If`
+/// two steps before is replaced by a comment if the script ends first.
+const STEP_CYCLE: [Step; 8] = [
+    Step::Comment,
+    Step::SetVariable,
+    Step::If,
+    Step::SetField,
+    Step::EndIf,
+    Step::GoToLayout,
+    Step::PerformScript,
+    Step::Comment,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Comment,
+    SetVariable,
+    If,
+    SetField,
+    EndIf,
+    GoToLayout,
+    PerformScript,
+}
+
+/// Run the `gen-sample` subcommand: write a synthetic export of the given size
+pub fn run(args: &SampleArgs) -> Result<ExitStatus, Error> {
+    let file = File::create(&args.output)
+        .with_context(|| format!("Failed to create {}", args.output.display()))?;
+    let mut sample = Sample {
+        out: BufWriter::new(file),
+        utf16: args.utf16,
+        args,
+        uuids: 0,
+    };
+    sample
+        .write()
+        .and_then(|_| sample.out.flush())
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+    println!(
+        "Wrote {} scripts of {} steps, {} layouts and {} tables of {} fields to {}",
+        args.scripts,
+        args.steps,
+        args.layouts,
+        args.tables,
+        args.fields,
+        args.output.display()
+    );
+    Ok(ExitStatus::Clean)
+}
+
+/// Writes the export element by element, so samples of any size are written in constant memory
+struct Sample<'a, W: Write> {
+    out: W,
+    /// Write UTF-16 with a byte order mark like FileMaker instead of UTF-8
+    utf16: bool,
+    args: &'a SampleArgs,
+    /// Number of UUIDs handed out, each derived from its number to keep samples reproducible
+    uuids: u64,
+}
+
+impl<W: Write> Sample<'_, W> {
+    fn write(&mut self) -> std::io::Result<()> {
+        if self.utf16 {
+            self.out.write_all(b"\xFF\xFE")?;
+        }
+        let file_name = self
+            .args
+            .output
+            .file_stem()
+            .map_or("Sample".into(), |stem| stem.to_string_lossy());
+        let header = format!(
+            "<?xml version=\"1.0\"?>\n<FMSaveAsXML version=\"2.2.1.0\" Source=\"20.3.1\" File=\"{}.fmp12\" UUID=\"{}\" locale=\"English\">\n\t<Structure membercount=\"1\">\n\t\t<AddAction membercount=\"6\">\n",
+            escape(&file_name),
+            self.uuid()
+        );
+        self.put(&header)?;
+        self.write_tables()?;
+        self.write_table_occurrences()?;
+        self.write_fields()?;
+        self.write_script_catalog()?;
+        self.write_layouts()?;
+        self.write_steps()?;
+        self.put("\t\t</AddAction>\n\t</Structure>\n</FMSaveAsXML>\n")
+    }
+
+    fn write_tables(&mut self) -> std::io::Result<()> {
+        let mut xml = format!(
+            "\t\t\t<BaseTableCatalog membercount=\"{}\">\n\t\t\t\t<UUID>{}</UUID>\n",
+            self.args.tables,
+            self.uuid()
+        );
+        for table in 1..=self.args.tables {
+            let uuid = self.uuid();
+            write!(
+                xml,
+                "\t\t\t\t<BaseTable id=\"{}\" comment=\"\" name=\"Table {table}\">\n\t\t\t\t\t<UUID>{uuid}</UUID>\n\t\t\t\t\t<TagList></TagList>\n\t\t\t\t</BaseTable>\n",
+                128 + table
+            )
+            .unwrap();
+        }
+        xml.push_str("\t\t\t</BaseTableCatalog>\n");
+        self.put(&xml)
+    }
+
+    fn write_table_occurrences(&mut self) -> std::io::Result<()> {
+        let mut xml = format!(
+            "\t\t\t<TableOccurrenceCatalog membercount=\"{}\">\n\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t<TagList></TagList>\n",
+            self.args.tables,
+            self.uuid()
+        );
+        for table in 1..=self.args.tables {
+            let uuid = self.uuid();
+            write!(
+                xml,
+                "\t\t\t\t<TableOccurrence View=\"Full\" height=\"86\" id=\"{}\" name=\"Table {table}\" type=\"Local\">\n\t\t\t\t\t<UUID>{uuid}</UUID>\n\t\t\t\t\t<BaseTableSourceReference type=\"BaseTableReference\">\n\t\t\t\t\t\t<BaseTableReference id=\"{}\" name=\"Table {table}\"></BaseTableReference>\n\t\t\t\t\t</BaseTableSourceReference>\n\t\t\t\t\t<CoordRect top=\"20\" left=\"20\" bottom=\"136\" right=\"151\"></CoordRect>\n\t\t\t\t\t<Color red=\"120\" green=\"120\" blue=\"120\" alpha=\"1.00\"></Color>\n\t\t\t\t\t<TagList></TagList>\n\t\t\t\t</TableOccurrence>\n",
+                table_occurrence_id(table),
+                128 + table
+            )
+            .unwrap();
+        }
+        xml.push_str("\t\t\t</TableOccurrenceCatalog>\n");
+        self.put(&xml)
+    }
+
+    fn write_fields(&mut self) -> std::io::Result<()> {
+        self.put(&format!(
+            "\t\t\t<FieldsForTables membercount=\"{}\">\n",
+            self.args.tables
+        ))?;
+        for table in 1..=self.args.tables {
+            let mut xml = format!(
+                "\t\t\t\t<FieldCatalog>\n\t\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t\t<TagList></TagList>\n\t\t\t\t\t<BaseTableReference id=\"{}\" name=\"Table {table}\"></BaseTableReference>\n\t\t\t\t\t<ObjectList membercount=\"{}\">\n",
+                self.uuid(),
+                128 + table,
+                self.args.fields
+            );
+            for field in 1..=self.args.fields {
+                let uuid = self.uuid();
+                write!(
+                    xml,
+                    "\t\t\t\t\t\t<Field id=\"{field}\" name=\"Field {field}\" fieldtype=\"Normal\" datatype=\"Text\" comment=\"\">\n\t\t\t\t\t\t\t<UUID>{uuid}</UUID>\n\t\t\t\t\t\t\t<AutoEnter type=\"\" prohibitModification=\"False\"></AutoEnter>\n\t\t\t\t\t\t\t<Validation type=\"OnlyDuringDataEntry\" allowOverride=\"True\" notEmpty=\"False\" unique=\"False\" existing=\"False\"></Validation>\n\t\t\t\t\t\t\t<Storage autoIndex=\"True\" index=\"None\" global=\"False\" maxRepetitions=\"1\">\n\t\t\t\t\t\t\t\t<LanguageReference name=\"English\" id=\"21\"></LanguageReference>\n\t\t\t\t\t\t\t</Storage>\n\t\t\t\t\t\t\t<TagList></TagList>\n\t\t\t\t\t\t</Field>\n"
+                )
+                .unwrap();
+            }
+            xml.push_str("\t\t\t\t\t</ObjectList>\n\t\t\t\t</FieldCatalog>\n");
+            self.put(&xml)?;
+        }
+        self.put("\t\t\t</FieldsForTables>\n")
+    }
+
+    /// The scripts in folders of `SCRIPTS_PER_FOLDER`, each folder closed by a marker
+    fn write_script_catalog(&mut self) -> std::io::Result<()> {
+        let folders = self.args.scripts.div_ceil(SCRIPTS_PER_FOLDER);
+        let header = format!(
+            "\t\t\t<ScriptCatalog membercount=\"{}\">\n\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t<TagList></TagList>\n",
+            self.args.scripts + 2 * folders,
+            self.uuid()
+        );
+        self.put(&header)?;
+        for folder in 1..=folders {
+            let mut xml = self.script_catalog_item(
+                self.args.scripts + 2 * folder - 1,
+                &format!("Folder {folder}"),
+                " isFolder=\"True\"",
+                1,
+            );
+            let first = (folder - 1) * SCRIPTS_PER_FOLDER + 1;
+            let last = (folder * SCRIPTS_PER_FOLDER).min(self.args.scripts);
+            for script in first..=last {
+                let item = self.script_catalog_item(script, &format!("Script {script}"), "", 0);
+                xml.push_str(&item);
+            }
+            let marker = self.script_catalog_item(
+                self.args.scripts + 2 * folder,
+                "--",
+                " isFolder=\"Marker\"",
+                2,
+            );
+            xml.push_str(&marker);
+            self.put(&xml)?;
+        }
+        self.put("\t\t\t</ScriptCatalog>\n")
+    }
+
+    fn script_catalog_item(&mut self, id: u32, name: &str, kind: &str, options: u32) -> String {
+        format!(
+            "\t\t\t\t<Script id=\"{id}\" name=\"{name}\"{kind}>\n\t\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t\t<Options hidden=\"False\" access=\"ReadWrite\" SiriShortcutVisible=\"False\" runwithfullaccess=\"False\" compatibility=\"0\">{options}</Options>\n\t\t\t\t\t<TagList></TagList>\n\t\t\t\t</Script>\n",
+            self.uuid()
+        )
+    }
+
+    /// Layouts of the table occurrences in turn, each showing a title and the first field
+    fn write_layouts(&mut self) -> std::io::Result<()> {
+        let header = format!(
+            "\t\t\t<LayoutCatalog membercount=\"{}\">\n\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t<TagList></TagList>\n",
+            self.args.layouts,
+            self.uuid()
+        );
+        self.put(&header)?;
+        for layout in 1..=self.args.layouts {
+            let table = (layout - 1) % self.args.tables + 1;
+            let (text_uuid, field_uuid, uuid) = (self.uuid(), self.uuid(), self.uuid());
+            let xml = format!(
+                "\t\t\t\t<Layout id=\"{layout}\" name=\"Layout {layout}\" width=\"740\">\n\
+                 \t\t\t\t\t<TableOccurrenceReference id=\"{}\" name=\"Table {table}\"></TableOccurrenceReference>\n\
+                 \t\t\t\t\t<PartsList membercount=\"1\">\n\
+                 \t\t\t\t\t\t<Part type=\"Body\" kind=\"4\">\n\
+                 \t\t\t\t\t\t\t<Definition type=\"Body\" kind=\"4\" size=\"452\" absolute=\"0\" Options=\"1024\"></Definition>\n\
+                 \t\t\t\t\t\t\t<ObjectList membercount=\"2\">\n\
+                 \t\t\t\t\t\t\t\t<LayoutObject id=\"1\" type=\"Text\" name=\"\" kind=\"2\">\n\
+                 \t\t\t\t\t\t\t\t\t<UUID>{text_uuid}</UUID>\n\
+                 \t\t\t\t\t\t\t\t\t<Bounds top=\"20\" left=\"20\" bottom=\"44\" right=\"320\"></Bounds>\n\
+                 \t\t\t\t\t\t\t\t\t<Options>0</Options>\n\
+                 \t\t\t\t\t\t\t\t\t<Text>\n\
+                 \t\t\t\t\t\t\t\t\t\t<Options>2</Options>\n\
+                 \t\t\t\t\t\t\t\t\t\t<StyledText>\n\
+                 \t\t\t\t\t\t\t\t\t\t\t<Data><![CDATA[Layout {layout}]]></Data>\n\
+                 \t\t\t\t\t\t\t\t\t\t</StyledText>\n\
+                 \t\t\t\t\t\t\t\t\t</Text>\n\
+                 \t\t\t\t\t\t\t\t</LayoutObject>\n\
+                 \t\t\t\t\t\t\t\t<LayoutObject id=\"2\" type=\"Edit Box\" name=\"\" kind=\"1\">\n\
+                 \t\t\t\t\t\t\t\t\t<UUID>{field_uuid}</UUID>\n\
+                 \t\t\t\t\t\t\t\t\t<Bounds top=\"60\" left=\"20\" bottom=\"84\" right=\"320\"></Bounds>\n\
+                 \t\t\t\t\t\t\t\t\t<Options>0</Options>\n\
+                 \t\t\t\t\t\t\t\t\t<Field>\n\
+                 {}\
+                 \t\t\t\t\t\t\t\t\t\t<Options>0</Options>\n\
+                 \t\t\t\t\t\t\t\t\t\t<Display Style=\"0\" show=\"1\"></Display>\n\
+                 \t\t\t\t\t\t\t\t\t\t<Usage inputMode=\"0\" type=\"1\"></Usage>\n\
+                 \t\t\t\t\t\t\t\t\t</Field>\n\
+                 \t\t\t\t\t\t\t\t</LayoutObject>\n\
+                 \t\t\t\t\t\t\t</ObjectList>\n\
+                 \t\t\t\t\t\t</Part>\n\
+                 \t\t\t\t\t</PartsList>\n\
+                 \t\t\t\t\t<UUID>{uuid}</UUID>\n\
+                 \t\t\t\t\t<TagList></TagList>\n\
+                 \t\t\t\t</Layout>\n",
+                table_occurrence_id(table),
+                indent(&field_reference(table, 1, " repetition=\"1\"", ""), 10),
+            );
+            self.put(&xml)?;
+        }
+        self.put("\t\t\t</LayoutCatalog>\n")
+    }
+
+    fn write_steps(&mut self) -> std::io::Result<()> {
+        self.put(&format!(
+            "\t\t\t<StepsForScripts membercount=\"{}\">\n",
+            self.args.scripts
+        ))?;
+        for script in 1..=self.args.scripts {
+            let mut xml = format!(
+                "\t\t\t\t<Script>\n\t\t\t\t\t<ScriptReference id=\"{script}\" name=\"Script {script}\"></ScriptReference>\n\t\t\t\t\t<ObjectList membercount=\"{}\">\n",
+                self.args.steps
+            );
+            for index in 0..self.args.steps {
+                let mut step = STEP_CYCLE[index as usize % STEP_CYCLE.len()];
+                if step == Step::If && index + 2 >= self.args.steps
+                    || step == Step::GoToLayout && self.args.layouts == 0
+                {
+                    step = Step::Comment;
+                }
+                let step = self.step(step, script, index);
+                xml.push_str(&step);
+            }
+            xml.push_str("\t\t\t\t\t</ObjectList>\n\t\t\t\t</Script>\n");
+            self.put(&xml)?;
+        }
+        self.put("\t\t\t</StepsForScripts>\n")
+    }
+
+    /// A step of a script, referring to the tables, fields, layouts and scripts of the sample
+    fn step(&mut self, step: Step, script: u32, index: u32) -> String {
+        let table = (script + index) % self.args.tables + 1;
+        let field = (script + index) % self.args.fields + 1;
+        let (id, name, options, parameters) = match step {
+            Step::Comment => (
+                89,
+                "# (comment)",
+                0,
+                vec![format!(
+                    "<Parameter type=\"Comment\">\n<Comment value=\"Step {} of script {script}: keep the totals of &quot;Table {table}&quot; up to date\"></Comment>\n</Parameter>",
+                    index + 1
+                )],
+            ),
+            Step::SetVariable => (
+                141,
+                "Set Variable",
+                16388,
+                vec![format!(
+                    "<Parameter type=\"Variable\">\n<value>\n{}\n</value>\n<Name value=\"$total{index}\"></Name>\n<repetition></repetition>\n</Parameter>",
+                    field_calculation(table, field, &format!(" + {index}"))
+                )],
+            ),
+            Step::If => (
+                68,
+                "If",
+                16384,
+                vec![format!(
+                    "<Parameter type=\"Calculation\">\n{}\n</Parameter>",
+                    field_calculation(table, field, " &gt; 0")
+                )],
+            ),
+            Step::SetField => (
+                76,
+                "Set Field",
+                16385,
+                vec![
+                    format!(
+                        "<Parameter type=\"FieldReference\">\n{}\n</Parameter>",
+                        field_reference(table, field, "", "<repetition value=\"1\"></repetition>\n")
+                    ),
+                    format!(
+                        "<Parameter type=\"Calculation\">\n{}\n</Parameter>",
+                        field_calculation(table, field, " * 2")
+                    ),
+                ],
+            ),
+            Step::EndIf => (70, "End If", 0, Vec::new()),
+            Step::GoToLayout => (
+                6,
+                "Go to Layout",
+                8,
+                vec![
+                    format!(
+                        "<Parameter type=\"LayoutReferenceContainer\">\n<LayoutReferenceContainer value=\"5\">\n<LayoutReference id=\"{layout}\" name=\"Layout {layout}\"></LayoutReference>\n</LayoutReferenceContainer>\n</Parameter>",
+                        layout = (script + index) % self.args.layouts.max(1) + 1
+                    ),
+                    "<Parameter type=\"Animation\">\n<Animation name=\"None\" value=\"0\"></Animation>\n</Parameter>".to_string(),
+                ],
+            ),
+            Step::PerformScript => (
+                1,
+                "Perform Script",
+                16448,
+                vec![format!(
+                    "<Parameter type=\"List\">\n<List name=\"From list\" value=\"1\">\n<ScriptReference id=\"{target}\" name=\"Script {target}\"></ScriptReference>\n</List>\n</Parameter>",
+                    target = (script + index) % self.args.scripts + 1
+                )],
+            ),
+        };
+
+        let mut xml = format!(
+            "<Step index=\"{index}\" id=\"{id}\" name=\"{name}\" enable=\"True\">\n<UUID>{}</UUID>\n<OwnerID></OwnerID>\n<Options>{options}</Options>\n",
+            self.uuid()
+        );
+        if !parameters.is_empty() {
+            writeln!(
+                xml,
+                "<ParameterValues membercount=\"{}\">",
+                parameters.len()
+            )
+            .unwrap();
+            for parameter in parameters {
+                xml.push_str(&parameter);
+                xml.push('\n');
+            }
+            xml.push_str("</ParameterValues>\n");
+        }
+        xml.push_str("</Step>\n");
+        indent(&xml, 6)
+    }
+
+    fn uuid(&mut self) -> String {
+        self.uuids += 1;
+        format!("{:08X}-0000-4000-8000-{:012X}", 0x5A3F_1E00, self.uuids)
+    }
+
+    fn put(&mut self, xml: &str) -> std::io::Result<()> {
+        match self.utf16 {
+            true => {
+                let bytes: Vec<u8> = xml.encode_utf16().flat_map(u16::to_le_bytes).collect();
+                self.out.write_all(&bytes)
+            }
+            false => self.out.write_all(xml.as_bytes()),
+        }
+    }
+}
+
+fn table_occurrence_id(table: u32) -> u32 {
+    FIRST_TABLE_OCCURRENCE_ID + table - 1
+}
+
+/// A reference to a field with its table occurrence, as in steps, calculations and layout objects
+fn field_reference(table: u32, field: u32, attributes: &str, repetition: &str) -> String {
+    format!(
+        "<FieldReference id=\"{field}\" name=\"Field {field}\"{attributes}>\n{repetition}<TableOccurrenceReference id=\"{}\" name=\"Table {table}\"></TableOccurrenceReference>\n</FieldReference>",
+        table_occurrence_id(table)
+    )
+}
+
+/// A calculation of a field followed by an expression like ` + 1`, given escaped
+fn field_calculation(table: u32, field: u32, expression: &str) -> String {
+    format!(
+        "<Calculation datatype=\"1\" position=\"0\">\n<Calculation>\n<Text><![CDATA[Table {table}::Field {field}{}]]></Text>\n<ChunkList>\n<Chunk type=\"FieldRef\">\n{}\n</Chunk>\n<Chunk type=\"NoRef\">{expression}</Chunk>\n</ChunkList>\n</Calculation>\n</Calculation>",
+        expression.replace("&gt;", ">"),
+        field_reference(table, field, " repetition=\"1\"", "")
+    )
+}
+
+/// Indent lines of nested elements by tabs, from `depth` for the first
+fn indent(xml: &str, depth: usize) -> String {
+    let mut indented = String::with_capacity(xml.len() * 2);
+    let mut level = depth;
+    for line in xml.lines() {
+        let closing = line.starts_with("</");
+        if closing {
+            level -= 1;
+        }
+        indented.extend(std::iter::repeat_n('\t', level));
+        indented.push_str(line);
+        indented.push('\n');
+        let opening = line.starts_with('<')
+            && !closing
+            && !line.starts_with("<![CDATA[")
+            && !line.ends_with("/>")
+            && !line[1..].contains('<');
+        if opening {
+            level += 1;
+        }
+    }
+    indented
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
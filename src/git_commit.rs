@@ -0,0 +1,97 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Error, Result};
+
+use crate::stats::RunSummary;
+
+pub const DEFAULT_MESSAGE: &str =
+    "Explode {sources} (FileMaker {versions}): {scripts} scripts, {layouts} layouts";
+
+/// Values of the placeholders of a commit message template
+pub struct CommitInfo<'a> {
+    /// File names of the exploded exports
+    pub sources: Vec<String>,
+    /// FileMaker versions that saved the exports
+    pub versions: Vec<String>,
+    pub summary: &'a RunSummary,
+}
+
+/// Replace `{sources}`, `{versions}`, `{files}`, `{scripts}`, `{layouts}` and `{custom_functions}`
+pub fn commit_message(template: &str, info: &CommitInfo) -> String {
+    let mut versions = info.versions.clone();
+    versions.sort();
+    versions.dedup();
+    template
+        .replace("{sources}", &info.sources.join(", "))
+        .replace("{versions}", &versions.join(", "))
+        .replace("{files}", &info.summary.files.to_string())
+        .replace("{scripts}", &info.summary.scripts.to_string())
+        .replace("{layouts}", &info.summary.layouts.to_string())
+        .replace(
+            "{custom_functions}",
+            &info.summary.custom_functions.to_string(),
+        )
+}
+
+/// Stage everything below the output directory and commit it to the repository containing it.
+/// Returns false if nothing changed, in which case no commit is made.
+pub fn commit_output(out_dir: &Path, message: &str) -> Result<bool, Error> {
+    git(out_dir, &["add", "--all", "--", "."])?;
+    let changed = Command::new("git")
+        .arg("-C")
+        .arg(out_dir)
+        .args(["diff", "--cached", "--quiet", "--", "."])
+        .status()
+        .context("Error running git")?;
+    if changed.success() {
+        return Ok(false);
+    }
+    // Only the output directory, so changes staged elsewhere in the repository stay staged
+    git(
+        out_dir,
+        &["commit", "--quiet", "--message", message, "--", "."],
+    )?;
+    Ok(true)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<(), Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Error running git")?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message() {
+        let summary = RunSummary {
+            files: 2,
+            scripts: 12,
+            layouts: 3,
+            ..Default::default()
+        };
+        let info = CommitInfo {
+            sources: vec!["Invoices.xml".to_string(), "Contacts.xml".to_string()],
+            versions: vec!["21.0.1".to_string(), "21.0.1".to_string()],
+            summary: &summary,
+        };
+        assert_eq!(
+            commit_message(DEFAULT_MESSAGE, &info),
+            "Explode Invoices.xml, Contacts.xml (FileMaker 21.0.1): 12 scripts, 3 layouts"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+
+use crate::config::Flags;
+use crate::manifest::{relative_path_string, sha256_hex, Manifest, ScriptReport};
+use crate::prune::prune_stale_files;
+
+/// State of an incremental run: the export is exploded into a staging directory and only
+/// files whose checksum differs from the previous manifest are copied into the target directory
+pub struct Incremental {
+    staging_dir: PathBuf,
+    target_dir: PathBuf,
+    previous: Manifest,
+    previous_checksums: HashMap<String, String>,
+    previous_reports: HashMap<String, ScriptReport>,
+    /// Whether the previous run rendered with the same version and options
+    same_rendering: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncResult {
+    pub written: usize,
+    pub unchanged: usize,
+    pub pruned: usize,
+}
+
+impl Incremental {
+    pub fn new(
+        staging_dir: &Path,
+        target_dir: &Path,
+        previous: Option<Manifest>,
+        rendering: &str,
+    ) -> Self {
+        let previous = previous.unwrap_or_default();
+        let same_rendering = previous.rendering.as_deref() == Some(rendering);
+        let previous_checksums = previous
+            .files
+            .iter()
+            .map(|entry| (entry.path.clone(), entry.sha256.clone()))
+            .collect();
+        let previous_reports = previous
+            .files
+            .iter()
+            .filter_map(|entry| Some((entry.path.clone(), entry.report.clone()?)))
+            .collect();
+        Self {
+            staging_dir: staging_dir.to_path_buf(),
+            target_dir: target_dir.to_path_buf(),
+            previous,
+            previous_checksums,
+            previous_reports,
+            same_rendering,
+        }
+    }
+
+    /// Reuse the file rendered from an unchanged source file by the previous run, instead of rendering it again,
+    /// if the names of the database (`symbols`, see [`SymbolTable::checksum`]) are unchanged as well
+    /// Returns the report of the previous rendering if its output was copied to `output_path` in the staging directory
+    ///
+    /// [`SymbolTable::checksum`]: crate::symbols::SymbolTable::checksum
+    pub fn reuse_rendered(
+        &self,
+        source_path: &Path,
+        source_content: &str,
+        output_path: &Path,
+        db_name: &str,
+        symbols: &str,
+    ) -> Option<ScriptReport> {
+        if !self.same_rendering
+            || self.previous.symbols.get(db_name).map(String::as_str) != Some(symbols)
+        {
+            return None;
+        }
+        let source_relative_path = relative_path_string(&self.staging_dir, source_path);
+        if self.previous_checksums.get(&source_relative_path)
+            != Some(&sha256_hex(source_content.as_bytes()))
+        {
+            return None;
+        }
+
+        let output_relative_path = relative_path_string(&self.staging_dir, output_path);
+        let previous_checksum = self.previous_checksums.get(&output_relative_path)?;
+        let previous_output_path = self.target_dir.join(&output_relative_path);
+        match fs::read(&previous_output_path) {
+            Ok(content) if &sha256_hex(&content) == previous_checksum => {
+                if let Some(parent) = output_path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                fs::write(output_path, content).ok()?;
+                Some(
+                    self.previous_reports
+                        .get(&output_relative_path)
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy new and changed files from the staging directory into the target directory
+    /// and delete files of the previous run that are no longer generated
+    pub fn sync(&self, current: &Manifest) -> Result<SyncResult, Error> {
+        let mut result = SyncResult::default();
+        for entry in &current.files {
+            let target_path = self.target_dir.join(&entry.path);
+            if self.previous_checksums.get(&entry.path) == Some(&entry.sha256)
+                && target_path.is_file()
+            {
+                result.unchanged += 1;
+                continue;
+            }
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Error creating directory {}", parent.display()))?;
+            }
+            fs::copy(self.staging_dir.join(&entry.path), &target_path)
+                .with_context(|| format!("Error writing file {}", target_path.display()))?;
+            result.written += 1;
+        }
+        result.pruned = prune_stale_files(&self.target_dir, &self.previous, current)?.len();
+        Ok(result)
+    }
+}
+
+/// Checksum of everything besides the source that the rendered files depend on: the version of the
+/// exploder, the options and the script template
+pub fn rendering_checksum(flags: &Flags, template: Option<&str>) -> String {
+    let key = format!(
+        "{} {}\n{flags:?}\n{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        template.unwrap_or_default()
+    );
+    sha256_hex(key.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Flags;
+    use crate::manifest::UnknownStep;
+    use crate::Section;
+
+    #[test]
+    fn test_sync_and_reuse() {
+        let root = std::env::temp_dir().join(format!("fm-incremental-test-{}", std::process::id()));
+        let staging = root.join("staging");
+        let target = root.join("target");
+        let db_names = ["db".to_string()];
+        let flags = Flags::default();
+        let rendering = rendering_checksum(&flags, None);
+
+        // First run writes everything
+        fs::create_dir_all(staging.join("db/scripts")).unwrap();
+        fs::create_dir_all(staging.join("db/scripts_sanitized")).unwrap();
+        fs::write(staging.join("db/scripts/a.xml"), "<a/>").unwrap();
+        fs::write(staging.join("db/scripts_sanitized/a.txt"), "A").unwrap();
+        fs::write(staging.join("db/scripts/b.xml"), "<b/>").unwrap();
+        let mut manifest = Manifest::build(&staging, &db_names, &flags).unwrap();
+        manifest.rendering = Some(rendering.clone());
+        manifest
+            .symbols
+            .insert("db".to_string(), "names".to_string());
+        let report = ScriptReport {
+            context: "db / script 'a'".to_string(),
+            warnings: Vec::new(),
+            unknown_steps: vec![UnknownStep {
+                id: 9999,
+                name: "Future step".to_string(),
+            }],
+        };
+        manifest
+            .files
+            .iter_mut()
+            .find(|entry| entry.path == "db/scripts_sanitized/a.txt")
+            .unwrap()
+            .report = Some(report.clone());
+        let incremental = Incremental::new(&staging, &target, None, &rendering);
+        let result = incremental.sync(&manifest).unwrap();
+        assert_eq!(result.written, 3);
+        manifest.write(&target).unwrap();
+
+        // Second run: a.xml is unchanged, b.xml is gone
+        fs::remove_dir_all(&staging).unwrap();
+        fs::create_dir_all(staging.join("db/scripts")).unwrap();
+        fs::write(staging.join("db/scripts/a.xml"), "<a/>").unwrap();
+        let output_path = staging.join("db/scripts_sanitized/a.txt");
+        // Other options render differently, nothing is reused
+        let step_numbers = Flags {
+            step_numbers: true,
+            ..Flags::default()
+        };
+        let incremental = Incremental::new(
+            &staging,
+            &target,
+            Manifest::read(&target).unwrap(),
+            &rendering_checksum(&step_numbers, None),
+        );
+        let reuse = |incremental: &Incremental, source: &str, symbols: &str| {
+            incremental.reuse_rendered(
+                &staging.join("db/scripts/a.xml"),
+                source,
+                &output_path,
+                "db",
+                symbols,
+            )
+        };
+        assert_eq!(reuse(&incremental, "<a/>", "names"), None);
+        assert!(!output_path.exists());
+
+        let incremental = Incremental::new(
+            &staging,
+            &target,
+            Manifest::read(&target).unwrap(),
+            &rendering,
+        );
+        // Renamed fields or scripts may render differently, nothing is reused
+        assert_eq!(reuse(&incremental, "<a/>", "renamed"), None);
+        assert!(!output_path.exists());
+        // The report of the previous rendering is replayed
+        assert_eq!(reuse(&incremental, "<a/>", "names"), Some(report));
+        assert_eq!(reuse(&incremental, "<x/>", "names"), None);
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "A");
+
+        let manifest = Manifest::build(&staging, &db_names, &flags).unwrap();
+        let result = incremental.sync(&manifest).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            result,
+            SyncResult {
+                written: 0,
+                unchanged: 2,
+                pruned: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sync_keeps_unselected_sections() {
+        let root =
+            std::env::temp_dir().join(format!("fm-incremental-only-test-{}", std::process::id()));
+        let staging = root.join("staging");
+        let target = root.join("target");
+        let db_names = ["db".to_string()];
+
+        // A full run
+        let flags = Flags::default();
+        fs::create_dir_all(staging.join("db/scripts")).unwrap();
+        fs::create_dir_all(staging.join("db/layouts")).unwrap();
+        fs::create_dir_all(staging.join("db/_")).unwrap();
+        fs::write(staging.join("db/scripts/a.xml"), "<a/>").unwrap();
+        fs::write(staging.join("db/layouts/l.xml"), "<l/>").unwrap();
+        fs::write(staging.join("db/_/metadata.xml"), "<m/>").unwrap();
+        let manifest = Manifest::build(&staging, &db_names, &flags).unwrap();
+        Incremental::new(&staging, &target, None, "")
+            .sync(&manifest)
+            .unwrap();
+        manifest.write(&target).unwrap();
+
+        // Then only the scripts
+        let flags = Flags {
+            only: vec![Section::Scripts],
+            ..Flags::default()
+        };
+        fs::remove_dir_all(&staging).unwrap();
+        fs::create_dir_all(staging.join("db/scripts")).unwrap();
+        fs::write(staging.join("db/scripts/b.xml"), "<b/>").unwrap();
+        let previous = Manifest::read(&target).unwrap().unwrap();
+        let mut manifest = Manifest::build(&staging, &db_names, &flags).unwrap();
+        manifest.keep_unselected_sections(&previous, &flags);
+        let result = Incremental::new(&staging, &target, Some(previous), "")
+            .sync(&manifest)
+            .unwrap();
+
+        assert!(target.join("db/layouts/l.xml").exists());
+        assert!(target.join("db/_/metadata.xml").exists());
+        assert!(!target.join("db/scripts/a.xml").exists());
+        assert!(manifest
+            .files
+            .iter()
+            .any(|entry| entry.path == "db/layouts/l.xml"));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            result,
+            SyncResult {
+                written: 1,
+                unchanged: 2,
+                pruned: 1,
+            }
+        );
+    }
+}
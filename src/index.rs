@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Flags;
+use crate::manifest::{db_output_dirs, element_id_from_path, relative_path_string};
+use crate::symbols::{SymbolKind, SymbolTable};
+use crate::utils::file_utils::collect_files;
+use crate::OutputTree;
+
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// Names, UUIDs and output files of the elements of all exports by id,
+/// written as `index.json` into the output root to track renames across exports
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Index {
+    pub generator: String,
+    pub databases: Vec<DatabaseIndex>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DatabaseIndex {
+    pub name: String,
+    pub scripts: Vec<IndexEntry>,
+    pub layouts: Vec<IndexEntry>,
+    pub tables: Vec<IndexEntry>,
+    pub table_occurrences: Vec<IndexEntry>,
+    pub value_lists: Vec<IndexEntry>,
+    pub fields: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IndexEntry {
+    pub id: u32,
+    /// Base table of a field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_id: Option<u32>,
+    pub uuid: Option<String>,
+    pub name: String,
+    /// Account and time of the last modification, where the export records them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+    /// Files generated from the element, relative to the output root and separated by `/`;
+    /// fields point to the files of their table
+    pub paths: Vec<String>,
+}
+
+impl Index {
+    /// Build the index from the catalogs of each export and the files written for its elements
+    pub fn build(
+        root_out_dir: &Path,
+        exports: &[(String, SymbolTable)],
+        flags: &Flags,
+    ) -> Result<Self, Error> {
+        let databases = exports
+            .iter()
+            .map(|(db_name, symbols)| DatabaseIndex::build(root_out_dir, db_name, symbols, flags))
+            .collect();
+        Ok(Self {
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            databases,
+        })
+    }
+
+    pub fn write(&self, root_out_dir: &Path) -> Result<PathBuf, Error> {
+        let index_path = root_out_dir.join(INDEX_FILE_NAME);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&index_path, format!("{json}\n"))
+            .with_context(|| format!("Error writing file {}", index_path.display()))?;
+        Ok(index_path)
+    }
+}
+
+impl DatabaseIndex {
+    fn build(root_out_dir: &Path, db_name: &str, symbols: &SymbolTable, flags: &Flags) -> Self {
+        let paths = element_paths(root_out_dir, db_name, flags);
+        let paths_of =
+            |kind: SymbolKind, id: u32| paths.get(&(kind, id)).cloned().unwrap_or_default();
+
+        let mut index = DatabaseIndex {
+            name: db_name.to_string(),
+            ..Default::default()
+        };
+        for (kind, id, symbol) in symbols.symbols() {
+            let entries = match kind {
+                SymbolKind::Script => &mut index.scripts,
+                SymbolKind::Layout => &mut index.layouts,
+                SymbolKind::BaseTable => &mut index.tables,
+                SymbolKind::TableOccurrence => &mut index.table_occurrences,
+                SymbolKind::ValueList => &mut index.value_lists,
+            };
+            entries.push(IndexEntry {
+                id,
+                table_id: None,
+                uuid: symbol.uuid.clone(),
+                name: symbol.name.clone(),
+                modified_by: symbol.modified_by.clone(),
+                modified_at: symbol.modified_at.clone(),
+                paths: paths_of(kind, id),
+            });
+        }
+        for (table_id, id, symbol) in symbols.fields() {
+            index.fields.push(IndexEntry {
+                id,
+                table_id: Some(table_id),
+                uuid: symbol.uuid.clone(),
+                name: symbol.name.clone(),
+                modified_by: symbol.modified_by.clone(),
+                modified_at: symbol.modified_at.clone(),
+                paths: paths_of(SymbolKind::BaseTable, table_id),
+            });
+        }
+
+        for entries in [
+            &mut index.scripts,
+            &mut index.layouts,
+            &mut index.tables,
+            &mut index.table_occurrences,
+            &mut index.value_lists,
+            &mut index.fields,
+        ] {
+            entries.sort_by_key(|entry| (entry.table_id, entry.id));
+        }
+        index
+    }
+}
+
+/// Output files of one database by the kind and id of the element they were generated from
+pub fn element_paths(
+    root_out_dir: &Path,
+    db_name: &str,
+    flags: &Flags,
+) -> HashMap<(SymbolKind, u32), Vec<String>> {
+    let mut file_paths = Vec::new();
+    for db_dir in db_output_dirs(root_out_dir, &[db_name.to_string()], flags) {
+        collect_files(&db_dir, &mut file_paths);
+    }
+    file_paths.sort();
+
+    let mut paths: HashMap<(SymbolKind, u32), Vec<String>> = HashMap::new();
+    for file_path in file_paths {
+        let relative_path = relative_path_string(root_out_dir, &file_path);
+        let domain = match flags.output_tree {
+            OutputTree::Db => relative_path.split('/').nth(1),
+            OutputTree::Domain => relative_path.split('/').next(),
+        };
+        let kind = domain.and_then(domain_kind);
+        let id = element_id_from_path(&file_path).and_then(|id| id.parse().ok());
+        if let (Some(kind), Some(id)) = (kind, id) {
+            paths.entry((kind, id)).or_default().push(relative_path);
+        }
+    }
+    paths
+}
+
+/// The kind of element the files of an output folder like `scripts_sanitized` are named after
+fn domain_kind(domain: &str) -> Option<SymbolKind> {
+    // Folders like `layouts__modify_action` hold the same elements for another action
+    let domain = domain.split("__").next().unwrap_or(domain);
+    match domain {
+        "scripts" | "scripts_sanitized" | "script_stubs" | "calc_comments" => {
+            Some(SymbolKind::Script)
+        }
+        "layouts" => Some(SymbolKind::Layout),
+        "tables" | "table_stubs" => Some(SymbolKind::BaseTable),
+        "table_occurrences" => Some(SymbolKind::TableOccurrence),
+        "value_lists" | "value_list_stubs" => Some(SymbolKind::ValueList),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_kind() {
+        assert_eq!(domain_kind("scripts_sanitized"), Some(SymbolKind::Script));
+        assert_eq!(
+            domain_kind("layouts__modify_action"),
+            Some(SymbolKind::Layout)
+        );
+        assert_eq!(domain_kind("table_stubs"), Some(SymbolKind::BaseTable));
+        assert_eq!(domain_kind("accounts"), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Error, Result};
+
+use crate::exit_status::ExitStatus;
+
+/// Folders of the layout, each kept in git by an empty `.gitkeep`
+const FOLDERS: [&str; 3] = ["exports", "exploded", "reports"];
+
+const CHECK_CONFIG_FILE_NAME: &str = "check.json";
+
+/// All rules, with the naming patterns left to fill in
+const CHECK_CONFIG: &str = r#"{
+  "rules": [
+    "broken-reference",
+    "unused-variable",
+    "read-before-set",
+    "unknown-step",
+    "script-name",
+    "field-name",
+    "variable-name",
+    "layout-name",
+    "table-name",
+    "table-occurrence-name",
+    "value-list-name"
+  ],
+  "naming": {
+    "scripts": null,
+    "fields": null,
+    "variables": null,
+    "layouts": null,
+    "tables": null,
+    "table_occurrences": null,
+    "value_lists": null
+  }
+}
+"#;
+
+const GITATTRIBUTES: &str = "\
+# Diff the UTF-16 XML exports as text with the fmxml driver
+exports/*.xml diff=fmxml
+# Keep the line endings of the exploded files as written
+exploded/** -text
+";
+
+const GITIGNORE: &str = "\
+# Reports of check and analyze runs
+reports/
+# Archives written with --output-zip
+*.zip
+.DS_Store
+";
+
+/// Diff driver converting the exports to UTF-8 text for `git diff` and `git log -p`
+const TEXTCONV: &str = "iconv -f UTF-16 -t UTF-8";
+
+/// Run the `init` subcommand: create the layout and set up the diff driver
+pub fn run(dir: &Path) -> Result<ExitStatus, Error> {
+    scaffold(dir)?;
+    let in_repository = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["config", "diff.fmxml.textconv", TEXTCONV])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    match in_repository {
+        true => println!("Configured the fmxml diff driver of the git repository"),
+        false => println!(
+            "Not in a git repository; after `git init` run: git config diff.fmxml.textconv \"{TEXTCONV}\""
+        ),
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// Create the folders and files of a repository versioning exports. Existing files are kept,
+/// missing lines are appended to `.gitattributes` and `.gitignore`.
+fn scaffold(dir: &Path) -> Result<(), Error> {
+    for folder in FOLDERS {
+        let path = dir.join(folder);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("Error creating directory {}", path.display()))?;
+        write_new(&path.join(".gitkeep"), "")?;
+    }
+    write_new(&dir.join(CHECK_CONFIG_FILE_NAME), CHECK_CONFIG)?;
+    append_missing_lines(&dir.join(".gitattributes"), GITATTRIBUTES)?;
+    append_missing_lines(&dir.join(".gitignore"), GITIGNORE)
+}
+
+/// Write a file unless it exists
+fn write_new(path: &Path, content: &str) -> Result<(), Error> {
+    if path.exists() {
+        println!("Kept {}", path.display());
+        return Ok(());
+    }
+    fs::write(path, content).with_context(|| format!("Error writing file {}", path.display()))?;
+    println!("Created {}", path.display());
+    Ok(())
+}
+
+/// Append the lines of `content` the file doesn't contain yet, creating it if needed
+fn append_missing_lines(path: &Path, content: &str) -> Result<(), Error> {
+    let existing = match path.exists() {
+        true => fs::read_to_string(path)
+            .with_context(|| format!("Error reading file {}", path.display()))?,
+        false => String::new(),
+    };
+    let existing_lines: Vec<&str> = existing.lines().map(str::trim).collect();
+    let missing: Vec<&str> = content
+        .lines()
+        .filter(|line| !existing_lines.contains(&line.trim()))
+        .collect();
+    if missing.is_empty() {
+        println!("Kept {}", path.display());
+        return Ok(());
+    }
+    let mut updated = existing.clone();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    for line in missing {
+        updated.push_str(line);
+        updated.push('\n');
+    }
+    fs::write(path, updated).with_context(|| format!("Error writing file {}", path.display()))?;
+    match existing.is_empty() {
+        true => println!("Created {}", path.display()),
+        false => println!("Updated {}", path.display()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check::CheckConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_scaffold() {
+        let dir = std::env::temp_dir().join("fm_exploder_init_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".gitignore"), "target/\n*.zip").unwrap();
+
+        scaffold(&dir).unwrap();
+        let gitignore = fs::read_to_string(dir.join(".gitignore")).unwrap();
+        let config = fs::read_to_string(dir.join(CHECK_CONFIG_FILE_NAME)).unwrap();
+        let exports_kept = dir.join("exports/.gitkeep").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(exports_kept);
+        assert_eq!(
+            gitignore,
+            "target/\n*.zip\n# Reports of check and analyze runs\nreports/\n# Archives written with --output-zip\n.DS_Store\n"
+        );
+        assert!(serde_json::from_str::<CheckConfig>(&config).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Error, Result};
+use encoding_rs_io::DecodeReaderBytes;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::utils::attributes::get_attribute;
+use crate::utils::file_utils::valid_dir_or_throw;
+
+/// Resolve the given inputs to a sorted list of XML export files
+/// An input may be a directory (all *.xml files in it), a single XML file or a glob pattern
+pub fn resolve_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if input.is_file() {
+            paths.push(input.clone());
+        } else if input.is_dir() {
+            paths.extend(xml_files_in_dir(input)?);
+        } else if is_glob_pattern(input) {
+            let pattern = input.to_string_lossy();
+            let matches = glob::glob(&pattern)
+                .with_context(|| format!("Invalid glob pattern '{pattern}'"))?;
+            for path in matches.flatten() {
+                if path.is_dir() {
+                    paths.extend(xml_files_in_dir(&path)?);
+                } else if is_xml_file(&path) {
+                    paths.push(path);
+                }
+            }
+        } else {
+            valid_dir_or_throw(input)?;
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Fail if two exports belong to the same database, as they would overwrite each other's output
+pub fn check_unique_db_names(paths: &[PathBuf]) -> Result<(), Error> {
+    let mut seen: HashMap<String, &Path> = HashMap::new();
+    for path in paths {
+        let Some(db_name) = read_db_name(path) else {
+            continue; // Reported as a parse error when exploding
+        };
+        if let Some(other_path) = seen.insert(db_name.clone(), path) {
+            bail!(
+                "'{}' and '{}' are both exports of database '{db_name}'",
+                other_path.display(),
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Read the database name from the root element of an export without parsing the rest
+pub fn read_db_name(path: &Path) -> Option<String> {
+    let file_name = read_root_attribute(path, "File")?;
+    Some(
+        file_name
+            .strip_suffix(".fmp12")
+            .unwrap_or(&file_name)
+            .to_string(),
+    )
+}
+
+/// Read the version of FileMaker that saved an export, e.g. `21.0.1`
+pub fn read_source_version(path: &Path) -> Option<String> {
+    read_root_attribute(path, "Source")
+}
+
+fn read_root_attribute(path: &Path, name: &str) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut reader = Reader::from_reader(BufReader::new(DecodeReaderBytes::new(file)));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => return get_attribute(&e, name),
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+fn xml_files_in_dir(dir_path: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(fs::read_dir(dir_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| is_xml_file(path))
+        .collect())
+}
+
+fn is_xml_file(path: &Path) -> bool {
+    path.is_file() && path.extension().unwrap_or_default() == "xml"
+}
+
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_inputs() {
+        let dir = Path::new("./tests/xml").to_path_buf();
+        let all = resolve_inputs(std::slice::from_ref(&dir)).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let glob = PathBuf::from("./tests/xml/Ooe*.xml");
+        let file = all[0].clone();
+        let selected = resolve_inputs(&[glob, file.clone(), file]).unwrap();
+        assert_eq!(selected.len(), 2);
+
+        assert!(resolve_inputs(&[PathBuf::from("./tests/does-not-exist")]).is_err());
+    }
+
+    #[test]
+    fn test_check_unique_db_names() {
+        let all = resolve_inputs(&[PathBuf::from("./tests/xml")]).unwrap();
+        assert!(check_unique_db_names(&all).is_ok());
+
+        let duplicate = vec![all[0].clone(), all[0].clone()];
+        assert!(check_unique_db_names(&duplicate).is_err());
+    }
+}
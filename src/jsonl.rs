@@ -0,0 +1,119 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Error, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::script_steps::parameters::parameter_values::Parameter;
+
+/// Destination of `--format jsonl`: one JSON object per line, written as soon as a script is rendered.
+/// The steps of a script are written in order, the scripts in the order they finish rendering,
+/// which varies between runs
+pub struct JsonlSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+    /// Set once the consumer closed the stream, e.g. `| head`; further records are dropped
+    closed: AtomicBool,
+}
+
+/// A rendered script step, the record type of the JSON Lines output
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct StepRecord<'a> {
+    pub db: &'a str,
+    pub script_id: &'a str,
+    pub script: &'a str,
+    /// Step number as shown in FileMaker's script workspace, starting at 1
+    pub index: usize,
+    pub step_id: u32,
+    pub step: &'a str,
+    pub enabled: bool,
+    pub text: &'a str,
+    /// The typed parameters the text was rendered from
+    pub parameters: &'a [Parameter],
+}
+
+impl JsonlSink {
+    /// Create a sink writing to stdout for `-`, or to the given file otherwise
+    pub fn create(output_path: &Path) -> Result<Self, Error> {
+        let writer: Box<dyn Write + Send> = if output_path == Path::new("-") {
+            Box::new(io::stdout())
+        } else {
+            if let Some(parent) = output_path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("Error creating directory {}", parent.display())
+                    })?;
+                }
+            }
+            let file = File::create(output_path)
+                .with_context(|| format!("Error creating file {}", output_path.display()))?;
+            Box::new(BufWriter::new(file))
+        };
+        Ok(Self {
+            writer: Mutex::new(writer),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// The records as JSON lines, encoded where they are rendered to keep the writer stage short
+    pub fn encode<T: Serialize>(records: &[T]) -> Result<Vec<u8>, Error> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+        Ok(lines)
+    }
+
+    /// Write encoded records as one block, so lines of exports processed in parallel don't interleave
+    pub fn write(&self, lines: &[u8]) -> Result<(), Error> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut writer = self.writer.lock().unwrap();
+        match writer.write_all(lines).and_then(|_| writer.flush()) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::parameters::comment::Comment;
+
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let path = std::env::temp_dir().join(format!("fm-jsonl-test-{}.jsonl", std::process::id()));
+        let sink = JsonlSink::create(&path).unwrap();
+        let record = StepRecord {
+            db: "Admin",
+            script_id: "12",
+            script: "Hello",
+            index: 1,
+            step_id: 89,
+            step: "Comment",
+            enabled: true,
+            text: "# Hello \"world\"",
+            parameters: &[Parameter::Comment(Comment {
+                text: "Hello \"world\"".to_string(),
+            })],
+        };
+        sink.write(&JsonlSink::encode(&[&record, &record]).unwrap())
+            .unwrap();
+        drop(sink);
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let expected = r##"{"db":"Admin","script_id":"12","script":"Hello","index":1,"step_id":89,"step":"Comment","enabled":true,"text":"# Hello \"world\"","parameters":[{"type":"comment","text":"Hello \"world\""}]}"##;
+        assert_eq!(content, format!("{expected}\n{expected}\n"));
+    }
+}
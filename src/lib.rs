@@ -0,0 +1,1564 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::{fs, time::Instant};
+
+use anyhow::{bail, Context, Result};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+
+use crate::config::Flags;
+use crate::diagnostics::DiagnosticsCollector;
+use crate::download::{download_url_inputs, is_url, UrlAuth};
+use crate::dry_run::plan_changes;
+use crate::exit_status::ExitStatus;
+use crate::folder_index::write_folder_indexes;
+use crate::git_commit::{commit_message, commit_output, CommitInfo};
+use crate::incremental::{rendering_checksum, Incremental};
+use crate::index::Index;
+use crate::inputs::{check_unique_db_names, read_db_name, read_source_version, resolve_inputs};
+use crate::jsonl::JsonlSink;
+use crate::manifest::{db_output_dirs, remove_run_records, sha256_hex, Manifest, RenderReports};
+use crate::notify::notify_webhook;
+use crate::profile::Profile;
+use crate::progress::Progress;
+use crate::prune::prune_stale_files;
+#[cfg(feature = "s3")]
+use crate::s3_upload::{upload_files, S3Config, UploadTarget};
+use crate::script_template::ScriptTemplate;
+use crate::single_file::write_single_file;
+use crate::stats::{dir_size, RunStats, RunSummary};
+use crate::symbol_cache::read_symbols;
+#[cfg(feature = "s3")]
+use crate::utils::file_utils::collect_files;
+use crate::utils::file_utils::ScratchDir;
+use crate::utils::xml_utils::XmlEventType;
+use crate::writer_pool::WriterPool;
+use crate::xml_processor::{explode_xml, RunContext};
+use crate::zip_output::write_zip;
+
+mod analysis;
+mod annotate;
+pub mod api;
+mod calc_formatter;
+mod catalog;
+mod check;
+mod config;
+mod csv_output;
+mod custom_function_sanitizer;
+mod dependencies;
+mod diagnostics;
+mod diff;
+mod download;
+mod dry_run;
+mod exit_status;
+mod fetch;
+mod folder_index;
+mod gen_sample;
+mod git_commit;
+mod incremental;
+mod index;
+mod init;
+mod inputs;
+mod jsonl;
+mod locale;
+mod manifest;
+mod mapped_export;
+mod notify;
+mod profile;
+mod progress;
+mod prune;
+#[cfg(feature = "python")]
+mod python;
+mod redact;
+#[cfg(feature = "s3")]
+mod s3_upload;
+mod schema;
+mod script_sanitizer;
+mod script_steps;
+mod script_template;
+mod search;
+mod since;
+mod single_file;
+mod sqlite_output;
+mod stats;
+mod summary;
+mod supporting;
+mod symbol_cache;
+mod symbols;
+#[cfg(test)]
+mod tests;
+mod utils;
+mod writer_pool;
+mod xml_processor;
+mod zip_output;
+
+#[derive(Debug, Clone, Default, ValueEnum)]
+enum OutputTree {
+    #[value(
+        name = "domain",
+        help = "Use domain (e.g. catalog name) as the root folder"
+    )]
+    Domain,
+
+    #[default]
+    #[value(name = "db", help = "Use database name as the root folder (default)")]
+    Db,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum LineEnding {
+    #[default]
+    #[value(name = "lf", help = "Unix line endings (default)")]
+    Lf,
+
+    #[value(name = "crlf", help = "Windows line endings")]
+    Crlf,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum OutputFormat {
+    #[default]
+    #[value(name = "files", help = "Explode into a folder tree (default)")]
+    Files,
+
+    #[value(
+        name = "jsonl",
+        help = "Stream one JSON object per script step, e.g. to stdout with --output -"
+    )]
+    Jsonl,
+
+    #[value(
+        name = "sqlite",
+        help = "Write the parsed model to a SQLite database file to query with SQL"
+    )]
+    Sqlite,
+
+    #[value(
+        name = "csv",
+        help = "Write the field, value list and relationship catalogs to one CSV file each"
+    )]
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum FieldNames {
+    #[default]
+    #[value(
+        name = "qualified",
+        help = "Table occurrence and field, e.g. Invoices::Total (default)"
+    )]
+    Qualified,
+
+    #[value(
+        name = "unqualified",
+        help = "Field only, as seen from the current layout's context, e.g. Total"
+    )]
+    Unqualified,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum BoolStyle {
+    #[default]
+    #[value(name = "words", help = "Label and state, e.g. Password: ON (default)")]
+    Words,
+
+    #[value(
+        name = "symbols",
+        help = "Check mark or cross and label, e.g. ✓ Password"
+    )]
+    Symbols,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum GraphFormat {
+    #[default]
+    #[value(name = "dot", help = "Graphviz DOT (default)")]
+    Dot,
+
+    #[value(name = "json", help = "JSON")]
+    Json,
+
+    #[value(name = "graphml", help = "GraphML, e.g. for yEd")]
+    Graphml,
+
+    #[value(name = "gexf", help = "GEXF, e.g. for Gephi")]
+    Gexf,
+}
+
+/// JSON outputs the `schema` subcommand describes
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum SchemaDocument {
+    #[value(name = "jsonl", help = "A record of --format jsonl")]
+    Jsonl,
+
+    #[value(name = "manifest", help = "manifest.json of --manifest")]
+    Manifest,
+
+    #[value(name = "index", help = "index.json of --index")]
+    Index,
+
+    #[value(name = "summary", help = "The summary printed with --summary-json")]
+    Summary,
+}
+
+/// Output formats of analyses reporting findings in scripts
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum FindingFormat {
+    #[default]
+    #[value(name = "text", help = "One line per finding (default)")]
+    Text,
+
+    #[value(name = "json", help = "JSON")]
+    Json,
+
+    #[value(
+        name = "sarif",
+        help = "SARIF 2.1.0 for code scanning, e.g. of GitHub or GitLab"
+    )]
+    Sarif,
+}
+
+impl FindingFormat {
+    /// The format of the text and JSON reports, `None` for SARIF
+    fn report_format(self) -> Option<ReportFormat> {
+        match self {
+            Self::Text => Some(ReportFormat::Text),
+            Self::Json => Some(ReportFormat::Json),
+            Self::Sarif => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum TableFormat {
+    #[default]
+    #[value(name = "csv", help = "CSV with a header row (default)")]
+    Csv,
+
+    #[value(name = "json", help = "JSON")]
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum ComplexityMetric {
+    #[value(name = "steps", help = "Number of steps, without comments")]
+    Steps,
+
+    #[value(name = "depth", help = "Deepest nesting of If and Loop blocks")]
+    Depth,
+
+    #[value(
+        name = "calls",
+        help = "Number of Perform Script and Perform Script on Server calls"
+    )]
+    Calls,
+
+    #[value(name = "calc-length", help = "Total length of all calculations")]
+    CalcLength,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum ReportFormat {
+    #[default]
+    #[value(name = "text", help = "One line per finding (default)")]
+    Text,
+
+    #[value(name = "json", help = "JSON")]
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum DiffFormat {
+    #[default]
+    #[value(
+        name = "text",
+        help = "One line per change, with the changed step lines of scripts (default)"
+    )]
+    Text,
+
+    #[value(
+        name = "unified",
+        help = "Unified diffs of the rendered steps of changed scripts, for review tools"
+    )]
+    Unified,
+
+    #[value(name = "json", help = "JSON")]
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+enum Locale {
+    #[value(help = "English")]
+    En,
+    #[value(help = "German")]
+    De,
+    #[value(help = "French")]
+    Fr,
+    #[value(help = "Japanese")]
+    Ja,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Section {
+    #[value(help = "Scripts and sanitized scripts")]
+    Scripts,
+    #[value(help = "Layouts")]
+    Layouts,
+    #[value(help = "Base tables, fields, table occurrences and relationships")]
+    Schema,
+    #[value(help = "Accounts, privilege sets, extended privileges and file access")]
+    Security,
+    #[value(name = "custom_functions", help = "Custom functions")]
+    CustomFunctions,
+    #[value(name = "value_lists", help = "Value lists")]
+    ValueLists,
+    #[value(help = "Custom menus and custom menu sets")]
+    Menus,
+    #[value(help = "Themes")]
+    Themes,
+    #[value(help = "Library (container) items")]
+    Libraries,
+    #[value(
+        name = "data_sources",
+        help = "External data sources and base directories"
+    )]
+    DataSources,
+    #[value(help = "Metadata and DDR info")]
+    Metadata,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Analyze XML exports without exploding them
+    #[command(subcommand)]
+    Analyze(Analysis),
+
+    /// Search rendered script steps, calculations and names of XML exports, or the files of output folders with a manifest.json
+    Search {
+        /// Regular expression to search for
+        pattern: String,
+
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Ignore case
+        #[arg(short = 'i', long)]
+        ignore_case: bool,
+
+        /// Search for the pattern as plain text instead of a regular expression
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+    },
+
+    /// Count scripts, steps by type, layouts, tables, fields and other elements of XML exports
+    Stats {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Compare two XML exports, matching scripts, layouts, tables, fields and value lists by UUID or id, so renamed elements are reported as renamed and their steps still compared
+    Diff {
+        /// The older XML export, or a directory of exports
+        old: PathBuf,
+
+        /// The newer XML export, or a directory of exports
+        new: PathBuf,
+
+        /// Output format: text (default), unified or json
+        #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+
+        /// Ignore changes of comment steps
+        #[arg(long = "ignore-comments")]
+        ignore_comments: bool,
+
+        /// Consider scripts unchanged whose steps were only reordered
+        #[arg(long = "ignore-order")]
+        ignore_order: bool,
+
+        /// Write the report to this file instead of stdout
+        #[arg(short = 'o', long = "output", value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the commit that last changed each line of a rendered script, in an output folder versioned with git
+    Annotate {
+        /// Name of the script, or `ID <id>`
+        script: String,
+
+        /// Output folder of an explode run, inside a git repository
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+
+        /// Only look for the script in this database
+        #[arg(long, value_name = "NAME")]
+        db: Option<String>,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Check XML exports against a configurable set of rules without writing files, exiting with code 6 if anything is found
+    Check {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// JSON file selecting the rules and setting the naming conventions
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+
+        /// Also write the results in a format for CI systems, e.g. `--report junit results.xml`
+        #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+        report: Vec<String>,
+    },
+
+    /// Create the folders, check config, .gitattributes and .gitignore of a repository versioning XML exports
+    Init {
+        /// Root of the repository
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+    },
+
+    /// Have FileMaker Server save an XML export to a shared folder, or take the newest one there, and explode it
+    Fetch(FetchArgs),
+
+    /// Print the JSON Schema of a JSON output, to validate it or generate code from it
+    Schema {
+        /// The output to describe: jsonl, manifest, index or summary
+        #[arg(value_enum)]
+        document: SchemaDocument,
+    },
+
+    /// Write a synthetic XML export of the given size, to measure performance without customer exports
+    GenSample(SampleArgs),
+}
+
+/// Where `fetch` finds the export and how it triggers it
+#[derive(clap::Args)]
+struct FetchArgs {
+    /// Folder the exports are saved to, e.g. a share of the server's Documents folder
+    #[arg(long, value_name = "DIR")]
+    share: PathBuf,
+
+    /// FileMaker Server URL, e.g. https://fms.example.com, to perform the export script with the OData API first
+    #[arg(long, value_name = "URL", requires_all = ["database", "script"])]
+    server: Option<String>,
+
+    /// Database of the export script
+    #[arg(long)]
+    database: Option<String>,
+
+    /// Script saving the export to the shared folder, e.g. with Save a Copy as XML
+    #[arg(long)]
+    script: Option<String>,
+
+    /// Account of the OData API login
+    #[arg(long, env = "FM_EXPLODER_FETCH_USER")]
+    user: Option<String>,
+
+    /// Password of the OData API login
+    #[arg(long, env = "FM_EXPLODER_FETCH_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+
+    /// Seconds to wait for the export saved by the script
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    wait: u64,
+
+    /// The target directory to write output
+    target: PathBuf,
+
+    /// Options of the explode run, after `--`, e.g. `-- --prune --index`
+    #[arg(last = true)]
+    explode_args: Vec<String>,
+}
+
+/// Size of the export written by `gen-sample`
+#[derive(clap::Args)]
+struct SampleArgs {
+    /// Scripts, in folders of 100
+    #[arg(long, default_value_t = 1000)]
+    scripts: u32,
+
+    /// Steps per script, cycling through comments, Set Variable, If, Set Field, End If, Go to Layout and Perform Script
+    #[arg(long, default_value_t = 50)]
+    steps: u32,
+
+    /// Layouts, each showing a text and a field
+    #[arg(long, default_value_t = 200)]
+    layouts: u32,
+
+    /// Tables, each with one table occurrence
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    tables: u32,
+
+    /// Fields per table
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    fields: u32,
+
+    /// Write UTF-16 like FileMaker instead of UTF-8
+    #[arg(long)]
+    utf16: bool,
+
+    /// File to write the export to
+    output: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum Analysis {
+    /// Perform Script and Perform Script on Server call graph of all scripts
+    Callgraph {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: dot (default), json, graphml or gexf
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+
+    /// Table occurrences and the relationships between them
+    RelationshipGraph {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: dot (default), json, graphml or gexf
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+
+    /// Scripts neither performed by other scripts nor used by layouts, custom menus or file options
+    UnusedScripts {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Scripts called from outside, e.g. via the Data API: one name, `*` pattern or `ID <id>` per line, `[Database]` headers
+        #[arg(long, value_name = "FILE")]
+        allowlist: Option<PathBuf>,
+
+        /// Output format: text (default), json or sarif
+        #[arg(long, value_enum, default_value_t = FindingFormat::Text)]
+        format: FindingFormat,
+
+        /// Output folder of an explode run of the same exports, to point SARIF results to the lines of the sanitized scripts
+        #[arg(long, value_name = "DIR")]
+        exploded: Option<PathBuf>,
+    },
+
+    /// Script steps referring to scripts, layouts, table occurrences, fields or value lists that no longer exist
+    BrokenRefs {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default), json or sarif
+        #[arg(long, value_enum, default_value_t = FindingFormat::Text)]
+        format: FindingFormat,
+
+        /// Output folder of an explode run of the same exports, to point SARIF results to the lines of the sanitized scripts
+        #[arg(long, value_name = "DIR")]
+        exploded: Option<PathBuf>,
+
+        /// Exit with code 6 if any broken reference is found, e.g. to fail a CI job
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Scripts and layout objects referring to each field
+    FieldUsage {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Text literals in calculations naming layouts, scripts or fields, which break when these are renamed
+    HardcodedNames {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// ExecuteSQL queries of all calculations, checking the table occurrences and fields they name
+    Sql {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Exit with code 6 if a query names a missing table occurrence or field
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Complexity metrics of each script, e.g. to find scripts that need refactoring
+    Complexity {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: csv (default) or json
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        format: TableFormat,
+
+        /// Sort the scripts by this metric, highest first, instead of by export and script order
+        #[arg(long, value_enum)]
+        sort: Option<ComplexityMetric>,
+    },
+
+    /// Scripts running with full access privileges and the layouts, custom menus and file options reaching them
+    FullAccess {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default), json or sarif
+        #[arg(long, value_enum, default_value_t = FindingFormat::Text)]
+        format: FindingFormat,
+
+        /// Output folder of an explode run of the same exports, to point SARIF results to the lines of the sanitized scripts
+        #[arg(long, value_name = "DIR")]
+        exploded: Option<PathBuf>,
+    },
+
+    /// Functions of plugins called by calculations, by plugin, with their callers
+    Plugins {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Comment lines of script steps and calculations with markers like TODO, as a task list
+    Todos {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Words marking a task, matched case sensitively as whole words
+        #[arg(long = "marker", value_delimiter = ',', default_values = ["TODO", "FIXME", "HACK"])]
+        markers: Vec<String>,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Identical and similar scripts, e.g. copy-pasted logic to consolidate
+    Duplicates {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Report scripts sharing at least this percentage of their steps; 100 for identical scripts only
+        #[arg(long, default_value_t = 90, value_parser = clap::value_parser!(u32).range(1..=100))]
+        threshold: u32,
+
+        /// Ignore scripts with fewer steps
+        #[arg(long, default_value_t = 5)]
+        min_steps: usize,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Custom functions and value lists nothing uses, to guide cleanup
+    Unreferenced {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default) or json
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Variables assigned but never read, and `$` variables read before their script assigns them
+    Lint {
+        #[command(flatten)]
+        input: AnalysisInput,
+
+        /// Output format: text (default), json or sarif
+        #[arg(long, value_enum, default_value_t = FindingFormat::Text)]
+        format: FindingFormat,
+
+        /// Output folder of an explode run of the same exports, to point SARIF results to the lines of the sanitized scripts
+        #[arg(long, value_name = "DIR")]
+        exploded: Option<PathBuf>,
+
+        /// Exit with code 6 if anything is found
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+/// Inputs and output shared by all analyses
+#[derive(clap::Args)]
+struct AnalysisInput {
+    /// Directories, XML export files or glob patterns to analyze
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Write the report to this file instead of stdout
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// Parse all as XML exported FileMaker solutions from source directory and explode them to target directory.
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The source directory, XML export file, glob pattern (e.g. "exports/*.xml") or HTTP(S) URL of an XML export to read input
+    #[arg(required = true, env = "FM_EXPLODER_SOURCE")]
+    source: Option<PathBuf>,
+
+    /// The target directory to write output
+    #[arg(required_unless_present_any = ["output_zip", "output"], env = "FM_EXPLODER_TARGET")]
+    target: Option<PathBuf>,
+
+    /// The target to write output, as an alternative to the positional argument; `-` writes --format jsonl to stdout
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "PATH",
+        conflicts_with_all = ["target", "output_zip"],
+        env = "FM_EXPLODER_OUTPUT"
+    )]
+    output: Option<PathBuf>,
+
+    /// Output format: files (default), jsonl, sqlite or csv
+    #[arg(long, value_enum, default_value_t = OutputFormat::Files, env = "FM_EXPLODER_FORMAT")]
+    format: OutputFormat,
+
+    /// Parse all lines (or skip less important ones to reduce noise)
+    #[arg(short, long, env = "FM_EXPLODER_ALL_LINES")]
+    all_lines: bool,
+
+    /// Retain all information from the main xml (or skip less important catalogs and attributes)
+    #[arg(short, long, env = "FM_EXPLODER_LOSSLESS")]
+    lossless: bool,
+
+    /// Specify the output tree root folder: domain or db (default)
+    #[arg(
+        short = 't',
+        long = "output_tree",
+        value_enum,
+        default_value_t = OutputTree::Db,
+        env = "FM_EXPLODER_OUTPUT_TREE"
+    )]
+    output_tree: OutputTree,
+
+    /// Line endings of all written files: lf (default) or crlf
+    #[arg(
+        long = "line-endings",
+        value_enum,
+        default_value_t = LineEnding::Lf,
+        env = "FM_EXPLODER_LINE_ENDINGS"
+    )]
+    line_endings: LineEnding,
+
+    /// Do not end written files with a line break
+    #[arg(long, env = "FM_EXPLODER_NO_TRAILING_NEWLINE")]
+    no_trailing_newline: bool,
+
+    /// Additionally concatenate all sanitized scripts into this single text file
+    #[arg(
+        long = "single-file",
+        value_name = "FILE",
+        env = "FM_EXPLODER_SINGLE_FILE"
+    )]
+    single_file: Option<PathBuf>,
+
+    /// Write all output into this zip archive instead of the target directory
+    #[arg(
+        long = "output-zip",
+        value_name = "FILE",
+        conflicts_with = "target",
+        env = "FM_EXPLODER_OUTPUT_ZIP"
+    )]
+    output_zip: Option<PathBuf>,
+
+    /// Write a manifest.json listing all generated files with their SHA-256 checksums
+    #[arg(long, env = "FM_EXPLODER_MANIFEST")]
+    manifest: bool,
+
+    /// Write an index.json mapping the ids and UUIDs of all scripts, layouts, tables and fields to their names and files
+    #[arg(long, env = "FM_EXPLODER_INDEX")]
+    index: bool,
+
+    /// Write an _index.md into each script and layout folder listing its contents, to browse the output on GitHub
+    #[arg(long = "folder-index", env = "FM_EXPLODER_FOLDER_INDEX")]
+    folder_index: bool,
+
+    /// Suppress progress bars and status messages; errors are still reported
+    #[arg(short, long, env = "FM_EXPLODER_QUIET")]
+    quiet: bool,
+
+    /// Print a machine-readable JSON summary of the run to stdout (implies --quiet)
+    #[arg(long = "summary-json", env = "FM_EXPLODER_SUMMARY_JSON")]
+    summary_json: bool,
+
+    /// Parse everything, but only list the files that would be created, updated or deleted in the target directory
+    #[arg(
+        long = "dry-run",
+        conflicts_with_all = ["output_zip", "single_file"],
+        env = "FM_EXPLODER_DRY_RUN"
+    )]
+    dry_run: bool,
+
+    /// Delete files listed in the previous manifest.json that are no longer generated (implies --manifest)
+    #[arg(long, conflicts_with_all = ["output_zip", "dry_run"], env = "FM_EXPLODER_PRUNE")]
+    prune: bool,
+
+    /// Only rewrite files whose checksum differs from the previous manifest.json and skip rendering unchanged scripts (implies --prune)
+    #[arg(long, conflicts_with_all = ["output_zip", "dry_run"], env = "FM_EXPLODER_INCREMENTAL")]
+    incremental: bool,
+
+    /// Stage the target directory in the git repository containing it and commit it
+    #[arg(
+        long = "git-commit",
+        conflicts_with_all = ["output_zip", "dry_run"],
+        env = "FM_EXPLODER_GIT_COMMIT"
+    )]
+    git_commit: bool,
+
+    /// Message of --git-commit; {sources}, {versions}, {files}, {scripts}, {layouts} and {custom_functions} are replaced
+    #[arg(
+        long = "git-message",
+        value_name = "TEMPLATE",
+        default_value = git_commit::DEFAULT_MESSAGE,
+        requires = "git_commit",
+        env = "FM_EXPLODER_GIT_MESSAGE"
+    )]
+    git_message: String,
+
+    /// Upload the output files, or the zip of --output-zip, to this S3 location after a successful run, e.g. s3://bucket/prefix
+    #[cfg(feature = "s3")]
+    #[arg(
+        long,
+        value_name = "S3_URL",
+        conflicts_with = "dry_run",
+        env = "FM_EXPLODER_UPLOAD"
+    )]
+    upload: Option<String>,
+
+    /// Endpoint of S3-compatible storage of --upload, e.g. http://localhost:9000 (default: AWS_ENDPOINT_URL or AWS S3)
+    #[cfg(feature = "s3")]
+    #[arg(
+        long = "upload-endpoint",
+        value_name = "URL",
+        requires = "upload",
+        env = "FM_EXPLODER_UPLOAD_ENDPOINT"
+    )]
+    upload_endpoint: Option<String>,
+
+    /// POST the JSON summary of the run to this URL when it completes, e.g. to notify a chat or an orchestrator
+    #[arg(
+        long = "notify-webhook",
+        value_name = "URL",
+        env = "FM_EXPLODER_NOTIFY_WEBHOOK"
+    )]
+    notify_webhook: Option<String>,
+
+    /// Parse the exports from memory maps instead of buffered reads, to lower the peak memory and speed up the passes over multi-GB exports
+    #[arg(long, env = "FM_EXPLODER_MMAP")]
+    mmap: bool,
+
+    /// Cache the symbol tables of the exports in this directory, so later runs on an unchanged export skip the first pass
+    #[arg(long = "cache-dir", value_name = "DIR", env = "FM_EXPLODER_CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Threads writing the output files while the exports are parsed and rendered, 0 to write them on the parsing threads
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 4,
+        env = "FM_EXPLODER_WRITERS"
+    )]
+    writers: usize,
+
+    /// Report the time and bytes spent per section and the slowest scripts on stderr, to find hotspots in large exports
+    #[arg(long, env = "FM_EXPLODER_PROFILE")]
+    profile: bool,
+
+    /// Mask SMTP credentials of Send Mail, account names and passwords of Re-Login, `--user` in cURL options and literal API keys in all output files
+    #[arg(long, env = "FM_EXPLODER_REDACT")]
+    redact: bool,
+
+    /// Only explode these sections, e.g. --only scripts,layouts (default: all). The output folders of the other sections are kept
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "lossless",
+        env = "FM_EXPLODER_ONLY"
+    )]
+    only: Vec<Section>,
+
+    /// Only explode scripts, layouts, tables and other catalog items modified on or after this date or time, e.g. 2025-06-01 or 2025-06-01T14:30, into an output folder without a previous run of the database; items without a modification timestamp are kept
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = since::parse_since,
+        conflicts_with_all = ["lossless", "prune", "incremental"],
+        env = "FM_EXPLODER_SINCE"
+    )]
+    since: Option<String>,
+
+    /// User of the basic authentication of the source and inputs given as URLs
+    #[arg(
+        long = "url-user",
+        value_name = "USER",
+        requires = "url_password",
+        conflicts_with = "url_token",
+        env = "FM_EXPLODER_URL_USER"
+    )]
+    url_user: Option<String>,
+
+    /// Password of --url-user
+    #[arg(
+        long = "url-password",
+        value_name = "PASSWORD",
+        requires = "url_user",
+        env = "FM_EXPLODER_URL_PASSWORD",
+        hide_env_values = true
+    )]
+    url_password: Option<String>,
+
+    /// Bearer token sent with the requests of the source and inputs given as URLs
+    #[arg(
+        long = "url-token",
+        value_name = "TOKEN",
+        env = "FM_EXPLODER_URL_TOKEN",
+        hide_env_values = true
+    )]
+    url_token: Option<String>,
+
+    /// Additional input directories, XML export files or glob patterns or URLs; each export is exploded into its own folder
+    #[arg(
+        short = 'i',
+        long = "input",
+        value_name = "PATH",
+        env = "FM_EXPLODER_INPUT"
+    )]
+    inputs: Vec<PathBuf>,
+
+    /// Render each sanitized script with this Tera template instead of the default text format
+    #[arg(long, value_name = "FILE", env = "FM_EXPLODER_TEMPLATE")]
+    template: Option<PathBuf>,
+
+    /// Prefix each step in scripts_sanitized with its step number as shown in FileMaker's script workspace
+    #[arg(long = "step-numbers", env = "FM_EXPLODER_STEP_NUMBERS")]
+    step_numbers: bool,
+
+    /// Render script step names and ON/OFF options in this language instead of the language of the export
+    #[arg(long, value_enum, env = "FM_EXPLODER_LOCALE")]
+    locale: Option<Locale>,
+
+    /// Re-indent Let, Case, If and While and normalize operator spacing in all rendered calculations
+    #[arg(long = "format-calcs", env = "FM_EXPLODER_FORMAT_CALCS")]
+    format_calcs: bool,
+
+    /// Spaces per indentation level of --format-calcs; 0 indents with tabs
+    #[arg(
+        long = "calc-indent",
+        value_name = "N",
+        default_value_t = 4,
+        requires = "format_calcs",
+        env = "FM_EXPLODER_CALC_INDENT"
+    )]
+    calc_indent: usize,
+
+    /// Collapse all rendered calculations to a single line, with ¶ for line breaks in text
+    #[arg(
+        long = "inline-calcs",
+        conflicts_with = "format_calcs",
+        env = "FM_EXPLODER_INLINE_CALCS"
+    )]
+    inline_calcs: bool,
+
+    /// Keep all rendered calculations as exported but write their line breaks as \n, one line per step
+    #[arg(
+        long = "escape-calc-newlines",
+        conflicts_with_all = ["format_calcs", "inline_calcs"],
+        env = "FM_EXPLODER_ESCAPE_CALC_NEWLINES"
+    )]
+    escape_calc_newlines: bool,
+
+    /// List the comments of all calculations of each script in calc_comments, e.g. to mine documentation
+    #[arg(long = "calc-comments", env = "FM_EXPLODER_CALC_COMMENTS")]
+    calc_comments: bool,
+
+    /// Append a Dependencies section to each script in scripts_sanitized, listing the layouts, scripts, fields and variables its steps use
+    #[arg(long, env = "FM_EXPLODER_DEPENDENCIES")]
+    dependencies: bool,
+
+    /// How fields targeted by script steps are named: qualified (default) or unqualified
+    #[arg(
+        long = "field-names",
+        value_enum,
+        default_value_t = FieldNames::Qualified,
+        env = "FM_EXPLODER_FIELD_NAMES"
+    )]
+    field_names: FieldNames,
+
+    /// How ON/OFF options of script steps are rendered: words (default) or symbols
+    #[arg(
+        long = "bool-style",
+        value_enum,
+        default_value_t = BoolStyle::Words,
+        env = "FM_EXPLODER_BOOL_STYLE"
+    )]
+    bool_style: BoolStyle,
+
+    /// Append the raw XML of unrecognized script steps and options beneath the rendered step, e.g. for bug reports
+    #[arg(long = "debug-unknown", env = "FM_EXPLODER_DEBUG_UNKNOWN")]
+    debug_unknown: bool,
+
+    /// Fail with exit code 3 if any malformed XML was skipped while parsing
+    #[arg(long, env = "FM_EXPLODER_STRICT")]
+    strict: bool,
+
+    /// Exit with code 3 after completing the run if any script step could not be fully rendered
+    #[arg(long, env = "FM_EXPLODER_STRICT_STEPS")]
+    strict_steps: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Skeleton {
+    pub content: String,
+    pub previous_line: String,
+    pub previous_event_type: XmlEventType,
+}
+
+/// Parse the command line and run the explode or a subcommand, the entry point of the binary
+pub fn run_cli() -> ExitCode {
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(err) => {
+            let _ = err.print();
+            return match err.use_stderr() {
+                true => ExitStatus::Usage.into(),
+                false => ExitStatus::Clean.into(), // --help and --version
+            };
+        }
+    };
+
+    if let Err(err) = args.validate() {
+        let _ = err.print();
+        return ExitStatus::Usage.into();
+    }
+
+    let result = match &args.command {
+        Some(Command::Analyze(analysis)) => analysis::run(analysis),
+        Some(Command::Search {
+            pattern,
+            input,
+            ignore_case,
+            fixed_strings,
+        }) => search::run(pattern, input, *ignore_case, *fixed_strings),
+        Some(Command::Stats { input, format }) => summary::run(input, *format),
+        Some(Command::Diff {
+            old,
+            new,
+            format,
+            ignore_comments,
+            ignore_order,
+            output,
+        }) => diff::run(
+            old,
+            new,
+            output.as_deref(),
+            *format,
+            diff::DiffOptions {
+                ignore_comments: *ignore_comments,
+                ignore_order: *ignore_order,
+            },
+        ),
+        Some(Command::Annotate {
+            script,
+            dir,
+            db,
+            format,
+        }) => annotate::run(script, dir, db.as_deref(), *format),
+        Some(Command::Check {
+            input,
+            config,
+            report,
+        }) => check::run(input, config.as_deref(), report),
+        Some(Command::Init { dir }) => init::run(dir),
+        Some(Command::Fetch(fetch_args)) => {
+            fetch::fetch(fetch_args).and_then(|export| explode_fetched(&export, fetch_args))
+        }
+        Some(Command::Schema { document }) => schema::run(*document),
+        Some(Command::GenSample(sample_args)) => gen_sample::run(sample_args),
+        None => run(args),
+    };
+    match result {
+        Ok(status) => status.into(),
+        Err(err) => {
+            eprintln!("Error: {err:#}");
+            ExitStatus::from_error(&err).into()
+        }
+    }
+}
+
+impl Args {
+    /// Check combinations of arguments clap can't express declaratively
+    fn validate(&self) -> Result<(), clap::Error> {
+        let writes_stdout = self.output.as_deref() == Some(Path::new("-"));
+        let format = self.format.to_possible_value().unwrap();
+        if self.format != OutputFormat::Files
+            && (self.output_zip.is_some()
+                || self.dry_run
+                || self.incremental
+                || self.prune
+                || self.manifest
+                || self.index
+                || self.folder_index)
+        {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                format!("--format {} can't be combined with --output-zip, --dry-run, --incremental, --prune, --manifest, --index or --folder-index", format.get_name()),
+            ));
+        }
+        if self.format != OutputFormat::Files && self.git_commit {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                "--git-commit is only supported with --format files",
+            ));
+        }
+        #[cfg(feature = "s3")]
+        if self.format != OutputFormat::Files && self.upload.is_some() {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                "--upload is only supported with --format files",
+            ));
+        }
+        #[cfg(feature = "s3")]
+        if let Some(Err(err)) = self.upload.as_deref().map(UploadTarget::parse) {
+            return Err(Args::command().error(ErrorKind::InvalidValue, err));
+        }
+        if let Some(webhook_url) = &self.notify_webhook {
+            if !is_url(Path::new(webhook_url)) {
+                return Err(Args::command().error(
+                    ErrorKind::InvalidValue,
+                    format!("--notify-webhook expects an http(s) URL, got '{webhook_url}'"),
+                ));
+            }
+        }
+        if self.format != OutputFormat::Jsonl && writes_stdout {
+            return Err(Args::command().error(
+                ErrorKind::InvalidValue,
+                "--output - is only supported with --format jsonl",
+            ));
+        }
+        let target = self.target.as_deref().or(self.output.as_deref());
+        if self.summary_json && self.format == OutputFormat::Jsonl && target == Some(Path::new("-"))
+        {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                "--summary-json can't be combined with --format jsonl written to stdout (-)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Credentials of the inputs given as URLs
+    fn url_auth(&self) -> UrlAuth {
+        match (&self.url_user, &self.url_password, &self.url_token) {
+            (Some(user), Some(password), _) => UrlAuth::Basic {
+                user: user.clone(),
+                password: password.clone(),
+            },
+            (_, _, Some(token)) => UrlAuth::Bearer(token.clone()),
+            _ => UrlAuth::None,
+        }
+    }
+
+    /// The options that affect how exports are parsed and rendered
+    fn flags(&self) -> Flags {
+        Flags {
+            parse_all_lines: self.all_lines,
+            lossless: self.lossless,
+            output_tree: self.output_tree.clone(),
+            line_endings: self.line_endings,
+            trailing_newline: !self.no_trailing_newline,
+            only: self.only.clone(),
+            since: self.since.clone(),
+            step_numbers: self.step_numbers,
+            debug_unknown: self.debug_unknown,
+            locale: self.locale,
+            format_calcs: self.format_calcs.then_some(self.calc_indent),
+            inline_calcs: self.inline_calcs,
+            escape_calc_newlines: self.escape_calc_newlines,
+            calc_comments: self.calc_comments,
+            dependencies: self.dependencies,
+            field_names: self.field_names,
+            bool_style: self.bool_style,
+            redact: self.redact,
+        }
+    }
+}
+
+/// Explode a fetched export with the options given to `fetch` after `--`
+fn explode_fetched(export: &Path, fetch_args: &FetchArgs) -> Result<ExitStatus> {
+    eprintln!("Exploding {}", export.display());
+    let mut command_line = vec![
+        env!("CARGO_PKG_NAME").into(),
+        export.as_os_str().to_owned(),
+        fetch_args.target.as_os_str().to_owned(),
+    ];
+    command_line.extend(fetch_args.explode_args.iter().map(Into::into));
+    let args = Args::try_parse_from(command_line)?;
+    args.validate()?;
+    run(args)
+}
+
+fn run(args: Args) -> Result<ExitStatus> {
+    let start = Instant::now();
+
+    let mut inputs = vec![args.source.clone().unwrap()];
+    inputs.extend(args.inputs.iter().cloned());
+    // Exports downloaded from URLs are removed when the run ends
+    let download_dir = match inputs.iter().any(|input| is_url(input)) {
+        true => Some(ScratchDir::create("download")?),
+        false => None,
+    };
+    if let Some(download_dir) = &download_dir {
+        inputs = download_url_inputs(&inputs, &download_dir.path, &args.url_auth())?;
+    }
+    let target_dir = args.target.clone().or(args.output.clone());
+    if args.format == OutputFormat::Sqlite {
+        let database_path = target_dir.unwrap();
+        let exit_status = sqlite_output::run(&inputs, &database_path)?;
+        if !args.quiet {
+            println!("Wrote the model to '{}'.", database_path.display());
+        }
+        return Ok(exit_status);
+    }
+    if args.format == OutputFormat::Csv {
+        let out_dir = target_dir.unwrap();
+        let exit_status = csv_output::run(&inputs, &out_dir)?;
+        if !args.quiet {
+            println!("Wrote the catalogs to '{}'.", out_dir.display());
+        }
+        return Ok(exit_status);
+    }
+    let is_jsonl = args.format == OutputFormat::Jsonl;
+    let is_staged = args.output_zip.is_some() || args.dry_run || args.incremental || is_jsonl;
+    // Staged runs leave nothing behind but their actual output, also when they fail
+    let staging_dir = match is_staged {
+        true => Some(ScratchDir::create("staging")?),
+        false => None,
+    };
+    let out_dir = match &staging_dir {
+        Some(staging_dir) => staging_dir.path.clone(),
+        None => target_dir.clone().unwrap(),
+    };
+    let flags = args.flags();
+
+    let paths = resolve_inputs(&inputs)?;
+    check_unique_db_names(&paths)?;
+    if !is_staged && flags.since.is_some() {
+        since::check_no_previous_output(&out_dir, &paths, &flags.output_tree)?;
+    }
+
+    // The previous manifest is needed to tell stale files from unrelated ones
+    let previous_manifest = match args.prune || args.incremental {
+        true => Manifest::read(target_dir.as_ref().unwrap())?,
+        false => None,
+    };
+    if !is_staged && (args.manifest || args.prune || args.index) {
+        remove_run_records(&out_dir)?;
+    }
+    // Rendered files of a previous run are only reused if rendered by the same version and options
+    let template_source = match &args.template {
+        Some(path) => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("Error reading template {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let rendering = rendering_checksum(&flags, template_source.as_deref());
+    let run = RunContext {
+        progress: Progress::new(
+            args.quiet || args.summary_json || args.output.as_deref() == Some(Path::new("-")),
+        ),
+        stats: RunStats::default(),
+        incremental: match args.incremental {
+            true => Some(Incremental::new(
+                &out_dir,
+                target_dir.as_ref().unwrap(),
+                previous_manifest.clone(),
+                &rendering,
+            )),
+            false => None,
+        },
+        jsonl: match is_jsonl {
+            true => Some(JsonlSink::create(target_dir.as_ref().unwrap())?),
+            false => None,
+        },
+        template: match &args.template {
+            Some(path) => Some(ScriptTemplate::from_file(path)?),
+            None => None,
+        },
+        diagnostics: DiagnosticsCollector::default(),
+        mmap: args.mmap,
+        cache_dir: args.cache_dir.clone(),
+        profile: args.profile.then(Profile::default),
+        writer: (args.writers > 0).then(|| WriterPool::new(args.writers)),
+        reports: RenderReports::default(),
+    };
+
+    run.progress
+        .println(format!("Start processing {} files...", paths.len()));
+
+    // Process XML files in parallel
+    let db_names = paths
+        .par_iter()
+        .filter_map(|path| {
+            let result = explode_xml(path, &out_dir, &flags, &run);
+            run.stats.record_file(&result);
+            match result {
+                Ok(db_name) => Some(db_name),
+                Err(err) => {
+                    let file_name = path.file_name().unwrap().to_str().unwrap();
+                    eprintln!("Failed to process file '{file_name}': {err}");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    // The manifest and index list the files on disk, so they are built once all are written
+    if let Some(writer) = &run.writer {
+        writer.finish();
+        run.stats.record_write_failures(writer.failures());
+    }
+
+    let diagnostics = run.diagnostics.sorted();
+    if !args.quiet {
+        for diagnostic in &diagnostics {
+            eprintln!("Warning: {diagnostic}");
+        }
+    }
+    if args.strict && !diagnostics.is_empty() {
+        bail!(
+            "{} parse warnings treated as errors (--strict)",
+            diagnostics.len()
+        );
+    }
+
+    if args.folder_index {
+        let count = write_folder_indexes(&out_dir, &db_names, &flags)?;
+        run.progress
+            .println(format!("Wrote {count} folder indexes."));
+    }
+
+    // The files of the output are written first, then its index and last its manifest
+    let mut manifest = None;
+    if let Some(incremental) = &run.incremental {
+        let target_dir = target_dir.as_ref().unwrap();
+        let mut current = Manifest::build(&out_dir, &db_names, &flags)?;
+        current.rendering = Some(rendering.clone());
+        current.add_reports(&out_dir, &run.reports);
+        if let Some(previous_manifest) = &previous_manifest {
+            current.keep_unselected_sections(previous_manifest, &flags);
+        }
+        remove_run_records(target_dir)?;
+        let result = incremental.sync(&current)?;
+        manifest = Some((current, target_dir));
+        run.progress.println(format!(
+            "Incremental: {} files written, {} unchanged, {} pruned.",
+            result.written, result.unchanged, result.pruned
+        ));
+    } else if args.manifest || args.prune {
+        let mut current = Manifest::build(&out_dir, &db_names, &flags)?;
+        current.rendering = Some(rendering.clone());
+        current.add_reports(&out_dir, &run.reports);
+        if let Some(previous_manifest) = &previous_manifest {
+            let pruned = prune_stale_files(&out_dir, previous_manifest, &current)?;
+            for relative_path in &pruned {
+                run.progress.println(format!("Pruned {relative_path}"));
+            }
+        }
+        manifest = Some((current, &out_dir));
+    }
+
+    if args.index {
+        let exports = paths
+            .par_iter()
+            .filter_map(|path| {
+                let symbols = read_symbols(path, None, args.cache_dir.as_deref()).ok()?;
+                Some((read_db_name(path)?, symbols))
+            })
+            .filter(|(db_name, _)| db_names.contains(db_name))
+            .collect::<Vec<_>>();
+        let index = Index::build(&out_dir, &exports, &flags)?;
+        match &run.incremental {
+            Some(_) => index.write(target_dir.as_ref().unwrap())?,
+            None => index.write(&out_dir)?,
+        };
+    }
+
+    let manifest_path = match &manifest {
+        Some((manifest, root_out_dir)) => Some(manifest.write(root_out_dir)?),
+        None => None,
+    };
+    // Read before a staged output is removed
+    let manifest_sha256 = match &manifest_path {
+        Some(manifest_path) => Some(sha256_hex(&fs::read(manifest_path)?)),
+        None => None,
+    };
+
+    let output_bytes = db_output_dirs(&out_dir, &db_names, &flags)
+        .iter()
+        .map(|db_dir| dir_size(db_dir))
+        .sum();
+
+    if args.dry_run {
+        let changes = plan_changes(&out_dir, target_dir.as_ref().unwrap(), &db_names, &flags);
+        for (change, relative_path) in &changes {
+            println!("{change} {relative_path}");
+        }
+        run.progress
+            .println(format!("Dry run: {} files would change.", changes.len()));
+    }
+
+    if let Some(single_file_path) = &args.single_file {
+        let count = write_single_file(&out_dir, single_file_path, &flags)?;
+        run.progress.println(format!(
+            "Wrote {count} scripts to '{}'.",
+            single_file_path.display()
+        ));
+    }
+
+    let zip_result = args
+        .output_zip
+        .as_ref()
+        .map(|zip_file_path| write_zip(&out_dir, zip_file_path));
+    drop(staging_dir);
+
+    if let (Some(zip_file_path), Some(zip_result)) = (&args.output_zip, zip_result) {
+        let count = zip_result?;
+        run.progress.println(format!(
+            "Wrote {count} files to '{}'.",
+            zip_file_path.display()
+        ));
+    }
+
+    let duration = start.elapsed();
+    if duration.as_secs() > 9 {
+        run.progress
+            .println(format!("Completed in {:?} seconds.", duration.as_secs()));
+    } else {
+        run.progress
+            .println(format!("Completed in {:?} ms.", duration.as_millis()));
+    }
+
+    let summary = RunSummary {
+        manifest_sha256,
+        ..run.stats.summary(duration, output_bytes)
+    };
+
+    if args.git_commit {
+        let info = CommitInfo {
+            sources: paths
+                .iter()
+                .filter_map(|path| path.file_name())
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .collect(),
+            versions: paths
+                .iter()
+                .filter_map(|path| read_source_version(path))
+                .collect(),
+            summary: &summary,
+        };
+        let target_dir = target_dir.as_ref().unwrap();
+        match commit_output(target_dir, &commit_message(&args.git_message, &info))? {
+            true => run.progress.println("Committed the output to git."),
+            false => run.progress.println("No changes to commit."),
+        }
+    }
+
+    #[cfg(feature = "s3")]
+    if let Some(upload) = &args.upload {
+        if matches!(
+            run.stats.exit_status(),
+            ExitStatus::Clean | ExitStatus::Warnings
+        ) {
+            let target = UploadTarget::parse(upload)?;
+            let config = S3Config::from_env(args.upload_endpoint.clone())?;
+            let count = match &args.output_zip {
+                Some(zip_file_path) => {
+                    let root = zip_file_path.parent().unwrap_or(Path::new(""));
+                    upload_files(root, std::slice::from_ref(zip_file_path), &target, &config)?
+                }
+                None => {
+                    let target_dir = target_dir.as_ref().unwrap();
+                    let mut file_paths = Vec::new();
+                    for db_dir in db_output_dirs(target_dir, &db_names, &flags) {
+                        collect_files(&db_dir, &mut file_paths);
+                    }
+                    file_paths.extend(
+                        ["manifest.json", "index.json"]
+                            .map(|file_name| target_dir.join(file_name))
+                            .into_iter()
+                            .filter(|file_path| file_path.is_file()),
+                    );
+                    upload_files(target_dir, &file_paths, &target, &config)?
+                }
+            };
+            run.progress
+                .println(format!("Uploaded {count} files to '{upload}'."));
+        }
+    }
+
+    if args.summary_json {
+        println!("{}", serde_json::to_string(&summary)?);
+    }
+
+    if let Some(webhook_url) = &args.notify_webhook {
+        notify_webhook(webhook_url, &summary)?;
+        run.progress.println("Posted the summary to the webhook.");
+    }
+
+    if let Some(profile) = &run.profile {
+        eprint!("{}", profile.report());
+    }
+
+    if args.strict_steps {
+        let unknown_steps = run.stats.unknown_step_usages();
+        if !unknown_steps.is_empty() {
+            eprintln!("Error: script steps without a renderer (--strict-steps):");
+            for unknown_step in &unknown_steps {
+                eprintln!("  {unknown_step}");
+            }
+            // A truncated export or a failed one is the more useful code to report
+            let status = run.stats.exit_status();
+            return Ok(match status.severity() < ExitStatus::Truncated.severity() {
+                true => ExitStatus::ParseError,
+                false => status,
+            });
+        }
+    }
+
+    Ok(run.stats.exit_status())
+}
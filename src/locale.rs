@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::Locale;
+
+/// Step names by step id, one `id<TAB>name` per line
+const EN: &str = include_str!("locales/en.tsv");
+const DE: &str = include_str!("locales/de.tsv");
+const FR: &str = include_str!("locales/fr.tsv");
+const JA: &str = include_str!("locales/ja.tsv");
+
+fn parse_step_names(table: &'static str) -> HashMap<u32, &'static str> {
+    table
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(id, name)| Some((id.parse().ok()?, name)))
+        .collect()
+}
+
+impl Locale {
+    /// The name FileMaker shows for the step in this language, if known
+    pub fn step_name(&self, step_id: u32) -> Option<&'static str> {
+        static TABLES: OnceLock<HashMap<Locale, HashMap<u32, &'static str>>> = OnceLock::new();
+        let tables = TABLES.get_or_init(|| {
+            [
+                (Locale::En, EN),
+                (Locale::De, DE),
+                (Locale::Fr, FR),
+                (Locale::Ja, JA),
+            ]
+            .into_iter()
+            .map(|(locale, table)| (locale, parse_step_names(table)))
+            .collect()
+        });
+        tables[self].get(&step_id).copied()
+    }
+
+    /// The words of the ON/OFF options in this language
+    pub fn on_off(&self) -> (&'static str, &'static str) {
+        match self {
+            Locale::En => ("ON", "OFF"),
+            Locale::De => ("Ein", "Aus"),
+            Locale::Fr => ("Activé", "Désactivé"),
+            Locale::Ja => ("オン", "オフ"),
+        }
+    }
+
+    /// Translate the name of a sanitized step, e.g. `Set Error Capture [ Ein ]` to
+    /// `Fehleraufzeichnung setzen [ Ein ]`; its ON/OFF options are rendered in the language already.
+    /// Names missing in the table keep the language of the export.
+    pub fn localize_step(&self, text: &str, step_id: u32, exported_name: &str) -> String {
+        let (disabled, step) = match text.strip_prefix("// ") {
+            Some(step) => ("// ", step),
+            None => ("", text),
+        };
+        match (self.step_name(step_id), step.strip_prefix(exported_name)) {
+            (Some(name), Some(rest)) if !exported_name.is_empty() => {
+                format!("{disabled}{name}{rest}")
+            }
+            _ => text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_names() {
+        assert_eq!(Locale::En.step_name(87), Some("Show Custom Dialog"));
+        assert_eq!(
+            Locale::De.step_name(87),
+            Some("Eigenes Dialogfeld anzeigen")
+        );
+        assert_eq!(Locale::Ja.step_name(68), Some("If"));
+        assert_eq!(Locale::Fr.step_name(999), None);
+    }
+
+    #[test]
+    fn test_localize_step() {
+        assert_eq!(
+            Locale::De.localize_step("Set Error Capture [ Ein ]", 86, "Set Error Capture"),
+            "Fehleraufzeichnung setzen [ Ein ]"
+        );
+        assert_eq!(
+            Locale::En.localize_step(
+                "// Gehe zu Layout [ Layout: <Start> ; Animation: OFF ]",
+                6,
+                "Gehe zu Layout"
+            ),
+            "// Go to Layout [ Layout: <Start> ; Animation: OFF ]"
+        );
+        assert_eq!(
+            Locale::Fr.localize_step("Future step [ ON ]", 9999, "Future step"),
+            "Future step [ ON ]"
+        );
+        assert_eq!(
+            Locale::Ja.localize_step("Set Variable [ $ON ; Value: \"ON\" ]", 141, "Set Variable"),
+            "変数を設定 [ $ON ; Value: \"ON\" ]"
+        );
+    }
+}
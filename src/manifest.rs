@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Error, Result};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Flags;
+use crate::diagnostics::Diagnostic;
+use crate::index::INDEX_FILE_NAME;
+use crate::utils::file_utils::collect_files;
+use crate::utils::path_section;
+use crate::OutputTree;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// List of all files emitted by a run, written as `manifest.json` into the output root
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Manifest {
+    pub generator: String,
+    /// Checksum of the version and the options the files were rendered with; an incremental run
+    /// only reuses rendered files if it is the same
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendering: Option<String>,
+    /// Checksum of the names of each database its scripts were rendered with; an incremental run
+    /// only reuses rendered scripts if it is the same
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub symbols: BTreeMap<String, String>,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ManifestEntry {
+    /// Path relative to the output root, always separated by `/`
+    pub path: String,
+    /// Internal FileMaker id of the element the file was generated from, if any
+    pub id: Option<String>,
+    pub sha256: String,
+    /// What rendering the script reported, if anything, replayed when an incremental run reuses it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<ScriptReport>,
+}
+
+/// Parse warnings and unknown steps of a rendered script
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScriptReport {
+    /// The script the unknown steps are used in, e.g. `Invoices / script 'Print'`
+    pub context: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_steps: Vec<UnknownStep>,
+}
+
+impl ScriptReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty() && self.unknown_steps.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UnknownStep {
+    pub id: u32,
+    pub name: String,
+}
+
+/// The symbol checksums and script reports of a run, collected while rendering for its manifest
+#[derive(Debug, Default)]
+pub struct RenderReports {
+    symbols: Mutex<BTreeMap<String, String>>,
+    scripts: Mutex<HashMap<PathBuf, ScriptReport>>,
+}
+
+impl RenderReports {
+    pub fn record_symbols(&self, db_name: &str, checksum: &str) {
+        self.symbols
+            .lock()
+            .unwrap()
+            .insert(db_name.to_string(), checksum.to_string());
+    }
+
+    /// Keep the report of the script rendered to `output_path`, unless there is nothing to report
+    pub fn record_script(&self, output_path: &Path, report: ScriptReport) {
+        if !report.is_empty() {
+            self.scripts
+                .lock()
+                .unwrap()
+                .insert(output_path.to_path_buf(), report);
+        }
+    }
+}
+
+impl Manifest {
+    /// Build the manifest for all files below the output folders of the given databases
+    pub fn build(root_out_dir: &Path, db_names: &[String], flags: &Flags) -> Result<Self, Error> {
+        let mut file_paths = Vec::new();
+        for db_dir in db_output_dirs(root_out_dir, db_names, flags) {
+            collect_files(&db_dir, &mut file_paths);
+        }
+
+        let mut files = Vec::with_capacity(file_paths.len());
+        for file_path in file_paths {
+            let content = fs::read(&file_path)
+                .with_context(|| format!("Error reading file {}", file_path.display()))?;
+            files.push(ManifestEntry {
+                path: relative_path_string(root_out_dir, &file_path),
+                id: element_id_from_path(&file_path),
+                sha256: sha256_hex(&content),
+                report: None,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            rendering: None,
+            symbols: BTreeMap::new(),
+            files,
+        })
+    }
+
+    /// Add the symbol checksums and script reports of the run that wrote the files
+    pub fn add_reports(&mut self, root_out_dir: &Path, reports: &RenderReports) {
+        self.symbols.extend(reports.symbols.lock().unwrap().clone());
+        let scripts = reports
+            .scripts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(path, report)| (relative_path_string(root_out_dir, path), report.clone()))
+            .collect::<HashMap<_, _>>();
+        for entry in &mut self.files {
+            entry.report = scripts.get(&entry.path).cloned();
+        }
+    }
+
+    /// With `--only`, take over the files of the previous run in the sections this run skipped, so
+    /// an incremental run neither rewrites nor prunes them
+    pub fn keep_unselected_sections(&mut self, previous: &Manifest, flags: &Flags) {
+        if flags.only.is_empty() {
+            return;
+        }
+        let paths = self
+            .files
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect::<HashSet<_>>();
+        self.files.extend(
+            previous
+                .files
+                .iter()
+                .filter(|entry| !paths.contains(&entry.path))
+                .filter(|entry| {
+                    path_section(&entry.path, &flags.output_tree)
+                        .is_none_or(|section| !flags.includes(section))
+                })
+                .cloned(),
+        );
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+        // Scripts of databases this run rendered none of keep the names they were rendered with
+        for (db_name, checksum) in &previous.symbols {
+            self.symbols
+                .entry(db_name.clone())
+                .or_insert_with(|| checksum.clone());
+        }
+    }
+
+    /// Read the manifest of a previous run from the output root, if there is one
+    pub fn read(root_out_dir: &Path) -> Result<Option<Self>, Error> {
+        let manifest_path = root_out_dir.join(MANIFEST_FILE_NAME);
+        if !manifest_path.is_file() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Error reading file {}", manifest_path.display()))?;
+        let manifest = serde_json::from_str(&json)
+            .with_context(|| format!("Error parsing manifest {}", manifest_path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    /// Write the manifest as the last file of a run, under a name of its own and renamed,
+    /// so a manifest is always complete
+    pub fn write(&self, root_out_dir: &Path) -> Result<PathBuf, Error> {
+        let manifest_path = root_out_dir.join(MANIFEST_FILE_NAME);
+        let temp_path = manifest_path.with_extension(format!("{}.tmp", std::process::id()));
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&temp_path, format!("{json}\n"))
+            .with_context(|| format!("Error writing file {}", temp_path.display()))?;
+        fs::rename(&temp_path, &manifest_path)
+            .with_context(|| format!("Error writing file {}", manifest_path.display()))?;
+        Ok(manifest_path)
+    }
+}
+
+/// Remove the manifest and index of a previous run before the first file of a run is written,
+/// so an output interrupted before they are written again has none and can be told apart
+pub fn remove_run_records(root_out_dir: &Path) -> Result<(), Error> {
+    for file_name in [MANIFEST_FILE_NAME, INDEX_FILE_NAME] {
+        let path = root_out_dir.join(file_name);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("Error removing file {}", path.display()))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// All output folders belonging to the given databases, depending on the output tree
+pub fn db_output_dirs(root_out_dir: &Path, db_names: &[String], flags: &Flags) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for db_name in db_names {
+        match flags.output_tree {
+            OutputTree::Db => dirs.push(root_out_dir.join(db_name)),
+            OutputTree::Domain => {
+                if let Ok(entries) = fs::read_dir(root_out_dir) {
+                    for entry in entries.flatten() {
+                        let db_dir = entry.path().join(db_name);
+                        if db_dir.is_dir() {
+                            dirs.push(db_dir);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    dirs
+}
+
+pub fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Extract the internal id from file names like `Script name - ID 12.xml`
+pub fn element_id_from_path(file_path: &Path) -> Option<String> {
+    let file_stem = file_path.file_stem()?.to_str()?;
+    let regex = Regex::new(r"(?:^| - )ID (\d+)$").unwrap();
+    regex
+        .captures(file_stem)
+        .map(|captures| captures[1].to_string())
+}
+
+pub fn relative_path_string(root_out_dir: &Path, file_path: &Path) -> String {
+    file_path
+        .strip_prefix(root_out_dir)
+        .unwrap_or(file_path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_element_id_from_path() {
+        assert_eq!(
+            element_id_from_path(Path::new("scripts/Folder - ID 3/Hello world - ID 12.xml")),
+            Some("12".to_string())
+        );
+        assert_eq!(
+            element_id_from_path(Path::new("accounts/ID 2.xml")),
+            Some("2".to_string())
+        );
+        assert_eq!(element_id_from_path(Path::new("_/metadata.xml")), None);
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_read_missing_manifest() {
+        let root = std::env::temp_dir().join("fm-manifest-read-test-does-not-exist");
+        assert!(Manifest::read(&root).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_build_manifest() {
+        let out_dir = std::env::temp_dir().join("fm_exploder_test_manifest");
+        let _ = fs::remove_dir_all(&out_dir);
+        fs::create_dir_all(out_dir.join("Db").join("scripts")).unwrap();
+        fs::create_dir_all(out_dir.join("Unrelated")).unwrap();
+        fs::write(
+            out_dir.join("Db").join("scripts").join("A - ID 1.xml"),
+            "abc",
+        )
+        .unwrap();
+        fs::write(out_dir.join("Unrelated").join("notes.txt"), "keep").unwrap();
+
+        let manifest = Manifest::build(&out_dir, &["Db".to_string()], &Flags::default()).unwrap();
+        assert_eq!(
+            manifest.files,
+            vec![ManifestEntry {
+                path: "Db/scripts/A - ID 1.xml".to_string(),
+                id: Some("1".to_string()),
+                sha256: sha256_hex(b"abc"),
+                report: None,
+            }]
+        );
+
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
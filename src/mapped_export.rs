@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{Context, Error, Result};
+use memmap2::Mmap;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// An export mapped into memory with `--mmap`, parsed from the mapped bytes instead of buffered
+/// reads of the file. The pages are shared with the page cache, so the symbol table pass and the
+/// main pass read the same memory and peak RSS doesn't grow with the buffers of both passes.
+pub struct MappedExport {
+    mmap: Mmap,
+    /// Length of the byte order mark, skipped like the decoding reader does
+    start: usize,
+}
+
+impl MappedExport {
+    /// Map the export, `None` if it's UTF-16 and has to be transcoded by the decoding reader
+    pub fn open(path: &Path) -> Result<Option<MappedExport>, Error> {
+        let file =
+            File::open(path).with_context(|| format!("Error opening file {}", path.display()))?;
+        // SAFETY: The export must not be modified while it's mapped; an export changed by
+        // another process during the run may be read inconsistently, as with buffered reads
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("Error mapping file {}", path.display()))?;
+        if mmap.starts_with(UTF16_LE_BOM) || mmap.starts_with(UTF16_BE_BOM) {
+            return Ok(None);
+        }
+        let start = match mmap.starts_with(UTF8_BOM) {
+            true => UTF8_BOM.len(),
+            false => 0,
+        };
+        Ok(Some(MappedExport { mmap, start }))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.mmap[self.start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    #[test]
+    fn test_open() {
+        let dir = ScratchDir::create("mmap-test").unwrap();
+        let utf8_path = dir.path.join("utf8.xml");
+        fs::write(&utf8_path, b"\xEF\xBB\xBF<FMSaveAsXML/>").unwrap();
+        let utf16_path = dir.path.join("utf16.xml");
+        fs::write(&utf16_path, b"\xFF\xFE<\x00/\x00>\x00").unwrap();
+
+        let mapped = MappedExport::open(&utf8_path).unwrap().unwrap();
+        assert_eq!(mapped.bytes(), b"<FMSaveAsXML/>");
+        assert!(MappedExport::open(&utf16_path).unwrap().is_none());
+        assert!(MappedExport::open(&dir.path.join("missing.xml")).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Error, Result};
+
+use crate::stats::RunSummary;
+
+/// POST the summary of a run as JSON to the URL of `--notify-webhook`
+pub fn notify_webhook(url: &str, summary: &RunSummary) -> Result<(), Error> {
+    let json = serde_json::to_string(summary)?;
+    ureq::Agent::new_with_defaults()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(json)
+        .with_context(|| format!("Error posting the run summary to {url}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_notify_webhook() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            (request_line, String::from_utf8(body).unwrap())
+        });
+
+        let summary = RunSummary {
+            files: 1,
+            scripts: 12,
+            manifest_sha256: Some("e3b0c442".to_string()),
+            ..Default::default()
+        };
+        notify_webhook(&format!("http://127.0.0.1:{port}/hooks/exports"), &summary).unwrap();
+
+        let (request_line, body) = server.join().unwrap();
+        assert_eq!(request_line.trim(), "POST /hooks/exports HTTP/1.1");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["scripts"], 12);
+        assert_eq!(json["manifest_sha256"], "e3b0c442");
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of the slowest scripts listed by the report
+const SLOWEST_SCRIPTS: usize = 20;
+
+/// Time and bytes of all file writes of the process, read before and after a run by `--profile`
+static WRITE_NANOS: AtomicU64 = AtomicU64::new(0);
+static WRITE_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Write a file, counting the time and bytes for `--profile`
+pub fn timed_write<T>(bytes: usize, write: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = write();
+    WRITE_NANOS.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    WRITE_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    result
+}
+
+/// Timings of a run collected with `--profile`. Times are summed over all exports and threads,
+/// so with several exports in parallel they can exceed the wall time of the run.
+pub struct Profile {
+    sections: Mutex<BTreeMap<String, SectionProfile>>,
+    scripts: Mutex<Vec<ScriptProfile>>,
+    write_nanos_at_start: u64,
+    write_bytes_at_start: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SectionProfile {
+    duration: Duration,
+    /// Bytes of the export read for the section
+    bytes: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct ScriptProfile {
+    db: String,
+    name: String,
+    duration: Duration,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            sections: Mutex::default(),
+            scripts: Mutex::default(),
+            write_nanos_at_start: WRITE_NANOS.load(Ordering::Relaxed),
+            write_bytes_at_start: WRITE_BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Profile {
+    /// Add the time spent on a section like `scripts` and the bytes of the export it spans
+    pub fn record_section(&self, section: &str, duration: Duration, bytes: u64) {
+        let mut sections = self.sections.lock().unwrap();
+        let entry = sections.entry(section.to_string()).or_default();
+        entry.duration += duration;
+        entry.bytes += bytes;
+    }
+
+    /// Record the time spent rendering a script
+    pub fn record_script(&self, db: &str, name: &str, duration: Duration) {
+        self.scripts.lock().unwrap().push(ScriptProfile {
+            db: db.to_string(),
+            name: name.to_string(),
+            duration,
+        });
+    }
+
+    /// The table of sections, the time spent writing files and the slowest scripts
+    pub fn report(&self) -> String {
+        let write_duration =
+            Duration::from_nanos(WRITE_NANOS.load(Ordering::Relaxed) - self.write_nanos_at_start);
+        let write_bytes = WRITE_BYTES.load(Ordering::Relaxed) - self.write_bytes_at_start;
+        let sections = self.sections.lock().unwrap();
+
+        let mut report = String::new();
+        writeln!(report, "Profile:").unwrap();
+        writeln!(report, "  {:<18} {:>10} {:>12}", "Section", "Time", "Bytes").unwrap();
+        for (section, profile) in sections.iter() {
+            writeln!(
+                report,
+                "  {section:<18} {:>7} ms {:>12}",
+                profile.duration.as_millis(),
+                profile.bytes
+            )
+            .unwrap();
+        }
+        writeln!(
+            report,
+            "  {:<18} {:>7} ms {:>12}  (written, included in the sections)",
+            "writing",
+            write_duration.as_millis(),
+            write_bytes
+        )
+        .unwrap();
+
+        let mut scripts = self.scripts.lock().unwrap();
+        scripts.sort_by_key(|script| std::cmp::Reverse(script.duration));
+        if !scripts.is_empty() {
+            writeln!(report, "Slowest scripts:").unwrap();
+        }
+        for script in scripts.iter().take(SLOWEST_SCRIPTS) {
+            writeln!(
+                report,
+                "  {:>7.2} ms  {} / {}",
+                script.duration.as_secs_f64() * 1000.0,
+                script.db,
+                script.name
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let profile = Profile::default();
+        profile.record_section("scripts", Duration::from_millis(40), 1000);
+        profile.record_section("scripts", Duration::from_millis(2), 24);
+        profile.record_section("layouts", Duration::from_millis(7), 512);
+        for index in 0..25 {
+            profile.record_script(
+                "Invoices",
+                &format!("Script {index}"),
+                Duration::from_millis(index),
+            );
+        }
+
+        let report = profile.report();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[2], "  layouts                  7 ms          512");
+        assert_eq!(lines[3], "  scripts                 42 ms         1024");
+        assert!(lines[4].starts_with("  writing "));
+        assert_eq!(lines[5], "Slowest scripts:");
+        assert_eq!(lines[6], "    24.00 ms  Invoices / Script 24");
+        assert_eq!(lines.len(), 6 + SLOWEST_SCRIPTS);
+    }
+}
@@ -0,0 +1,43 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+const BAR_TEMPLATE: &str = "{prefix:.bold} [{bar:30}] {bytes}/{total_bytes} (ETA {eta}) {msg}";
+
+/// Progress reporting for a run; one bar per input file, or nothing at all in quiet mode
+#[derive(Default)]
+pub struct Progress {
+    multi: Option<MultiProgress>,
+}
+
+impl Progress {
+    pub fn new(quiet: bool) -> Self {
+        Self {
+            multi: (!quiet).then(MultiProgress::new),
+        }
+    }
+
+    /// Create a progress bar tracking the bytes read from an input file
+    pub fn file_bar(&self, file_name: &str, file_len: u64) -> ProgressBar {
+        let Some(multi) = &self.multi else {
+            return ProgressBar::hidden();
+        };
+        let bar = multi.add(ProgressBar::new(file_len));
+        bar.set_style(
+            ProgressStyle::with_template(BAR_TEMPLATE)
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(file_name.to_string());
+        bar
+    }
+
+    /// Print a line without tearing the progress bars; suppressed in quiet mode
+    pub fn println(&self, message: impl AsRef<str>) {
+        match &self.multi {
+            None => {}
+            Some(multi) if multi.is_hidden() => println!("{}", message.as_ref()),
+            Some(multi) => {
+                let _ = multi.println(message);
+            }
+        }
+    }
+}
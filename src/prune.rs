@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+use anyhow::{Context, Error, Result};
+
+use crate::manifest::Manifest;
+
+/// Delete files listed in the previous manifest that the current run no longer generated
+/// Files unknown to the previous manifest are never touched; folders left empty are removed
+/// Paths that are not relative or lead out of the output folder are ignored
+/// Returns the relative paths of the deleted files
+pub fn prune_stale_files(
+    root_out_dir: &Path,
+    previous: &Manifest,
+    current: &Manifest,
+) -> Result<Vec<String>, Error> {
+    let current_paths = current
+        .files
+        .iter()
+        .map(|entry| entry.path.as_str())
+        .collect::<HashSet<_>>();
+
+    let mut pruned = Vec::new();
+    for entry in &previous.files {
+        if current_paths.contains(entry.path.as_str()) {
+            continue;
+        }
+        if !is_relative_path(&entry.path) {
+            continue;
+        }
+        let file_path = root_out_dir.join(&entry.path);
+        if !file_path.is_file() || !is_inside(root_out_dir, &file_path) {
+            continue;
+        }
+        fs::remove_file(&file_path)
+            .with_context(|| format!("Error deleting file {}", file_path.display()))?;
+        remove_empty_parents(root_out_dir, &file_path);
+        pruned.push(entry.path.clone());
+    }
+    Ok(pruned)
+}
+
+/// Whether the manifest path is relative and without `.` or `..` segments
+fn is_relative_path(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Whether the file is in the output folder, also after following symbolic links
+fn is_inside(root_out_dir: &Path, file_path: &Path) -> bool {
+    match (root_out_dir.canonicalize(), file_path.canonicalize()) {
+        (Ok(root), Ok(file)) => file.starts_with(root),
+        _ => false,
+    }
+}
+
+fn remove_empty_parents(root_out_dir: &Path, file_path: &Path) {
+    let mut dir = file_path.parent();
+    while let Some(dir_path) = dir {
+        if dir_path == root_out_dir || !dir_path.starts_with(root_out_dir) {
+            break;
+        }
+        // Fails for non-empty folders, which ends the walk
+        if fs::remove_dir(dir_path).is_err() {
+            break;
+        }
+        dir = dir_path.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::ManifestEntry;
+
+    fn manifest(paths: &[&str]) -> Manifest {
+        Manifest {
+            generator: String::new(),
+            rendering: None,
+            symbols: Default::default(),
+            files: paths
+                .iter()
+                .map(|path| ManifestEntry {
+                    path: path.to_string(),
+                    id: None,
+                    sha256: String::new(),
+                    report: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_prune_stale_files() {
+        let root = std::env::temp_dir().join(format!("fm-prune-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("old/scripts")).unwrap();
+        fs::create_dir_all(root.join("db/scripts")).unwrap();
+        fs::write(root.join("old/scripts/gone.txt"), "").unwrap();
+        fs::write(root.join("db/scripts/kept.txt"), "").unwrap();
+        fs::write(root.join("db/scripts/notes.md"), "").unwrap();
+
+        let previous = manifest(&["db/scripts/kept.txt", "old/scripts/gone.txt"]);
+        let current = manifest(&["db/scripts/kept.txt"]);
+        let pruned = prune_stale_files(&root, &previous, &current).unwrap();
+
+        assert_eq!(pruned, vec!["old/scripts/gone.txt".to_string()]);
+        assert!(!root.join("old").exists());
+        assert!(root.join("db/scripts/kept.txt").exists());
+        assert!(root.join("db/scripts/notes.md").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_prune_ignores_paths_outside_output() {
+        let base =
+            std::env::temp_dir().join(format!("fm-prune-outside-test-{}", std::process::id()));
+        let root = base.join("out");
+        fs::create_dir_all(&root).unwrap();
+        let outside = base.join("outside.txt");
+        fs::write(&outside, "").unwrap();
+
+        let previous = manifest(&["../outside.txt", outside.to_str().unwrap()]);
+        let pruned = prune_stale_files(&root, &previous, &manifest(&[])).unwrap();
+
+        assert!(pruned.is_empty());
+        assert!(outside.exists());
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
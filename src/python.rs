@@ -0,0 +1,48 @@
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::api;
+
+/// Explode an XML export given as bytes, with options of the command line, e.g. `["--lossless"]`.
+/// Returns the content of the written files by their path relative to the output root.
+#[pyfunction]
+#[pyo3(signature = (xml, options = Vec::new()))]
+fn explode_bytes<'py>(
+    py: Python<'py>,
+    xml: &[u8],
+    options: Vec<String>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let files = py
+        .detach(|| api::explode_bytes(xml, &options))
+        .map_err(runtime_error)?;
+    let dict = PyDict::new(py);
+    for (path, content) in files {
+        dict.set_item(path, PyBytes::new(py, &content))?;
+    }
+    Ok(dict)
+}
+
+/// The script steps of an XML export given as bytes, as dicts like the records of `--format jsonl`,
+/// with the typed parameters of each step
+#[pyfunction]
+#[pyo3(signature = (xml, options = Vec::new()))]
+fn steps<'py>(py: Python<'py>, xml: &[u8], options: Vec<String>) -> PyResult<Bound<'py, PyAny>> {
+    let steps = py
+        .detach(|| api::steps(xml, &options))
+        .map_err(runtime_error)?;
+    let json = serde_json::to_string(&steps).map_err(runtime_error)?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+fn runtime_error(err: impl Into<anyhow::Error>) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", err.into()))
+}
+
+#[pymodule]
+fn fm_xml_export_exploder(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(explode_bytes, module)?)?;
+    module.add_function(wrap_pyfunction!(steps, module)?)?;
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
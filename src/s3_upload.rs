@@ -0,0 +1,380 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use hmac::{Hmac, KeyInit, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rayon::prelude::*;
+use sha2::Sha256;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::manifest::{relative_path_string, sha256_hex};
+
+/// Characters S3 expects percent-encoded in a key, all but the unreserved ones of RFC 3986
+const KEY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Payload hash of a signed request whose body isn't hashed, so files can be streamed
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Where `--upload` puts the output, parsed from `s3://bucket/prefix`
+#[derive(Debug, PartialEq)]
+pub struct UploadTarget {
+    pub bucket: String,
+    /// Prepended to the path of each file, without leading or trailing `/`
+    pub prefix: String,
+}
+
+impl UploadTarget {
+    pub fn parse(url: &str) -> Result<Self, Error> {
+        let Some(rest) = url.strip_prefix("s3://") else {
+            bail!("--upload expects an s3://bucket/prefix URL, got '{url}'");
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            bail!("--upload URL '{url}' has no bucket");
+        }
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        })
+    }
+
+    fn key(&self, relative_path: &str) -> String {
+        match self.prefix.is_empty() {
+            true => relative_path.to_string(),
+            false => format!("{}/{relative_path}", self.prefix),
+        }
+    }
+}
+
+/// Credentials and endpoint of the S3 API, read from the usual AWS environment variables
+pub struct S3Config {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+    /// Endpoint of S3-compatible storage, e.g. `http://localhost:9000` of MinIO; addressed
+    /// path-style as `<endpoint>/<bucket>/<key>`. Without it AWS is addressed virtual-hosted.
+    pub endpoint: Option<String>,
+}
+
+impl S3Config {
+    pub fn from_env(endpoint: Option<String>) -> Result<Self, Error> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")
+                .context("--upload requires AWS_ACCESS_KEY_ID to be set")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")
+                .context("--upload requires AWS_SECRET_ACCESS_KEY to be set")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+            region: var("AWS_REGION")
+                .or_else(|| var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            endpoint: endpoint.or_else(|| var("AWS_ENDPOINT_URL")),
+        })
+    }
+
+    /// The URL of an object and the host it's signed for
+    fn object_url(&self, bucket: &str, key: &str) -> Result<(String, String), Error> {
+        let encoded_key = key
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, KEY_ENCODE_SET).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        match &self.endpoint {
+            Some(endpoint) => {
+                let endpoint = endpoint.trim_end_matches('/');
+                let host = endpoint
+                    .split_once("://")
+                    .map(|(_, host)| host)
+                    .filter(|host| !host.is_empty() && !host.contains('/'))
+                    .ok_or_else(|| anyhow!("Invalid S3 endpoint '{endpoint}'"))?;
+                Ok((
+                    format!("{endpoint}/{bucket}/{encoded_key}"),
+                    host.to_string(),
+                ))
+            }
+            None => {
+                let host = format!("{bucket}.s3.{}.amazonaws.com", self.region);
+                Ok((format!("https://{host}/{encoded_key}"), host))
+            }
+        }
+    }
+}
+
+/// Upload the files with their paths relative to `root` as keys below the prefix, in parallel.
+/// Returns the number of uploaded files.
+pub fn upload_files(
+    root: &Path,
+    file_paths: &[PathBuf],
+    target: &UploadTarget,
+    config: &S3Config,
+) -> Result<usize, Error> {
+    let agent = ureq::Agent::new_with_defaults();
+    file_paths.par_iter().try_for_each(|file_path| {
+        let key = target.key(&relative_path_string(root, file_path));
+        put_object(&agent, config, &target.bucket, &key, file_path).with_context(|| {
+            format!(
+                "Error uploading {} to s3://{}/{key}",
+                file_path.display(),
+                target.bucket
+            )
+        })
+    })?;
+    Ok(file_paths.len())
+}
+
+fn put_object(
+    agent: &ureq::Agent,
+    config: &S3Config,
+    bucket: &str,
+    key: &str,
+    file_path: &Path,
+) -> Result<(), Error> {
+    let (url, host) = config.object_url(bucket, key)?;
+    let path = url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|index| &rest[index..]))
+        .unwrap_or("/");
+    let amz_date = amz_date(SystemTime::now());
+    let mut headers = vec![
+        ("host", host),
+        ("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &config.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let authorization = authorization(config, "PUT", path, &headers, &amz_date);
+
+    let file = File::open(file_path)
+        .with_context(|| format!("Error opening file {}", file_path.display()))?;
+    let mut request = agent.put(&url).header("Authorization", authorization);
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.header(*name, value);
+    }
+    request.send(file)?;
+    Ok(())
+}
+
+/// The `Authorization` header of AWS Signature Version 4 for a request without query string;
+/// `headers` must be sorted by name and include `host`
+fn authorization(
+    config: &S3Config,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    amz_date: &str,
+) -> String {
+    let date = &amz_date[..8];
+    let scope = format!("{date}/{}/s3/aws4_request", config.region);
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let canonical_request =
+        format!("{method}\n{path}\n\n{canonical_headers}\n{signed_headers}\n{UNSIGNED_PAYLOAD}");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let key = signing_key(&config.secret_access_key, date, &config.region, "s3");
+    let signature: String = hmac_sha256(&key, string_to_sign.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        config.access_key_id
+    )
+}
+
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(
+        format!("AWS4{secret_access_key}").as_bytes(),
+        date.as_bytes(),
+    );
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The time as `YYYYMMDD'T'HHMMSS'Z'` in UTC, e.g. `20130524T000000Z`
+fn amz_date(time: SystemTime) -> String {
+    OffsetDateTime::from(time)
+        .format(format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn test_parse_upload_target() {
+        assert_eq!(
+            UploadTarget::parse("s3://exports/fm/invoices/").unwrap(),
+            UploadTarget {
+                bucket: "exports".to_string(),
+                prefix: "fm/invoices".to_string(),
+            }
+        );
+        assert_eq!(UploadTarget::parse("s3://exports").unwrap().prefix, "");
+        assert!(UploadTarget::parse("https://exports").is_err());
+        assert!(UploadTarget::parse("s3:///prefix").is_err());
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example of the AWS documentation on deriving a signing key
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+
+    #[test]
+    fn test_amz_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_369_353_600);
+        assert_eq!(amz_date(time), "20130524T000000Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(amz_date(time), "20240229T123456Z");
+    }
+
+    #[test]
+    fn test_object_url() {
+        let mut config = S3Config {
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            session_token: None,
+            region: "eu-central-1".to_string(),
+            endpoint: None,
+        };
+        assert_eq!(
+            config
+                .object_url("exports", "fm/Invoices/scripts/Print & send.txt")
+                .unwrap(),
+            (
+                "https://exports.s3.eu-central-1.amazonaws.com/fm/Invoices/scripts/Print%20%26%20send.txt".to_string(),
+                "exports.s3.eu-central-1.amazonaws.com".to_string()
+            )
+        );
+        config.endpoint = Some("http://localhost:9000/".to_string());
+        assert_eq!(
+            config.object_url("exports", "a.txt").unwrap(),
+            (
+                "http://localhost:9000/exports/a.txt".to_string(),
+                "localhost:9000".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_upload_files() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let (mut authorization, mut content_length) = (String::new(), 0);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    match name.to_ascii_lowercase().as_str() {
+                        "authorization" => authorization = value.trim().to_string(),
+                        "content-length" => content_length = value.trim().parse().unwrap(),
+                        _ => {}
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            (
+                request_line,
+                authorization,
+                String::from_utf8(body).unwrap(),
+            )
+        });
+
+        let dir = ScratchDir::create("upload-test").unwrap();
+        let file_path = dir.path.join("Invoices").join("scripts").join("Print.txt");
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, "Go to Layout [ \"Invoices\" ]").unwrap();
+        let config = S3Config {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+            region: "us-east-1".to_string(),
+            endpoint: Some(format!("http://127.0.0.1:{port}")),
+        };
+        let target = UploadTarget::parse("s3://exports/fm").unwrap();
+        let count = upload_files(&dir.path, &[file_path], &target, &config).unwrap();
+
+        let (request_line, authorization, body) = server.join().unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            request_line.trim(),
+            "PUT /exports/fm/Invoices/scripts/Print.txt HTTP/1.1"
+        );
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+        assert_eq!(body, "Go to Layout [ \"Invoices\" ]");
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::{Error, Result};
+use schemars::{schema_for, Schema};
+
+use crate::exit_status::ExitStatus;
+use crate::index::Index;
+use crate::jsonl::StepRecord;
+use crate::manifest::Manifest;
+use crate::stats::RunSummary;
+use crate::SchemaDocument;
+
+/// Run the `schema` subcommand: print the JSON Schema of one of the JSON outputs
+pub fn run(document: SchemaDocument) -> Result<ExitStatus, Error> {
+    println!("{}", serde_json::to_string_pretty(&schema(document))?);
+    Ok(ExitStatus::Clean)
+}
+
+fn schema(document: SchemaDocument) -> Schema {
+    let mut schema = match document {
+        SchemaDocument::Jsonl => schema_for!(StepRecord),
+        SchemaDocument::Manifest => schema_for!(Manifest),
+        SchemaDocument::Index => schema_for!(Index),
+        SchemaDocument::Summary => schema_for!(RunSummary),
+    };
+    schema.insert(
+        "$comment".to_string(),
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")).into(),
+    );
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let schema = schema(SchemaDocument::Manifest).to_value();
+        assert_eq!(schema["title"], "Manifest");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["generator", "files"])
+        );
+        let entry = &schema["$defs"]["ManifestEntry"];
+        assert_eq!(entry["properties"]["sha256"]["type"], "string");
+        assert_eq!(
+            entry["properties"]["path"]["description"],
+            "Path relative to the output root, always separated by `/`"
+        );
+    }
+
+    #[test]
+    fn test_schema_jsonl() {
+        let schema = schema(SchemaDocument::Jsonl).to_value();
+        assert_eq!(schema["title"], "StepRecord");
+        assert_eq!(schema["properties"]["enabled"]["type"], "boolean");
+    }
+}
@@ -1,132 +1,497 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::sync_channel;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
 
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
+use rayon::prelude::*;
 
+use crate::analysis::model::StepModel;
+use crate::calc_formatter::{collect_calc_comments, with_calc_style};
 use crate::config::Flags;
+use crate::dependencies::Dependencies;
+use crate::diagnostics::{line_column_at, Diagnostics};
+use crate::jsonl::{JsonlSink, StepRecord};
+use crate::manifest::{ScriptReport, UnknownStep};
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
-use crate::script_steps::sanitizer::sanitize;
+use crate::script_steps::parameters::boolean::{with_bool_locale, with_bool_style};
+use crate::script_steps::parameters::field_reference::with_field_names;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::sanitizer::{parse, render};
+use crate::script_steps::step::Step;
+use crate::symbols::{with_symbols, SymbolKind, SymbolTable};
 use crate::utils::attributes::get_attribute;
 use crate::utils::write_text_file;
 use crate::utils::xml_utils::{
     cdata_element_to_string, end_element_to_string, general_ref_to_string, local_name_to_string,
-    start_element_to_string, text_element_to_string,
+    pretty_print_xml, start_element_to_string, text_element_to_string, unescape_entities,
 };
+use crate::xml_processor::RunContext;
+
+/// Last line of a sanitized script that is incomplete in the export
+const TRUNCATED_MARKER: &str = "🚨🚨🚨 <TRUNCATED EXPORT> 🚨🚨🚨";
 
 #[derive(Debug, Default)]
 struct ScriptInfo {
     id: String,
     name: String,
-    text: String,
+    steps: Vec<RenderedStep>,
+    diagnostics: Diagnostics,
+    /// The script XML ended before the script was complete, e.g. of an interrupted export
+    truncated: bool,
+    /// Collected with `--dependencies` only
+    dependencies: Dependencies,
+}
+
+/// A sanitized script step, the common input of the native renderer, templates and JSON Lines output
+#[derive(Debug, Default)]
+pub struct RenderedStep {
+    pub index: usize,
+    pub id: u32,
+    pub name: String,
+    pub enabled: bool,
+    pub is_comment: bool,
+    pub indent_level: usize,
+    /// Lines of the sanitized step; empty if the step could not be parsed
+    pub lines: Vec<String>,
+    /// `/* */` and `//` comments of the calculations of the step, verbatim
+    pub calc_comments: Vec<String>,
+    /// Typed parameters of the step, read for the JSON Lines output only
+    pub parameters: Vec<Parameter>,
+    /// Rendered without a parse warning, so nothing of the step fell back to a placeholder
+    pub is_understood: bool,
+}
+
+impl RenderedStep {
+    /// Indentation of the first line
+    pub fn indent(&self) -> String {
+        "\t".repeat(self.indent_level)
+    }
+
+    /// Indentation of all following lines, e.g. of multi-line comments or calculations
+    pub fn continuation_indent(&self) -> String {
+        match self.is_comment {
+            true => format!("{}  ", self.indent()),
+            false => "\t".repeat(self.indent_level + 4),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+/// Render the steps of a script in the default text format
+/// With `step_numbers` each step is prefixed with its right-aligned step number
+pub fn render_script_text(steps: &[RenderedStep], step_numbers: bool) -> String {
+    let width = steps.len().to_string().len();
+    let mut text = String::new();
+    for step in steps {
+        for (i, line) in step.lines.iter().enumerate() {
+            if step_numbers {
+                match i {
+                    0 => text.push_str(&format!("{:>width$} ", step.index)),
+                    _ => text.push_str(&" ".repeat(width + 1)),
+                }
+            }
+            let indent = match i {
+                0 => step.indent(),
+                _ => step.continuation_indent(),
+            };
+            text.push_str(&format!("{indent}{line}\n"));
+        }
+    }
+    text
+}
+
+/// Limits of the raw XML appended by `--debug-unknown`
+const DEBUG_XML_MAX_LINES: usize = 40;
+const DEBUG_XML_MAX_LINE_LENGTH: usize = 200;
+
+/// One line per calculation comment with the number and name of its step,
+/// e.g. `12 If: // find mode`; lines of multi-line comments are indented by a tab
+pub fn render_calc_comments(steps: &[RenderedStep]) -> String {
+    let mut text = String::new();
+    for step in steps {
+        for comment in &step.calc_comments {
+            text.push_str(&format!("{} {}: ", step.index, step.name));
+            text.push_str(&comment.replace('\r', "\n\t"));
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Pretty-print the raw XML of a step for `--debug-unknown`, truncated to keep the script readable
+fn debug_xml_lines(step_xml: &str) -> Vec<String> {
+    let pretty = pretty_print_xml(step_xml);
+    let all_lines = pretty.split(['\n', '\r']).collect::<Vec<_>>();
+    let mut lines = all_lines
+        .iter()
+        .take(DEBUG_XML_MAX_LINES)
+        .map(
+            |line| match line.char_indices().nth(DEBUG_XML_MAX_LINE_LENGTH) {
+                Some((end, _)) => format!("| {}…", &line[..end]),
+                None => format!("| {line}"),
+            },
+        )
+        .collect::<Vec<_>>();
+    if all_lines.len() > DEBUG_XML_MAX_LINES {
+        lines.push(format!(
+            "| … {} more lines",
+            all_lines.len() - DEBUG_XML_MAX_LINES
+        ));
+    }
+    lines
 }
 
 #[derive(Debug, Default)]
 struct ScriptStepInfo {
+    index: usize,
     id: u32,
+    name: String,
+    enabled: bool,
     content: String,
+    /// Byte offset of the step in the script's XML
+    offset: u64,
     indent_level_current: usize,
     indent_level_next: usize,
 }
 
+/// The scripts of one export to sanitize
+pub struct ScriptExport<'a> {
+    pub db_name: &'a str,
+    /// The exploded script XML files
+    pub xml_out_dir_path: &'a Path,
+    pub text_out_dir_path: &'a Path,
+    /// Where to list the calculation comments of each script, if requested
+    pub calc_comments_out_dir_path: Option<&'a Path>,
+    /// Catalogs of the export to resolve references without a name
+    pub symbols: Option<Arc<SymbolTable>>,
+    /// Checksum of the names of `symbols`, see [`SymbolTable::checksum`]
+    pub symbols_checksum: String,
+}
+
+/// Rendered scripts waiting for the writer stage; the renderers block while it's full, so a slow
+/// disk can't let rendered scripts pile up in memory
+const WRITE_QUEUE_CAPACITY: usize = 64;
+
+/// The files of a rendered script, passed from the render stage to the writer stage
+struct RenderedScript {
+    text_file_path: PathBuf,
+    text: String,
+    calc_comments: Option<(PathBuf, String)>,
+    /// Encoded records of `--format jsonl`
+    jsonl_lines: Option<Vec<u8>>,
+}
+
 /// Process all XML files in the script_steps directory and create sanitized text versions
-/// This function mirrors the folder structure of the XML files
-pub fn create_sanitized_scripts(
-    scripts_xml_out_dir_path: &Path,
-    scripts_text_out_dir_path: &Path,
-    flags: &Flags,
-) {
-    // Recursively process all XML files in the script_steps directory
-    process_directory_recursively(
-        scripts_xml_out_dir_path,
-        scripts_xml_out_dir_path,
-        scripts_text_out_dir_path,
-        flags,
-    );
+/// This function mirrors the folder structure of the XML files.
+/// Scripts are rendered in parallel and written by one writer thread, connected by a bounded queue.
+pub fn create_sanitized_scripts(export: &ScriptExport, flags: &Flags, run: &RunContext) {
+    run.reports
+        .record_symbols(export.db_name, &export.symbols_checksum);
+    let mut xml_file_paths = Vec::new();
+    collect_xml_files(export.xml_out_dir_path, &mut xml_file_paths);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = sync_channel::<RenderedScript>(WRITE_QUEUE_CAPACITY);
+        scope.spawn(move || {
+            for rendered in receiver {
+                write_rendered_script(&rendered, flags, run);
+            }
+        });
+        xml_file_paths
+            .par_iter()
+            .for_each_with(sender, |sender, xml_file_path| {
+                if let Some(rendered) = render_script_xml_file(xml_file_path, export, flags, run) {
+                    // Only fails if the writer panicked, which the scope propagates
+                    let _ = sender.send(rendered);
+                }
+            });
+    });
 }
 
-fn process_directory_recursively(
-    current_dir: &Path,
-    scripts_xml_out_dir_path: &Path,
-    scripts_text_out_dir_path: &Path,
-    flags: &Flags,
-) {
+fn collect_xml_files(current_dir: &Path, xml_file_paths: &mut Vec<PathBuf>) {
     if let Ok(entries) = fs::read_dir(current_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("xml") {
-                process_script_xml_file(
-                    &path,
-                    scripts_xml_out_dir_path,
-                    scripts_text_out_dir_path,
-                    flags,
-                );
+                xml_file_paths.push(path);
             } else if path.is_dir() {
                 // Recursively process subdirectories
-                process_directory_recursively(
-                    &path,
-                    scripts_xml_out_dir_path,
-                    scripts_text_out_dir_path,
-                    flags,
-                );
+                collect_xml_files(&path, xml_file_paths);
             }
         }
     }
 }
 
-fn process_script_xml_file(
+/// The writer stage
+fn write_rendered_script(rendered: &RenderedScript, flags: &Flags, run: &RunContext) {
+    // Ensure the output directory exists
+    if let Some(parent) = rendered.text_file_path.parent() {
+        fs::create_dir_all(parent)
+            .unwrap_or_else(|err| panic!("Error creating directory {}: {}", parent.display(), err));
+    }
+    let result = write_text_file(
+        &rendered.text_file_path,
+        &rendered.text,
+        flags,
+        run.writer.as_ref(),
+    );
+    run.stats.record_write_result(result);
+
+    if let Some((calc_comments_file_path, calc_comments)) = &rendered.calc_comments {
+        if let Some(parent) = calc_comments_file_path.parent() {
+            fs::create_dir_all(parent).unwrap_or_else(|err| {
+                panic!("Error creating directory {}: {}", parent.display(), err)
+            });
+        }
+        let result = write_text_file(
+            calc_comments_file_path,
+            calc_comments,
+            flags,
+            run.writer.as_ref(),
+        );
+        run.stats.record_write_result(result);
+    }
+
+    if let (Some(jsonl), Some(lines)) = (&run.jsonl, &rendered.jsonl_lines) {
+        if let Err(err) = jsonl.write(lines) {
+            eprintln!("Error writing JSON Lines output: {err}");
+        }
+    }
+}
+
+/// The render stage; `None` if the script can't be read or is reused from an incremental run
+fn render_script_xml_file(
     xml_file_path: &Path,
-    scripts_xml_out_dir_path: &Path,
-    scripts_text_out_dir_path: &Path,
+    export: &ScriptExport,
     flags: &Flags,
-) {
+    run: &RunContext,
+) -> Option<RenderedScript> {
+    let db_name = export.db_name;
     // Read the XML file content
     let xml_content = match fs::read_to_string(xml_file_path) {
         Ok(content) => content,
         Err(e) => {
             eprintln!("Error reading file {}: {}", xml_file_path.display(), e);
-            return;
+            return None;
         }
     };
 
+    // Determine the relative path from the XML file to maintain folder structure
+    let relative_path = xml_file_path
+        .strip_prefix(export.xml_out_dir_path)
+        .unwrap_or(xml_file_path);
+    // Change extension to .txt
+    let text_file_path = export
+        .text_out_dir_path
+        .join(relative_path)
+        .with_extension("txt");
+
+    // Skip rendering if the script is unchanged since the previous incremental run
+    if let Some(incremental) = run
+        .incremental
+        .as_ref()
+        .filter(|_| export.calc_comments_out_dir_path.is_none())
+    {
+        if let Some(report) = incremental.reuse_rendered(
+            xml_file_path,
+            &xml_content,
+            &text_file_path,
+            db_name,
+            &export.symbols_checksum,
+        ) {
+            record_script_report(&text_file_path, report, run);
+            return None;
+        }
+    }
+
     // Parse the script and create sanitized text
-    let script_info = parse_script_xml(&xml_content, flags);
-    if let Some(script_info) = script_info {
-        // Determine the relative path from the XML file to maintain folder structure
-        let relative_path = xml_file_path
-            .strip_prefix(scripts_xml_out_dir_path)
-            .unwrap_or(xml_file_path);
-        let output_file_path = scripts_text_out_dir_path.join(relative_path);
-
-        // Ensure the output directory exists
-        if let Some(parent) = output_file_path.parent() {
-            fs::create_dir_all(parent).unwrap_or_else(|err| {
-                panic!("Error creating directory {}: {}", parent.display(), err)
-            });
+    let start = Instant::now();
+    let mut script_info = with_symbols(export.symbols.clone(), || {
+        parse_script_xml(&xml_content, flags, run.jsonl.is_some())
+    })?;
+    let mut diagnostics = std::mem::take(&mut script_info.diagnostics);
+    if script_info.truncated {
+        diagnostics.warn(
+            xml_content.len() as u64,
+            "Script is incomplete, the export ended in the middle of it",
+        );
+    }
+    let file_name = xml_file_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    for diagnostic in &mut diagnostics.entries {
+        if let Ok((line, column)) = line_column_at(xml_content.as_bytes(), diagnostic.offset) {
+            diagnostic.location = Some(format!("{file_name}:{line}:{column}"));
         }
+    }
+    let mut report = ScriptReport {
+        context: format!("{db_name} / script '{}'", script_info.name),
+        ..Default::default()
+    };
+    let mut warnings = Diagnostics::default();
+    warnings.append(&report.context, diagnostics);
+    report.warnings = warnings.entries;
+    report.unknown_steps = script_info
+        .steps
+        .iter()
+        .filter(|step| !step.is_understood)
+        .map(|step| UnknownStep {
+            id: step.id,
+            name: step.name.clone(),
+        })
+        .collect();
+    record_script_report(&text_file_path, report, run);
 
-        // Change extension to .txt
-        let output_file_path = output_file_path.with_extension("txt");
-        write_text_file(&output_file_path, &script_info.text);
+    let text = match &run.template {
+        Some(template) => template
+            .render(
+                db_name,
+                &script_info.id,
+                &script_info.name,
+                export.symbols.as_ref().and_then(|symbols| {
+                    symbols.symbol(SymbolKind::Script, script_info.id.parse().ok()?)
+                }),
+                &script_info.steps,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!(
+                    "Error rendering template for script '{}': {err}",
+                    script_info.name
+                );
+                render_script_text(&script_info.steps, flags.step_numbers)
+            }),
+        None => render_script_text(&script_info.steps, flags.step_numbers),
+    };
+    let text = format!("{text}{}", script_info.dependencies.render());
+    let text = match script_info.truncated {
+        true => format!("{text}{TRUNCATED_MARKER}"),
+        false => text,
+    };
+
+    let calc_comments = export
+        .calc_comments_out_dir_path
+        .map(|calc_comments_out_dir_path| {
+            (
+                calc_comments_out_dir_path.join(relative_path.with_extension("txt")),
+                render_calc_comments(&script_info.steps),
+            )
+        })
+        .filter(|(_, calc_comments)| !calc_comments.is_empty());
+
+    let jsonl_lines = match &run.jsonl {
+        Some(_) => {
+            let texts = script_info
+                .steps
+                .iter()
+                .map(RenderedStep::text)
+                .collect::<Vec<_>>();
+            let records = script_info
+                .steps
+                .iter()
+                .zip(&texts)
+                .map(|(step, text)| StepRecord {
+                    db: db_name,
+                    script_id: &script_info.id,
+                    script: &script_info.name,
+                    index: step.index,
+                    step_id: step.id,
+                    step: &step.name,
+                    enabled: step.enabled,
+                    text,
+                    parameters: &step.parameters,
+                })
+                .collect::<Vec<_>>();
+            match JsonlSink::encode(&records) {
+                Ok(lines) => Some(lines),
+                Err(err) => {
+                    eprintln!("Error writing JSON Lines output: {err}");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    if let Some(profile) = &run.profile {
+        profile.record_script(db_name, &script_info.name, start.elapsed());
+    }
+    Some(RenderedScript {
+        text_file_path,
+        text,
+        calc_comments,
+        jsonl_lines,
+    })
+}
+
+/// Count the parse warnings and unknown steps of a script, rendered or reused from the previous run,
+/// and keep them for the manifest
+fn record_script_report(text_file_path: &Path, report: ScriptReport, run: &RunContext) {
+    for warning in &report.warnings {
+        run.stats.record_parse_warning();
+        run.diagnostics.push(warning.clone());
     }
+    for step in &report.unknown_steps {
+        run.stats
+            .record_unknown_step(step.id, &step.name, &report.context);
+    }
+    run.reports.record_script(text_file_path, report);
 }
 
-fn parse_script_xml(xml_content: &str, flags: &Flags) -> Option<ScriptInfo> {
+/// Parse a step once and render it with the rendering options of `flags`,
+/// along with the comments of its calculations
+fn sanitize_step(
+    step_id: &u32,
+    step_xml: &str,
+    flags: &Flags,
+    diagnostics: &mut Diagnostics,
+) -> ((Option<Step>, Option<String>), Vec<String>) {
+    collect_calc_comments(|| {
+        with_calc_style(flags.calc_style(), || {
+            with_field_names(flags.field_names, || {
+                with_bool_style(flags.bool_style, || {
+                    with_bool_locale(flags.locale, || {
+                        let step = parse(step_id, step_xml, diagnostics);
+                        let sanitized = step.as_ref().and_then(|step| render(step, diagnostics));
+                        (step, sanitized)
+                    })
+                })
+            })
+        })
+    })
+}
+
+/// Sanitize the steps of a script; `typed_steps` also keeps the typed parameters of each step
+fn parse_script_xml(xml_content: &str, flags: &Flags, typed_steps: bool) -> Option<ScriptInfo> {
     let mut script_info = ScriptInfo::default();
     let mut in_step = false;
     let mut step_info = ScriptStepInfo::default();
+    let mut step_diagnostics = Diagnostics::default();
 
     let mut reader = Reader::from_str(xml_content);
     let mut buf = Vec::new();
     let mut depth = 0;
 
     loop {
+        let event_offset = reader.buffer_position();
         match reader.read_event_into(&mut buf) {
             Err(e) => {
-                println!("Error parsing XML: {e}");
+                script_info
+                    .diagnostics
+                    .warn(reader.error_position(), format!("Error parsing XML: {e}"));
+                break;
+            }
+            Ok(Event::Eof) => {
+                script_info.truncated = depth > 0;
                 break;
             }
-            Ok(Event::Eof) => break,
             Ok(Event::Start(e)) => {
                 depth += 1;
 
@@ -134,7 +499,7 @@ fn parse_script_xml(xml_content: &str, flags: &Flags) -> Option<ScriptInfo> {
                     // Script element - we'll get ID and name from ScriptReference
                 } else if depth == 2 && e.name().as_ref() == b"ScriptReference" {
                     // Extract script ID and name from ScriptReference
-                    for attr in crate::utils::attributes::get_attributes(&e).unwrap() {
+                    for attr in crate::utils::attributes::get_attributes(&e).unwrap_or_default() {
                         match attr.0.as_str() {
                             "id" => script_info.id = attr.1.to_string(),
                             "name" => script_info.name = attr.1.to_string(),
@@ -143,10 +508,21 @@ fn parse_script_xml(xml_content: &str, flags: &Flags) -> Option<ScriptInfo> {
                     }
                 } else if depth == 3 && local_name_to_string(e.name().as_ref()) == "Step" {
                     in_step = true;
+                    step_info.offset = event_offset;
                     step_info.indent_level_current = step_info.indent_level_next;
-                    step_info.id = get_attribute(&e, "id").unwrap().parse::<u32>().unwrap();
+                    step_info.index += 1;
+                    step_info.id = match get_attribute(&e, "id").and_then(|id| id.parse().ok()) {
+                        Some(id) => id,
+                        None => {
+                            step_diagnostics.warn(0, "Step without a valid id");
+                            0
+                        }
+                    };
+                    step_info.name = get_attribute(&e, "name").unwrap_or_default();
+                    step_info.enabled =
+                        get_attribute(&e, "enable").unwrap_or("True".to_string()) == "True";
 
-                    if get_attribute(&e, "enable").unwrap_or("True".to_string()) == "True" {
+                    if step_info.enabled {
                         match id_to_script_step(&step_info.id) {
                             ScriptStep::IfStart | ScriptStep::LoopStart => {
                                 step_info.indent_level_next += 1
@@ -184,52 +560,85 @@ fn parse_script_xml(xml_content: &str, flags: &Flags) -> Option<ScriptInfo> {
 
                 if depth == 2 && local_name_to_string(e.name().as_ref()) == "Step" {
                     let is_comment = id_to_script_step(&step_info.id) == ScriptStep::Comment;
-                    match sanitize(&step_info.id, &step_info.content) {
-                        None => {}
+                    let ((step, sanitized), calc_comments) = sanitize_step(
+                        &step_info.id,
+                        &step_info.content,
+                        flags,
+                        &mut step_diagnostics,
+                    );
+                    let is_understood = id_to_script_step(&step_info.id) != ScriptStep::Unknown
+                        && step_diagnostics.entries.is_empty();
+                    for diagnostic in &mut step_diagnostics.entries {
+                        diagnostic.offset += step_info.offset;
+                    }
+                    script_info.diagnostics.append(
+                        &format!("step {} ({})", step_info.index, step_info.name),
+                        std::mem::take(&mut step_diagnostics),
+                    );
+                    let mut lines = match sanitized {
+                        None => Vec::new(),
                         Some(text) => {
-                            let mut first_line_done = false;
-                            let mut add_indent = 0;
-                            for line in text.split('\r') {
-                                let mut indent =
-                                    "\t".repeat(step_info.indent_level_current + add_indent);
-                                if is_comment && first_line_done {
-                                    indent.push_str(&" ".repeat(2));
-                                }
-
-                                script_info.text.push_str(&format!("{indent}{line}\n"));
-                                if !first_line_done {
-                                    first_line_done = true;
-                                    if !is_comment {
-                                        add_indent = 4
-                                    };
-                                }
-                            }
+                            let text = match flags.locale {
+                                Some(locale) if !is_comment => locale.localize_step(
+                                    &text,
+                                    step_info.id,
+                                    &unescape_entities(&step_info.name),
+                                ),
+                                _ => text,
+                            };
+                            text.split('\r').map(str::to_string).collect()
                         }
+                    };
+                    if flags.debug_unknown && !is_understood {
+                        lines.extend(debug_xml_lines(&step_info.content));
+                    }
+                    if flags.dependencies {
+                        script_info.dependencies.add_step(&StepModel {
+                            number: step_info.index,
+                            id: step_info.id,
+                            name: step_info.name.clone(),
+                            enabled: step_info.enabled,
+                            xml: step_info.content.clone(),
+                        });
                     }
+                    let parameters = match (typed_steps, step) {
+                        (true, Some(step)) => step.parameters,
+                        _ => Vec::new(),
+                    };
+                    script_info.steps.push(RenderedStep {
+                        index: step_info.index,
+                        id: step_info.id,
+                        name: flags
+                            .locale
+                            .and_then(|locale| locale.step_name(step_info.id))
+                            .map(str::to_string)
+                            .unwrap_or_else(|| step_info.name.clone()),
+                        enabled: step_info.enabled,
+                        is_comment,
+                        indent_level: step_info.indent_level_current,
+                        lines,
+                        calc_comments,
+                        parameters,
+                        is_understood,
+                    });
                     step_info.indent_level_current = step_info.indent_level_next;
                     step_info.content.clear()
                 }
             }
-            Ok(Event::CData(e)) => {
-                if in_step {
-                    step_info
-                        .content
-                        .push_str(cdata_element_to_string(&e).as_str());
-                }
+            Ok(Event::CData(e)) if in_step => {
+                step_info
+                    .content
+                    .push_str(cdata_element_to_string(&e).as_str());
             }
-            Ok(Event::Comment(e)) | Ok(Event::Text(e)) => {
-                if in_step {
-                    step_info
-                        .content
-                        .push_str(text_element_to_string(&e, true).as_str());
-                }
+            Ok(Event::Comment(e)) | Ok(Event::Text(e)) if in_step => {
+                step_info
+                    .content
+                    .push_str(text_element_to_string(&e, true).as_str());
             }
-            Ok(Event::GeneralRef(e)) => {
-                if in_step {
-                    step_info
-                        .content
-                        .push_str(general_ref_to_string(&e, true).as_str());
-                }
+            Ok(Event::GeneralRef(e)) if in_step => {
+                step_info
+                    .content
+                    .push_str(general_ref_to_string(&e, true).as_str());
             }
             _ => {}
         }
@@ -243,3 +652,228 @@ fn parse_script_xml(xml_content: &str, flags: &Flags) -> Option<ScriptInfo> {
         Some(script_info)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT_XML: &str = r##"<Script>
+	<ScriptReference id="1" name="Numbers"></ScriptReference>
+	<ObjectList membercount="4">
+		<Step id="89" name="# (comment)" enable="True">
+			<ParameterValues membercount="1">
+				<Parameter type="Comment">
+					<Comment value="first&#13;second"></Comment>
+				</Parameter>
+			</ParameterValues>
+		</Step>
+		<Step id="68" name="If" enable="True">
+			<ParameterValues membercount="1">
+				<Parameter type="Calculation">
+					<Calculation>
+						<Calculation>
+							<Text><![CDATA[$a = 1]]></Text>
+						</Calculation>
+					</Calculation>
+				</Parameter>
+			</ParameterValues>
+		</Step>
+		<Step id="93" name="Beep" enable="True"></Step>
+		<Step id="70" name="End If" enable="True"></Step>
+	</ObjectList>
+</Script>"##;
+
+    #[test]
+    fn test_truncated() {
+        let script_info = parse_script_xml(SCRIPT_XML, &Flags::default(), false).unwrap();
+        assert!(!script_info.truncated);
+
+        let end = SCRIPT_XML.find(r#"<Step id="70""#).unwrap();
+        let script_info = parse_script_xml(&SCRIPT_XML[..end], &Flags::default(), false).unwrap();
+        assert!(script_info.truncated);
+        assert_eq!(script_info.steps.len(), 3);
+    }
+
+    #[test]
+    fn test_step_numbers() {
+        let script_info = parse_script_xml(SCRIPT_XML, &Flags::default(), false).unwrap();
+        assert_eq!(
+            render_script_text(&script_info.steps, false),
+            "# first\n  second\nIf [ $a = 1 ]\n\tBeep\nEnd If\n"
+        );
+        assert_eq!(
+            render_script_text(&script_info.steps, true),
+            "1 # first\n    second\n2 If [ $a = 1 ]\n3 \tBeep\n4 End If\n"
+        );
+    }
+
+    #[test]
+    fn test_debug_unknown() {
+        let flags = Flags {
+            debug_unknown: true,
+            ..Flags::default()
+        };
+        let xml = SCRIPT_XML.replace(
+            r#"<Step id="93" name="Beep" enable="True"></Step>"#,
+            r#"<Step id="9999" name="Future step" enable="True"><Options>1</Options></Step>"#,
+        );
+        let script_info = parse_script_xml(&xml, &flags, false).unwrap();
+        assert_eq!(
+            script_info.steps[2].lines,
+            vec![
+                "Future step",
+                "| <Step id=\"9999\" name=\"Future step\" enable=\"True\">",
+                "|   <Options>1</Options>",
+                "| </Step>",
+            ]
+        );
+        assert_eq!(script_info.steps[1].lines, vec!["If [ $a = 1 ]"]);
+    }
+
+    #[test]
+    fn test_one_diagnostic_per_parse_warning() {
+        // Parse warnings are counted from the diagnostics, so a step must report each one once
+        let xml = SCRIPT_XML.replace(
+            r#"<Step id="93" name="Beep" enable="True"></Step>"#,
+            r#"<Step id="1" enable="True"></Step>"#,
+        );
+        let script_info = parse_script_xml(&xml, &Flags::default(), false).unwrap();
+        assert_eq!(script_info.diagnostics.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_step_without_id() {
+        let xml = SCRIPT_XML.replace(
+            r#"<Step id="93" name="Beep" enable="True"></Step>"#,
+            r#"<Step name="Beep" enable="True"></Step>"#,
+        );
+        let script_info = parse_script_xml(&xml, &Flags::default(), false).unwrap();
+        assert!(!script_info.steps[2].is_understood);
+        let messages: Vec<_> = script_info
+            .diagnostics
+            .entries
+            .iter()
+            .map(|diagnostic| format!("{}: {}", diagnostic.context, diagnostic.message))
+            .collect();
+        assert_eq!(messages, ["step 3 (Beep): Step without a valid id"]);
+    }
+
+    #[test]
+    fn test_is_understood() {
+        let is_understood = |step: &str| {
+            let xml =
+                SCRIPT_XML.replace(r#"<Step id="93" name="Beep" enable="True"></Step>"#, step);
+            let script_info = parse_script_xml(&xml, &Flags::default(), false).unwrap();
+            script_info.steps[2].is_understood
+        };
+        assert!(is_understood(
+            r#"<Step id="93" name="Beep" enable="True"></Step>"#
+        ));
+        // Rendered by the generic renderer
+        assert!(is_understood(
+            r#"<Step id="39" name="Sort Records" enable="True"></Step>"#
+        ));
+        // Rendered with a parse warning
+        assert!(!is_understood(r#"<Step id="1" enable="True"></Step>"#));
+        assert!(!is_understood(
+            r#"<Step id="9999" name="Future step" enable="True"></Step>"#
+        ));
+    }
+
+    #[test]
+    fn test_locale() {
+        let flags = Flags {
+            locale: Some(crate::Locale::De),
+            ..Flags::default()
+        };
+        let script_info = parse_script_xml(SCRIPT_XML, &flags, false).unwrap();
+        assert_eq!(
+            render_script_text(&script_info.steps, false),
+            "# first\n  second\nWenn [ $a = 1 ]\n\tFehlerton\nEnde (wenn)\n"
+        );
+        assert_eq!(script_info.steps[3].name, "Ende (wenn)");
+    }
+
+    #[test]
+    fn test_format_calcs() {
+        let xml = SCRIPT_XML.replace(
+            "$a = 1",
+            "Case($a=1;\"first calculation\";$a=2;\"second calculation\";\"any other calculation\")",
+        );
+        let flags = Flags {
+            format_calcs: Some(2),
+            ..Flags::default()
+        };
+        let script_info = parse_script_xml(&xml, &flags, false).unwrap();
+        assert_eq!(
+            script_info.steps[1].lines,
+            vec![
+                "If [ Case (",
+                "  $a = 1 ; \"first calculation\" ;",
+                "  $a = 2 ; \"second calculation\" ;",
+                "  \"any other calculation\"",
+                ") ]"
+            ]
+        );
+
+        let script_info = parse_script_xml(&xml, &Flags::default(), false).unwrap();
+        assert_eq!(script_info.steps[1].lines.len(), 1);
+    }
+
+    #[test]
+    fn test_inline_calcs() {
+        let xml = SCRIPT_XML.replace("$a = 1", "Let ( [\r~a = 1 ;\r~b = 2\r] ;\r~a + ~b\r)");
+        let flags = Flags {
+            inline_calcs: true,
+            ..Flags::default()
+        };
+        let script_info = parse_script_xml(&xml, &flags, false).unwrap();
+        assert_eq!(
+            script_info.steps[1].lines,
+            vec!["If [ Let ( [ ~a = 1 ; ~b = 2 ] ; ~a + ~b ) ]"]
+        );
+    }
+
+    #[test]
+    fn test_escape_calc_newlines() {
+        let xml = SCRIPT_XML.replace("$a = 1", "$a = 1 // first\r and \"a\rb\"");
+        let flags = Flags {
+            escape_calc_newlines: true,
+            ..Flags::default()
+        };
+        let script_info = parse_script_xml(&xml, &flags, false).unwrap();
+        assert_eq!(
+            script_info.steps[1].lines,
+            vec!["If [ $a = 1 // first\\n and \"a\\nb\" ]"]
+        );
+    }
+
+    #[test]
+    fn test_calc_comments() {
+        let xml = SCRIPT_XML.replace(
+            "$a = 1",
+            "$a = 1 /* ]]]]><![CDATA[> */ // first\rand \"// text\" /* second\rline */",
+        );
+        let script_info = parse_script_xml(&xml, &Flags::default(), false).unwrap();
+        assert_eq!(
+            script_info.steps[1].lines,
+            vec![
+                "If [ $a = 1 /* ]]> */ // first",
+                "and \"// text\" /* second",
+                "line */ ]"
+            ]
+        );
+        assert_eq!(
+            render_calc_comments(&script_info.steps),
+            "2 If: /* ]]> */\n2 If: // first\n2 If: /* second\n\tline */\n"
+        );
+    }
+
+    #[test]
+    fn test_debug_xml_lines_truncated() {
+        let xml = format!("<A>{}</A>", "<B/>".repeat(100));
+        let lines = debug_xml_lines(&xml);
+        assert_eq!(lines.len(), DEBUG_XML_MAX_LINES + 1);
+        assert_eq!(lines.last().unwrap(), "| … 62 more lines");
+    }
+}
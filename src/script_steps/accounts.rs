@@ -0,0 +1,255 @@
+use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// Re-Login and the account management steps: Add Account, Delete Account, Reset Account
+/// Password, Enable Account and Change Password. Their options are shown after the account name
+/// and password calculations, like FileMaker does.
+pub fn sanitize(step: &Step) -> String {
+    let script_step = id_to_script_step(&step.id);
+    let mut parameters: Vec<String> = Vec::new();
+    let mut options: Vec<String> = Vec::new();
+
+    let push_calculation =
+        |parameters: &mut Vec<String>, parameter_type: &str, calculation: String| {
+            if !calculation.is_empty() {
+                let label = calculation_label(&script_step, parameter_type);
+                parameters.push(format!("{label}: {calculation}"));
+            }
+        };
+
+    for parameter in &step.parameters {
+        match parameter {
+            Parameter::Calculation { name, calculation } => push_calculation(
+                &mut parameters,
+                name.as_deref().unwrap_or("Calculation"),
+                calculation.display().unwrap_or_default(),
+            ),
+            Parameter::Boolean(boolean) => options.extend(option(&script_step, boolean)),
+            Parameter::Generic(generic) => {
+                for element in generic.descendants() {
+                    match element.name.as_str() {
+                        "Calculation" => push_calculation(
+                            &mut parameters,
+                            &generic.parameter_type,
+                            element.text.clone().unwrap_or_default(),
+                        ),
+                        "List" if generic.parameter_type == "AccountType" => {
+                            if let Some(account_type) = element.attribute("name") {
+                                parameters.push(format!("Authenticate via: {account_type}"));
+                            }
+                        }
+                        // Id 0 is the placeholder of a step without a privilege set
+                        "PrivilegeSetReference" if element.attribute("id") != Some("0") => {
+                            let privilege_set = element.attribute("name").unwrap_or_default();
+                            parameters.push(format!("Privilege Set: \"{privilege_set}\""));
+                        }
+                        "Boolean" => options.extend(option(
+                            &script_step,
+                            &Boolean::from_parsed_element(element, &step.id),
+                        )),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    parameters.extend(options);
+    step.display_with(&parameters)
+}
+
+fn calculation_label(script_step: &ScriptStep, parameter_type: &str) -> &'static str {
+    match (script_step, parameter_type) {
+        (ScriptStep::ResetAccountPassword, "Password") => "New Password",
+        (_, "Password") => "Password",
+        (_, "Old") => "Old Password",
+        (_, "New") => "New Password",
+        _ => "Account Name",
+    }
+}
+
+/// The flag of a step, like `With dialog: OFF`, `Expire password: ON` or `Activate`
+fn option(script_step: &ScriptStep, boolean: &Boolean) -> Option<String> {
+    let value = boolean.value?;
+    match (script_step, boolean.name.as_deref()?) {
+        // Named after the state the account is set to
+        (ScriptStep::EnableAccount, _) => boolean.state.clone(),
+        (ScriptStep::ResetAccountPassword, _) => {
+            Some(Boolean::labeled_bool_to_string("Expire password", value))
+        }
+        (_, label) => Some(Boolean::labeled_bool_to_string(label, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_re_login() {
+        let xml = r#"
+            <Step index="761" id="138" name="Re-Login" enable="True">
+                <UUID>35002F21-C902-4441-A17E-10498B442420</UUID>
+                <OwnerID></OwnerID>
+                <Options>16512</Options>
+                <ParameterValues membercount="3">
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="Name">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA[$Kontoname]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="Password">
+                        <Calculation datatype="1" position="1">
+                            <Calculation>
+                                <Text><![CDATA[$Passwort]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Re-Login [ Account Name: $Kontoname ; Password: $Passwort ; With dialog: OFF ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_add_account() {
+        let xml = r#"
+            <Step index="749" id="134" name="Konto hinzufügen" enable="True">
+                <UUID>C2DDA7DD-E8A3-4DC5-A9C3-2EDB5DEAAE1D</UUID>
+                <OwnerID></OwnerID>
+                <Options>16386</Options>
+                <ParameterValues membercount="5">
+                    <Parameter type="AccountType">
+                        <List name="FileMaker" value="0"></List>
+                    </Parameter>
+                    <Parameter type="Name">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA["gast"]]></Text>
+                                <ChunkList hash="D72FF10B72F4C510ECE89027C65BC06C">
+                                    <Chunk type="NoRef">&quot;gast&quot;</Chunk>
+                                </ChunkList>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="Password">
+                        <Calculation datatype="1" position="1">
+                            <Calculation>
+                                <Text><![CDATA[$Passwort]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="PrivilegeSetReference">
+                        <PrivilegeSetReference id="2" name="[Nur Dateneingabe]"></PrivilegeSetReference>
+                    </Parameter>
+                    <Parameter type="Boolean">
+                        <Boolean type="Passwort ändern" value="True"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            r#"Konto hinzufügen [ Authenticate via: FileMaker ; Account Name: "gast" ; Password: $Passwort ; Privilege Set: "[Nur Dateneingabe]" ; Passwort ändern: ON ]"#
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_account_steps() {
+        let steps = [
+            (
+                r#"<Step index="756" id="135" name="Delete Account" enable="True">
+                    <ParameterValues membercount="1">
+                        <Parameter type="Calculation">
+                            <Calculation datatype="1" position="0">
+                                <Calculation><Text><![CDATA[$Kontoname]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                "Delete Account [ Account Name: $Kontoname ]",
+            ),
+            (
+                r#"<Step index="758" id="137" name="Enable Account" enable="True">
+                    <ParameterValues membercount="2">
+                        <Parameter type="Calculation">
+                            <Calculation datatype="1" position="0">
+                                <Calculation><Text><![CDATA[$Kontoname]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                        <Parameter type="Boolean">
+                            <Boolean value="False" name="Deactivate" type="enable"></Boolean>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                "Enable Account [ Account Name: $Kontoname ; Deactivate ]",
+            ),
+            (
+                r#"<Step index="763" id="136" name="Reset Account Password" enable="True">
+                    <ParameterValues membercount="3">
+                        <Parameter type="Name">
+                            <Calculation datatype="1" position="0">
+                                <Calculation><Text><![CDATA[$Kontoname]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                        <Parameter type="Password">
+                            <Calculation datatype="1" position="1">
+                                <Calculation><Text><![CDATA[$PasswortNeu]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                        <Parameter type="Boolean">
+                            <Boolean value="True" type="Password"></Boolean>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                "Reset Account Password [ Account Name: $Kontoname ; New Password: $PasswortNeu ; Expire password: ON ]",
+            ),
+            (
+                r#"<Step index="753" id="83" name="Change Password" enable="True">
+                    <ParameterValues membercount="3">
+                        <Parameter type="Old">
+                            <Calculation datatype="1" position="0">
+                                <Calculation><Text><![CDATA[$PasswortAlt]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                        <Parameter type="New">
+                            <Calculation datatype="1" position="1">
+                                <Calculation><Text><![CDATA[$PasswortNeu]]></Text></Calculation>
+                            </Calculation>
+                        </Parameter>
+                        <Parameter type="Boolean">
+                            <Boolean type="With dialog" id="128" value="True"></Boolean>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                "Change Password [ Old Password: $PasswortAlt ; New Password: $PasswortNeu ; With dialog: ON ]",
+            ),
+            (
+                r#"<Step index="2" id="135" name="Delete Account" enable="True">
+                    <OwnerID></OwnerID>
+                    <Options>0</Options>
+                </Step>"#,
+                "Delete Account",
+            ),
+        ];
+
+        for (xml, expected_output) in steps {
+            assert_eq!(sanitize_xml(xml), Some(expected_output.to_string()));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// The steps arranging the records of the current layout: View As with its view, Sort Records by
+/// Field with the sort order and the field, which is the active one if none is set, and Unsort
+/// Records.
+pub fn sanitize(step: &Step) -> String {
+    let params: Vec<String> = step
+        .parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Parameter::List(list) => list.display(),
+            Parameter::FieldReference(field) => field.display(),
+            _ => None,
+        })
+        .filter(|param| !param.is_empty())
+        .collect();
+
+    step.display_with(&params)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_view_as() {
+        let xml = r#"
+            <Step index="119" id="30" name="Datensätze zeigen als" enable="True">
+                <UUID>1D320655-0E0C-4674-8CF4-65B70D85F8E6</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="List">
+                        <List name="Als Formular anzeigen" value="1"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Datensätze zeigen als [ Als Formular anzeigen ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_sort_records_by_field() {
+        let xml = r#"
+            <Step index="554" id="154" name="Datensätze nach Feld sortieren" enable="True">
+                <UUID>285EC5C0-FAFA-7A2C-4560-9D6BAA8F290A</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="List">
+                        <List name="Absteigend" value="2"></List>
+                    </Parameter>
+                    <Parameter type="FieldReference">
+                        <FieldReference id="12" name="__ID" UUID="658441A8-04DB-4055-BA92-C4AC8CEF6A5A">
+                            <repetition value="1"></repetition>
+                            <TableOccurrenceReference id="1065113" name="_Syntax" UUID="2F73E4BF-801D-4854-8CB1-0B02B9B99906"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Datensätze nach Feld sortieren [ Absteigend ; _Syntax::__ID ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,60 +1,37 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
+/// Close Window and Select Window. The window is either the current one or found by a calculated
+/// name, optionally restricted to windows of the current file.
+pub fn sanitize(step: &Step) -> String {
+    let select = step
+        .parameters
+        .iter()
+        .find_map(|parameter| match parameter {
+            Parameter::WindowReference(window) => window.select.as_ref(),
+            _ => None,
+        });
+    let current_window = select.and_then(|select| select.kind.as_deref()) == Some("current");
+    let calculation = select
+        .and_then(|select| select.name.as_deref())
+        .unwrap_or_default();
+    let only_current_file = select.is_some_and(|select| select.current_file);
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut calculation = String::new();
-    let mut only_current_file = false;
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"Name" => {
-                    match get_attribute(&e, "current").unwrap().as_str() {
-                        "True" => {
-                            only_current_file = true;
-                        }
-                        "False" => {
-                            only_current_file = false;
-                        }
-                        _ => {}
-                    };
-                }
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap()
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
+    let name = &step.name;
+    if current_window {
+        format!("{name} [ Current Window ]")
     } else if calculation.is_empty() {
-        Some(name.to_string())
+        name.to_string()
     } else if only_current_file {
-        Some(format!("{name} [ Name: {calculation} ; Current file ]"))
+        format!("{name} [ Name: {calculation} ; Current file ]")
     } else {
-        Some(format!("{name} [ Name: {calculation} ]"))
+        format!("{name} [ Name: {calculation} ]")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test_close_current() {
@@ -74,8 +51,8 @@ mod tests {
             </Step>
         "#;
 
-        let expected_output = Some("Fenster schließen".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output = Some("Fenster schließen [ Current Window ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -107,7 +84,7 @@ mod tests {
         "#;
 
         let expected_output = Some(r#"Fenster schließen [ Name: "Foo Bar" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -140,6 +117,36 @@ mod tests {
 
         let expected_output =
             Some(r#"Fenster schließen [ Name: "Foo Bar" ; Current file ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_select_by_name() {
+        let xml = r#"
+            <Step index="599" id="123" name="Select Window" enable="True">
+                <UUID>F7417055-A719-4BDD-9ECC-6F6E5882423C</UUID>
+                <OwnerID></OwnerID>
+                <Options>-2147467262</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="WindowReference">
+                        <WindowReference>
+                            <Select kind="1" type="Calculated">
+                                <Name current="True">
+                                    <Calculation datatype="1" position="0">
+                                        <Calculation>
+                                            <Text><![CDATA[$FensterName]]></Text>
+                                        </Calculation>
+                                    </Calculation>
+                                </Name>
+                            </Select>
+                        </WindowReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Select Window [ Name: $FensterName ; Current file ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
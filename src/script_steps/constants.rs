@@ -1,8 +1,8 @@
 use strum_macros::{Display, FromRepr};
 
 // Although it has no effect on this application, we track unused script IDs in this constant.
-const UNKNOWN_SCRIPT_STEP: [u32; 16] = [
-    2, 3, 15, 52, 53, 54, 58, 100, 110, 162, 163, 170, 171, 173, 198, 204,
+const UNKNOWN_SCRIPT_STEP: [u32; 15] = [
+    2, 15, 52, 53, 54, 58, 100, 110, 162, 163, 170, 171, 173, 198, 204,
 ];
 
 // https://help.claris.com/en/pro-help/content/script-steps-reference.html
@@ -11,6 +11,7 @@ const UNKNOWN_SCRIPT_STEP: [u32; 16] = [
 pub enum ScriptStep {
     Unknown = 0,
     PerformScript = 1,
+    SaveACopyAsXml = 3,
     GoToPreviousField = 4,
     GoToNextField = 5,
     GoToLayout = 6,
@@ -18,10 +19,13 @@ pub enum ScriptStep {
     DuplicateRecordRequest = 8,
     DeleteRecordRequest = 9,
     DeleteAllRecords = 10,
+    InsertFromIndex = 11,
+    InsertFromLastVisited = 12,
     InsertCurrentDate = 13,
     InsertCurrentTime = 14,
     GoToRecordRequestPage = 16,
     GoToField = 17,
+    CheckSelection = 18,
     CheckRecord = 19,
     CheckFoundSet = 20,
     UnsortRecords = 21,
@@ -32,25 +36,57 @@ pub enum ScriptStep {
     OmitMultipleRecords = 26,
     ShowOmittedOnly = 27,
     PerformFind = 28,
+    ShowHideToolbars = 29,
     ViewAs = 30,
     AdjustWindow = 31,
     OpenHelp = 32,
+    OpenFile = 33,
+    CloseFile = 34,
+    ImportRecords = 35,
+    ExportRecords = 36,
+    SaveACopyAs = 37,
     OpenManageDatabase = 38,
+    SortRecords = 39,
+    RelookupFieldContents = 40,
+    EnterPreviewMode = 41,
+    PrintSetup = 42,
+    Print = 43,
     ExitApplication = 44,
+    UndoRedo = 45,
+    Cut = 46,
+    Copy = 47,
+    Paste = 48,
+    Clear = 49,
     SelectAll = 50,
+    RevertRecordRequest = 51,
     EnterBrowseMode = 55,
+    InsertPicture = 56,
+    SendEvent = 57,
+    InsertQuickTime = 59,
+    InsertCurrentUserName = 60,
     InsertText = 61,
+    PauseResumeScript = 62,
+    SendMail = 63,
+    SendDdeExecute = 64,
+    DialPhone = 65,
+    Speak = 66,
+    PerformAppleScript = 67,
     IfStart = 68,
     Else = 69,
     IfEnd = 70,
     LoopStart = 71,
     ExitLoopIf = 72,
     LoopEnd = 73,
+    GoToRelatedRecord = 74,
     CommitRecordRequests = 75,
     SetField = 76,
+    InsertCalculatedResult = 77,
+    InsertObject = 78,
     FreezeWindow = 79,
     RefreshWindow = 80,
+    ScrollWindow = 81,
     NewFile = 82,
+    ChangePassword = 83,
     SetMultiUser = 84,
     AllowUserAbort = 85,
     SetErrorCapture = 86,
@@ -59,45 +95,132 @@ pub enum ScriptStep {
     Comment = 89,
     HaltScript = 90,
     ReplaceFieldContents = 91,
+    ShowHideTextRuler = 92,
     Beep = 93,
     SetUseSystemFormats = 94,
+    RecoverFile = 95,
+    SaveACopyAsAddOnPackage = 96,
+    SetZoomLevel = 97,
+    CopyAllRecordsRequests = 98,
     GoToPortalRow = 99,
     CopyRecordRequest = 101,
     FlushCacheToDisk = 102,
     ExitScript = 103,
+    DeletePortalRow = 104,
     OpenPreferences = 105,
     CorrectWord = 106,
     SpellingOptions = 107,
     SelectDictionaries = 108,
     EditUserDictionary = 109,
+    OpenUrl = 111,
     OpenManageValueLists = 112,
     OpenSharing = 113,
     OpenFileOptions = 114,
     AllowFormattingBar = 115,
+    SetNextSerialValue = 116,
+    ExecuteSql = 117,
     OpenHosts = 118,
+    MoveResizeWindow = 119,
+    ArrangeAllWindows = 120,
     CloseWindow = 121,
     NewWindow = 122,
+    SelectWindow = 123,
+    SetWindowTitle = 124,
     IfElse = 125,
     ConstrainFoundSet = 126,
     ExtendFoundSet = 127,
+    PerformFindReplace = 128,
     OpenFindReplace = 129,
+    SetSelection = 130,
+    InsertFile = 131,
+    ExportFieldContents = 132,
+    OpenRecordRequest = 133,
+    AddAccount = 134,
+    DeleteAccount = 135,
+    ResetAccountPassword = 136,
+    EnableAccount = 137,
+    ReLogin = 138,
+    ConvertFile = 139,
     OpenManageDataSources = 140,
     SetVariable = 141,
+    InstallMenuSet = 142,
+    SaveRecordsAsExcel = 143,
+    SaveRecordsAsPdf = 144,
     GoToObject = 145,
+    SetWebViewer = 146,
+    SetFieldByName = 147,
+    InstallOnTimerScript = 148,
     OpenEditSavedFinds = 149,
+    PerformQuickFind = 150,
     OpenManageLayouts = 151,
+    SaveRecordsAsSnapshotLink = 152,
+    SortRecordsByField = 154,
+    FindMatchingRecords = 155,
     OpenManageContainers = 156,
+    InstallPlugInFile = 157,
+    InsertPdf = 158,
+    InsertAudioVideo = 159,
+    InsertFromUrl = 160,
+    InsertFromDevice = 161,
+    PerformScriptOnServer = 164,
     OpenManageThemes = 165,
+    ShowHideMenubar = 166,
     RefreshObject = 167,
+    SetLayoutObjectAnimation = 168,
     ClosePopover = 169,
     UploadToServer = 172,
+    EnableTouchKeyboard = 174,
+    PerformJavaScriptInWebViewer = 175,
+    SetAllowedOrientations = 176,
+    AvPlayerPlay = 177,
     AvPlayerSetPlaybackState = 178,
+    AvPlayerSetOptions = 179,
+    RefreshPortal = 180,
+    GetFolderPath = 181,
     TruncateTable = 182,
     OpenFavorites = 183,
+    OpenStarterSolution = 184,
+    ConfigureRegionMonitorScript = 185,
+    PluginStep = 186,
+    ConfigureLocalNotification = 187,
+    GetFileExists = 188,
+    GetFileSize = 189,
+    CreateDataFile = 190,
+    OpenDataFile = 191,
+    WriteToDataFile = 192,
+    ReadFromDataFile = 193,
+    GetDataFilePosition = 194,
+    SetDataFilePosition = 195,
+    CloseDataFile = 196,
+    DeleteFile = 197,
+    RenameFile = 199,
     SetErrorLogging = 200,
+    ConfigureNfcReading = 201,
+    ConfigureMachineLearningModel = 202,
+    ExecuteFileMakerDataApi = 203,
     OpenTransaction = 205,
     CommitTransaction = 206,
     RevertTransaction = 207,
+    SetSessionIdentifier = 208,
+    SetDictionary = 209,
+    PerformScriptOnServerWithCallback = 210,
+    TriggerClarisConnectFlow = 211,
+    ConfigureAiAccount = 212,
+    FineTuneModel = 213,
+    PerformSqlQueryByNaturalLanguage = 214,
+    InsertEmbedding = 215,
+    InsertEmbeddingInFoundSet = 216,
+    SetAiCallLogging = 217,
+    PerformSemanticFind = 218,
+    PerformRagAction = 219,
+    GenerateResponseFromModel = 220,
+    PerformFindByNaturalLanguage = 221,
+    ConfigureRegressionModel = 222,
+    SetRevertTransactionOnError = 223,
+    SaveRecordsAsJsonl = 225,
+    ConfigurePromptTemplate = 226,
+    ConfigureRagAccount = 227,
+    GoToListOfRecords = 228,
 }
 
 pub fn id_to_script_step(id: &u32) -> ScriptStep {
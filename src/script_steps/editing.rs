@@ -0,0 +1,79 @@
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// The editing steps Cut, Copy, Paste and Clear with their target field and flags, and Undo/Redo
+/// with its operation. Without a target field, the active field is used.
+pub fn sanitize(step: &Step) -> String {
+    let mut params = Vec::new();
+    params.extend(
+        step.field()
+            .and_then(|field| field.display())
+            .filter(|field_reference| !field_reference.is_empty()),
+    );
+    params.extend(
+        step.parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::Boolean(boolean) => boolean.display(),
+                Parameter::List(list) => list.display(),
+                _ => None,
+            }),
+    );
+
+    step.display_with(&params)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_paste_into_field() {
+        let xml = r#"
+            <Step index="185" id="48" name="Einfügen" enable="True">
+                <UUID>88A881BD-D72E-1172-D1BF-8886BC727064</UUID>
+                <OwnerID></OwnerID>
+                <Options>4096</Options>
+                <ParameterValues membercount="3">
+                    <Parameter type="Boolean">
+                        <Boolean type="Auswahl" id="4096" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="Boolean">
+                        <Boolean type="Ohne Stil" id="512" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="FieldReference">
+                        <FieldReference id="1" name="__ID" UUID="">
+                            <TableOccurrenceReference id="1065089" name="_Home"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Einfügen [ _Home::__ID ; Auswahl: ON ; Ohne Stil: OFF ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let xml = r#"
+            <Step index="8" id="45" name="Undo/Redo" enable="True">
+                <UUID>6CBB76CC-39D8-8962-56DC-FDC68D536387</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Collapsed" id="33554432" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="List">
+                        <List name="Redo" value="2"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Undo/Redo [ Redo ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
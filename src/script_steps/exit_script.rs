@@ -1,53 +1,25 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut calculation = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap()
-                }
-                _ => {}
-            },
-            _ => {}
+/// Exit Script with the result calculation handed back to the calling script
+pub fn sanitize(step: &Step) -> String {
+    match step.all_calculations().pop() {
+        Some(calculation) if !calculation.is_empty() => {
+            format!("{} [ {calculation} ]", step.name)
         }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else if calculation.is_empty() {
-        Some(format!("{name} []"))
-    } else {
-        Some(format!("{name} [ {calculation} ]"))
+        _ => format!("{} []", step.name),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
         let xml = r#"<Step id="103" name="Aktuelles Script verlassen" enable="True"></Step>"#;
 
         let expected_output = Some("Aktuelles Script verlassen []".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -71,6 +43,6 @@ mod tests {
         "#;
 
         let expected_output = Some("Aktuelles Script verlassen [ $Foo ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
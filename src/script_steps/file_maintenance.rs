@@ -0,0 +1,170 @@
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// The file maintenance steps Save a Copy as, Recover File and Convert File, with their paths,
+/// the type of copy, the source of a converted file and their flags, in the order of the export
+pub fn sanitize(step: &Step) -> String {
+    let mut params: Vec<String> = Vec::new();
+    for parameter in &step.parameters {
+        match parameter {
+            Parameter::Boolean(boolean) => params.extend(boolean.display()),
+            Parameter::List(list) => params.extend(list.display()),
+            Parameter::Generic(generic) => push_elements(&mut params, &generic.elements, None),
+            Parameter::DataSourceReference(data_source) => {
+                push_elements(&mut params, &data_source.elements, None)
+            }
+            _ => {}
+        }
+    }
+
+    step.display_with(&params)
+}
+
+/// The paths and calculations of `elements`; `source` is the XML or XSL source of a converted
+/// file with the kind of its location
+fn push_elements(params: &mut Vec<String>, elements: &[Element], source: Option<&str>) {
+    for element in elements {
+        match element.name.as_str() {
+            "UniversalPathList" => {
+                // Alternative paths are separated by line breaks
+                let path: String = element
+                    .descendants()
+                    .into_iter()
+                    .filter(|location| location.name == "Location")
+                    .filter_map(|location| location.text.as_deref())
+                    .collect();
+                let paths = path.lines().collect::<Vec<_>>().join(" ");
+                match source {
+                    Some(source) => params.push(format!("{source}: {paths}")),
+                    None if !paths.is_empty() => params.push(paths),
+                    None => {}
+                }
+                if element.attribute("AutoOpen") == Some("True") {
+                    params.push("Automatically open".to_string());
+                }
+                if element.attribute("CreateMail") == Some("True") {
+                    params.push("Create email".to_string());
+                }
+            }
+            "XML" | "XSL" => {
+                let source = match element.attribute("index") {
+                    Some("1") => format!("{} (from file)", element.name),
+                    Some("2") => format!("{} (from HTTP)", element.name),
+                    Some("3") => format!("{} (from calculation)", element.name),
+                    _ => element.name.clone(),
+                };
+                push_elements(params, &element.children, Some(&source));
+            }
+            "Calculation" => {
+                if let Some(source) = source {
+                    let calculation = element.text.as_deref().unwrap_or_default();
+                    params.push(format!("{source}: {calculation}"));
+                }
+            }
+            _ => push_elements(params, &element.children, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_save_a_copy_as() {
+        let xml = r#"
+            <Step index="688" id="37" name="Save a Copy as" enable="True">
+                <Options>1610612770</Options>
+                <ParameterValues membercount="3">
+                    <Parameter type="UniversalPathList">
+                        <UniversalPathList AutoOpen="True" CreateMail="True" membercount="1">
+                            <ObjectList>
+                                <Location><![CDATA[file:$path
+file:Backup.fmp12]]></Location>
+                            </ObjectList>
+                        </UniversalPathList>
+                    </Parameter>
+                    <Parameter type="List">
+                        <List name="compacted copy (smaller)" value="2"></List>
+                    </Parameter>
+                    <Parameter type="Boolean">
+                        <Boolean type="Create folders" id="512" value="False"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Save a Copy as [ file:$path file:Backup.fmp12 ; Automatically open ; Create email ; compacted copy (smaller) ; Create folders: OFF ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_recover_file() {
+        let xml = r#"
+            <Step index="682" id="95" name="Datei wiederherstellen" enable="True">
+                <Options>160</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Mit Dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="UniversalPathList">
+                        <UniversalPathList membercount="1">
+                            <ObjectList>
+                                <Location><![CDATA[$file]]></Location>
+                            </ObjectList>
+                        </UniversalPathList>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Datei wiederherstellen [ Mit Dialog: OFF ; $file ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_convert_file() {
+        let xml = r#"
+            <Step index="646" id="139" name="Convert File" enable="True">
+                <Options>34</Options>
+                <ParameterValues membercount="5">
+                    <Parameter type="Boolean">
+                        <Boolean type="Open File" id="256" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="DataSourceReference">
+                        <DataSourceReference fileType="XML " isPredefined="False" delimiter="4" value="4">
+                            <XML index="2">
+                                <Tooltip>
+                                    <Calculation>
+                                        <Text><![CDATA["http:xml"]]></Text>
+                                    </Calculation>
+                                </Tooltip>
+                            </XML>
+                            <XSL index="1">
+                                <UniversalPathList fileType="XML " membercount="1">
+                                    <ObjectList>
+                                        <Location><![CDATA[$PathXSLT]]></Location>
+                                    </ObjectList>
+                                </UniversalPathList>
+                            </XSL>
+                        </DataSourceReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            r#"Convert File [ Open File: ON ; With dialog: ON ; XML (from HTTP): "http:xml" ; XSL (from file): $PathXSLT ]"#
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,61 +1,15 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut calculation = String::new();
-    let mut repetition = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(ref e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(e, "name").unwrap().to_string();
-                }
-                b"Name" => {
-                    calculation = Calculation::from_xml(&mut reader, e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default()
-                }
-                b"repetition" => {
-                    repetition = Calculation::from_xml(&mut reader, e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default()
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else if !calculation.is_empty() && !repetition.is_empty() {
-        Some(format!(
-            "{name} [ {calculation} ; Repetition: {repetition} ]"
-        ))
-    } else if !calculation.is_empty() {
-        Some(format!("{name} [ {calculation} ]"))
-    } else if !repetition.is_empty() {
-        Some(format!("{name} [ ; Repetition: {repetition} ]"))
-    } else {
-        Some(format!("{name} []"))
+pub fn sanitize(step: &Step) -> String {
+    match step.target().and_then(|object| object.display_parameter()) {
+        Some(object) => format!("{} [ {object} ]", step.name),
+        None => format!("{} []", step.name),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -81,8 +35,8 @@ mod tests {
             </Step>
         "#;
 
-        let expected_output = Some(r#"Gehe zu Objekt [ "Foo Bar" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output = Some(r#"Gehe zu Objekt [ Object Name: "Foo Bar" ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -119,7 +73,7 @@ mod tests {
         "#;
 
         let expected_output =
-            Some(r#"Gehe zu Objekt [ "Foo Bar" ; Repetition: $Rep ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some(r#"Gehe zu Objekt [ Object Name: "Foo Bar" ; Repetition: $Rep ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
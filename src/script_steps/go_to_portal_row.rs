@@ -1,69 +1,30 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::step::Step;
 
-use crate::utils::attributes::get_attribute;
+pub fn sanitize(step: &Step) -> String {
+    let select = step.all_booleans().pop().unwrap_or_default();
+    let position = step
+        .elements()
+        .into_iter()
+        .rev()
+        .filter(|element| element.name == "List")
+        .find_map(|list| list.attribute("name"))
+        .unwrap_or_default();
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut select_label = String::new();
-    let mut select = false;
-    let mut position = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(&e, "name").unwrap().to_string();
-                }
-                b"Boolean" => {
-                    select_label = get_attribute(&e, "type").unwrap().to_string();
-                    match get_attribute(&e, "value").unwrap().as_str() {
-                        "True" => {
-                            select = true;
-                        }
-                        "False" => {
-                            select = false;
-                        }
-                        _ => {}
-                    };
-                }
-                b"List" => {
-                    position = get_attribute(&e, "name").unwrap().to_string();
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else {
-        Some(format!(
-            "{} [ {}: {} ; {} ]",
-            name,
-            select_label,
-            match select {
-                true => {
-                    "ON"
-                }
-                false => {
-                    "OFF"
-                }
-            },
-            position
-        ))
-    }
+    format!(
+        "{} [ {} ; {} ]",
+        step.name,
+        Boolean::labeled_bool_to_string(
+            select.name.as_deref().unwrap_or_default(),
+            select.value.unwrap_or_default()
+        ),
+        position
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -84,6 +45,6 @@ mod tests {
 
         let expected_output =
             Some("Gehe zu Ausschnittreihe [ Auswahl: ON ; Letzte(r) ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
@@ -1,83 +1,34 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
-
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-
-    let mut option = String::new();
-    let mut option_type = String::new();
-    let mut boolean_option_type = String::new();
-    let mut boolean_option_value = false;
-    let mut calculation = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"List" => {
-                    option = get_attribute(&e, "name").unwrap().to_string();
-                    option_type = get_attribute(&e, "value").unwrap().to_string();
-                }
-                b"Boolean" => {
-                    boolean_option_type = get_attribute(&e, "type").unwrap().to_string();
-                    boolean_option_value = get_attribute(&e, "value").unwrap() == "True";
-                }
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap()
-                }
-                _ => {}
-            },
-            _ => {}
+use crate::script_steps::step::Step;
+
+pub fn sanitize(step: &Step) -> String {
+    let records = step.generic("Records");
+    let list = records.and_then(|records| records.find("List"));
+    let option = list
+        .and_then(|list| list.attribute("name"))
+        .unwrap_or_default();
+    let dialog = step
+        .all_booleans()
+        .last()
+        .and_then(|dialog| dialog.display());
+    let calculation = step.all_calculations().pop().unwrap_or_default();
+
+    let name = &step.name;
+    if list.and_then(|list| list.attribute("value")) == Some("5") {
+        match dialog {
+            None => format!("{name} [ {calculation} ]"),
+            Some(dialog) => format!("{name} [ {dialog} ; {calculation} ]"),
         }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else if option_type == "5" {
-        if boolean_option_type.is_empty() {
-            Some(format!("{name} [ {calculation} ]"))
-        } else {
-            Some(format!(
-                "{} [ {}: {} ; {} ]",
-                name,
-                boolean_option_type,
-                match boolean_option_value {
-                    true => "ON".to_string(),
-                    false => "OFF".to_string(),
-                },
-                calculation
-            ))
-        }
-    } else if boolean_option_type.is_empty() {
-        Some(format!("{name} [ {option} ]"))
     } else {
-        Some(format!(
-            "{} [ {} ; {}: {} ]",
-            name,
-            option,
-            boolean_option_type,
-            match boolean_option_value {
-                true => "ON".to_string(),
-                false => "OFF".to_string(),
-            }
-        ))
+        match dialog {
+            None => format!("{name} [ {option} ]"),
+            Some(dialog) => format!("{name} [ {option} ; {dialog} ]"),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test_go_to_first() {
@@ -95,7 +46,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Gehe zu Datens./Abfrage/Seite [ Erste(r) ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -114,7 +65,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Gehe zu Datens./Abfrage/Seite [ Letzte(r) ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -137,7 +88,7 @@ mod tests {
         let expected_output = Some(
             "Gehe zu Datens./Abfrage/Seite [ Vorherige(r) ; Nach letztem beenden: ON ]".to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -161,7 +112,7 @@ mod tests {
             "Gehe zu Datens./Abfrage/Seite [ Vorherige(r) ; Nach letztem beenden: OFF ]"
                 .to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -184,7 +135,7 @@ mod tests {
         let expected_output = Some(
             "Gehe zu Datens./Abfrage/Seite [ Nächste(r) ; Nach letztem beenden: ON ]".to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -207,7 +158,7 @@ mod tests {
         let expected_output = Some(
             "Gehe zu Datens./Abfrage/Seite [ Nächste(r) ; Nach letztem beenden: OFF ]".to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -242,7 +193,7 @@ mod tests {
             "Gehe zu Datens./Abfrage/Seite [ Mit Dialog: OFF ; Hole( LayoutNummer ) + 1 ]"
                 .to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -272,6 +223,6 @@ mod tests {
 
         let expected_output =
             Some("Gehe zu Datens./Abfrage/Seite [ Mit Dialog: ON ; $Nr ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
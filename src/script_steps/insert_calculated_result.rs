@@ -0,0 +1,88 @@
+use crate::script_steps::step::Step;
+
+pub fn sanitize(step: &Step) -> String {
+    let mut parameters = Vec::new();
+    parameters.extend(
+        step.all_booleans()
+            .last()
+            .and_then(|select| select.display()),
+    );
+    parameters.extend(step.target().and_then(|target| target.display_parameter()));
+    parameters.extend(
+        step.all_calculations()
+            .pop()
+            .filter(|calculation| !calculation.is_empty()),
+    );
+
+    step.display_with(&parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test() {
+        let xml = r#"
+            <Step index="247" id="77" name="Berechneten Wert einfügen" enable="True">
+                <Options>20481</Options>
+                <ParameterValues membercount="3">
+                    <Parameter type="Boolean">
+                        <Boolean type="Auswahl" id="4096" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="Target">
+                        <FieldReference id="6" name="_gInputText1">
+                            <repetition value="1"></repetition>
+                            <TableOccurrenceReference id="1065123" name="_Home"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                    <Parameter type="Calculation">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA["hello"]]></Text>
+                                <ChunkList hash="8E488168CD8AF4E46983E95C3C202FC9">
+                                    <Chunk type="NoRef">&quot;hello&quot;</Chunk>
+                                </ChunkList>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            r#"Berechneten Wert einfügen [ Auswahl: ON ; Target: _Home::_gInputText1 ; "hello" ]"#
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_variable_target() {
+        let xml = r#"
+            <Step index="218" id="77" name="Insert Calculated Result" enable="True">
+                <Options>5</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Select" id="4096" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="Target">
+                        <Variable value="$TargetVar">
+                            <repetition>
+                                <Calculation datatype="1" position="32">
+                                    <Calculation>
+                                        <Text><![CDATA[1]]></Text>
+                                    </Calculation>
+                                </Calculation>
+                            </repetition>
+                        </Variable>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Insert Calculated Result [ Select: OFF ; Target: $TargetVar ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
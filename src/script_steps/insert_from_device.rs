@@ -0,0 +1,274 @@
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// The parameters in the order they are read, with the type of the parameter they are in
+#[derive(Default)]
+struct Device {
+    parameter_type: String,
+    source: String,
+    target: String,
+    options: Vec<String>,
+    duration: Option<String>,
+}
+
+/// Insert from Device with its target field, the source (camera, bar code, signature, music
+/// library, …) and the options of that source, like the camera, resolution, maximum duration or
+/// the bar code types.
+pub fn sanitize(step: &Step) -> String {
+    let mut device = Device::default();
+    for parameter in &step.parameters {
+        match parameter {
+            Parameter::FieldReference(field) => {
+                device.parameter_type = "FieldReference".to_string();
+                device.push_field(field.display().unwrap_or_default());
+            }
+            Parameter::List(list) => {
+                device.parameter_type = "List".to_string();
+                device.push_list(list.name.as_deref().unwrap_or_default());
+                device.push_elements(list.elements.iter().flat_map(Element::descendants));
+            }
+            Parameter::Boolean(boolean) => {
+                device.parameter_type = "Boolean".to_string();
+                device.push_boolean(
+                    boolean.name.as_deref().unwrap_or_default(),
+                    boolean.value == Some(true),
+                );
+            }
+            Parameter::Calculation { name, calculation } => {
+                device.parameter_type = name.as_deref().unwrap_or("Calculation").to_string();
+                device.push_calculation(calculation.display().unwrap_or_default());
+            }
+            Parameter::Generic(generic) => {
+                device.parameter_type.clone_from(&generic.parameter_type);
+                device.push_elements(generic.descendants());
+            }
+            _ => {}
+        }
+    }
+
+    let mut params = Vec::new();
+    if !device.target.is_empty() {
+        params.push(device.target);
+    }
+    if !device.source.is_empty() {
+        params.push(format!("Type: {}", device.source));
+    }
+    params.extend(device.options);
+
+    step.display_with(&params)
+}
+
+impl Device {
+    fn push_elements<'a>(&mut self, elements: impl IntoIterator<Item = &'a Element>) {
+        for element in elements {
+            let attribute = |name: &str| element.attribute(name).unwrap_or_default();
+            match element.name.as_str() {
+                // The options of a source are parameters inside its list
+                "Parameter" => self.parameter_type = attribute("type").to_string(),
+                "FieldReference" => self.push_field(element.text.clone().unwrap_or_default()),
+                "List" => {
+                    self.push_list(attribute("name"));
+                    if self.parameter_type == "type" {
+                        let types: Vec<&str> = element
+                            .descendants()
+                            .into_iter()
+                            .filter(|type_name| type_name.name == "name")
+                            .filter_map(|type_name| type_name.text.as_deref())
+                            .collect();
+                        if !types.is_empty() {
+                            self.options.push(format!("Types: {}", types.join(", ")));
+                        }
+                    }
+                }
+                "Boolean" => self.push_boolean(attribute("type"), attribute("value") == "True"),
+                "Calculation" => self.push_calculation(element.text.clone().unwrap_or_default()),
+                _ => {}
+            }
+        }
+    }
+
+    fn push_field(&mut self, field_reference: String) {
+        // The target comes before the source, a bar code can be scanned from a field
+        match self.source.is_empty() {
+            true => self.target = field_reference,
+            false if !field_reference.is_empty() => {
+                self.options.push(format!("Scan from: {field_reference}"))
+            }
+            false => {}
+        }
+    }
+
+    fn push_list(&mut self, list_name: &str) {
+        match self.parameter_type.as_str() {
+            "List" if self.source.is_empty() => self.source = list_name.to_string(),
+            "type" => {}
+            _ if !list_name.is_empty() => self
+                .options
+                .push(format!("{}: {list_name}", self.parameter_type)),
+            _ => {}
+        }
+    }
+
+    // Max duration and Start immediately are named in the export's language
+    fn push_boolean(&mut self, label: &str, value: bool) {
+        match self.parameter_type.as_str() {
+            "Duration" if value => self.duration = Some(label.to_string()),
+            "Duration" => {}
+            _ => self
+                .options
+                .push(Boolean::labeled_bool_to_string(label, value)),
+        }
+    }
+
+    fn push_calculation(&mut self, calculation: String) {
+        match self.parameter_type.as_str() {
+            "Duration" => {
+                if let Some(label) = self.duration.take() {
+                    self.options.push(format!("{label}: {calculation}"))
+                }
+            }
+            _ if !calculation.is_empty() => self
+                .options
+                .push(format!("{}: {calculation}", self.parameter_type)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_camera() {
+        let xml = r#"
+            <Step index="63" id="161" name="Von Gerät einfügen" enable="True">
+                <UUID>7B3E0162-20EF-1736-80A2-60714EEC472F</UUID>
+                <OwnerID></OwnerID>
+                <Options>2</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="List">
+                        <List name="Kamera" value="3">
+                            <Options type="Camera">
+                                <Parameter type="Camera">
+                                    <List name="Hinten" value="1"></List>
+                                </Parameter>
+                                <Parameter type="Resolution">
+                                    <List name="Original" value="0"></List>
+                                </Parameter>
+                            </Options>
+                        </List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Von Gerät einfügen [ Type: Kamera ; Camera: Hinten ; Resolution: Original ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_video_camera() {
+        let xml = r#"
+            <Step index="314" id="161" name="Insert from Device" enable="True">
+                <UUID>6CD401EA-1CC0-2892-ABEC-95573CFD7BC2</UUID>
+                <OwnerID></OwnerID>
+                <Options>16387</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="FieldReference">
+                        <FieldReference id="1" name="Video" UUID="">
+                            <TableOccurrenceReference id="1065089" name="Media"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                    <Parameter type="List">
+                        <List name="Video Camera" value="4">
+                            <Options type="Video">
+                                <Parameter type="Camera">
+                                    <List name="Back" value="1"></List>
+                                </Parameter>
+                                <Parameter type="Resolution">
+                                    <List name="Full" value="0"></List>
+                                </Parameter>
+                                <Parameter type="Duration">
+                                    <Boolean value="True" type="Max duration"></Boolean>
+                                    <Calculation datatype="1" position="0">
+                                        <Calculation>
+                                            <Text><![CDATA[60]]></Text>
+                                        </Calculation>
+                                    </Calculation>
+                                </Parameter>
+                                <Parameter type="Start">
+                                    <Boolean value="True" type="Start immediately"></Boolean>
+                                </Parameter>
+                            </Options>
+                        </List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Insert from Device [ Media::Video ; Type: Video Camera ; Camera: Back ; Resolution: Full ; Max duration: 60 ; Start immediately: ON ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_barcode_and_signature() {
+        let steps = [
+            (
+                r#"<Step index="317" id="161" name="Insert from Device" enable="True">
+                    <ParameterValues membercount="1">
+                        <Parameter type="List">
+                            <List name="Barcode" value="6">
+                                <Options type="Barcode">
+                                    <Parameter type="Camera">
+                                        <List name="Back" value="1"></List>
+                                    </Parameter>
+                                    <Parameter type="type">
+                                        <List value="3">
+                                            <name>Code 39</name>
+                                            <name>QR Code</name>
+                                        </List>
+                                    </Parameter>
+                                </Options>
+                            </List>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                "Insert from Device [ Type: Barcode ; Camera: Back ; Types: Code 39, QR Code ]",
+            ),
+            (
+                r#"<Step index="321" id="161" name="Insert from Device" enable="True">
+                    <ParameterValues membercount="1">
+                        <Parameter type="List">
+                            <List name="Signature" value="7">
+                                <Options type="Signature">
+                                    <Parameter type="Title">
+                                        <Calculation datatype="1" position="1">
+                                            <Calculation><Text><![CDATA["Title"]]></Text></Calculation>
+                                        </Calculation>
+                                    </Parameter>
+                                    <Parameter type="Presentation">
+                                        <List name="Overlay" value="1"></List>
+                                    </Parameter>
+                                </Options>
+                            </List>
+                        </Parameter>
+                    </ParameterValues>
+                </Step>"#,
+                r#"Insert from Device [ Type: Signature ; Title: "Title" ; Presentation: Overlay ]"#,
+            ),
+        ];
+
+        for (xml, expected_output) in steps {
+            assert_eq!(sanitize_xml(xml), Some(expected_output.to_string()));
+        }
+    }
+}
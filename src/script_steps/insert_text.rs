@@ -1,76 +1,32 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
-
-use crate::script_steps::parameters::target::Target;
-use crate::script_steps::parameters::text::Text;
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut text: Option<String> = None;
-    let mut target: Option<String> = None;
-    let mut select = false;
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(&e, "name").unwrap_or_default();
-                }
-                b"Boolean" => {
-                    if get_attribute(&e, "id").unwrap_or_default() == "4096" // Select
-                        && get_attribute(&e, "value").unwrap_or_default() == "True"
-                    {
-                        select = true;
-                    }
-                }
-                b"Text" => {
-                    text = Text::from_xml(&mut reader, &e).unwrap().display();
-                }
-                b"Parameter" => {
-                    let target_type = get_attribute(&e, "type").unwrap();
-                    if target_type.as_str() == "Target" {
-                        target = Target::from_xml(&mut reader, &e).unwrap().display();
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
+use crate::script_steps::step::Step;
+
+pub fn sanitize(step: &Step) -> String {
+    let mut v = Vec::with_capacity(3);
+    v.extend(
+        step.all_booleans()
+            .last()
+            .and_then(|select| select.display()),
+    );
+    v.extend(step.target().and_then(|target| target.display_parameter()));
+
+    if let Some(text) = step
+        .generic("Text")
+        .and_then(|text| text.find("Text"))
+        .and_then(|text| text.attribute("value"))
+    {
+        v.push(format!("\"{text}\""));
     }
 
-    if name.is_empty() {
-        None
+    if v.is_empty() {
+        format!("{} []", step.name)
     } else {
-        let mut v = Vec::with_capacity(3);
-        if select {
-            v.push("Select".to_string());
-        }
-        if let Some(target) = target {
-            v.push(format!("Target: {target}"));
-        }
-
-        if let Some(text) = text {
-            v.push(format!("\"{text}\""));
-        }
-
-        let params = v.join(" ; ");
-        if params.is_empty() {
-            Some(format!("{name} []"))
-        } else {
-            Some(format!("{name} [ {params} ]"))
-        }
+        step.display_with(&v)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn empty() {
@@ -89,8 +45,8 @@ mod tests {
                 </ParameterValues>
             </Step>
             "#;
-        let expected_output = Some("Insert Text []".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output = Some("Insert Text [ Select: OFF ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -110,8 +66,8 @@ mod tests {
                 </ParameterValues>
             </Step>
             "#;
-        let expected_output = Some("Insert Text [ Select ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output = Some("Insert Text [ Select: ON ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -136,8 +92,8 @@ mod tests {
                 </ParameterValues>
             </Step>
             "#;
-        let expected_output = Some("Insert Text [ Select ; Target: $hello ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output = Some("Insert Text [ Select: ON ; Target: $hello ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -163,8 +119,8 @@ mod tests {
             </Step>
             "#;
         let expected_output =
-            Some("Insert Text [ Select ; Target: $hello ; \"a\rb\rc\" ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some("Insert Text [ Select: ON ; Target: $hello ; \"a\rb\rc\" ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -190,8 +146,8 @@ mod tests {
             </Step>
             "#;
         let expected_output =
-            Some("Insert Text [ Select ; Target: $hello[4] ; \"a\rb\rc\" ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some("Insert Text [ Select: ON ; Target: $hello[4] ; \"a\rb\rc\" ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -218,8 +174,8 @@ mod tests {
             </Step>
             "#;
         let expected_output =
-            Some("Insert Text [ Select ; Target: Foo::id ; \"a\rb\rc\" ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some("Insert Text [ Select: ON ; Target: Foo::id ; \"a\rb\rc\" ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -246,8 +202,8 @@ mod tests {
             </Step>
             "#;
         let expected_output =
-            Some("Insert Text [ Select ; Target: Foo::id[5] ; \"a\rb\rc\" ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some("Insert Text [ Select: ON ; Target: Foo::id[5] ; \"a\rb\rc\" ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -274,7 +230,7 @@ mod tests {
             "#;
 
         let expected_output =
-            Some("Text einfügen [ Select ; Target: $idleCalcExpression ; \"\"hello\" & \rList(\"RemoteControl.PressKey\" ; \"l\" ; \"l\" )\" ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+            Some("Text einfügen [ Auswahl: ON ; Target: $idleCalcExpression ; \"\"hello\" & \rList(\"RemoteControl.PressKey\" ; \"l\" ; \"l\" )\" ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
@@ -0,0 +1,79 @@
+use crate::script_steps::step::Step;
+
+/// Insert from Index, Insert from Last Visited and Insert Current Date, Time and User Name with
+/// their select flag and the target field or variable. Without a target, the active field is used.
+pub fn sanitize(step: &Step) -> String {
+    let mut parameters = Vec::new();
+    parameters.extend(step.booleans().last().and_then(|select| select.display()));
+    match step.target() {
+        Some(target) => parameters.extend(target.display_parameter()),
+        None => parameters.extend(
+            step.field()
+                .and_then(|field| field.display())
+                .map(|field| format!("Target: {field}")),
+        ),
+    }
+
+    step.display_with(&parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_insert_current_date() {
+        let xml = r#"
+            <Step index="234" id="13" name="Insert Current Date" enable="True">
+                <UUID>C65167EA-C50C-72E2-3831-F41ABFA15453</UUID>
+                <OwnerID></OwnerID>
+                <Options>4096</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Select" id="4096" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="Target">
+                        <Variable value="$UseSetVariable">
+                            <repetition>
+                                <Calculation datatype="1" position="32">
+                                    <Calculation>
+                                        <Text><![CDATA[1]]></Text>
+                                    </Calculation>
+                                </Calculation>
+                            </repetition>
+                        </Variable>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Insert Current Date [ Select: ON ; Target: $UseSetVariable ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_insert_from_index() {
+        let xml = r#"
+            <Step index="326" id="11" name="Aus Index einfügen" enable="True">
+                <UUID>F9FEE243-CE09-3ED4-7571-3D0AA2988B81</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Auswahl" id="4096" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="FieldReference">
+                        <FieldReference id="1" name="_gInputText1" UUID="">
+                            <TableOccurrenceReference id="1065089" name="_Home"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some("Aus Index einfügen [ Auswahl: OFF ; Target: _Home::_gInputText1 ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
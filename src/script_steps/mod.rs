@@ -1,19 +1,36 @@
 pub(crate) mod constants;
+pub(crate) mod parameters;
 pub(crate) mod sanitizer;
 
+mod accounts;
+mod arrange;
 mod close_window;
+mod editing;
 mod exit_script;
+mod file_maintenance;
 mod go_to_object;
 mod go_to_portal_row;
 mod go_to_record;
+mod insert_calculated_result;
+mod insert_from_device;
 mod insert_text;
-mod is_enabled;
+mod insert_value;
 mod omit_multiple_records;
-mod parameters;
+mod on_off;
+mod open_dialog;
+pub(crate) mod parse_error;
+mod pause_resume_script;
 mod perform_find;
+mod perform_find_replace;
 mod perform_script;
+mod records;
 mod refresh_object;
+mod relookup_field_contents;
 mod replace_field_contents;
 mod sanitize;
+mod set_field_by_name;
 mod set_field_data;
+mod set_selection;
 mod set_variable;
+mod set_zoom_level;
+pub(crate) mod step;
@@ -1,89 +1,26 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
+/// Omit Multiple Records with its dialog flag and the calculation of the number of records, and
+/// the other found set steps without options: Show All Records, Omit Record and Show Omitted Only
+pub fn sanitize(step: &Step) -> String {
+    let mut params = Vec::new();
+    params.extend(
+        step.all_booleans()
+            .last()
+            .and_then(|dialog| dialog.display()),
+    );
+    params.extend(
+        step.all_calculations()
+            .pop()
+            .filter(|calculation| !calculation.is_empty()),
+    );
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut option_name = String::new();
-    let mut state = false;
-    let mut calculation = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    match get_attribute(&e, "name") {
-                        None => {}
-                        Some(value) => {
-                            name = value.to_string();
-                        }
-                    }
-                    continue;
-                }
-                b"Boolean" => {
-                    if let Some(name) = get_attribute(&e, "type") {
-                        option_name = name.to_string();
-                    }
-
-                    match get_attribute(&e, "value").unwrap().as_str() {
-                        "True" => {
-                            state = true;
-                        }
-                        "False" => {
-                            state = false;
-                        }
-                        _ => {}
-                    }
-                    continue;
-                }
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap()
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if option_name.is_empty() && calculation.is_empty() {
-        Some(format!("{name} []"))
-    } else if !calculation.is_empty() {
-        Some(format!(
-            "{} [ {}: {} ; {} ]",
-            name,
-            option_name,
-            match state {
-                true => "ON",
-                false => "OFF",
-            },
-            calculation
-        ))
-    } else {
-        Some(format!(
-            "{} [ {}: {} ]",
-            name,
-            option_name,
-            match state {
-                true => "ON",
-                false => "OFF",
-            }
-        ))
-    }
+    step.display_with(&params)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -98,7 +35,21 @@ mod tests {
         "#;
 
         let expected_output = Some("Mehrere ausschließen [ Mit Dialog: ON ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_without_options() {
+        let xml = r#"
+            <Step index="99" id="25" name="Aktuellen Datens. ausschließen" enable="True">
+                <UUID>48F2B9CA-E495-8034-6D76-F50C9A8F0DC2</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+            </Step>
+        "#;
+
+        let expected_output = Some("Aktuellen Datens. ausschließen".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -125,6 +76,6 @@ mod tests {
         "#;
 
         let expected_output = Some("Mehrere ausschließen [ Mit Dialog: OFF ; 123 ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
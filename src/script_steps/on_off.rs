@@ -0,0 +1,86 @@
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// Steps switching a setting of the environment on or off, like Allow Formatting Bar, Set Use
+/// System Formats, Set Multi-User and Set Layout Object Animation, whose states are named in the
+/// export's language
+pub fn sanitize(step: &Step) -> String {
+    let state = step
+        .parameters
+        .iter()
+        .rev()
+        .find_map(|parameter| match parameter {
+            Parameter::Boolean(boolean) => {
+                Some(Boolean::bool_to_string(boolean.value == Some(true)))
+            }
+            Parameter::List(list) => list.name.clone(),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    match state.is_empty() {
+        true => step.name.clone(),
+        false => format!("{} [ {state} ]", step.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_boolean() {
+        let xml = r#"
+            <Step index="176" id="115" name="Formatierungsleiste zulassen" enable="True">
+                <UUID>57446146-216E-316D-EC71-752FCBD52F82</UUID>
+                <OwnerID></OwnerID>
+                <Options>65536</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Boolean">
+                        <Boolean id="131072" value="False"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Formatierungsleiste zulassen [ OFF ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_list() {
+        let xml = r#"
+            <Step index="136" id="84" name="Netzwerkzugriff einstellen" enable="True">
+                <Options>2</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="List">
+                        <List name="Ein (Versteckt)" value="2"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Netzwerkzugriff einstellen [ Ein (Versteckt) ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_layout_object_animation() {
+        let xml = r#"
+            <Step index="31" id="168" name="Set Layout Object Animation" enable="True">
+                <UUID>8A95ACA3-7DA7-3B6D-4AE6-6C554D2D1C0D</UUID>
+                <OwnerID></OwnerID>
+                <Options>196608</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Boolean">
+                        <Boolean id="131072" value="True"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Set Layout Object Animation [ ON ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// The "Open …" steps opening a dialog or workspace, like Open Manage Database or Open Hosts.
+/// Current exports have no parameters for them; options of other versions are shown by their
+/// labels.
+pub fn sanitize(step: &Step) -> String {
+    let options: Vec<String> = step
+        .parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Parameter::Boolean(boolean) => boolean.display(),
+            Parameter::List(list) => list.display(),
+            Parameter::Generic(generic) => generic
+                .find("List")
+                .and_then(|list| list.attribute("name"))
+                .map(str::to_string),
+            _ => None,
+        })
+        .collect();
+
+    step.display_with(&options)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test() {
+        let xml = r#"
+            <Step index="164" id="38" name="Datenbank verwalten" enable="True">
+                <UUID>0E6F1CA3-9284-DE99-491D-DA7E883346A7</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+            </Step>
+        "#;
+
+        let expected_output = Some("Datenbank verwalten".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_options() {
+        let xml = r#"
+            <Step index="15" id="172" name="Open Upload to Host" enable="True">
+                <Options>0</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="Host">
+                        <List name="Local" value="0"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Open Upload to Host [ With dialog: OFF ; Local ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,26 +1,38 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::attributes::get_attribute;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Animation {
     pub value: Option<String>,
 }
 
 impl Animation {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Result<Animation, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Animation, ParseError> {
         let mut depth = 1;
         let mut item = Animation { value: None };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     if e.name().as_ref() == b"Animation" {
-                        item.value = get_attribute(&e, "name");
+                        item.value = parse_unescaped_attribute(&e, "name");
                     }
                     depth += 1;
                 }
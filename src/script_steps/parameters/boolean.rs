@@ -1,58 +1,78 @@
+use std::cell::Cell;
+
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
 use crate::script_steps::parameters::constants::{
-    CommitRecordRequestsOptions, GoToFieldOptions, RefreshWindowOptions,
+    CommitRecordRequestsOptions, GoToFieldOptions, RefreshWindowOptions, UndoRedoOptions,
 };
-use crate::utils::attributes::get_attributes;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::{parse_unescaped_attribute, unescaped_attribute};
+use crate::utils::buffer_pool::PooledBuffer;
+use crate::{BoolStyle, Locale};
+
+thread_local! {
+    static BOOL_STYLE: Cell<BoolStyle> = const { Cell::new(BoolStyle::Words) };
+    static BOOL_LOCALE: Cell<Option<Locale>> = const { Cell::new(None) };
+}
+
+/// Render all ON/OFF options rendered by `f` on this thread as configured
+pub fn with_bool_style<T>(bool_style: BoolStyle, f: impl FnOnce() -> T) -> T {
+    let previous = BOOL_STYLE.with(|cell| cell.replace(bool_style));
+    let result = f();
+    BOOL_STYLE.with(|cell| cell.set(previous));
+    result
+}
+
+/// Render the ON/OFF words rendered by `f` on this thread in the language of `locale`
+pub fn with_bool_locale<T>(locale: Option<Locale>, f: impl FnOnce() -> T) -> T {
+    let previous = BOOL_LOCALE.with(|cell| cell.replace(locale));
+    let result = f();
+    BOOL_LOCALE.with(|cell| cell.set(previous));
+    result
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Boolean {
     pub step_id: u32,
     pub id: Option<u32>,
     pub name: Option<String>,
     pub value: Option<bool>,
+    /// The state the flag sets, e.g. `Activate` of Enable Account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
 }
 
 impl Boolean {
     pub fn from_xml(
         reader: &mut Reader<&[u8]>,
         _e: &BytesStart,
-        _step_id: &u32,
-    ) -> Result<Boolean, String> {
+        step_id: &u32,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Boolean, ParseError> {
         let mut depth = 1;
         let mut item = Boolean {
-            step_id: *_step_id,
-            id: None,
-            name: None,
-            value: None,
+            step_id: *step_id,
+            ..Default::default()
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
                     if let b"Boolean" = e.name().as_ref() {
-                        for attr in get_attributes(&e).unwrap() {
-                            match attr.0.as_str() {
-                                "id" => {
-                                    if let Ok(id) = attr.1.parse::<u32>() {
-                                        item.id = Some(id);
-                                    }
-                                }
-                                "type" => item.name = Some(attr.1),
-                                "value" => match attr.1.as_str() {
-                                    "True" => item.value = Some(true),
-                                    "False" => item.value = Some(false),
-                                    _ => {}
-                                },
-                                _ => {}
-                            }
-                        }
+                        item = Boolean::from_element(&e, step_id);
                     }
                 }
                 Ok(Event::End(_)) => {
@@ -69,6 +89,36 @@ impl Boolean {
         Ok(item)
     }
 
+    /// Reads the flag from a `Boolean` element
+    pub fn from_element(e: &BytesStart, step_id: &u32) -> Boolean {
+        Boolean {
+            step_id: *step_id,
+            id: unescaped_attribute(e, "id").and_then(|id| id.parse().ok()),
+            name: parse_unescaped_attribute(e, "type"),
+            value: match unescaped_attribute(e, "value").as_deref() {
+                Some("True") => Some(true),
+                Some("False") => Some(false),
+                _ => None,
+            },
+            state: parse_unescaped_attribute(e, "name"),
+        }
+    }
+
+    /// Reads the flag from a `Boolean` element inside a parameter without a dedicated parser
+    pub fn from_parsed_element(element: &Element, step_id: &u32) -> Boolean {
+        Boolean {
+            step_id: *step_id,
+            id: element.attribute("id").and_then(|id| id.parse().ok()),
+            name: element.attribute("type").map(str::to_string),
+            value: match element.attribute("value") {
+                Some("True") => Some(true),
+                Some("False") => Some(false),
+                _ => None,
+            },
+            state: element.attribute("name").map(str::to_string),
+        }
+    }
+
     pub fn should_hide_bool(&self) -> bool {
         let step_id = id_to_script_step(&self.step_id);
         let param_id = self.id.unwrap_or(0);
@@ -88,13 +138,27 @@ impl Boolean {
                 ScriptStep::RefreshWindow,
                 RefreshWindowOptions::FLUSH_CACHED_EXTERNAL_DATA,
             ) | (ScriptStep::GoToField, GoToFieldOptions::SELECT_PERFORM,)
+                | (ScriptStep::UndoRedo, UndoRedoOptions::COLLAPSED)
         )
     }
 
     pub fn bool_to_string(bool: bool) -> String {
-        match bool {
-            true => "ON".to_string(),
-            false => "OFF".to_string(),
+        let (on, off) = BOOL_LOCALE
+            .with(Cell::get)
+            .map_or(("ON", "OFF"), |locale| locale.on_off());
+        match (BOOL_STYLE.with(Cell::get), bool) {
+            (BoolStyle::Words, true) => on.to_string(),
+            (BoolStyle::Words, false) => off.to_string(),
+            (BoolStyle::Symbols, true) => "✓".to_string(),
+            (BoolStyle::Symbols, false) => "✗".to_string(),
+        }
+    }
+
+    /// A labeled option like `With dialog: OFF`, or `✗ With dialog` with symbols
+    pub fn labeled_bool_to_string(label: &str, bool: bool) -> String {
+        match BOOL_STYLE.with(Cell::get) {
+            BoolStyle::Words => format!("{label}: {}", Self::bool_to_string(bool)),
+            BoolStyle::Symbols => format!("{} {label}", Self::bool_to_string(bool)),
         }
     }
 
@@ -108,10 +172,9 @@ impl Boolean {
         }
 
         match &self.name {
-            Some(name) => self.value.map(|bool_value| {
-                let formatted_string = format!("{}: {}", name, Self::bool_to_string(bool_value));
-                formatted_string
-            }),
+            Some(name) => self
+                .value
+                .map(|bool_value| Self::labeled_bool_to_string(name, bool_value)),
             None => self.value.map(Self::bool_to_string),
         }
     }
@@ -122,7 +185,9 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
-    use crate::script_steps::parameters::boolean::Boolean;
+    use crate::diagnostics::Diagnostics;
+    use crate::script_steps::parameters::boolean::{with_bool_locale, with_bool_style, Boolean};
+    use crate::{BoolStyle, Locale};
 
     #[test]
     fn test() {
@@ -142,10 +207,15 @@ mod tests {
         let script_id: u32 = 0;
 
         assert_eq!(
-            Boolean::from_xml(&mut reader, &element, &script_id)
-                .unwrap()
-                .display()
-                .unwrap(),
+            Boolean::from_xml(
+                &mut reader,
+                &element,
+                &script_id,
+                &mut Diagnostics::default()
+            )
+            .unwrap()
+            .display()
+            .unwrap(),
             expected_output
         );
     }
@@ -167,11 +237,76 @@ mod tests {
         let expected_output = "OFF".to_string();
         let script_id: u32 = 0;
         assert_eq!(
-            Boolean::from_xml(&mut reader, &element, &script_id)
-                .unwrap()
-                .display()
-                .unwrap(),
+            Boolean::from_xml(
+                &mut reader,
+                &element,
+                &script_id,
+                &mut Diagnostics::default()
+            )
+            .unwrap()
+            .display()
+            .unwrap(),
             expected_output
         );
     }
+
+    #[test]
+    fn test_from_element() {
+        let xml = r#"<Boolean type="With dialog" id="128" value="True"></Boolean>"#;
+
+        let mut reader = Reader::from_str(xml);
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let script_id: u32 = 9;
+        assert_eq!(
+            Boolean::from_element(&element, &script_id).display(),
+            Some("With dialog: ON".to_string())
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        let xml = r#"<Boolean type="With dialog" id="128" value="False"></Boolean>"#;
+
+        let mut reader = Reader::from_str(xml);
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let script_id: u32 = 9;
+        let boolean = Boolean::from_element(&element, &script_id);
+        assert_eq!(
+            with_bool_style(BoolStyle::Symbols, || boolean.display()),
+            Some("✗ With dialog".to_string())
+        );
+        assert_eq!(boolean.display(), Some("With dialog: OFF".to_string()));
+    }
+
+    #[test]
+    fn test_locale() {
+        let xml = r#"<Boolean type="With dialog" id="128" value="False"></Boolean>"#;
+
+        let mut reader = Reader::from_str(xml);
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let script_id: u32 = 9;
+        let boolean = Boolean::from_element(&element, &script_id);
+        assert_eq!(
+            with_bool_locale(Some(Locale::De), || boolean.display()),
+            Some("With dialog: Aus".to_string())
+        );
+        assert_eq!(
+            with_bool_locale(Some(Locale::De), || {
+                with_bool_style(BoolStyle::Symbols, || boolean.display())
+            }),
+            Some("✗ With dialog".to_string())
+        );
+    }
 }
@@ -1,6 +1,10 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::buffer_pool::PooledBuffer;
 use crate::utils::xml_utils::{local_name_to_string, text_to_string};
 
 #[derive(Debug, Default)]
@@ -13,17 +17,21 @@ impl BooleanContainer {
     pub fn from_xml(
         reader: &mut Reader<&[u8]>,
         e: &BytesStart,
-    ) -> Result<BooleanContainer, String> {
+        diagnostics: &mut Diagnostics,
+    ) -> Result<BooleanContainer, ParseError> {
         let mut depth = 1;
         let mut item = BooleanContainer {
             name: local_name_to_string(e.name().as_ref()),
             value: None,
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(_)) => {
                     depth += 1;
@@ -47,13 +55,6 @@ impl BooleanContainer {
         Ok(item)
     }
 
-    pub fn bool_to_string(bool: bool) -> String {
-        match bool {
-            true => "ON".to_string(),
-            false => "OFF".to_string(),
-        }
-    }
-
     pub fn display(&self) -> Option<String> {
         if self.name.as_str() == "DimParentWindow" {
             return None;
@@ -62,18 +63,13 @@ impl BooleanContainer {
         if matches!(
             self.name.as_str(),
             "Close" | "Minimize" | "Maximize" | "Resize" | "MenuBar" | "Toolbar"
-        ) && self.value.unwrap()
+        ) && self.value == Some(true)
         {
             return None;
         }
 
         self.value.map(|bool_value| {
-            let formatted_string = format!(
-                "{}: {}",
-                self.name.replace("MenuBar", "Menu"),
-                Self::bool_to_string(bool_value)
-            );
-            formatted_string
+            Boolean::labeled_bool_to_string(&self.name.replace("MenuBar", "Menu"), bool_value)
         })
     }
 }
@@ -83,6 +79,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::boolean_container::BooleanContainer;
 
     #[test]
@@ -97,7 +94,7 @@ mod tests {
 
         let expected_output = "Close: OFF".to_string();
         assert_eq!(
-            BooleanContainer::from_xml(&mut reader, &element)
+            BooleanContainer::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap_or_default(),
@@ -1,50 +1,63 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
-
-use crate::utils::attributes::get_attribute;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::calc_formatter::format_rendered_calculation;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::{parse_unescaped_attribute, unescaped_attribute};
+use crate::utils::buffer_pool::PooledBuffer;
 use crate::utils::xml_utils::cdata_to_string;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Button {
     pub label: Option<String>,
+    /// Not rendered, like in FileMaker's script workspace
     pub commit: bool,
 }
 
 impl Button {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Button {
-        let mut label = get_attribute(e, "value");
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Button, ParseError> {
+        let mut label = parse_unescaped_attribute(e, "value").map(format_rendered_calculation);
         let mut commit = false;
         let mut in_text = false;
+        let mut text = String::new();
         let mut depth = 1;
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(inner)) => {
                     depth += 1;
                     match inner.name().as_ref() {
                         b"Text" => in_text = true,
                         b"Boolean" => {
-                            if let Some(val) = get_attribute(&inner, "value") {
+                            if let Some(val) = unescaped_attribute(&inner, "value") {
                                 commit = val == "True";
                             }
                         }
                         _ => {}
                     }
                 }
-                Ok(Event::CData(cdata)) => {
-                    if in_text && label.is_none() {
-                        let text = cdata_to_string(&cdata);
-                        if !text.is_empty() {
-                            label = Some(text);
-                        }
-                    }
+                Ok(Event::CData(cdata)) if in_text => {
+                    text.push_str(&cdata_to_string(&cdata));
                 }
                 Ok(Event::End(end)) => {
                     if end.name().as_ref() == b"Text" {
                         in_text = false;
+                        if label.is_none() && !text.is_empty() {
+                            label = Some(format_rendered_calculation(std::mem::take(&mut text)));
+                        }
                     }
                     depth -= 1;
                     if depth == 0 {
@@ -56,7 +69,7 @@ impl Button {
             buf.clear();
         }
 
-        Button { label, commit }
+        Ok(Button { label, commit })
     }
 
     pub fn display(&self, button_type: &str) -> Option<String> {
@@ -82,6 +95,7 @@ mod tests {
     use quick_xml::Reader;
 
     use super::Button;
+    use crate::diagnostics::Diagnostics;
 
     #[test]
     fn test_button_with_calculation() {
@@ -102,7 +116,7 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let button = Button::from_xml(&mut reader, &element);
+        let button = Button::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(button.label, Some(r#""OK""#.to_string()));
         assert!(!button.commit);
         assert_eq!(
@@ -125,7 +139,7 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let button = Button::from_xml(&mut reader, &element);
+        let button = Button::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(button.label, Some("Save".to_string()));
         assert!(button.commit);
         assert_eq!(
@@ -148,7 +162,7 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let button = Button::from_xml(&mut reader, &element);
+        let button = Button::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(button.label, None);
         assert!(!button.commit);
         assert_eq!(button.display("Button2"), None);
@@ -168,7 +182,7 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let button = Button::from_xml(&mut reader, &element);
+        let button = Button::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(
             button.display("Button3"),
             Some("Button 3: Maybe".to_string())
@@ -1,34 +1,56 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::calc_formatter::format_rendered_calculation;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
 use crate::utils;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Calculation {
     pub calculation: Option<String>,
 }
 
 impl Calculation {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Result<Calculation, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Calculation, ParseError> {
         let mut depth = 1;
         let mut in_text = false;
         let mut item = Calculation { calculation: None };
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Start(e)) => {
                     depth += 1;
-                    if e.name().as_ref() == b"Text" {
-                        in_text = true
+                    in_text = e.name().as_ref() == b"Text";
+                    if in_text {
+                        // Some exports store the calculation in an attribute instead of CDATA
+                        if let Some(value) = parse_unescaped_attribute(&e, "value") {
+                            item.push_text(&value);
+                        }
                     }
                 }
                 Ok(Event::Eof) => break,
-                Ok(Event::CData(e)) => {
-                    if !in_text {
-                        continue;
-                    }
-                    item.calculation = Some(utils::xml_utils::cdata_to_string(&e));
+                // A calculation containing `]]>`, e.g. in a comment, is split into several CDATA sections
+                Ok(Event::CData(e)) if in_text => {
+                    item.push_text(&utils::xml_utils::cdata_to_string(&e));
+                }
+                Ok(Event::Text(e)) if in_text => {
+                    item.push_text(&utils::xml_utils::text_to_string(&e));
+                }
+                Ok(Event::GeneralRef(e)) if in_text => {
+                    item.push_text(&utils::xml_utils::general_ref_to_string(&e, false));
                 }
                 Ok(Event::End(e)) => {
                     depth -= 1;
@@ -48,13 +70,17 @@ impl Calculation {
         Ok(item)
     }
 
+    fn push_text(&mut self, text: &str) {
+        self.calculation.get_or_insert_default().push_str(text);
+    }
+
     pub fn display(&self) -> Option<String> {
         match &self.calculation {
             Some(item) => {
                 if item.is_empty() {
                     None
                 } else {
-                    Some(item.clone())
+                    Some(format_rendered_calculation(item.clone()))
                 }
             }
             None => None,
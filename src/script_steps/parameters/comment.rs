@@ -1,25 +1,39 @@
-use quick_xml::escape::unescape;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::attributes::get_attribute;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
-pub struct Comment {}
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Comment {
+    pub text: String,
+}
 
 impl Comment {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Result<String, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Comment, ParseError> {
         let mut depth = 1;
-        let mut comment = String::new();
-        let mut buf: Vec<u8> = Vec::new();
+        let mut item = Comment::default();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
+                Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
 
                     if e.name().as_ref() == b"Comment" {
-                        comment = get_attribute(&e, "value").unwrap_or_default();
+                        item.text = parse_unescaped_attribute(&e, "value").unwrap_or_default();
                     };
                 }
                 Ok(Event::End(_)) => {
@@ -33,6 +47,10 @@ impl Comment {
             buf.clear();
         }
 
-        Ok(unescape(&comment).unwrap().to_string())
+        Ok(item)
+    }
+
+    pub fn display(&self) -> Option<String> {
+        Some(self.text.clone())
     }
 }
@@ -17,3 +17,8 @@ pub struct GoToFieldOptions;
 impl GoToFieldOptions {
     pub const SELECT_PERFORM: u32 = 4096;
 }
+
+pub struct UndoRedoOptions;
+impl UndoRedoOptions {
+    pub const COLLAPSED: u32 = 33554432;
+}
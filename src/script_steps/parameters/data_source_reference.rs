@@ -1,31 +1,46 @@
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parse_error::ParseError;
 use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DataSourceReference {
     pub id: Option<String>,
     pub name: Option<String>,
+    /// What's inside the reference, e.g. the XML and XSL sources of Convert File
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Element>,
 }
 
 impl DataSourceReference {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _e: &BytesStart) -> Option<DataSourceReference> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<DataSourceReference, ParseError> {
         let mut depth = 1;
-        let mut item = DataSourceReference {
-            id: None,
-            name: None,
-        };
+        let mut item = DataSourceReference::default();
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
                     if e.name().as_ref() == b"DataSourceReference" {
                         item.id = parse_unescaped_attribute(&e, "id");
                         item.name = parse_unescaped_attribute(&e, "name");
+                        item.elements = Element::from_xml(reader, &e, diagnostics)?.children;
+                        depth -= 1;
                     }
                 }
                 Ok(Event::End(_)) => {
@@ -39,7 +54,7 @@ impl DataSourceReference {
             buf.clear();
         }
 
-        Some(item)
+        Ok(item)
     }
 
     pub fn display(&self) -> Option<String> {
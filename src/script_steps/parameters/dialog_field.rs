@@ -1,11 +1,16 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::parameters::calculation::Calculation;
 use crate::script_steps::parameters::target::Target;
-use crate::utils::attributes::get_attribute;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DialogField {
     pub target: Option<String>,
     pub label: Option<String>,
@@ -13,31 +18,38 @@ pub struct DialogField {
 }
 
 impl DialogField {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _e: &BytesStart) -> DialogField {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<DialogField, ParseError> {
         let mut item = DialogField::default();
         let mut depth = 1;
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(inner)) => {
                     depth += 1;
                     match inner.name().as_ref() {
                         b"Parameter" => {
-                            if let Some(param_type) = get_attribute(&inner, "type") {
-                                match param_type.as_str() {
+                            if let Some(param_type) = unescaped_attribute(&inner, "type") {
+                                match param_type.as_ref() {
                                     "Target" => {
-                                        if let Ok(target) = Target::from_xml(reader, &inner) {
-                                            item.target = target.display();
-                                        }
+                                        item.target =
+                                            Target::from_xml(reader, &inner, diagnostics)?
+                                                .display();
                                         depth -= 1;
                                     }
                                     "Label" => {
-                                        if let Ok(calc) = Calculation::from_xml(reader, &inner) {
-                                            item.label = calc.display();
-                                        }
+                                        item.label =
+                                            Calculation::from_xml(reader, &inner, diagnostics)?
+                                                .display();
                                         depth -= 1;
                                     }
                                     _ => {}
@@ -46,9 +58,9 @@ impl DialogField {
                         }
                         b"Boolean" => {
                             let is_password =
-                                get_attribute(&inner, "type").as_deref() == Some("Password");
+                                unescaped_attribute(&inner, "type").as_deref() == Some("Password");
                             let is_true =
-                                get_attribute(&inner, "value").as_deref() == Some("True");
+                                unescaped_attribute(&inner, "value").as_deref() == Some("True");
                             if is_password && is_true {
                                 item.password = true;
                             }
@@ -67,7 +79,7 @@ impl DialogField {
             buf.clear();
         }
 
-        item
+        Ok(item)
     }
 
     pub fn display(&self, field_type: &str) -> Option<String> {
@@ -102,6 +114,7 @@ mod tests {
     use quick_xml::Reader;
 
     use super::DialogField;
+    use crate::diagnostics::Diagnostics;
 
     #[test]
     fn test_field_with_variable_and_label() {
@@ -135,7 +148,8 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let field = DialogField::from_xml(&mut reader, &element);
+        let field =
+            DialogField::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(field.target, Some("$input1".to_string()));
         assert_eq!(field.label, Some(r#""label1""#.to_string()));
         assert!(!field.password);
@@ -177,7 +191,8 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let field = DialogField::from_xml(&mut reader, &element);
+        let field =
+            DialogField::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert!(field.password);
         assert_eq!(
             field.display("Field1"),
@@ -217,10 +232,45 @@ mod tests {
             _ => panic!("Wrong read event"),
         };
 
-        let field = DialogField::from_xml(&mut reader, &element);
+        let field =
+            DialogField::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
         assert_eq!(
             field.display("Field2"),
             Some(r#"Input 2: $input2 ; Label 2: "second""#.to_string())
         );
     }
+
+    #[test]
+    fn test_field_with_repetition_calculation() {
+        let xml = r#"
+            <Parameter type="Field3">
+                <Parameter type="Target">
+                    <FieldReference id="18" name="MyGlobal_g">
+                        <repetition>
+                            <Calculation datatype="1" position="32">
+                                <Calculation>
+                                    <Text><![CDATA[$i + 1]]></Text>
+                                </Calculation>
+                            </Calculation>
+                        </repetition>
+                        <TableOccurrenceReference id="1065089" name="TestTable"></TableOccurrenceReference>
+                    </FieldReference>
+                </Parameter>
+                <Boolean type="Password" value="False"></Boolean>
+            </Parameter>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let field =
+            DialogField::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
+        assert_eq!(
+            field.display("Field3"),
+            Some("Input 3: TestTable::MyGlobal_g[$i + 1]".to_string())
+        );
+    }
 }
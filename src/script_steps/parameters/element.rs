@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::calculation::Calculation;
+use crate::script_steps::parameters::target::Target;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{reference_name, SymbolKind};
+use crate::utils::attributes::get_attributes;
+use crate::utils::buffer_pool::PooledBuffer;
+use crate::utils::xml_utils::{
+    cdata_to_string, general_ref_to_string, local_name_to_string, skip_rest_of_element,
+    text_to_string, unescape_entities,
+};
+
+/// An element of a parameter without a dedicated parser, kept for the steps rendering it themselves.
+/// Calculations, fields and variables are kept rendered as their text.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Element {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    /// Read an element with its content, up to and including its end
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Element, ParseError> {
+        // The open elements, starting with the one being read
+        let mut open = vec![Element::from_start(e)];
+        let mut text = String::new();
+
+        let mut buf = PooledBuffer::take();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => {
+                    let rendered = match e.name().as_ref() {
+                        b"Calculation" => Calculation::from_xml(reader, &e, diagnostics)?.display(),
+                        b"FieldReference" | b"Variable" => {
+                            Target::from_xml(reader, &e, diagnostics)?.display()
+                        }
+                        b"DDRREF" => {
+                            skip_rest_of_element(reader, &e);
+                            buf.clear();
+                            continue;
+                        }
+                        _ => {
+                            Self::set_text(open.last_mut(), &mut text);
+                            open.push(Element::from_start(&e));
+                            buf.clear();
+                            continue;
+                        }
+                    };
+                    let mut element = Element::from_start(&e);
+                    element.text = rendered;
+                    if let Some(parent) = open.last_mut() {
+                        parent.children.push(element);
+                    }
+                }
+                Ok(Event::Empty(e)) => {
+                    if let Some(parent) = open.last_mut() {
+                        parent.children.push(Element::from_start(&e));
+                    }
+                }
+                Ok(Event::CData(e)) => text.push_str(&cdata_to_string(&e)),
+                Ok(Event::Text(e)) => text.push_str(&text_to_string(&e)),
+                Ok(Event::GeneralRef(e)) => text.push_str(&general_ref_to_string(&e, false)),
+                Ok(Event::End(_)) => {
+                    Self::set_text(open.last_mut(), &mut text);
+                    if open.len() == 1 {
+                        break;
+                    }
+                    if let (Some(element), Some(parent)) = (open.pop(), open.last_mut()) {
+                        parent.children.push(element);
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(open.swap_remove(0))
+    }
+
+    /// The element without its content; references are named like they are rendered
+    fn from_start(e: &BytesStart) -> Element {
+        let name = local_name_to_string(e.name().as_ref());
+        let mut attributes: BTreeMap<String, String> = get_attributes(e)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key, unescape_entities(&value)))
+            .collect();
+        if name == "ScriptReference" {
+            if let Some(script_name) = reference_name(e, SymbolKind::Script) {
+                attributes.insert("name".to_string(), script_name);
+            }
+        }
+        Element {
+            name,
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    fn set_text(element: Option<&mut Element>, text: &mut String) {
+        let trimmed = text.trim();
+        if let Some(element) = element.filter(|_| !trimmed.is_empty()) {
+            element.text.get_or_insert_default().push_str(trimmed);
+        }
+        text.clear();
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// This element and all inside it, in document order
+    pub fn descendants(&self) -> Vec<&Element> {
+        let mut elements = vec![self];
+        for child in &self.children {
+            elements.extend(child.descendants());
+        }
+        elements
+    }
+
+    /// The first element with the given name among this one and those inside it
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.descendants()
+            .into_iter()
+            .find(|element| element.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    use super::*;
+
+    #[test]
+    fn test_from_xml() {
+        let xml = r#"
+            <Parameter type="Variable">
+                <value>
+                    <Calculation datatype="1" position="0">
+                        <Calculation>
+                            <DDRREF kind="ChunkList" hash="A">_0</DDRREF>
+                            <Text><![CDATA[$a & "b"]]></Text>
+                        </Calculation>
+                    </Calculation>
+                </value>
+                <Name value="$result"></Name>
+                <Location><![CDATA[file:a.txt]]>&#13;<![CDATA[file:b.txt]]></Location>
+                <Empty name="Tom &amp; Jerry"/>
+            </Parameter>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let Ok(Event::Start(e)) = reader.read_event() else {
+            panic!("Wrong read event")
+        };
+
+        let parameter = Element::from_xml(&mut reader, &e, &mut Diagnostics::default()).unwrap();
+        assert_eq!(parameter.attribute("type"), Some("Variable"));
+        let children = parameter.children;
+        let names: Vec<&str> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, ["value", "Name", "Location", "Empty"]);
+        assert_eq!(
+            children[0]
+                .find("Calculation")
+                .and_then(|c| c.text.as_deref()),
+            Some(r#"$a & "b""#)
+        );
+        assert_eq!(children[1].attribute("value"), Some("$result"));
+        assert_eq!(children[2].text.as_deref(), Some("file:a.txt\rfile:b.txt"));
+        assert_eq!(children[3].attribute("name"), Some("Tom & Jerry"));
+        assert!(reader.read_event().is_ok_and(|event| event == Event::Eof));
+    }
+}
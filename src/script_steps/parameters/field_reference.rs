@@ -1,66 +1,76 @@
+use std::cell::Cell;
+
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::repetition::Repetition;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{field_name, id_attribute, reference_name, SymbolKind};
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
+use crate::FieldNames;
+
+thread_local! {
+    static FIELD_NAMES: Cell<FieldNames> = const { Cell::new(FieldNames::Qualified) };
+}
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::{get_attribute, get_attributes};
+/// Name all fields rendered by `f` on this thread as configured
+pub fn with_field_names<T>(field_names: FieldNames, f: impl FnOnce() -> T) -> T {
+    let previous = FIELD_NAMES.with(|cell| cell.replace(field_names));
+    let result = f();
+    FIELD_NAMES.with(|cell| cell.set(previous));
+    result
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct FieldReference {
     pub table_reference: Option<String>,
     pub field_reference: Option<String>,
-    pub repetition: Option<i32>,
+    /// Repetition other than the first, e.g. `2` or `$i + 1`
+    pub repetition: Option<String>,
 }
 
 impl FieldReference {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Result<FieldReference, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<FieldReference, ParseError> {
         let mut depth = 1;
         let mut item = FieldReference {
             table_reference: None,
-            field_reference: get_attribute(e, "name"),
+            field_reference: parse_unescaped_attribute(e, "name"),
             repetition: None,
         };
+        let mut field_id = id_attribute(e);
+        let mut table_occurrence_id = None;
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
                     match e.name().as_ref() {
                         b"FieldReference" => {
-                            item.field_reference = get_attribute(&e, "name");
+                            item.field_reference = parse_unescaped_attribute(&e, "name");
+                            field_id = id_attribute(&e);
                         }
                         b"TableOccurrenceReference" => {
-                            for attr in get_attributes(&e).unwrap() {
-                                if attr.0 == "name" {
-                                    match e.name().as_ref() {
-                                        b"TableOccurrenceReference" => {
-                                            item.table_reference = Option::from(attr.1);
-                                        }
-                                        b"Calculation" => {
-                                            item.field_reference = Option::from(
-                                                Calculation::from_xml(reader, &e)
-                                                    .unwrap()
-                                                    .display()
-                                                    .unwrap(),
-                                            );
-                                            depth -= 1;
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
+                            item.table_reference = reference_name(&e, SymbolKind::TableOccurrence);
+                            table_occurrence_id = id_attribute(&e);
                         }
                         b"repetition" => {
-                            match get_attribute(&e, "value") {
-                                None => {}
-                                Some(repetition) => {
-                                    if let Ok(repetition) = repetition.parse::<i32>() {
-                                        item.repetition = Some(repetition)
-                                    }
-                                }
-                            };
+                            item.repetition =
+                                Repetition::from_xml(reader, &e, diagnostics)?.display();
+                            depth -= 1;
                         }
                         _ => {}
                     }
@@ -76,6 +86,19 @@ impl FieldReference {
             buf.clear();
         }
 
+        if item
+            .field_reference
+            .as_deref()
+            .unwrap_or_default()
+            .is_empty()
+        {
+            if let (Some(table_occurrence_id), Some(field_id)) = (table_occurrence_id, field_id) {
+                if let Some(name) = field_name(table_occurrence_id, field_id) {
+                    item.field_reference = Some(name);
+                }
+            }
+        }
+
         Ok(item)
     }
 
@@ -96,20 +119,13 @@ impl FieldReference {
             }
         };
 
-        let repetition = self.repetition.unwrap_or(1);
-        if repetition != 1 {
-            Some(format!(
-                "{}::{}[{}]",
-                table_reference.as_str(),
-                field_reference.as_str(),
-                repetition
-            ))
-        } else {
-            Some(format!(
-                "{}::{}",
-                table_reference.as_str(),
-                field_reference.as_str()
-            ))
+        let field = match FIELD_NAMES.with(Cell::get) {
+            FieldNames::Qualified => format!("{table_reference}::{field_reference}"),
+            FieldNames::Unqualified => field_reference,
+        };
+        match &self.repetition {
+            Some(repetition) => Some(format!("{field}[{repetition}]")),
+            None => Some(field),
         }
     }
 }
@@ -119,7 +135,12 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
-    use crate::script_steps::parameters::field_reference::FieldReference;
+    use crate::diagnostics::Diagnostics;
+    use std::sync::Arc;
+
+    use crate::script_steps::parameters::field_reference::{with_field_names, FieldReference};
+    use crate::symbols::{with_symbols, SymbolTable};
+    use crate::FieldNames;
 
     #[test]
     fn test_field_reference() {
@@ -138,7 +159,7 @@ mod tests {
 
         let expected_output = "Foo::Bar".to_string();
         assert_eq!(
-            FieldReference::from_xml(&mut reader, &element)
+            FieldReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
@@ -163,11 +184,107 @@ mod tests {
 
         let expected_output = "Foo::Bar[1337]".to_string();
         assert_eq!(
-            FieldReference::from_xml(&mut reader, &element)
+            FieldReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
+                .unwrap()
+                .display()
+                .unwrap(),
+            expected_output
+        );
+    }
+
+    #[test]
+    fn test_field_reference_with_repetition_calculation() {
+        let xml = r#"
+            <FieldReference id="6" name="TextField1">
+                <repetition>
+                    <Calculation datatype="1" position="10">
+                        <Calculation>
+                            <Text><![CDATA[Get ( ActiveRepetitionNumber ) + 1]]></Text>
+                        </Calculation>
+                    </Calculation>
+                </repetition>
+                <TableOccurrenceReference id="1065089" name="TestTable"></TableOccurrenceReference>
+            </FieldReference>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let expected_output =
+            "TestTable::TextField1[Get ( ActiveRepetitionNumber ) + 1]".to_string();
+        assert_eq!(
+            FieldReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
             expected_output
         );
     }
+
+    #[test]
+    fn test_unqualified() {
+        let xml = r#"
+            <FieldReference id="4" name="Bar">
+                <repetition value="2"></repetition>
+                <TableOccurrenceReference id="1065090" name="Foo"></TableOccurrenceReference>
+            </FieldReference>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let field_reference =
+            FieldReference::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
+        assert_eq!(
+            with_field_names(FieldNames::Unqualified, || field_reference.display()),
+            Some("Bar[2]".to_string())
+        );
+        assert_eq!(field_reference.display(), Some("Foo::Bar[2]".to_string()));
+    }
+
+    #[test]
+    fn test_field_name_from_catalog() {
+        let catalogs = r#"
+            <Structure>
+                <TableOccurrenceCatalog>
+                    <TableOccurrence id="1065090" name="Foo">
+                        <BaseTableSourceReference type="BaseTableReference">
+                            <BaseTableReference id="129" name="FooTable"></BaseTableReference>
+                        </BaseTableSourceReference>
+                    </TableOccurrence>
+                </TableOccurrenceCatalog>
+                <FieldsForTables>
+                    <FieldCatalog>
+                        <BaseTableReference id="129" name="FooTable"></BaseTableReference>
+                        <ObjectList membercount="1">
+                            <Field id="4" name="Bar"></Field>
+                        </ObjectList>
+                    </FieldCatalog>
+                </FieldsForTables>
+            </Structure>
+        "#;
+        let symbols = SymbolTable::from_reader(&mut Reader::from_str(catalogs));
+
+        let xml = r#"
+            <FieldReference id="4" name="">
+                <TableOccurrenceReference id="1065090" name=""></TableOccurrenceReference>
+            </FieldReference>
+        "#;
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let field_reference = with_symbols(Some(Arc::new(symbols)), || {
+            FieldReference::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap()
+        });
+        assert_eq!(field_reference.display(), Some("Foo::Bar".to_string()));
+    }
 }
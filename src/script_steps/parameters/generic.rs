@@ -0,0 +1,286 @@
+use quick_xml::events::BytesStart;
+use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parse_error::ParseError;
+
+/// Best-effort rendering of a parameter type without a dedicated parser
+/// Collects booleans, calculations, targets and named values from the parameter tree
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Generic {
+    pub parameter_type: String,
+    pub values: Vec<String>,
+    /// Text directly inside the parameter, e.g. `uninstall` of Configure Machine Learning Model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// The elements of the parameter, for the steps rendering it themselves
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Element>,
+}
+
+impl Generic {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        parameter_type: &str,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Generic, ParseError> {
+        let parameter = Element::from_xml(reader, e, diagnostics)?;
+        let mut item = Generic {
+            parameter_type: parameter_type.to_string(),
+            ..Default::default()
+        };
+        item.push_elements(&parameter.children, None);
+        if let Some(text) = &parameter.text {
+            item.push_value(None, text.clone());
+        }
+        item.text = parameter.text;
+        item.elements = parameter.children;
+
+        Ok(item)
+    }
+
+    /// The first element with the given name anywhere in the parameter
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.elements.iter().find_map(|element| element.find(name))
+    }
+
+    /// All elements of the parameter, in document order
+    pub fn descendants(&self) -> Vec<&Element> {
+        self.elements
+            .iter()
+            .flat_map(Element::descendants)
+            .collect()
+    }
+
+    /// Take the values of the elements inside `parent`, which is `None` for the parameter itself
+    fn push_elements(&mut self, elements: &[Element], parent: Option<&str>) {
+        for element in elements {
+            match element.name.as_str() {
+                "Calculation" | "FieldReference" | "Variable" => {
+                    if let Some(text) = &element.text {
+                        self.push_value(parent.map(str::to_string), text.clone());
+                    }
+                }
+                _ => {
+                    self.push_element_value(element, parent.is_none());
+                    if let Some(text) = &element.text {
+                        self.push_value(Some(element.name.clone()), text.clone());
+                    }
+                    self.push_elements(&element.children, Some(&element.name));
+                }
+            }
+        }
+    }
+
+    /// Take the value of an element from its `value` or `name` attribute, labeled with its `type` attribute or name
+    /// Direct children of the parameter with only a `type` attribute contribute the type itself
+    fn push_element_value(&mut self, element: &Element, is_direct_child: bool) {
+        let type_label = element.attribute("type").map(str::to_string);
+        let (label, value) = match element.attribute("value") {
+            Some(value @ ("True" | "False")) => {
+                let label = type_label.unwrap_or(element.name.clone());
+                let value = value == "True";
+                match label == self.parameter_type {
+                    true => self.values.push(Boolean::bool_to_string(value)),
+                    false => self
+                        .values
+                        .push(Boolean::labeled_bool_to_string(&label, value)),
+                }
+                return;
+            }
+            Some(value) if !value.is_empty() && element.name != "List" => (
+                type_label.or(Some(element.name.clone())),
+                Some(value.to_string()),
+            ),
+            _ => (
+                type_label,
+                element
+                    .attribute("name")
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string),
+            ),
+        };
+        match (label, value) {
+            (label, Some(value)) => self.push_value(label, value),
+            (Some(label), None) if is_direct_child => self.values.push(label),
+            _ => {}
+        }
+    }
+
+    fn push_value(&mut self, label: Option<String>, value: String) {
+        match label {
+            Some(label) if label != self.parameter_type => {
+                self.values.push(format!("{label}: {value}"))
+            }
+            _ => self.values.push(value),
+        }
+    }
+
+    pub fn display(&self) -> Option<String> {
+        match self.values.is_empty() {
+            true => None,
+            false => Some(format!(
+                "{}: {}",
+                self.parameter_type,
+                self.values.join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    use crate::diagnostics::Diagnostics;
+    use crate::script_steps::parameters::generic::Generic;
+
+    fn display(xml: &str) -> Option<String> {
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+        let parameter_type =
+            crate::utils::attributes::parse_unescaped_attribute(&element, "type").unwrap();
+        Generic::from_xml(
+            &mut reader,
+            &element,
+            &parameter_type,
+            &mut Diagnostics::default(),
+        )
+        .unwrap()
+        .display()
+    }
+
+    #[test]
+    fn test_calculation() {
+        let xml = r#"
+            <Parameter type="URL">
+                <URL autoEncode="True">
+                    <Calculation datatype="1" position="0">
+                        <Calculation>
+                            <Text><![CDATA["https://example.com"]]></Text>
+                            <ChunkList hash="FD70454C54C1E240B804AE37822E7C52">
+                                <Chunk type="NoRef">&quot;https://example.com&quot;</Chunk>
+                            </ChunkList>
+                        </Calculation>
+                    </Calculation>
+                </URL>
+            </Parameter>
+        "#;
+        assert_eq!(
+            display(xml),
+            Some(r#"URL: "https://example.com""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_booleans_and_named_values() {
+        let xml = r#"
+            <Parameter type="Start">
+                <Boolean value="True" type="Sofort starten"></Boolean>
+            </Parameter>
+        "#;
+        assert_eq!(display(xml), Some("Start: Sofort starten: ON".to_string()));
+
+        let xml = r#"
+            <Parameter type="Hide">
+                <Boolean type="Hide" value="False"></Boolean>
+            </Parameter>
+        "#;
+        assert_eq!(display(xml), Some("Hide: OFF".to_string()));
+
+        let xml = r#"
+            <Parameter type="Presentation">
+                <List name="Vollbild" value="0"></List>
+            </Parameter>
+        "#;
+        assert_eq!(display(xml), Some("Presentation: Vollbild".to_string()));
+
+        let xml = r#"
+            <Parameter type="Options">
+                <Options type="Unbegrenzt"></Options>
+            </Parameter>
+        "#;
+        assert_eq!(display(xml), Some("Options: Unbegrenzt".to_string()));
+    }
+
+    #[test]
+    fn test_targets_and_text() {
+        let xml = r#"
+            <Parameter type="SortSpecification">
+                <SortSpecification value="True" maintain="True">
+                    <SortList membercount="1">
+                        <Sort type="Ascending">
+                            <PrimaryField>
+                                <FieldReference id="1" name="Sort">
+                                    <TableOccurrenceReference id="1065113" name="_Syntax"></TableOccurrenceReference>
+                                </FieldReference>
+                            </PrimaryField>
+                        </Sort>
+                    </SortList>
+                </SortSpecification>
+            </Parameter>
+        "#;
+        assert_eq!(
+            display(xml),
+            Some("SortSpecification: ON, PrimaryField: _Syntax::Sort".to_string())
+        );
+
+        let xml = r#"
+            <Parameter type="UniversalPathList">
+                <UniversalPathList AutoOpen="True" CreateMail="True" membercount="1">
+                    <ObjectList>
+                        <Location><![CDATA[file:file.txt]]></Location>
+                    </ObjectList>
+                </UniversalPathList>
+            </Parameter>
+        "#;
+        assert_eq!(
+            display(xml),
+            Some("UniversalPathList: Location: file:file.txt".to_string())
+        );
+
+        let xml = r#"
+            <Parameter type="Email">
+                <Boolean type="Mit Dialog" position="156" value="True"></Boolean>
+                <Send OAuthAuthentication="False" SMTP="False">
+                    <Multiple value="False"></Multiple>
+                    <To>
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <DDRREF kind="ChunkList" hash="0271AE98C34751CC4A2670AE3B1470B6">_69E088D7_0</DDRREF>
+                                <Text><![CDATA[$To]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </To>
+                    <Subject></Subject>
+                </Send>
+            </Parameter>
+        "#;
+        assert_eq!(
+            display(xml),
+            Some("Email: Mit Dialog: ON, Multiple: OFF, To: $To".to_string())
+        );
+
+        assert_eq!(display(r#"<Parameter type="PageSetup"></Parameter>"#), None);
+
+        let xml = r#"
+            <Parameter type="Options">
+                <List name="Replace &amp; Find" value="1"></List>
+                <Text>Tom &amp; &quot;Jerry&quot;&#33;</Text>
+            </Parameter>
+        "#;
+        assert_eq!(
+            display(xml),
+            Some(r#"Options: Replace & Find, Text: Tom & "Jerry"!"#.to_string())
+        );
+    }
+}
@@ -1,23 +1,36 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{reference_name, SymbolKind};
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 use crate::utils::xml_utils::{general_ref_to_string, text_to_string};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct LayoutReferenceContainer {
     pub reference_type: String,
     pub layout_reference: Option<String>,
 }
 
 impl LayoutReferenceContainer {
-    pub fn parse_label(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Result<String, String> {
+    pub fn parse_label(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<String, ParseError> {
         let mut label = String::new();
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Text(e)) => {
                     label.push_str(&text_to_string(&e));
@@ -34,39 +47,49 @@ impl LayoutReferenceContainer {
         Ok(label)
     }
 
-    pub fn from_xml(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Result<Self, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, ParseError> {
         let mut depth = 1;
         let mut item = LayoutReferenceContainer {
-            reference_type: get_attribute(e, "value")
+            reference_type: parse_unescaped_attribute(e, "value")
                 .unwrap_or("".to_string())
                 .to_string(),
             ..Default::default()
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
                     match e.name().as_ref() {
                         b"LayoutReferenceContainer" => {
-                            item.reference_type = get_attribute(&e, "value")
+                            item.reference_type = parse_unescaped_attribute(&e, "value")
                                 .unwrap_or("".to_string())
                                 .to_string();
                         }
                         b"LayoutReference" => {
-                            item.layout_reference = get_attribute(&e, "name");
+                            item.layout_reference = reference_name(&e, SymbolKind::Layout);
                         }
                         b"Label" => {
-                            item.layout_reference =
-                                Some(LayoutReferenceContainer::parse_label(reader, &e).unwrap());
+                            item.layout_reference = Some(LayoutReferenceContainer::parse_label(
+                                reader,
+                                &e,
+                                diagnostics,
+                            )?);
                             depth -= 1;
                         }
                         b"Calculation" => {
                             item.layout_reference =
-                                Calculation::from_xml(reader, &e).unwrap().display();
+                                Calculation::from_xml(reader, &e, diagnostics)?.display();
                             depth -= 1;
                         }
                         _ => {}
@@ -126,7 +149,7 @@ mod tests {
 
         let expected_output = Some("Layout: <Originallayout>".to_string());
         assert_eq!(
-            LayoutReferenceContainer::from_xml(&mut reader, &element)
+            LayoutReferenceContainer::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -149,7 +172,7 @@ mod tests {
 
         let expected_output = Some(r#"Layout: "Aufgabenliste""#.to_string());
         assert_eq!(
-            LayoutReferenceContainer::from_xml(&mut reader, &element)
+            LayoutReferenceContainer::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -179,7 +202,7 @@ mod tests {
 
         let expected_output = Some(r#"Layoutname: "LAYOUT_NAME_CALCULATION""#.to_string());
         assert_eq!(
-            LayoutReferenceContainer::from_xml(&mut reader, &element)
+            LayoutReferenceContainer::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -204,7 +227,7 @@ mod tests {
 
         let expected_output = Some(r#"Layout: "Palettes""#.to_string());
         assert_eq!(
-            LayoutReferenceContainer::from_xml(&mut reader, &element)
+            LayoutReferenceContainer::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -1,13 +1,23 @@
-use quick_xml::escape::unescape;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
-use crate::utils::attributes::get_attribute;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct List {
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// What's inside the list, e.g. the script of Perform Script or the options of a device
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Element>,
 }
 
 impl List {
@@ -15,26 +25,31 @@ impl List {
         reader: &mut Reader<&[u8]>,
         _: &BytesStart,
         step_id: &u32,
-    ) -> Result<List, String> {
+        diagnostics: &mut Diagnostics,
+    ) -> Result<List, ParseError> {
         let mut depth = 1;
-        let mut item = List { name: None };
+        let mut item = List::default();
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
                     if let b"List" = e.name().as_ref() {
-                        if let Some(name) = get_attribute(&e, "name") {
-                            if let Ok(name) = unescape(name.as_str()) {
-                                item.name = match id_to_script_step(step_id) {
-                                    ScriptStep::LoopStart => Some(format!("Flush: {name}")),
-                                    _ => Some(name.to_string()),
-                                }
+                        if let Some(name) = parse_unescaped_attribute(&e, "name") {
+                            item.name = match id_to_script_step(step_id) {
+                                ScriptStep::LoopStart => Some(format!("Flush: {name}")),
+                                _ => Some(name),
                             }
                         }
+                        item.value = parse_unescaped_attribute(&e, "value");
+                        item.elements = Element::from_xml(reader, &e, diagnostics)?.children;
+                        depth -= 1;
                     }
                 }
                 Ok(Event::End(_)) => {
@@ -51,6 +66,11 @@ impl List {
         Ok(item)
     }
 
+    /// The first element with the given name anywhere in the list
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.elements.iter().find_map(|element| element.find(name))
+    }
+
     pub fn display(&self) -> Option<String> {
         self.name.clone()
     }
@@ -61,6 +81,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::list::List;
 
     #[test]
@@ -80,10 +101,15 @@ mod tests {
         let expected_output = "_Home".to_string();
         let script_id: u32 = 136;
         assert_eq!(
-            List::from_xml(&mut reader, &element, &script_id)
-                .unwrap()
-                .display()
-                .unwrap(),
+            List::from_xml(
+                &mut reader,
+                &element,
+                &script_id,
+                &mut Diagnostics::default()
+            )
+            .unwrap()
+            .display()
+            .unwrap(),
             expected_output
         );
     }
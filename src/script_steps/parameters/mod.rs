@@ -4,18 +4,20 @@ pub(crate) mod boolean_container;
 pub(crate) mod button;
 pub(crate) mod calculation;
 pub(crate) mod comment;
-pub(crate) mod dialog_field;
 pub(crate) mod constants;
 pub(crate) mod data_source_reference;
+pub(crate) mod dialog_field;
+pub(crate) mod element;
 pub(crate) mod field_reference;
+pub(crate) mod generic;
 pub(crate) mod layout_reference;
 pub(crate) mod list;
 pub(crate) mod parameter_values;
 pub(crate) mod related;
+pub(crate) mod repetition;
 pub(crate) mod script_reference;
 pub(crate) mod select;
 pub(crate) mod style;
 pub(crate) mod target;
-pub(crate) mod text;
 pub(crate) mod variable_reference;
 pub(crate) mod window_reference;
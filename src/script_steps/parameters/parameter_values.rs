@@ -1,6 +1,10 @@
 use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
 use crate::script_steps::parameters::animation::Animation;
 use crate::script_steps::parameters::boolean::Boolean;
@@ -10,17 +14,96 @@ use crate::script_steps::parameters::comment::Comment;
 use crate::script_steps::parameters::data_source_reference::DataSourceReference;
 use crate::script_steps::parameters::dialog_field::DialogField;
 use crate::script_steps::parameters::field_reference::FieldReference;
+use crate::script_steps::parameters::generic::Generic;
 use crate::script_steps::parameters::layout_reference::LayoutReferenceContainer;
 use crate::script_steps::parameters::list::List;
 use crate::script_steps::parameters::related::Related;
 use crate::script_steps::parameters::script_reference::ScriptReference;
 use crate::script_steps::parameters::target::Target;
 use crate::script_steps::parameters::window_reference::WindowReference;
-use crate::utils::attributes::get_attribute;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
+/// A `<Parameter>` of a script step, typed by its `type` attribute
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Parameter {
+    Animation(Animation),
+    Boolean(Boolean),
+    List(List),
+    Target(Target),
+    /// A calculation, named by its parameter type unless it's a plain `Calculation`, e.g. `Condition`
+    Calculation {
+        name: Option<String>,
+        #[serde(flatten)]
+        calculation: Calculation,
+    },
+    LayoutReferenceContainer(LayoutReferenceContainer),
+    FieldReference(FieldReference),
+    Comment(Comment),
+    WindowReference(WindowReference),
+    Related(Related),
+    ScriptReference(ScriptReference),
+    DataSourceReference(DataSourceReference),
+    /// E.g. `Button1` of Show Custom Dialog
+    Button {
+        name: String,
+        #[serde(flatten)]
+        button: Button,
+    },
+    /// E.g. `Field1` of Show Custom Dialog
+    DialogField {
+        name: String,
+        #[serde(flatten)]
+        field: DialogField,
+    },
+    Generic(Generic),
+    /// A parameter that was malformed and skipped, with a parse warning
+    NotParsed {
+        parameter_type: String,
+    },
+}
+
+impl Parameter {
+    pub fn display(&self) -> Option<String> {
+        match self {
+            Parameter::Animation(animation) => animation.display(),
+            Parameter::Boolean(boolean) => boolean.display(),
+            Parameter::List(list) => list.display(),
+            Parameter::Target(target) => target.display_parameter(),
+            Parameter::Calculation {
+                name: None,
+                calculation,
+            } => calculation.display(),
+            Parameter::Calculation {
+                name: Some(name),
+                calculation,
+            } => calculation
+                .display()
+                .map(|display| format!("{name}: {display}")),
+            Parameter::LayoutReferenceContainer(layout) => layout.display(),
+            Parameter::FieldReference(field) => field.display(),
+            Parameter::Comment(comment) => comment.display(),
+            Parameter::WindowReference(window) => window.display(),
+            Parameter::Related(related) => related.display(),
+            Parameter::ScriptReference(script) => script.display(),
+            Parameter::DataSourceReference(data_source) => data_source.display(),
+            Parameter::Button { name, button } => button.display(name),
+            Parameter::DialogField { name, field } => field.display(name),
+            Parameter::Generic(generic) => generic.display(),
+            Parameter::NotParsed { parameter_type } => {
+                Some(format!(r#"⚠️ PARAMETER "{parameter_type}" NOT PARSED ⚠️"#))
+            }
+        }
+    }
+}
+
+/// The typed parameters of a script step; rendering them is left to [`ParameterValues::display_parameters`]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ParameterValues {
     pub step_id: u32,
-    pub parameters: Vec<String>,
+    pub parameters: Vec<Parameter>,
 }
 
 impl ParameterValues {
@@ -28,17 +111,21 @@ impl ParameterValues {
         reader: &mut Reader<&[u8]>,
         _e: &BytesStart,
         step_id: &u32,
-    ) -> Result<ParameterValues, String> {
+        diagnostics: &mut Diagnostics,
+    ) -> Result<ParameterValues, ParseError> {
         let mut depth = 1;
         let mut item = ParameterValues {
             step_id: *step_id,
             parameters: Vec::new(),
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
@@ -50,146 +137,92 @@ impl ParameterValues {
                         continue;
                     }
 
-                    let parameter_type = get_attribute(&e, "type");
-                    if parameter_type.is_none() {
+                    // Untyped parameters, e.g. of Send Event, are not rendered
+                    let Some(parameter_type) = unescaped_attribute(&e, "type") else {
+                        let _ = reader.read_to_end(QName(b"Parameter"));
+                        depth -= 1;
                         continue;
-                    }
+                    };
 
-                    let parameter_type = parameter_type.unwrap();
-                    match parameter_type.as_str() {
+                    // Where to resume if the parameter turns out to be malformed
+                    let checkpoint = reader.clone();
+                    let parsed = match parameter_type.as_ref() {
                         "Animation" => {
-                            if let Ok(param_value) = Animation::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
-                        }
-                        "Boolean" => {
-                            if let Ok(param_value) = Boolean::from_xml(reader, &e, step_id) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            Animation::from_xml(reader, &e, diagnostics).map(Parameter::Animation)
                         }
+                        "Boolean" => Boolean::from_xml(reader, &e, step_id, diagnostics)
+                            .map(Parameter::Boolean),
                         "List" => {
-                            if let Ok(param_value) = List::from_xml(reader, &e, step_id) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            List::from_xml(reader, &e, step_id, diagnostics).map(Parameter::List)
                         }
-                        "Target" => {
-                            if let Ok(param_value) = Target::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(format!(
-                                        "{}: {}",
-                                        parameter_type.as_str(),
-                                        display
-                                    ));
-                                }
-                            }
-                            depth -= 1;
+                        "Target" | "Object" => {
+                            Target::from_xml(reader, &e, diagnostics).map(Parameter::Target)
                         }
                         "Calculation" => {
-                            if let Ok(param_value) = Calculation::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
+                            Calculation::from_xml(reader, &e, diagnostics).map(|calculation| {
+                                Parameter::Calculation {
+                                    name: None,
+                                    calculation,
                                 }
-                            }
-                            depth -= 1;
+                            })
                         }
                         "Name" | "Condition" | "ErrorCode" | "ErrorMessage" | "CustomDebugInfo"
-                        | "Title" | "Message" => {
-                            if let Ok(param_value) = Calculation::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(format!(
-                                        "{}: {}",
-                                        parameter_type.as_str(),
-                                        display
-                                    ));
-                                }
-                            }
-                            depth -= 1;
-                        }
+                        | "Title" | "Message" => Calculation::from_xml(reader, &e, diagnostics)
+                            .map(|calculation| Parameter::Calculation {
+                                name: Some(parameter_type.to_string()),
+                                calculation,
+                            }),
                         "LayoutReferenceContainer" => {
-                            if let Ok(param_value) = LayoutReferenceContainer::from_xml(reader, &e)
-                            {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
-                        }
-                        "FieldReference" => {
-                            if let Ok(param_value) = FieldReference::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            LayoutReferenceContainer::from_xml(reader, &e, diagnostics)
+                                .map(Parameter::LayoutReferenceContainer)
                         }
+                        "FieldReference" => FieldReference::from_xml(reader, &e, diagnostics)
+                            .map(Parameter::FieldReference),
                         "Comment" => {
-                            if let Ok(param_value) = Comment::from_xml(reader, &e) {
-                                item.parameters.push(param_value);
-                            }
-                            depth -= 1;
-                        }
-                        "WindowReference" => {
-                            if let Some(param_value) = WindowReference::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            Comment::from_xml(reader, &e, diagnostics).map(Parameter::Comment)
                         }
+                        "WindowReference" => WindowReference::from_xml(reader, &e, diagnostics)
+                            .map(Parameter::WindowReference),
                         "Related" => {
-                            if let Some(param_value) = Related::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
-                        }
-                        "ScriptReference" => {
-                            if let Some(param_value) = ScriptReference::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            Related::from_xml(reader, &e, diagnostics).map(Parameter::Related)
                         }
+                        "ScriptReference" => ScriptReference::from_xml(reader, &e, diagnostics)
+                            .map(Parameter::ScriptReference),
                         "DataSourceReference" => {
-                            if let Some(param_value) = DataSourceReference::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    item.parameters.push(display);
-                                }
-                            }
-                            depth -= 1;
+                            DataSourceReference::from_xml(reader, &e, diagnostics)
+                                .map(Parameter::DataSourceReference)
                         }
                         "Button1" | "Button2" | "Button3" => {
-                            let button = Button::from_xml(reader, &e);
-                            if let Some(display) = button.display(parameter_type.as_str()) {
-                                item.parameters.push(display);
-                            }
-                            depth -= 1;
+                            Button::from_xml(reader, &e, diagnostics).map(|button| {
+                                Parameter::Button {
+                                    name: parameter_type.to_string(),
+                                    button,
+                                }
+                            })
                         }
                         "Field1" | "Field2" | "Field3" => {
-                            let field = DialogField::from_xml(reader, &e);
-                            if let Some(display) =
-                                field.display(parameter_type.as_str())
-                            {
-                                item.parameters.push(display);
-                            }
-                            depth -= 1;
+                            DialogField::from_xml(reader, &e, diagnostics).map(|field| {
+                                Parameter::DialogField {
+                                    name: parameter_type.to_string(),
+                                    field,
+                                }
+                            })
                         }
-                        _ => {
-                            item.parameters
-                                .push(format!(r#"⚠️ PARAMETER "{parameter_type}" NOT PARSED ⚠️"#));
+                        _ => Generic::from_xml(reader, &e, &parameter_type, diagnostics)
+                            .map(Parameter::Generic),
+                    };
+                    match parsed {
+                        Ok(parameter) => item.parameters.push(parameter),
+                        Err(err) => {
+                            diagnostics.warn(err.offset, &err);
+                            *reader = checkpoint;
+                            let _ = reader.read_to_end(QName(b"Parameter"));
+                            item.parameters.push(Parameter::NotParsed {
+                                parameter_type: parameter_type.into_owned(),
+                            });
                         }
                     }
+                    depth -= 1;
                 }
                 Ok(Event::End(_)) => {
                     depth -= 1;
@@ -205,12 +238,15 @@ impl ParameterValues {
         Ok(item)
     }
 
-    pub fn display(&self) -> Option<String> {
-        match id_to_script_step(&self.step_id) {
+    /// Render the parameters of a step, dropping those the script workspace doesn't show
+    pub fn display_parameters(step_id: &u32, parameters: &[Parameter]) -> Option<String> {
+        match id_to_script_step(step_id) {
             ScriptStep::RevertTransaction => {
-                let mut modified_parameters = self.parameters.clone();
-                modified_parameters
-                    .retain(|param| !param.ends_with(": ON") && !param.ends_with(": OFF"));
+                let mut modified_parameters: Vec<String> = parameters
+                    .iter()
+                    .filter(|parameter| !matches!(parameter, Parameter::Boolean(_)))
+                    .filter_map(Parameter::display)
+                    .collect();
 
                 let mut iter = modified_parameters.iter().rev();
                 if let Some(last) = iter.next() {
@@ -228,21 +264,21 @@ impl ParameterValues {
             ScriptStep::SetErrorLogging => {
                 let mut modified_parameters: Vec<String> = Vec::new();
 
-                let mut iter = self.parameters.iter();
-                if let Some(first) = iter.next() {
-                    if first.ends_with(": ON") {
-                        modified_parameters.push(String::from("ON"))
-                    } else {
-                        modified_parameters.push(String::from("OFF"))
-                    }
-                }
-                if let Some(second) = iter.next() {
-                    modified_parameters.push(second.clone());
+                let mut iter = parameters.iter();
+                if let Some(Parameter::Boolean(first)) = iter.next() {
+                    modified_parameters.push(Boolean::bool_to_string(first.value == Some(true)))
                 }
+                modified_parameters.extend(iter.next().and_then(Parameter::display));
 
                 Some(modified_parameters.join(" ; "))
             }
-            _ => Some(self.parameters.join(" ; ")),
+            _ => Some(
+                parameters
+                    .iter()
+                    .filter_map(Parameter::display)
+                    .collect::<Vec<_>>()
+                    .join(" ; "),
+            ),
         }
     }
 }
@@ -252,6 +288,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::parameter_values::ParameterValues;
 
     #[test]
@@ -272,12 +309,45 @@ mod tests {
 
         let expected_output = "Pause: OFF".to_string();
         let script_id: u32 = 0;
+        let parameter_values = ParameterValues::from_xml(
+            &mut reader,
+            &element,
+            &script_id,
+            &mut Diagnostics::default(),
+        )
+        .unwrap();
         assert_eq!(
-            ParameterValues::from_xml(&mut reader, &element, &script_id)
-                .unwrap()
-                .display()
-                .unwrap(),
+            ParameterValues::display_parameters(&script_id, &parameter_values.parameters).unwrap(),
             expected_output
         );
     }
+
+    #[test]
+    fn test_parameter_without_type() {
+        let xml = r#"
+            <ParameterValues membercount="2">
+                <Parameter>
+                    <Boolean type="Pause" id="16777216" value="False"></Boolean>
+                </Parameter>
+                <Parameter type="Boolean">
+                    <Boolean type="Pause" id="16777216" value="True"></Boolean>
+                </Parameter>
+            </ParameterValues>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let mut diagnostics = Diagnostics::default();
+        let parameter_values =
+            ParameterValues::from_xml(&mut reader, &element, &0, &mut diagnostics).unwrap();
+        assert_eq!(
+            ParameterValues::display_parameters(&0, &parameter_values.parameters),
+            Some("Pause: ON".to_string())
+        );
+        assert!(diagnostics.entries.is_empty());
+    }
 }
@@ -1,25 +1,38 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::parameters::layout_reference::LayoutReferenceContainer;
-use crate::utils::attributes::get_attribute;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{reference_name, SymbolKind};
+use crate::utils::attributes::unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Related {
     pub parameters: Vec<String>,
 }
 
 impl Related {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Option<Related> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Related, ParseError> {
         let mut depth = 1;
         let mut item = Related {
             parameters: Vec::new(),
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
@@ -27,14 +40,13 @@ impl Related {
                     let element_name = e.name();
                     match element_name.as_ref() {
                         b"TableOccurrenceReference" => {
-                            let table_occurrence = get_attribute(&e, "name")
+                            let table_occurrence = reference_name(&e, SymbolKind::TableOccurrence)
                                 .unwrap_or("🚨🚨🚨 <BROKEN REFERENCE> 🚨🚨🚨".to_string());
                             item.parameters.push(format!("Table: {table_occurrence}"));
                         }
                         b"LayoutReferenceContainer" => {
                             item.parameters.push(
-                                LayoutReferenceContainer::from_xml(reader, &e)
-                                    .unwrap()
+                                LayoutReferenceContainer::from_xml(reader, &e, diagnostics)?
                                     .display()
                                     .unwrap_or("".to_string()),
                             );
@@ -45,12 +57,13 @@ impl Related {
                             depth -= 1;
                         }
                         b"Options" => {
-                            if let Some(show_related) = get_attribute(&e, "ShowRelated") {
+                            if let Some(show_related) = unescaped_attribute(&e, "ShowRelated") {
                                 if show_related == "True" {
                                     item.parameters.push("Show related".to_string())
                                 }
                             };
-                            if let Some(match_found_set) = get_attribute(&e, "matchFoundSet") {
+                            if let Some(match_found_set) = unescaped_attribute(&e, "matchFoundSet")
+                            {
                                 if match_found_set == "True" {
                                     item.parameters.push("Match found set".to_string())
                                 }
@@ -70,7 +83,7 @@ impl Related {
             buf.clear()
         }
 
-        Some(item)
+        Ok(item)
     }
 
     pub fn display(&self) -> Option<String> {
@@ -0,0 +1,104 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::calculation::Calculation;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
+
+/// The repetition of a field or variable, either a number in the `value` attribute
+/// or a calculation like `$i + 1`
+#[derive(Debug, Default)]
+pub struct Repetition {
+    pub repetition: Option<String>,
+}
+
+impl Repetition {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Repetition, ParseError> {
+        let mut depth = 1;
+        let mut item = Repetition {
+            repetition: parse_unescaped_attribute(e, "value"),
+        };
+
+        let mut buf = PooledBuffer::take();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => {
+                    if e.name().as_ref() == b"Calculation" {
+                        item.repetition = Calculation::from_xml(reader, &e, diagnostics)?.display();
+                    } else {
+                        depth += 1;
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(item)
+    }
+
+    /// The repetition to render in brackets; `None` for the first repetition
+    pub fn display(&self) -> Option<String> {
+        match self.repetition.as_deref().map(str::trim) {
+            None | Some("") | Some("1") => None,
+            Some(repetition) => Some(repetition.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(xml: &str) -> Option<String> {
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+        Repetition::from_xml(&mut reader, &element, &mut Diagnostics::default())
+            .unwrap()
+            .display()
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(parse(r#"<repetition value="1"></repetition>"#), None);
+        assert_eq!(
+            parse(r#"<repetition value="2"></repetition>"#),
+            Some("2".to_string())
+        );
+        assert_eq!(parse(r#"<repetition></repetition>"#), None);
+    }
+
+    #[test]
+    fn test_calculation() {
+        let xml = r#"
+            <repetition>
+                <Calculation datatype="1" position="10">
+                    <Calculation>
+                        <Text><![CDATA[$i + 1]]></Text>
+                    </Calculation>
+                </Calculation>
+            </repetition>
+        "#;
+        assert_eq!(parse(xml), Some("$i + 1".to_string()));
+    }
+}
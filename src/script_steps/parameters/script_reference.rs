@@ -1,25 +1,38 @@
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{reference_name, SymbolKind};
 use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptReference {
     pub data_source_name: Option<String>,
     pub script_name: Option<String>,
 }
 
 impl ScriptReference {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _e: &BytesStart) -> Option<ScriptReference> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<ScriptReference, ParseError> {
         let mut depth = 1;
         let mut item = ScriptReference {
             data_source_name: None,
             script_name: None,
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
@@ -28,7 +41,7 @@ impl ScriptReference {
                             item.data_source_name = parse_unescaped_attribute(&e, "name")
                         }
                         b"ScriptReference" => {
-                            item.script_name = parse_unescaped_attribute(&e, "name")
+                            item.script_name = reference_name(&e, SymbolKind::Script)
                         }
                         _ => {}
                     }
@@ -44,7 +57,7 @@ impl ScriptReference {
             buf.clear();
         }
 
-        Some(item)
+        Ok(item)
     }
 
     pub fn display(&self) -> Option<String> {
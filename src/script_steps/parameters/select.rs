@@ -1,32 +1,56 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::parameters::calculation::Calculation;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::{parse_unescaped_attribute, unescaped_attribute};
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+/// How a window is selected: the current one, or by the calculated name
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Select {
-    pub text: Option<String>,
+    /// E.g. `current` or `Calculated`
+    pub kind: Option<String>,
+    pub name: Option<String>,
+    /// Whether only the windows of the current file are searched for the name
+    pub current_file: bool,
 }
 
 impl Select {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Result<Select, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Select, ParseError> {
         let mut depth = 1;
-        let mut item = Select { text: None };
+        let mut item = Select {
+            kind: parse_unescaped_attribute(e, "type"),
+            ..Default::default()
+        };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
-                    if e.name().as_ref() == b"Calculation" {
-                        if let Ok(param_value) = Calculation::from_xml(reader, &e) {
-                            if let Some(display) = param_value.display() {
-                                item.text = Some(format!("Name: {display}"));
-                            }
+                    match e.name().as_ref() {
+                        b"Name" => {
+                            item.current_file =
+                                unescaped_attribute(&e, "current").as_deref() == Some("True")
+                        }
+                        b"Calculation" => {
+                            item.name = Calculation::from_xml(reader, &e, diagnostics)?.display();
+                            depth -= 1;
                         }
-                        depth -= 1;
+                        _ => {}
                     }
                 }
                 Ok(Event::End(_)) => {
@@ -44,7 +68,7 @@ impl Select {
     }
 
     pub fn display(&self) -> Option<String> {
-        self.text.clone()
+        self.name.as_ref().map(|name| format!("Name: {name}"))
     }
 }
 
@@ -53,6 +77,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::select::Select;
 
     #[test]
@@ -66,7 +91,9 @@ mod tests {
         };
 
         assert_eq!(
-            Select::from_xml(&mut reader, &element).unwrap().display(),
+            Select::from_xml(&mut reader, &element, &mut Diagnostics::default())
+                .unwrap()
+                .display(),
             None
         );
     }
@@ -95,7 +122,9 @@ mod tests {
         };
 
         assert_eq!(
-            Select::from_xml(&mut reader, &element).unwrap().display(),
+            Select::from_xml(&mut reader, &element, &mut Diagnostics::default())
+                .unwrap()
+                .display(),
             Some("Name: $FensterName".to_string())
         );
     }
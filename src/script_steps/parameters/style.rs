@@ -1,7 +1,10 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
-use crate::utils::attributes::get_attribute;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
 #[derive(Debug, Default)]
 pub struct Style {
@@ -9,16 +12,23 @@ pub struct Style {
 }
 
 impl Style {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Result<Style, String> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Style, ParseError> {
         let mut depth = 1;
         let item = Style {
-            style: get_attribute(e, "name"),
+            style: parse_unescaped_attribute(e, "name"),
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(_)) => {
                     depth += 1;
@@ -38,7 +48,7 @@ impl Style {
     }
 
     pub fn display(&self) -> Option<String> {
-        Some(format!("Style: {}", self.style.clone().unwrap()))
+        self.style.as_ref().map(|style| format!("Style: {style}"))
     }
 }
 
@@ -47,6 +57,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::style::Style;
 
     #[test]
@@ -61,7 +72,7 @@ mod tests {
 
         let expected_output = "Style: Dokument".to_string();
         assert_eq!(
-            Style::from_xml(&mut reader, &element)
+            Style::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
@@ -1,30 +1,81 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::calculation::Calculation;
 use crate::script_steps::parameters::field_reference::FieldReference;
 use crate::script_steps::parameters::variable_reference::VariableReference;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+/// What a script step points at: a field, a variable or a layout object by its object name
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TargetReference {
+    Field(FieldReference),
+    Variable(VariableReference),
+    /// E.g. of Go to Object or Refresh Portal
+    Object {
+        name: Option<String>,
+        repetition: Option<String>,
+    },
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Target {
-    pub target: Option<String>,
+    pub target: Option<TargetReference>,
 }
 
 impl Target {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _e: &BytesStart) -> Result<Target, String> {
+    /// Read a `FieldReference` or `Variable` element, an `Object` parameter,
+    /// or any other element holding a field or variable, e.g. a `Target` parameter
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Target, ParseError> {
+        match e.name().as_ref() {
+            b"FieldReference" => {
+                return Ok(Target {
+                    target: Some(TargetReference::Field(FieldReference::from_xml(
+                        reader,
+                        e,
+                        diagnostics,
+                    )?)),
+                })
+            }
+            b"Variable" => {
+                return Ok(Target {
+                    target: Some(TargetReference::Variable(VariableReference::from_xml(
+                        reader,
+                        e,
+                        diagnostics,
+                    )?)),
+                })
+            }
+            b"Parameter" if unescaped_attribute(e, "type").as_deref() == Some("Object") => {
+                return Self::object_from_xml(reader, diagnostics)
+            }
+            _ => {}
+        }
+
         let mut depth = 1;
         let mut item = Target { target: None };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => match e.name().as_ref() {
-                    b"Variable" => {
-                        item.target = VariableReference::from_xml(reader, &e).unwrap().display();
-                    }
-                    b"FieldReference" => {
-                        item.target = FieldReference::from_xml(reader, &e).unwrap().display();
+                    b"Variable" | b"FieldReference" => {
+                        item = Target::from_xml(reader, &e, diagnostics)?;
                     }
                     _ => {
                         depth += 1;
@@ -44,8 +95,80 @@ impl Target {
         Ok(item)
     }
 
+    /// The object name and repetition calculations of an `Object` parameter
+    fn object_from_xml(
+        reader: &mut Reader<&[u8]>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Target, ParseError> {
+        let mut depth = 1;
+        let mut name = None;
+        let mut repetition = None;
+
+        let mut buf = PooledBuffer::take();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => match e.name().as_ref() {
+                    b"Name" => name = Calculation::from_xml(reader, &e, diagnostics)?.display(),
+                    b"repetition" => {
+                        repetition = Calculation::from_xml(reader, &e, diagnostics)?.display()
+                    }
+                    _ => {
+                        depth += 1;
+                    }
+                },
+                Ok(Event::End(_)) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(Target {
+            target: Some(TargetReference::Object {
+                name: name.filter(|name| !name.is_empty()),
+                repetition: repetition.filter(|repetition| !repetition.is_empty()),
+            }),
+        })
+    }
+
+    /// The field or variable, e.g. `Invoices::Total[2]` or `$total`,
+    /// or the object name and repetition of a layout object
     pub fn display(&self) -> Option<String> {
-        self.target.clone()
+        match self.target.as_ref()? {
+            TargetReference::Field(field) => field.display(),
+            TargetReference::Variable(variable) => variable.display(),
+            TargetReference::Object { name, repetition } => {
+                let mut parts = Vec::new();
+                if let Some(name) = name {
+                    parts.push(format!("Object Name: {name}"));
+                }
+                if let Some(repetition) = repetition {
+                    parts.push(format!("Repetition: {repetition}"));
+                }
+                match parts.is_empty() {
+                    true => None,
+                    false => Some(parts.join(" ; ")),
+                }
+            }
+        }
+    }
+
+    /// The target as a step parameter: `Target: ` and the field or variable,
+    /// or the object name and repetition of a layout object
+    pub fn display_parameter(&self) -> Option<String> {
+        match self.target.as_ref()? {
+            TargetReference::Object { .. } => self.display(),
+            _ => self.display().map(|display| format!("Target: {display}")),
+        }
     }
 }
 
@@ -54,6 +177,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::target::Target;
 
     #[test]
@@ -74,11 +198,68 @@ mod tests {
 
         let expected_output = "$foo".to_string();
         assert_eq!(
-            Target::from_xml(&mut reader, &element)
+            Target::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
             expected_output
         );
     }
+
+    #[test]
+    fn test_field_reference() {
+        let xml = r#"
+            <FieldReference id="4" name="Total">
+                <repetition value="2"></repetition>
+                <TableOccurrenceReference id="1065090" name="Invoices"></TableOccurrenceReference>
+            </FieldReference>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let target = Target::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
+        assert_eq!(target.display().unwrap(), "Invoices::Total[2]");
+        assert_eq!(
+            target.display_parameter().unwrap(),
+            "Target: Invoices::Total[2]"
+        );
+    }
+
+    #[test]
+    fn test_object() {
+        let xml = r#"
+            <Parameter type="Object">
+                <Name>
+                    <Calculation datatype="1" position="0">
+                        <Calculation>
+                            <Text><![CDATA["Obj"]]></Text>
+                        </Calculation>
+                    </Calculation>
+                </Name>
+                <repetition>
+                    <Calculation datatype="1" position="1">
+                        <Calculation>
+                            <Text><![CDATA[2]]></Text>
+                        </Calculation>
+                    </Calculation>
+                </repetition>
+            </Parameter>
+        "#;
+
+        let mut reader = Reader::from_str(xml.trim());
+        let element = match reader.read_event() {
+            Ok(Event::Start(e)) => e,
+            _ => panic!("Wrong read event"),
+        };
+
+        let target = Target::from_xml(&mut reader, &element, &mut Diagnostics::default()).unwrap();
+        assert_eq!(
+            target.display_parameter().unwrap(),
+            r#"Object Name: "Obj" ; Repetition: 2"#
+        );
+    }
 }
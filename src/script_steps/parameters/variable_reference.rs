@@ -1,38 +1,46 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::attributes::get_attribute;
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::repetition::Repetition;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::attributes::parse_unescaped_attribute;
+use crate::utils::buffer_pool::PooledBuffer;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct VariableReference {
     pub name: Option<String>,
-    pub repetition: Option<i32>,
+    /// Repetition other than the first, e.g. `2` or `$i + 1`
+    pub repetition: Option<String>,
 }
 
 impl VariableReference {
     pub fn from_xml(
         reader: &mut Reader<&[u8]>,
         e: &BytesStart,
-    ) -> Result<VariableReference, String> {
+        diagnostics: &mut Diagnostics,
+    ) -> Result<VariableReference, ParseError> {
         let mut depth = 1;
         let mut item = VariableReference {
-            name: get_attribute(e, "value"),
+            name: parse_unescaped_attribute(e, "value"),
             repetition: None,
         };
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
-                    depth += 1;
                     if e.name().as_ref() == b"repetition" {
-                        if let Some(repetition) = get_attribute(&e, "value") {
-                            if let Ok(repetition) = repetition.parse::<i32>() {
-                                item.repetition = Some(repetition)
-                            }
-                        };
+                        item.repetition = Repetition::from_xml(reader, &e, diagnostics)?.display();
+                    } else {
+                        depth += 1;
                     }
                 }
                 Ok(Event::End(_)) => {
@@ -50,15 +58,10 @@ impl VariableReference {
     }
 
     pub fn display(&self) -> Option<String> {
-        if let Some(name) = &self.name {
-            let repetition = self.repetition.unwrap_or(1);
-            if repetition != 1 {
-                Some(format!("{name}[{repetition}]"))
-            } else {
-                Some(name.clone())
-            }
-        } else {
-            None
+        let name = self.name.as_ref()?;
+        match &self.repetition {
+            Some(repetition) => Some(format!("{name}[{repetition}]")),
+            None => Some(name.clone()),
         }
     }
 }
@@ -68,6 +71,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::variable_reference::VariableReference;
 
     #[test]
@@ -86,7 +90,7 @@ mod tests {
 
         let expected_output = "$foo".to_string();
         assert_eq!(
-            VariableReference::from_xml(&mut reader, &element)
+            VariableReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
@@ -110,7 +114,7 @@ mod tests {
 
         let expected_output = "$foo[1337]".to_string();
         assert_eq!(
-            VariableReference::from_xml(&mut reader, &element)
+            VariableReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display()
                 .unwrap(),
@@ -1,16 +1,23 @@
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::diagnostics::Diagnostics;
 use crate::script_steps::parameters::boolean_container::BooleanContainer;
 use crate::script_steps::parameters::calculation::Calculation;
 use crate::script_steps::parameters::layout_reference::LayoutReferenceContainer;
 use crate::script_steps::parameters::select::Select;
 use crate::script_steps::parameters::style::Style;
+use crate::script_steps::parse_error::ParseError;
+use crate::utils::buffer_pool::PooledBuffer;
 use crate::utils::xml_utils::local_name_to_string;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct WindowReference {
     pub parameters: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select: Option<Select>,
 }
 
 pub fn first_char_uppercase(s: &str) -> String {
@@ -22,16 +29,21 @@ pub fn first_char_uppercase(s: &str) -> String {
 }
 
 impl WindowReference {
-    pub fn from_xml(reader: &mut Reader<&[u8]>, _: &BytesStart) -> Option<WindowReference> {
+    pub fn from_xml(
+        reader: &mut Reader<&[u8]>,
+        _: &BytesStart,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<WindowReference, ParseError> {
         let mut depth = 1;
-        let mut window_reference = WindowReference {
-            parameters: Vec::new(),
-        };
+        let mut window_reference = WindowReference::default();
 
-        let mut buf: Vec<u8> = Vec::new();
+        let mut buf = PooledBuffer::take();
         loop {
             match reader.read_event_into(&mut buf) {
-                Err(_) => continue,
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
                 Ok(Event::Eof) => break,
                 Ok(Event::Start(e)) => {
                     depth += 1;
@@ -39,46 +51,43 @@ impl WindowReference {
                     let element_name = e.name();
                     match element_name.as_ref() {
                         b"Style" => {
-                            if let Ok(param_value) = Style::from_xml(reader, &e) {
-                                if let Some(display) = param_value.display() {
-                                    window_reference.parameters.push(display);
-                                }
+                            if let Some(display) =
+                                Style::from_xml(reader, &e, diagnostics)?.display()
+                            {
+                                window_reference.parameters.push(display);
                             }
                             depth -= 1;
                         }
                         b"LayoutReferenceContainer" => {
                             window_reference.parameters.push(
-                                LayoutReferenceContainer::from_xml(reader, &e)
-                                    .unwrap()
+                                LayoutReferenceContainer::from_xml(reader, &e, diagnostics)?
                                     .display()
                                     .unwrap_or("".to_string()),
                             );
                             depth -= 1;
                         }
                         b"Select" => {
-                            if let Ok(param_value) = Select::from_xml(reader, &e) {
-                                if let Some(calc) = param_value.display() {
-                                    window_reference.parameters.push(calc);
-                                }
-                            }
+                            let select = Select::from_xml(reader, &e, diagnostics)?;
+                            window_reference.parameters.extend(select.display());
+                            window_reference.select = Some(select);
                             depth -= 1;
                         }
                         b"Name" | b"height" | b"width" | b"top" | b"left" | b"Text" => {
-                            if let Ok(param_value) = Calculation::from_xml(reader, &e) {
-                                if let Some(calc) = param_value.display() {
-                                    let name = local_name_to_string(element_name.as_ref());
-                                    let name = first_char_uppercase(name.as_str());
-                                    window_reference.parameters.push(format!("{name}: {calc}"));
-                                }
+                            if let Some(calc) =
+                                Calculation::from_xml(reader, &e, diagnostics)?.display()
+                            {
+                                let name = local_name_to_string(element_name.as_ref());
+                                let name = first_char_uppercase(name.as_str());
+                                window_reference.parameters.push(format!("{name}: {calc}"));
                             }
                             depth -= 1;
                         }
                         b"Close" | b"Minimize" | b"Maximize" | b"Resize" | b"MenuBar"
                         | b"Toolbar" | b"DimParentWindow" => {
-                            if let Ok(param_value) = BooleanContainer::from_xml(reader, &e) {
-                                if let Some(calc) = param_value.display() {
-                                    window_reference.parameters.push(calc);
-                                }
+                            if let Some(calc) =
+                                BooleanContainer::from_xml(reader, &e, diagnostics)?.display()
+                            {
+                                window_reference.parameters.push(calc);
                             }
                             depth -= 1;
                         }
@@ -96,7 +105,7 @@ impl WindowReference {
             buf.clear()
         }
 
-        Some(window_reference)
+        Ok(window_reference)
     }
 
     pub fn display(&self) -> Option<String> {
@@ -109,6 +118,7 @@ mod tests {
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
+    use crate::diagnostics::Diagnostics;
     use crate::script_steps::parameters::window_reference::WindowReference;
 
     #[test]
@@ -147,7 +157,7 @@ mod tests {
         };
         let expected_output = Some("Style: Dokument ; Layout: <Originallayout>".to_string());
         assert_eq!(
-            WindowReference::from_xml(&mut reader, &element)
+            WindowReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -235,7 +245,7 @@ mod tests {
         };
         let expected_output = Some(r#"Style: Dokument ; Name: "Foo Bar" ; Layout: <Originallayout> ; Height: 100 ; Width: 200 ; Top: 300 ; Left: 400 ; Minimize: OFF ; Maximize: OFF ; Resize: OFF ; Menu: OFF ; Toolbar: OFF"#.to_string());
         assert_eq!(
-            WindowReference::from_xml(&mut reader, &element)
+            WindowReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
@@ -303,7 +313,7 @@ mod tests {
             r#"Height: $$AppWindowHeight ; Width: $$AppWindowWidth ; Top: 0 ; Left: 0"#.to_string(),
         );
         assert_eq!(
-            WindowReference::from_xml(&mut reader, &element)
+            WindowReference::from_xml(&mut reader, &element, &mut Diagnostics::default())
                 .unwrap()
                 .display(),
             expected_output
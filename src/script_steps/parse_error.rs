@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+/// Why a script step or one of its parameters could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The element that could not be understood, e.g. `FieldReference`
+    pub element: String,
+    /// Byte offset in the step XML
+    pub offset: u64,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>: {}", self.element, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,74 @@
+use crate::script_steps::step::Step;
+
+/// Pause/Resume Script, pausing either indefinitely or for the duration of a calculation
+pub fn sanitize(step: &Step) -> String {
+    let options = step.generic("Options");
+    // The label of a duration already ends with a colon, like "Duration (seconds): "
+    let option = options
+        .and_then(|options| options.find("Options"))
+        .and_then(|options| options.attribute("type"))
+        .unwrap_or_default()
+        .trim();
+    let duration = options
+        .and_then(|options| options.find("Calculation"))
+        .and_then(|duration| duration.text.as_deref())
+        .unwrap_or_default();
+
+    let name = &step.name;
+    if option.is_empty() {
+        name.to_string()
+    } else if duration.is_empty() {
+        format!("{name} [ {option} ]")
+    } else {
+        format!("{name} [ {option} {duration} ]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_indefinitely() {
+        let xml = r#"
+            <Step index="23" id="62" name="Pause/Resume Script" enable="True">
+                <UUID>D3BE77B4-8409-46D9-8903-5C342F1EB654</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Options">
+                        <Options type="Indefinitely"></Options>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Pause/Resume Script [ Indefinitely ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_duration() {
+        let xml = r#"
+            <Step index="59" id="62" name="Scriptpause setzen" enable="True">
+                <UUID>6BEB3451-678C-4A56-9939-89E52BF84771</UUID>
+                <OwnerID></OwnerID>
+                <Options>16384</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Options">
+                        <Options type="Dauer (Sekunden): ">
+                            <Calculation datatype="1" position="0">
+                                <Calculation>
+                                    <Text><![CDATA[1.2]]></Text>
+                                </Calculation>
+                            </Calculation>
+                        </Options>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Scriptpause setzen [ Dauer (Sekunden): 1.2 ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,48 +1,15 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-
-    let mut restore = false;
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(&e, "name").unwrap().to_string();
-                }
-                b"ParameterValues" => {
-                    restore = true;
-                    break;
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        println!("empty primitive");
-        None
-    } else {
-        match restore {
-            true => Some(format!("{name} [ ⚠️ RESTORE ⚠️ ]")),
-            false => Some(name),
-        }
+pub fn sanitize(step: &Step) -> String {
+    match step.parameters.is_empty() {
+        false => format!("{} [ ⚠️ RESTORE ⚠️ ]", step.name),
+        true => step.name.clone(),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -53,7 +20,7 @@ mod tests {
 		"#;
 
         let expected_output = Some("Ergebnismenge suchen".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -90,6 +57,6 @@ mod tests {
         "#;
 
         let expected_output = Some("Ergebnismenge suchen [ ⚠️ RESTORE ⚠️ ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
@@ -0,0 +1,199 @@
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// Perform Find/Replace and Perform Quick Find, which only has the calculation of its search text
+pub fn sanitize(step: &Step) -> String {
+    let mut search_text = String::new();
+    let mut find = String::new();
+    let mut replace = String::new();
+    let mut operation = String::new();
+    // The direction, followed by the match case and whole word flags that are on
+    let mut settings: Vec<String> = Vec::new();
+    let mut across = String::new();
+    let mut within = String::new();
+    let mut dialog = String::new();
+
+    for parameter in &step.parameters {
+        match parameter {
+            Parameter::Calculation { calculation, .. } => {
+                search_text = calculation.display().unwrap_or_default()
+            }
+            Parameter::Boolean(boolean) => {
+                dialog = Boolean::labeled_bool_to_string(
+                    boolean.name.as_deref().unwrap_or_default(),
+                    boolean.value == Some(true),
+                )
+            }
+            Parameter::Generic(generic) => {
+                for element in generic.descendants() {
+                    let attribute = |name: &str| element.attribute(name).unwrap_or_default();
+                    match (element.name.as_str(), generic.parameter_type.as_str()) {
+                        ("Calculation", "find") => find = element.text.clone().unwrap_or_default(),
+                        ("Calculation", "replace") => {
+                            replace = element.text.clone().unwrap_or_default()
+                        }
+                        ("Calculation", _) => {
+                            search_text = element.text.clone().unwrap_or_default()
+                        }
+                        ("List", "perform") => operation = attribute("name").to_string(),
+                        ("List", "direction") => settings.insert(0, attribute("name").to_string()),
+                        ("List", "across") => across = attribute("name").to_string(),
+                        ("List", "within") => within = attribute("name").to_string(),
+                        ("Boolean", "case" | "whole") if attribute("value") == "True" => {
+                            settings.push(attribute("type").to_string())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut parameters = Vec::new();
+    if !search_text.is_empty() {
+        parameters.push(search_text);
+    }
+    if !find.is_empty() {
+        parameters.push(format!("Find: {find}"));
+    }
+    if !replace.is_empty() {
+        parameters.push(format!("Replace: {replace}"));
+    }
+    if !operation.is_empty() {
+        parameters.push(format!("Search operation: {operation}"));
+    }
+    if !settings.is_empty() {
+        parameters.push(format!("Search settings: {}", settings.join(", ")));
+    }
+    if !across.is_empty() {
+        parameters.push(format!("Search across: {across}"));
+    }
+    if !within.is_empty() {
+        parameters.push(format!("Search within: {within}"));
+    }
+    if !dialog.is_empty() {
+        parameters.push(dialog);
+    }
+
+    step.display_with(&parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_find_replace() {
+        let xml = r#"
+            <Step index="193" id="128" name="Perform Find/Replace" enable="True">
+                <Options>16512</Options>
+                <ParameterValues membercount="9">
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="perform">
+                        <List name="Find Next" value="0"></List>
+                    </Parameter>
+                    <Parameter type="find">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA[$Suchen]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="replace">
+                        <Calculation datatype="1" position="1">
+                            <Calculation>
+                                <Text><![CDATA[$Ersetzen]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="direction">
+                        <List name="Backward" value="1"></List>
+                    </Parameter>
+                    <Parameter type="case">
+                        <Boolean type="Match case" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="whole">
+                        <Boolean type="Match whole words only" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="across">
+                        <List name="Current record/request" value="1"></List>
+                    </Parameter>
+                    <Parameter type="within">
+                        <List name="Current field" value="1"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Perform Find/Replace [ Find: $Suchen ; Replace: $Ersetzen ; Search operation: Find Next ; Search settings: Backward, Match case, Match whole words only ; Search across: Current record/request ; Search within: Current field ; With dialog: OFF ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_find_next() {
+        let xml = r#"
+            <Step index="47" id="128" name="Suchen/Ersetzen ausführen" enable="True">
+                <Options>0</Options>
+                <ParameterValues membercount="9">
+                    <Parameter type="Boolean">
+                        <Boolean type="Mit Dialog" id="128" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="perform">
+                        <List name="Weitersuchen" value="0"></List>
+                    </Parameter>
+                    <Parameter type="find"></Parameter>
+                    <Parameter type="replace"></Parameter>
+                    <Parameter type="direction">
+                        <List name="Vorwärts" value="0"></List>
+                    </Parameter>
+                    <Parameter type="case">
+                        <Boolean type="Groß-/Kleinschreibung" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="whole">
+                        <Boolean type="Nur ganzes Wort suchen" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="across">
+                        <List name="Alle Datensätze/Abfragen" value="0"></List>
+                    </Parameter>
+                    <Parameter type="within">
+                        <List name="Alle Felder" value="0"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Suchen/Ersetzen ausführen [ Search operation: Weitersuchen ; Search settings: Vorwärts ; Search across: Alle Datensätze/Abfragen ; Search within: Alle Felder ; Mit Dialog: ON ]"
+                .to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_quick_find() {
+        let xml = r#"
+            <Step index="536" id="150" name="Perform Quick Find" enable="True">
+                <Options>16384</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Calculation">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA["Hello"]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(r#"Perform Quick Find [ "Hello" ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,83 +1,45 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::{get_attribute, parse_unescaped_attribute};
+pub fn sanitize(step: &Step) -> String {
+    let list = step.lists().last();
+    let find_in_list = |name: &str| list.and_then(|list| list.find(name));
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut data_source_reference: Option<String> = None;
-    let mut script_reference_type = String::new();
-    let mut script_reference_type_id = String::new();
-    let mut script_reference = String::new();
-    let mut calculation = String::new();
+    let mut parameters = vec![list.and_then(|list| list.name.clone()).unwrap_or_default()];
 
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(&e, "name").unwrap().to_string();
-                }
-                b"List" => {
-                    script_reference_type_id = get_attribute(&e, "value").unwrap().to_string();
-                    script_reference_type = get_attribute(&e, "name").unwrap().to_string();
-                    if script_reference_type_id.as_str() == "2" {
-                        script_reference = Calculation::from_xml(&mut reader, &e)
-                            .unwrap()
-                            .display()
-                            .unwrap();
-                    }
-                }
-                b"DataSourceReference" => {
-                    data_source_reference = parse_unescaped_attribute(&e, "name")
-                }
-                b"ScriptReference" => script_reference = parse_unescaped_attribute(&e, "name")?,
-                b"Parameter" => {
-                    if get_attribute(&e, "type").unwrap_or("".to_string()).as_str() == "Parameter" {
-                        calculation = Calculation::from_xml(&mut reader, &e)
-                            .unwrap()
-                            .display()
-                            .unwrap_or_default();
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    let mut parameters = vec![script_reference_type.to_string()];
-
-    if script_reference_type_id.as_str() != "2" {
-        parameters.push(format!("\"{script_reference}\""));
+    if list.and_then(|list| list.value.as_deref()) == Some("2") {
+        parameters.push(
+            find_in_list("Calculation")
+                .and_then(|calculation| calculation.text.clone())
+                .unwrap_or_default(),
+        );
     } else {
-        parameters.push(script_reference.to_string());
+        let script_reference = find_in_list("ScriptReference")
+            .and_then(|script| script.attribute("name"))
+            .or(step.script.as_deref())
+            .unwrap_or_default();
+        parameters.push(format!("\"{script_reference}\""));
     }
 
-    if let Some(ref data_source_value) = data_source_reference {
+    if let Some(data_source_value) =
+        find_in_list("DataSourceReference").and_then(|file| file.attribute("name"))
+    {
         parameters.push(format!("File: \"{data_source_value}\""));
     }
 
-    if !calculation.is_empty() {
+    if let Some(calculation) = step
+        .generic("Parameter")
+        .and_then(|parameter| parameter.find("Calculation"))
+        .and_then(|calculation| calculation.text.as_deref())
+    {
         parameters.push(format!("Parameter: {calculation}"));
     }
 
-    if name.is_empty() {
-        println!("empty primitive");
-        None
-    } else {
-        Some(format!("{name} [ {} ]", parameters.join(" ; ")))
-    }
+    format!("{} [ {} ]", step.name, parameters.join(" ; "))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test_without_parameter() {
@@ -99,7 +61,7 @@ mod tests {
 
         let expected_output =
             Some(r#"Script ausführen [ Aus Liste ; "Do something" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -133,7 +95,7 @@ mod tests {
         "#;
 
         let expected_output = Some(r#"Script ausführen [ Aus Liste ; "Do something" ; Parameter: cf_ScriptparameterSetzen ( "CurlId" ; $curl ) ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -174,7 +136,7 @@ mod tests {
 
         let expected_output =
             Some(r#"Script ausführen [ Nach Name ; "Do something" ; Parameter: 123 ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -209,6 +171,6 @@ mod tests {
             r#"Script ausführen [ Aus Liste ; "Do something" ; File: "App_Utils" ; Parameter: 123 ]"#
                 .to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
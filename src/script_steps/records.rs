@@ -0,0 +1,50 @@
+use crate::script_steps::step::Step;
+
+/// New, Duplicate and Delete Record/Request, Delete All Records and Delete Portal Row. The
+/// deleting steps always show whether they ask for confirmation.
+pub fn sanitize(step: &Step) -> String {
+    let options: Vec<String> = step
+        .booleans()
+        .filter_map(|boolean| boolean.display())
+        .collect();
+
+    step.display_with(&options)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_new_record() {
+        let xml = r#"
+            <Step index="1" id="7" name="New Record/Request" enable="True">
+                <UUID>2CBCAC62-DA81-41E5-B162-AF7E7ED83502</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+            </Step>
+        "#;
+
+        let expected_output = Some("New Record/Request".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_delete_without_dialog() {
+        let xml = r#"
+            <Step index="418" id="104" name="Delete Portal Row" enable="True">
+                <UUID>14F942DA-40AC-477F-9259-8D0B226B32EC</UUID>
+                <OwnerID></OwnerID>
+                <Options>128</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Delete Portal Row [ With dialog: OFF ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
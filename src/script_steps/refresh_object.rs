@@ -1,66 +1,16 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::utils::attributes::get_attribute;
-use crate::utils::xml_utils;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut in_object_name_calculation = false;
-    let mut in_repetition_calculation = false;
-    let mut object_name_calculation = String::new();
-    let mut repetition_calculation = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"Name" => in_object_name_calculation = true,
-                b"repetition" => in_repetition_calculation = true,
-                _ => {}
-            },
-            Ok(Event::CData(e)) => {
-                if in_object_name_calculation {
-                    object_name_calculation.push_str(xml_utils::cdata_to_string(&e).as_str());
-                }
-                if in_repetition_calculation {
-                    repetition_calculation.push_str(xml_utils::cdata_to_string(&e).as_str());
-                }
-            }
-            Ok(Event::End(e)) => {
-                if e.name().as_ref() == b"Calculation" {
-                    in_object_name_calculation = false;
-                    in_repetition_calculation = false;
-                }
-            }
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    let mut params = Vec::new();
-    if !object_name_calculation.is_empty() && object_name_calculation != "1" {
-        params.push(format!("Name: {object_name_calculation}"));
-    }
-    if !repetition_calculation.is_empty() && repetition_calculation != "1" {
-        params.push(repetition_calculation);
-    }
-
-    let params = params.join(" ; ");
-    if params.is_empty() {
-        Some(format!("{name} []"))
-    } else {
-        Some(format!("{name} [ {params} ]"))
+/// Refresh Object and Refresh Portal, which refreshes a portal by its object name
+pub fn sanitize(step: &Step) -> String {
+    match step.target().and_then(|object| object.display_parameter()) {
+        Some(object) => format!("{} [ {object} ]", step.name),
+        None => format!("{} []", step.name),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -79,7 +29,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Objekt aktualisieren []".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -116,8 +66,9 @@ mod tests {
             </Step>
         "#;
 
-        let expected_output = Some(r#"Objekt aktualisieren [ Name: "Foo" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output =
+            Some(r#"Objekt aktualisieren [ Object Name: "Foo" ; Repetition: 1 ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -154,7 +105,34 @@ mod tests {
             </Step>
         "#;
 
-        let expected_output = Some(r#"Objekt aktualisieren [ Name: "Foo" ; 2 ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        let expected_output =
+            Some(r#"Objekt aktualisieren [ Object Name: "Foo" ; Repetition: 2 ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_refresh_portal() {
+        let xml = r#"
+            <Step index="916" id="180" name="Ausschnitt aktualisieren" enable="True">
+                <UUID>24BAA9F4-9E4D-25B8-610F-FD5B368D182D</UUID>
+                <OwnerID></OwnerID>
+                <Options>16384</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Object">
+                        <Name>
+                            <Calculation datatype="1" position="0">
+                                <Calculation>
+                                    <Text><![CDATA["Portal1"]]></Text>
+                                </Calculation>
+                            </Calculation>
+                        </Name>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some(r#"Ausschnitt aktualisieren [ Object Name: "Portal1" ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
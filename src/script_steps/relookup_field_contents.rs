@@ -0,0 +1,61 @@
+use crate::script_steps::step::Step;
+
+pub fn sanitize(step: &Step) -> String {
+    let mut params = Vec::new();
+    params.extend(step.booleans().last().and_then(|dialog| dialog.display()));
+    // Without a target field, the field of the current layout object is looked up again
+    params.extend(
+        step.field()
+            .and_then(|field| field.display())
+            .filter(|field_reference| !field_reference.is_empty()),
+    );
+
+    step.display_with(&params)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test() {
+        let xml = r#"
+            <Step index="366" id="40" name="Referenzwerte holen" enable="True">
+                <Options>129</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Mit Dialog" id="128" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="FieldReference">
+                        <FieldReference id="7" name="_gID_ActivePalette">
+                            <repetition value="1"></repetition>
+                            <TableOccurrenceReference id="1065098" name="_Syntax"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Referenzwerte holen [ Mit Dialog: OFF ; _Syntax::_gID_ActivePalette ]".to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_without_field() {
+        let xml = r#"
+            <Step index="365" id="40" name="Relookup Field Contents" enable="True">
+                <Options>0</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Boolean">
+                        <Boolean type="With dialog" id="128" value="True"></Boolean>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Relookup Field Contents [ With dialog: ON ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -1,13 +1,9 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
-
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::script_steps::parameters::field_reference::FieldReference;
-use crate::utils::attributes::get_attribute;
-
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
 
+pub fn sanitize(step: &Step) -> String {
     let mut params: Vec<(String, String)> = Vec::new();
     let mut calculation = String::new();
     let mut calculation_label = String::new();
@@ -16,71 +12,71 @@ pub fn sanitize(step: &str) -> Option<String> {
     let mut auto_increment_interval_value = String::new();
     let mut list_name_to_input_dialog_label = false;
 
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"Boolean" => {
-                    let value = get_attribute(&e, "value").unwrap().as_str() == "True";
-                    if !value {
-                        continue;
-                    };
-                    let label = get_attribute(&e, "type").unwrap();
-                    params.push((
-                        label,
-                        match value {
-                            true => "ON".to_string(),
-                            false => "OFF".to_string(),
-                        },
-                    ));
-                }
-                b"FieldReference" => {
-                    let field_reference = FieldReference::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap();
-                    params.push(("".to_string(), field_reference))
-                }
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default();
+    let push_boolean = |params: &mut Vec<(String, String)>, label: &str, value: bool| {
+        if value {
+            params.push((label.to_string(), Boolean::bool_to_string(value)));
+        }
+    };
+
+    for parameter in &step.parameters {
+        let elements: Vec<&Element> = match parameter {
+            Parameter::Boolean(boolean) => {
+                push_boolean(
+                    &mut params,
+                    boolean.name.as_deref().unwrap_or_default(),
+                    boolean.value == Some(true),
+                );
+                continue;
+            }
+            Parameter::FieldReference(field) => {
+                params.push(("".to_string(), field.display().unwrap_or_default()));
+                continue;
+            }
+            Parameter::Calculation {
+                calculation: calc, ..
+            } => {
+                calculation = calc.display().unwrap_or_default();
+                continue;
+            }
+            Parameter::Generic(generic) => generic.descendants(),
+            _ => continue,
+        };
+
+        for element in elements {
+            let attribute = |name: &str| element.attribute(name).unwrap_or_default().to_string();
+            match element.name.as_str() {
+                "Boolean" => push_boolean(
+                    &mut params,
+                    &attribute("type"),
+                    attribute("value") == "True",
+                ),
+                "FieldReference" => {
+                    params.push(("".to_string(), element.text.clone().unwrap_or_default()))
                 }
-                b"List" => {
-                    if list_name_to_input_dialog_label {
-                        if get_attribute(&e, "value").unwrap() == "True" {
-                            if let Some(last_param) = params.last_mut() {
-                                last_param.0.clone_from(&last_param.1);
-                                last_param.1 = get_attribute(&e, "name").unwrap().to_string();
-                            }
+                "Calculation" => calculation = element.text.clone().unwrap_or_default(),
+                "List" => {
+                    if list_name_to_input_dialog_label && attribute("value") == "True" {
+                        if let Some(last_param) = params.last_mut() {
+                            last_param.0.clone_from(&last_param.1);
+                            last_param.1 = attribute("name");
                         }
-                        continue;
                     }
 
-                    match get_attribute(&e, "value").unwrap_or_default().as_str() {
-                        "0" | "1" => {
-                            params.push(("".to_string(), get_attribute(&e, "name").unwrap()))
-                        }
+                    match attribute("value").as_str() {
+                        "0" | "1" => params.push(("".to_string(), attribute("name"))),
                         "2" => {
-                            params.push(("".to_string(), get_attribute(&e, "name").unwrap()));
+                            params.push(("".to_string(), attribute("name")));
                             list_name_to_input_dialog_label = true;
                         }
-                        "3" => calculation_label = get_attribute(&e, "name").unwrap(),
+                        "3" => calculation_label = attribute("name"),
                         _ => {}
                     }
                 }
-                b"Initial" => auto_increment_initial_value = get_attribute(&e, "value").unwrap(),
-                b"increment" => auto_increment_interval_value = get_attribute(&e, "value").unwrap(),
+                "Initial" => auto_increment_initial_value = attribute("value"),
+                "increment" => auto_increment_interval_value = attribute("value"),
                 _ => {}
-            },
-            _ => {}
+            }
         }
-        buf.clear()
     }
 
     if !calculation.is_empty() {
@@ -94,27 +90,25 @@ pub fn sanitize(step: &str) -> Option<String> {
         params.push(("Interval".to_string(), auto_increment_interval_value))
     }
 
-    if name.is_empty() {
-        None
-    } else {
-        let formatted_params: Vec<String> = params
-            .iter()
-            .map(|(key, value)| {
-                if key.is_empty() {
-                    value.replace(": ", "").to_string()
-                } else {
-                    format!("{}: {}", key.replace(": ", ""), value)
-                }
-            })
-            .collect();
+    let formatted_params: Vec<String> = params
+        .iter()
+        .map(|(key, value)| {
+            if key.is_empty() {
+                value.replace(": ", "").to_string()
+            } else if *value == Boolean::bool_to_string(true) {
+                Boolean::labeled_bool_to_string(&key.replace(": ", ""), true)
+            } else {
+                format!("{}: {}", key.replace(": ", ""), value)
+            }
+        })
+        .collect();
 
-        Some(format!("{} [ {} ]", name, formatted_params.join(" ; ")))
-    }
+    format!("{} [ {} ]", step.name, formatted_params.join(" ; "))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test_broken_reference() {
@@ -138,7 +132,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Ersetze alle Feldwerte [ Mit Dialog: ON ; 🚨🚨🚨 BROKEN REFERENCE 🚨🚨🚨 ; Aktueller Inhalt ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -170,7 +164,7 @@ mod tests {
             "Ersetze alle Feldwerte [ Mit Dialog: ON ; Durch Berechnung ersetzen: 123456 ]"
                 .to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -199,7 +193,7 @@ mod tests {
             "Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::On ; Aktueller Inhalt ]"
                 .to_string(),
         );
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -228,7 +222,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::On ; Durch fortlaufende Nummern ersetzen: Werte der Eingabeoptionen ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -260,7 +254,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::On ; Durch fortlaufende Nummern ersetzen ; Initial: 1 ; Interval: 1 ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -292,7 +286,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::__ID ; Durch fortlaufende Nummern ersetzen ; Eingabeoptionen aktualisieren: ON ; Initial: 100 ; Interval: 10 ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -324,7 +318,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::__ID ; Durch fortlaufende Nummern ersetzen ; Eingabeoptionen aktualisieren: ON ; Initial: 100 ; Interval: 10 ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -360,6 +354,6 @@ mod tests {
 
         let expected_output =
             Some(r#"Ersetze alle Feldwerte [ Mit Dialog: ON ; _Syntax::On ; Durch Berechnung ersetzen: "Calc" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
@@ -1,79 +1,47 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
-
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
-use crate::script_steps::parameters::parameter_values::ParameterValues;
-use crate::utils::attributes::get_attribute;
-
-pub fn from_xml(step_id: &u32, step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut parameters: Vec<String> = Vec::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    if let Some(value) = get_attribute(&e, "name") {
-                        name = value.to_string();
-                    }
-                    continue;
-                }
-                b"ParameterValues" => parameters.push(
-                    ParameterValues::from_xml(&mut reader, &e, step_id)
-                        .unwrap()
-                        .display()
-                        .unwrap(),
-                ),
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
+use crate::script_steps::step::Step;
 
-    let parameters = parameters.join(" ; ");
+/// Render a step without a dedicated renderer from its typed parameters
+pub fn render(step: &Step) -> String {
+    let name = step.name.as_str();
+    let parameters = step.display_parameters().unwrap_or_default();
 
-    if id_to_script_step(step_id) == ScriptStep::Comment {
+    if id_to_script_step(&step.id) == ScriptStep::Comment {
         if parameters.trim().is_empty() {
-            return Some("".to_string());
+            return "".to_string();
         } else {
-            return Some(format!("# {parameters}"));
+            return format!("# {parameters}");
         }
     }
 
     let parameters = parameters.trim();
     if parameters.is_empty() {
         if matches!(
-            id_to_script_step(step_id),
+            id_to_script_step(&step.id),
             ScriptStep::GoToField
                 | ScriptStep::IfStart
                 | ScriptStep::IfElse
                 | ScriptStep::ExitLoopIf
         ) {
-            return Some(format!("{name} []"));
+            return format!("{name} []");
         }
 
-        return Some(name.to_string());
+        return name.to_string();
     };
 
-    Some(format!("{name} [ {parameters} ]"))
+    format!("{name} [ {parameters} ]")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
         let xml = r#"<Step id="79" name="Fenster fixieren" enable="True">"#;
 
         let expected_output = Some("Fenster fixieren".to_string());
-        let script_id: u32 = 79;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -90,8 +58,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Fehleraufzeichnung setzen [ ON ]".to_string());
-        let script_id: u32 = 86;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -108,8 +75,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Fehleraufzeichnung setzen [ OFF ]".to_string());
-        let script_id: u32 = 86;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -126,8 +92,7 @@ mod tests {
         "#;
 
         let expected_output = Some("Suchenmodus aktivieren [ Pause: OFF ]".to_string());
-        let script_id: u32 = 22;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -150,14 +115,14 @@ mod tests {
 
         let expected_output =
             Some("Tabelle leeren [ Mit Dialog: OFF ; <Tabelle nicht vorhanden> ]".to_string());
-        let script_id: u32 = 182;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
 
 #[cfg(test)]
 mod commit_tests {
-    use super::*;
+    use crate::diagnostics::Diagnostics;
+    use crate::script_steps::sanitizer::{sanitize, sanitize_xml};
 
     #[test]
     fn test() {
@@ -180,8 +145,7 @@ mod commit_tests {
 
         let expected_output =
             Some("Schreibe Änderung Datens./Abfrage [ Mit Dialog: OFF ]".to_string());
-        let script_id: u32 = 75;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -207,8 +171,7 @@ mod commit_tests {
             "Schreibe Änderung Datens./Abfrage [ Mit Dialog: OFF ; Schreiben erzwingen ]"
                 .to_string(),
         );
-        let script_id: u32 = 75;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -231,8 +194,7 @@ mod commit_tests {
         "#;
 
         let expected_output = Some("Schreibe Änderung Datens./Abfrage [ Dateneingabeüberprüfung unterdrücken ; Mit Dialog: OFF ]".to_string());
-        let script_id: u32 = 75;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -255,8 +217,7 @@ mod commit_tests {
         "#;
 
         let expected_output = Some("Schreibe Änderung Datens./Abfrage [ Dateneingabeüberprüfung unterdrücken ; Mit Dialog: ON ; Schreiben erzwingen ]".to_string());
-        let script_id: u32 = 75;
-        assert_eq!(from_xml(&script_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -294,11 +255,9 @@ mod commit_tests {
                 </ParameterValues>
             </Step>
         "#;
-
-        let step_id: u32 = 122;
         let expected_output =
             Some("Neues Fenster [ Style: Dokument ; Layout: <Originallayout> ]".to_string());
-        assert_eq!(from_xml(&step_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -381,9 +340,20 @@ mod commit_tests {
                 </ParameterValues>
             </Step>
         "#;
-
-        let step_id: u32 = 122;
         let expected_output = Some(r#"Neues Fenster [ Style: Dokument ; Name: "Foo Bar" ; Layout: <Originallayout> ; Height: 100 ; Width: 200 ; Top: 300 ; Left: 400 ; Minimize: OFF ; Maximize: OFF ; Resize: OFF ; Menu: OFF ; Toolbar: OFF ]"#.to_string());
-        assert_eq!(from_xml(&step_id, xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_malformed_xml_is_reported() {
+        let xml =
+            r#"<Step id="79" name="Fenster fixieren" enable="True"><Options>4</Option></Step>"#;
+        let mut diagnostics = Diagnostics::default();
+        assert_eq!(
+            sanitize(&79, xml, &mut diagnostics),
+            Some("Fenster fixieren".to_string())
+        );
+        assert_eq!(diagnostics.entries.len(), 1);
+        assert_eq!(diagnostics.entries[0].offset, 62);
     }
 }
@@ -1,38 +1,210 @@
+use crate::diagnostics::Diagnostics;
 use crate::script_steps;
 use crate::script_steps::constants::{id_to_script_step, ScriptStep};
+use crate::script_steps::step::Step;
 
-pub fn sanitize(step_id: &u32, step_xml: &str) -> Option<String> {
-    let is_enabled = script_steps::is_enabled::sanitize(step_xml);
-
-    let step_sanitized = match id_to_script_step(step_id) {
-        ScriptStep::PerformScript => script_steps::perform_script::sanitize(step_xml),
-        ScriptStep::GoToRecordRequestPage => script_steps::go_to_record::sanitize(step_xml),
-        ScriptStep::OmitMultipleRecords => script_steps::omit_multiple_records::sanitize(step_xml),
-        ScriptStep::PerformFind => script_steps::perform_find::sanitize(step_xml),
-        ScriptStep::InsertText => script_steps::insert_text::sanitize(step_xml),
-        ScriptStep::SetField => script_steps::set_field_data::sanitize(step_xml),
-        ScriptStep::ReplaceFieldContents => {
-            script_steps::replace_field_contents::sanitize(step_xml)
-        }
-        ScriptStep::GoToPortalRow => script_steps::go_to_portal_row::sanitize(step_xml),
-        ScriptStep::ExitScript => script_steps::exit_script::sanitize(step_xml),
-        ScriptStep::CloseWindow => script_steps::close_window::sanitize(step_xml),
-        ScriptStep::ConstrainFoundSet => script_steps::perform_find::sanitize(step_xml),
-        ScriptStep::ExtendFoundSet => script_steps::perform_find::sanitize(step_xml),
-        ScriptStep::SetVariable => script_steps::set_variable::sanitize(step_xml),
-        ScriptStep::GoToObject => script_steps::go_to_object::sanitize(step_xml),
-        ScriptStep::RefreshObject => script_steps::refresh_object::sanitize(step_xml),
-        _ => script_steps::sanitize::from_xml(step_id, step_xml),
+pub fn sanitize(step_id: &u32, step_xml: &str, diagnostics: &mut Diagnostics) -> Option<String> {
+    parse(step_id, step_xml, diagnostics).and_then(|step| render(&step, diagnostics))
+}
+
+/// Parse a `<Step>` element, reporting why it couldn't be
+pub fn parse(step_id: &u32, step_xml: &str, diagnostics: &mut Diagnostics) -> Option<Step> {
+    Step::from_xml(step_id, step_xml, diagnostics)
+        .map_err(|err| diagnostics.warn(err.offset, &err))
+        .ok()
+}
+
+/// Render a parsed step like the script workspace shows it
+pub fn render(step: &Step, diagnostics: &mut Diagnostics) -> Option<String> {
+    if step.name.is_empty() {
+        diagnostics.warn(0, "Step without a name");
+        return None;
+    }
+
+    let rendered = match id_to_script_step(&step.id) {
+        ScriptStep::PerformScript => script_steps::perform_script::sanitize(step),
+        ScriptStep::GoToRecordRequestPage => script_steps::go_to_record::sanitize(step),
+        ScriptStep::ShowAllRecords
+        | ScriptStep::OmitRecord
+        | ScriptStep::OmitMultipleRecords
+        | ScriptStep::ShowOmittedOnly => script_steps::omit_multiple_records::sanitize(step),
+        ScriptStep::NewRecordRequest
+        | ScriptStep::DuplicateRecordRequest
+        | ScriptStep::DeleteRecordRequest
+        | ScriptStep::DeleteAllRecords
+        | ScriptStep::DeletePortalRow => script_steps::records::sanitize(step),
+        ScriptStep::UndoRedo
+        | ScriptStep::Cut
+        | ScriptStep::Copy
+        | ScriptStep::Paste
+        | ScriptStep::Clear => script_steps::editing::sanitize(step),
+        ScriptStep::ViewAs | ScriptStep::SortRecordsByField | ScriptStep::UnsortRecords => {
+            script_steps::arrange::sanitize(step)
+        }
+        ScriptStep::PerformFind => script_steps::perform_find::sanitize(step),
+        ScriptStep::InsertText => script_steps::insert_text::sanitize(step),
+        ScriptStep::SetField => script_steps::set_field_data::sanitize(step),
+        ScriptStep::ReplaceFieldContents => script_steps::replace_field_contents::sanitize(step),
+        ScriptStep::GoToPortalRow => script_steps::go_to_portal_row::sanitize(step),
+        ScriptStep::ExitScript => script_steps::exit_script::sanitize(step),
+        ScriptStep::PauseResumeScript => script_steps::pause_resume_script::sanitize(step),
+        ScriptStep::CloseWindow | ScriptStep::SelectWindow => {
+            script_steps::close_window::sanitize(step)
+        }
+        ScriptStep::ConstrainFoundSet => script_steps::perform_find::sanitize(step),
+        ScriptStep::ExtendFoundSet => script_steps::perform_find::sanitize(step),
+        ScriptStep::SetVariable => script_steps::set_variable::sanitize(step),
+        ScriptStep::GoToObject => script_steps::go_to_object::sanitize(step),
+        ScriptStep::RefreshObject | ScriptStep::RefreshPortal => {
+            script_steps::refresh_object::sanitize(step)
+        }
+        ScriptStep::RelookupFieldContents => script_steps::relookup_field_contents::sanitize(step),
+        ScriptStep::InsertCalculatedResult => {
+            script_steps::insert_calculated_result::sanitize(step)
+        }
+        ScriptStep::InsertFromIndex
+        | ScriptStep::InsertFromLastVisited
+        | ScriptStep::InsertCurrentDate
+        | ScriptStep::InsertCurrentTime
+        | ScriptStep::InsertCurrentUserName => script_steps::insert_value::sanitize(step),
+        ScriptStep::InsertFromDevice => script_steps::insert_from_device::sanitize(step),
+        ScriptStep::SetFieldByName => script_steps::set_field_by_name::sanitize(step),
+        ScriptStep::PerformFindReplace | ScriptStep::PerformQuickFind => {
+            script_steps::perform_find_replace::sanitize(step)
+        }
+        ScriptStep::OpenHelp
+        | ScriptStep::OpenManageDatabase
+        | ScriptStep::OpenScriptWorkspace
+        | ScriptStep::OpenPreferences
+        | ScriptStep::OpenManageValueLists
+        | ScriptStep::OpenSharing
+        | ScriptStep::OpenFileOptions
+        | ScriptStep::OpenHosts
+        | ScriptStep::OpenFindReplace
+        | ScriptStep::OpenManageDataSources
+        | ScriptStep::OpenEditSavedFinds
+        | ScriptStep::OpenManageLayouts
+        | ScriptStep::OpenManageContainers
+        | ScriptStep::OpenManageThemes
+        | ScriptStep::UploadToServer
+        | ScriptStep::OpenFavorites => script_steps::open_dialog::sanitize(step),
+        ScriptStep::SetMultiUser
+        | ScriptStep::AllowUserAbort
+        | ScriptStep::SetErrorCapture
+        | ScriptStep::SetUseSystemFormats
+        | ScriptStep::AllowFormattingBar
+        | ScriptStep::SetLayoutObjectAnimation => script_steps::on_off::sanitize(step),
+        ScriptStep::SaveACopyAs | ScriptStep::RecoverFile | ScriptStep::ConvertFile => {
+            script_steps::file_maintenance::sanitize(step)
+        }
+        ScriptStep::SetSelection => script_steps::set_selection::sanitize(step),
+        ScriptStep::SetZoomLevel => script_steps::set_zoom_level::sanitize(step),
+        ScriptStep::ChangePassword
+        | ScriptStep::AddAccount
+        | ScriptStep::DeleteAccount
+        | ScriptStep::ResetAccountPassword
+        | ScriptStep::EnableAccount
+        | ScriptStep::ReLogin => script_steps::accounts::sanitize(step),
+        _ => script_steps::sanitize::render(step),
     };
 
-    match step_sanitized {
-        None => {
-            println!("Could not parse: {step_xml}");
-            None
+    match step.enabled {
+        true => Some(rendered),
+        false => Some(format!("// {rendered}")),
+    }
+}
+
+/// Render a `<Step>` element by the id it carries
+#[cfg(test)]
+pub fn sanitize_xml(step_xml: &str) -> Option<String> {
+    let step_id = regex::Regex::new(r#"<Step [^>]*\bid="(\d+)""#)
+        .unwrap()
+        .captures(step_xml)
+        .and_then(|captures| captures[1].parse().ok())?;
+    sanitize(&step_id, step_xml, &mut Diagnostics::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::Read;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use encoding_rs_io::DecodeReaderBytes;
+    use regex::Regex;
+
+    use super::*;
+
+    /// The first occurrence of every script step in the sample export, keyed by step id
+    fn sample_steps() -> BTreeMap<u32, String> {
+        let file = std::fs::File::open("tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml").unwrap();
+        let mut xml = String::new();
+        DecodeReaderBytes::new(file)
+            .read_to_string(&mut xml)
+            .unwrap();
+
+        let step_re = Regex::new(r#"(?s)<Step [^>]*\bid="(\d+)"[^>]*>.*?</Step>"#).unwrap();
+        let mut steps = BTreeMap::new();
+        for captures in step_re.captures_iter(&xml) {
+            let step_id = captures[1].parse().unwrap();
+            steps
+                .entry(step_id)
+                .or_insert_with(|| captures[0].to_string());
+        }
+        steps
+    }
+
+    #[test]
+    fn test_unparsed_step_is_reported_once() {
+        let mut diagnostics = Diagnostics::default();
+        let xml = r#"<Step enable="True" id="1" name=""></Step>"#;
+        assert_eq!(sanitize(&1, xml, &mut diagnostics), None);
+        assert_eq!(diagnostics.entries.len(), 1);
+        assert_eq!(diagnostics.entries[0].message, "Step without a name");
+    }
+
+    #[test]
+    fn test_render_step_without_xml() {
+        let step = Step {
+            id: 103,
+            name: "Exit Script".to_string(),
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            render(&step, &mut Diagnostics::default()),
+            Some("// Exit Script []".to_string())
+        );
+    }
+
+    #[test]
+    fn test_malformed_steps_do_not_panic() {
+        let attribute_re = Regex::new(r#"\s[A-Za-z]+="[^"]*""#).unwrap();
+        let steps = sample_steps();
+        assert!(steps.len() > 50);
+
+        let mut panicked = Vec::new();
+        for (step_id, xml) in &steps {
+            let mut mutations: Vec<String> = attribute_re
+                .find_iter(xml)
+                .map(|m| format!("{}{}", &xml[..m.start()], &xml[m.end()..]))
+                .collect();
+            mutations.extend(
+                xml.char_indices()
+                    .map(|(i, _)| i)
+                    .filter(|i| i % 5 == 0)
+                    .map(|i| xml[..i].to_string()),
+            );
+
+            for mutation in mutations {
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    sanitize(step_id, &mutation, &mut Diagnostics::default())
+                }));
+                if result.is_err() {
+                    panicked.push(mutation);
+                }
+            }
         }
-        Some(step) => match is_enabled {
-            true => Some(step),
-            false => Some(format!("// {step}")),
-        },
+        assert!(panicked.is_empty(), "Panicked on: {panicked:#?}");
     }
 }
@@ -0,0 +1,84 @@
+use crate::script_steps::step::Step;
+
+pub fn sanitize(step: &Step) -> String {
+    // The calculation of the target field name comes first, at position 1
+    let mut calculations = step.all_calculations().into_iter();
+    let target_name = calculations.next().unwrap_or_default();
+    let result = calculations.next().unwrap_or_default();
+
+    let name = &step.name;
+    if target_name.is_empty() && result.is_empty() {
+        name.to_string()
+    } else if result.is_empty() {
+        format!("{name} [ {target_name} ]")
+    } else {
+        format!("{name} [ {target_name} ; {result} ]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test() {
+        let xml = r#"
+            <Step index="383" id="147" name="Feld nach Namen einstellen" enable="True">
+                <Options>134234112</Options>
+                <ParameterValues membercount="3">
+                    <Parameter type="Boolean">
+                        <Boolean type="Zielfeld angeben" id="134217728" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="Calculation">
+                        <Calculation datatype="1" position="1">
+                            <Calculation>
+                                <Text><![CDATA["Test::Egal"]]></Text>
+                                <ChunkList hash="FA83FC23A60740B98B7362B284BE3B3B">
+                                    <Chunk type="NoRef">&quot;Test::Egal&quot;</Chunk>
+                                </ChunkList>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                    <Parameter type="Calculation">
+                        <Calculation datatype="1" position="0">
+                            <Calculation>
+                                <Text><![CDATA[1+2]]></Text>
+                                <ChunkList hash="CE443E22F9C48854815009E8615DBC26">
+                                    <Chunk type="NoRef">1+2</Chunk>
+                                </ChunkList>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output =
+            Some(r#"Feld nach Namen einstellen [ "Test::Egal" ; 1+2 ]"#.to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_without_result() {
+        let xml = r#"
+            <Step index="387" id="147" name="Set Field By Name" enable="True">
+                <Options>16384</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Specify target field" id="134217728" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="Calculation">
+                        <Calculation datatype="1" position="1">
+                            <Calculation>
+                                <Text><![CDATA[$FieldName]]></Text>
+                            </Calculation>
+                        </Calculation>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Set Field By Name [ $FieldName ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
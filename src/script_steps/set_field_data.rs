@@ -1,52 +1,18 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::script_steps::parameters::field_reference::FieldReference;
-use crate::utils::attributes::get_attribute;
+pub fn sanitize(step: &Step) -> String {
+    let field_reference = step
+        .field()
+        .and_then(|field| field.display())
+        .unwrap_or_default();
+    let calculation = step.all_calculations().pop().unwrap_or_default();
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut field_reference = String::new();
-    let mut calculation = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => name = get_attribute(&e, "name").unwrap().to_string(),
-                b"FieldReference" => {
-                    field_reference = FieldReference::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap()
-                }
-                b"Calculation" => {
-                    calculation = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default();
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else {
-        Some(format!("{name} [ {field_reference} ; {calculation} ]"))
-    }
+    format!("{} [ {field_reference} ; {calculation} ]", step.name)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -80,6 +46,6 @@ mod tests {
 
         let expected_output =
             Some("Feldwert setzen [ TableFoo::FieldFoo ; TableBar::FieldBar ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
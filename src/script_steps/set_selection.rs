@@ -0,0 +1,88 @@
+use crate::script_steps::step::Step;
+
+/// Set Selection with its target field and the start and end position calculations. Without a
+/// target field, the selection is set in the active field.
+pub fn sanitize(step: &Step) -> String {
+    let mut params = Vec::new();
+    params.extend(
+        step.field()
+            .and_then(|field| field.display())
+            .filter(|field_reference| !field_reference.is_empty()),
+    );
+
+    let position = |label: &str| {
+        step.generic("Select")
+            .and_then(|select| select.find(label))
+            .and_then(|position| position.find("Calculation"))
+            .and_then(|calculation| calculation.text.clone())
+            .filter(|calculation| !calculation.is_empty())
+    };
+    params.extend(position("Start").map(|start| format!("Start Position: {start}")));
+    params.extend(position("End").map(|end| format!("End Position: {end}")));
+
+    step.display_with(&params)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_set_selection() {
+        let xml = r#"
+            <Step index="199" id="130" name="Auswahl festlegen" enable="True">
+                <UUID>E98A085C-C42D-4B9A-BCF8-1F3966A458FF</UUID>
+                <OwnerID></OwnerID>
+                <Options>16385</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="FieldReference">
+                        <FieldReference id="1" name="Name" UUID="">
+                            <TableOccurrenceReference id="1065089" name="Kunden"></TableOccurrenceReference>
+                        </FieldReference>
+                    </Parameter>
+                    <Parameter type="Select">
+                        <Start>
+                            <Calculation datatype="1" position="0">
+                                <Calculation>
+                                    <Text><![CDATA[1]]></Text>
+                                </Calculation>
+                            </Calculation>
+                        </Start>
+                        <End>
+                            <Calculation datatype="1" position="1">
+                                <Calculation>
+                                    <Text><![CDATA[12]]></Text>
+                                </Calculation>
+                            </Calculation>
+                        </End>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some(
+            "Auswahl festlegen [ Kunden::Name ; Start Position: 1 ; End Position: 12 ]".to_string(),
+        );
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_empty() {
+        let xml = r#"
+            <Step index="49" id="130" name="Auswahl festlegen" enable="True">
+                <UUID>9CCEA1F7-BADF-41A0-8B6C-49CBF8B41292</UUID>
+                <OwnerID></OwnerID>
+                <Options>0</Options>
+                <ParameterValues membercount="1">
+                    <Parameter type="Select">
+                        <Start></Start>
+                        <End></End>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Auswahl festlegen".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
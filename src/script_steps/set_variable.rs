@@ -1,61 +1,32 @@
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use crate::script_steps::step::Step;
 
-use crate::script_steps::parameters::calculation::Calculation;
-use crate::utils::attributes::get_attribute;
+pub fn sanitize(step: &Step) -> String {
+    let variable = step.generic("Variable");
+    let variable_name = variable
+        .and_then(|variable| variable.find("Name"))
+        .and_then(|name| name.attribute("value"))
+        .unwrap_or_default();
+    let calculation = |name: &str| {
+        variable
+            .and_then(|variable| variable.find(name))
+            .and_then(|element| element.find("Calculation"))
+            .and_then(|calculation| calculation.text.as_deref())
+            .unwrap_or_default()
+    };
+    let value = calculation("value");
+    let repetition = calculation("repetition");
 
-pub fn sanitize(step: &str) -> Option<String> {
-    let mut name = String::new();
-    let mut variable_name = String::new();
-    let mut value = String::new();
-    let mut repetition = String::new();
-
-    let mut reader = Reader::from_str(step);
-    let mut buf: Vec<u8> = Vec::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Err(_) => continue,
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"Step" => {
-                    name = get_attribute(&e, "name").unwrap().to_string();
-                }
-                b"Name" => {
-                    variable_name = get_attribute(&e, "value").unwrap().to_string();
-                }
-                b"value" => {
-                    value = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default();
-                }
-                b"repetition" => {
-                    repetition = Calculation::from_xml(&mut reader, &e)
-                        .unwrap()
-                        .display()
-                        .unwrap_or_default();
-                }
-                _ => {}
-            },
-            _ => {}
-        }
-        buf.clear()
-    }
-
-    if name.is_empty() {
-        None
-    } else if repetition.is_empty() {
-        Some(format!("{name} [ {variable_name} ; {value} ]"))
+    let name = &step.name;
+    if repetition.is_empty() {
+        format!("{name} [ {variable_name} ; {value} ]")
     } else {
-        Some(format!(
-            "{name} [ {variable_name}[{repetition}] ; {value} ]"
-        ))
+        format!("{name} [ {variable_name}[{repetition}] ; {value} ]")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::script_steps::sanitizer::sanitize_xml;
 
     #[test]
     fn test() {
@@ -82,7 +53,7 @@ mod tests {
         "#;
 
         let expected_output = Some(r#"Variable setzen [ $Foo ; "Bar" ]"#.to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 
     #[test]
@@ -121,6 +92,6 @@ mod tests {
         "#;
 
         let expected_output = Some("Variable setzen [ $Foo[$Rep] ; $Bar ]".to_string());
-        assert_eq!(sanitize(xml.trim()), expected_output);
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
     }
 }
@@ -0,0 +1,70 @@
+use crate::script_steps::parameters::parameter_values::Parameter;
+use crate::script_steps::step::Step;
+
+/// Set Zoom Level with the lock flag and the percentage or zoom in/out step.
+pub fn sanitize(step: &Step) -> String {
+    let options: Vec<String> = step
+        .parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Parameter::Boolean(boolean) => boolean.display(),
+            Parameter::List(list) => list.display(),
+            Parameter::Generic(generic) => generic
+                .find("List")
+                .and_then(|list| list.attribute("name"))
+                .map(str::to_string),
+            _ => None,
+        })
+        .collect();
+
+    step.display_with(&options)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::sanitizer::sanitize_xml;
+
+    #[test]
+    fn test_set_zoom_level() {
+        let xml = r#"
+            <Step index="608" id="97" name="Set Zoom Level" enable="True">
+                <UUID>34313555-A5E5-4734-807C-0268D03C3A39</UUID>
+                <OwnerID></OwnerID>
+                <Options>524290</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Lock" id="524288" value="True"></Boolean>
+                    </Parameter>
+                    <Parameter type="List">
+                        <List name="25%" value="1"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Set Zoom Level [ Lock: ON ; 25% ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+
+    #[test]
+    fn test_unlocked() {
+        let xml = r#"
+            <Step index="10" id="97" name="Set Zoom Level" enable="True">
+                <UUID>E3C59E5F-2E24-4D04-8561-763FF94474D2</UUID>
+                <OwnerID></OwnerID>
+                <Options>2</Options>
+                <ParameterValues membercount="2">
+                    <Parameter type="Boolean">
+                        <Boolean type="Lock" id="524288" value="False"></Boolean>
+                    </Parameter>
+                    <Parameter type="List">
+                        <List name="100%" value="4"></List>
+                    </Parameter>
+                </ParameterValues>
+            </Step>
+        "#;
+
+        let expected_output = Some("Set Zoom Level [ Lock: OFF ; 100% ]".to_string());
+        assert_eq!(sanitize_xml(xml.trim()), expected_output);
+    }
+}
@@ -0,0 +1,264 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostics;
+use crate::script_steps::parameters::boolean::Boolean;
+use crate::script_steps::parameters::element::Element;
+use crate::script_steps::parameters::field_reference::FieldReference;
+use crate::script_steps::parameters::generic::Generic;
+use crate::script_steps::parameters::list::List;
+use crate::script_steps::parameters::parameter_values::{Parameter, ParameterValues};
+use crate::script_steps::parameters::target::Target;
+use crate::script_steps::parse_error::ParseError;
+use crate::symbols::{reference_name, SymbolKind};
+use crate::utils::attributes::{parse_unescaped_attribute, unescaped_attribute};
+use crate::utils::buffer_pool::PooledBuffer;
+
+/// A script step with its typed parameters, the model the step renderers and the JSON output share
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Step {
+    pub id: u32,
+    /// As named by the export, in its language
+    pub name: String,
+    pub enabled: bool,
+    pub parameters: Vec<Parameter>,
+    /// The script a custom menu item performs, which older exports reference outside the parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
+impl Step {
+    /// Parse a `<Step>` element; steps with a dedicated renderer are read like all others
+    pub fn from_xml(
+        step_id: &u32,
+        step: &str,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Step, ParseError> {
+        let mut item = Step {
+            id: *step_id,
+            enabled: true,
+            ..Default::default()
+        };
+
+        let mut reader = Reader::from_str(step);
+        let mut buf = PooledBuffer::take();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Err(err) => {
+                    diagnostics.warn(reader.error_position(), err);
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => match e.name().as_ref() {
+                    b"Step" => {
+                        if let Some(value) = parse_unescaped_attribute(&e, "name") {
+                            item.name = value.to_string();
+                        }
+                        if unescaped_attribute(&e, "enable").as_deref() == Some("False") {
+                            item.enabled = false;
+                        }
+                    }
+                    b"ScriptReference" => {
+                        item.script = reference_name(&e, SymbolKind::Script)
+                            .or_else(|| parse_unescaped_attribute(&e, "name"));
+                    }
+                    b"ParameterValues" => item.parameters.extend(
+                        ParameterValues::from_xml(&mut reader, &e, step_id, diagnostics)?
+                            .parameters,
+                    ),
+                    _ => {}
+                },
+                _ => {}
+            }
+            buf.clear()
+        }
+
+        Ok(item)
+    }
+
+    /// The parameters as rendered between the brackets of the step, e.g. `Pause: OFF ; 5`
+    pub fn display_parameters(&self) -> Option<String> {
+        ParameterValues::display_parameters(&self.id, &self.parameters)
+    }
+
+    /// The step with its parameters, or only its name without any
+    pub fn display_with(&self, parameters: &[String]) -> String {
+        if parameters.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} [ {} ]", self.name, parameters.join(" ; "))
+        }
+    }
+
+    /// The elements of the parameters without a dedicated parser and inside lists, in document
+    /// order, e.g. the `Boolean` of `Records`
+    pub fn elements(&self) -> Vec<&Element> {
+        self.parameters.iter().flat_map(elements_of).collect()
+    }
+
+    /// The `Boolean` parameters, e.g. `With dialog`
+    pub fn booleans(&self) -> impl Iterator<Item = &Boolean> {
+        self.parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::Boolean(boolean) => Some(boolean),
+                _ => None,
+            })
+    }
+
+    /// The `List` parameters, e.g. the state of Set Zoom Level
+    pub fn lists(&self) -> impl Iterator<Item = &List> {
+        self.parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::List(list) => Some(list),
+                _ => None,
+            })
+    }
+
+    /// The flags of all parameters, also those inside parameters without a dedicated parser
+    pub fn all_booleans(&self) -> Vec<Boolean> {
+        self.parameters
+            .iter()
+            .flat_map(|parameter| match parameter {
+                Parameter::Boolean(boolean) => vec![boolean.clone()],
+                _ => elements_of(parameter)
+                    .into_iter()
+                    .filter(|element| element.name == "Boolean")
+                    .map(|element| Boolean::from_parsed_element(element, &self.id))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The rendered calculations of all parameters, also those inside parameters without a
+    /// dedicated parser
+    pub fn all_calculations(&self) -> Vec<String> {
+        self.parameters
+            .iter()
+            .flat_map(|parameter| match parameter {
+                Parameter::Calculation { calculation, .. } => {
+                    vec![calculation.display().unwrap_or_default()]
+                }
+                _ => elements_of(parameter)
+                    .into_iter()
+                    .filter(|element| element.name == "Calculation")
+                    .map(|element| element.text.clone().unwrap_or_default())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The last `FieldReference` parameter
+    pub fn field(&self) -> Option<&FieldReference> {
+        self.parameters
+            .iter()
+            .rev()
+            .find_map(|parameter| match parameter {
+                Parameter::FieldReference(field) => Some(field),
+                _ => None,
+            })
+    }
+
+    /// The last `Target` or `Object` parameter
+    pub fn target(&self) -> Option<&Target> {
+        self.parameters
+            .iter()
+            .rev()
+            .find_map(|parameter| match parameter {
+                Parameter::Target(target) => Some(target),
+                _ => None,
+            })
+    }
+
+    /// The parameter of the given type without a dedicated parser, e.g. `Records`
+    pub fn generic(&self, parameter_type: &str) -> Option<&Generic> {
+        self.parameters
+            .iter()
+            .find_map(|parameter| match parameter {
+                Parameter::Generic(generic) if generic.parameter_type == parameter_type => {
+                    Some(generic)
+                }
+                _ => None,
+            })
+    }
+}
+
+/// The elements of a parameter without a dedicated parser or inside a list
+fn elements_of(parameter: &Parameter) -> Vec<&Element> {
+    match parameter {
+        Parameter::Generic(generic) => generic.descendants(),
+        Parameter::List(list) => list
+            .elements
+            .iter()
+            .flat_map(Element::descendants)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script_steps::parameters::calculation::Calculation;
+
+    use super::*;
+
+    const XML: &str = r#"
+        <Step id="141" name="Set Variable" enable="False">
+            <ParameterValues membercount="2">
+                <Parameter type="Name">
+                    <Name><Calculation><Text><![CDATA[$count]]></Text></Calculation></Name>
+                </Parameter>
+                <Parameter type="Boolean">
+                    <Boolean type="Pause" id="16777216" value="True"></Boolean>
+                </Parameter>
+            </ParameterValues>
+        </Step>
+    "#;
+
+    #[test]
+    fn test_from_xml() {
+        let step = Step::from_xml(&141, XML.trim(), &mut Diagnostics::default()).unwrap();
+        assert_eq!(
+            step,
+            Step {
+                id: 141,
+                name: "Set Variable".to_string(),
+                enabled: false,
+                parameters: vec![
+                    Parameter::Calculation {
+                        name: Some("Name".to_string()),
+                        calculation: Calculation {
+                            calculation: Some("$count".to_string()),
+                        },
+                    },
+                    Parameter::Boolean(Boolean {
+                        step_id: 141,
+                        id: Some(16777216),
+                        name: Some("Pause".to_string()),
+                        value: Some(true),
+                        state: None,
+                    }),
+                ],
+                script: None,
+            }
+        );
+        assert_eq!(
+            step.display_parameters(),
+            Some("Name: $count ; Pause: ON".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let step = Step::from_xml(&141, XML.trim(), &mut Diagnostics::default()).unwrap();
+        let json = serde_json::to_value(&step).unwrap();
+        assert_eq!(
+            json["parameters"][0],
+            serde_json::json!({"type": "calculation", "name": "Name", "calculation": "$count"})
+        );
+        assert_eq!(serde_json::from_value::<Step>(json).unwrap(), step);
+    }
+}
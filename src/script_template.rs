@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Error, Result};
+use serde::Serialize;
+use tera::Tera;
+
+use crate::script_sanitizer::RenderedStep;
+use crate::symbols::Symbol;
+
+const TEMPLATE_NAME: &str = "script";
+
+/// A user supplied Tera template rendering one script to the content of its text file
+pub struct ScriptTemplate {
+    tera: Tera,
+}
+
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    db: &'a str,
+    script: ScriptContext<'a>,
+    steps: Vec<StepContext<'a>>,
+}
+
+#[derive(Serialize)]
+struct ScriptContext<'a> {
+    id: &'a str,
+    name: &'a str,
+    /// From the script catalog, if the export records them
+    modified_by: Option<&'a str>,
+    modified_at: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct StepContext<'a> {
+    index: usize,
+    id: u32,
+    name: &'a str,
+    enabled: bool,
+    is_comment: bool,
+    text: String,
+    lines: &'a [String],
+    indent: String,
+    continuation_indent: String,
+}
+
+impl ScriptTemplate {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Error reading template {}", path.display()))?;
+        Self::from_source(&source).with_context(|| format!("Invalid template {}", path.display()))
+    }
+
+    pub fn from_source(source: &str) -> Result<Self, Error> {
+        let mut tera = Tera::default();
+        tera.autoescape_on(Vec::new());
+        tera.add_raw_template(TEMPLATE_NAME, source)?;
+        Ok(Self { tera })
+    }
+
+    pub fn render(
+        &self,
+        db_name: &str,
+        script_id: &str,
+        script_name: &str,
+        script_symbol: Option<&Symbol>,
+        steps: &[RenderedStep],
+    ) -> Result<String, Error> {
+        let context = TemplateContext {
+            db: db_name,
+            script: ScriptContext {
+                id: script_id,
+                name: script_name,
+                modified_by: script_symbol.and_then(|symbol| symbol.modified_by.as_deref()),
+                modified_at: script_symbol.and_then(|symbol| symbol.modified_at.as_deref()),
+            },
+            steps: steps
+                .iter()
+                .map(|step| StepContext {
+                    index: step.index,
+                    id: step.id,
+                    name: &step.name,
+                    enabled: step.enabled,
+                    is_comment: step.is_comment,
+                    text: step.text(),
+                    lines: &step.lines,
+                    indent: step.indent(),
+                    continuation_indent: step.continuation_indent(),
+                })
+                .collect(),
+        };
+        let context = tera::Context::from_serialize(context)?;
+        Ok(self.tera.render(TEMPLATE_NAME, &context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script_sanitizer::render_script_text;
+
+    /// The template reproducing the default text format, a starting point for custom templates
+    const DEFAULT_TEMPLATE: &str = include_str!("templates/script.txt.tera");
+
+    fn step(index: usize, indent_level: usize, is_comment: bool, lines: &[&str]) -> RenderedStep {
+        RenderedStep {
+            index,
+            id: if is_comment { 89 } else { 68 },
+            name: String::new(),
+            enabled: true,
+            is_comment,
+            indent_level,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            calc_comments: Vec::new(),
+            parameters: Vec::new(),
+            is_understood: true,
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_native_output() {
+        let steps = vec![
+            step(1, 0, true, &["# First line", "second line"]),
+            step(2, 0, false, &["If [ $a = 1 and", "$b = 2 ]"]),
+            step(3, 1, false, &["Set Variable [ $c ; 3 ]"]),
+            step(4, 1, false, &[]),
+            step(5, 0, false, &["End If"]),
+        ];
+        let template = ScriptTemplate::from_source(DEFAULT_TEMPLATE).unwrap();
+        let rendered = template.render("db", "1", "Script", None, &steps).unwrap();
+        assert_eq!(rendered, render_script_text(&steps, false));
+    }
+
+    #[test]
+    fn test_modification() {
+        let symbol = Symbol {
+            name: "Script".to_string(),
+            modified_by: Some("Admin".to_string()),
+            modified_at: Some("2025-06-09T10:20:05".to_string()),
+            ..Default::default()
+        };
+        let source = "{{ script.modified_at }} by {{ script.modified_by }}{% if not script.modified_by %}-{% endif %}";
+        let template = ScriptTemplate::from_source(source).unwrap();
+        let rendered = template
+            .render("db", "1", "Script", Some(&symbol), &[])
+            .unwrap();
+        assert_eq!(rendered, "2025-06-09T10:20:05 by Admin");
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let steps = vec![step(1, 0, false, &["Beep"])];
+        let source = "{{ db }}/{{ script.name }}:{% for step in steps %} {{ step.index }}={{ step.text }}{% endfor %}";
+        let template = ScriptTemplate::from_source(source).unwrap();
+        let rendered = template.render("db", "1", "A & B", None, &steps).unwrap();
+        assert_eq!(rendered, "db/A & B: 1=Beep");
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use regex::{Regex, RegexBuilder};
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::analysis::{read_exports, write_output};
+use crate::diagnostics::Diagnostics;
+use crate::exit_status::ExitStatus;
+use crate::manifest::{Manifest, MANIFEST_FILE_NAME};
+use crate::script_steps::sanitizer::sanitize;
+use crate::symbols::SymbolKind;
+use crate::AnalysisInput;
+
+/// Run the `search` subcommand: exports are parsed, output folders with a manifest are read
+pub fn run(
+    pattern: &str,
+    input: &AnalysisInput,
+    ignore_case: bool,
+    fixed_strings: bool,
+) -> Result<ExitStatus, Error> {
+    let pattern = match fixed_strings {
+        true => regex::escape(pattern),
+        false => pattern.to_string(),
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .with_context(|| format!("Invalid search pattern '{pattern}'"))?;
+
+    let (output_dirs, exports): (Vec<PathBuf>, Vec<PathBuf>) = input
+        .inputs
+        .iter()
+        .cloned()
+        .partition(|path| path.join(MANIFEST_FILE_NAME).is_file());
+
+    let mut matches = Vec::new();
+    for output_dir in &output_dirs {
+        matches.extend(search_output_dir(output_dir, &regex)?);
+    }
+    if !exports.is_empty() {
+        for model in read_exports(&exports)? {
+            matches.extend(search_export(&model, &regex));
+        }
+    }
+
+    let report: String = matches.iter().map(|line| format!("{line}\n")).collect();
+    write_output(input, &report)?;
+    Ok(ExitStatus::Clean)
+}
+
+/// Matching lines of the files listed in the manifest of an output folder, as `path:line: text`
+fn search_output_dir(output_dir: &Path, regex: &Regex) -> Result<Vec<String>, Error> {
+    let manifest = Manifest::read(output_dir)?.unwrap_or_default();
+    let mut matches = Vec::new();
+    for entry in &manifest.files {
+        let path = output_dir.join(&entry.path);
+        // Binary files like images of the supporting files are skipped
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for (number, line) in content.lines().enumerate() {
+            if regex.is_match(line) {
+                matches.push(format!("{}:{}: {}", entry.path, number + 1, line.trim()));
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Matching rendered steps, calculations and names of an export
+fn search_export(model: &ExportModel, regex: &Regex) -> Vec<String> {
+    let mut matches = Vec::new();
+    let mut push_lines = |location: &dyn std::fmt::Display, text: &str| {
+        for line in text.lines().filter(|line| regex.is_match(line)) {
+            matches.push(format!("{}: {location}: {}", model.db_name, line.trim()));
+        }
+    };
+
+    for script in &model.scripts {
+        for step in &script.steps {
+            let rendered = sanitize(&step.id, &step.xml, &mut Diagnostics::default());
+            let owner = CalculationOwner::ScriptStep {
+                script_id: script.id,
+                script: script.name.clone(),
+                step: step.number,
+            };
+            push_lines(&owner, &rendered.unwrap_or_default().replace('\r', "\n"));
+        }
+    }
+    for calculation in &model.calculations {
+        push_lines(&calculation.owner, &calculation.text.replace('\r', "\n"));
+    }
+
+    let mut names: Vec<String> = model
+        .symbols
+        .symbols()
+        .map(|(kind, _, symbol)| format!("{} {}", kind_label(kind), symbol.name))
+        .chain(model.symbols.fields().map(|(table_id, _, field)| {
+            let table = model
+                .symbols
+                .name(SymbolKind::BaseTable, table_id)
+                .unwrap_or_default();
+            format!("field {table}::{}", field.name)
+        }))
+        .filter(|name| regex.is_match(name.split_once(' ').map_or("", |(_, name)| name)))
+        .collect();
+    names.sort();
+    matches.extend(
+        names
+            .into_iter()
+            .map(|name| format!("{}: {name}", model.db_name)),
+    );
+    matches
+}
+
+fn kind_label(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Script => "script",
+        SymbolKind::Layout => "layout",
+        SymbolKind::BaseTable => "table",
+        SymbolKind::TableOccurrence => "table occurrence",
+        SymbolKind::ValueList => "value list",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total">
+                        <Calculation><Text><![CDATA[Sum ( Lines::Amount )]]></Text></Calculation>
+                    </Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <ScriptCatalog>
+            <Script id="1" name="Recalculate totals"></Script>
+        </ScriptCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Recalculate totals"></ScriptReference>
+                <ObjectList>
+                    <Step id="89" name="# (comment)" enable="True"></Step>
+                    <Step id="141" name="Set Variable" enable="True">
+                        <ParameterValues>
+                            <Parameter type="Variable">
+                                <value><Calculation><Text><![CDATA[Invoices::Total]]></Text></Calculation></value>
+                                <Name value="$total"></Name>
+                            </Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_search_export() {
+        let model = read_model(&export(CATALOGS));
+        let regex = RegexBuilder::new("total")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            search_export(&model, &regex),
+            vec![
+                "Invoices: script Recalculate totals (ID 1) step 2: Set Variable [ $total ; Invoices::Total ]",
+                "Invoices: field Invoices::Total",
+                "Invoices: script Recalculate totals",
+            ]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{bail, Error};
+use regex::Regex;
+
+use crate::config::CatalogType;
+use crate::inputs::read_db_name;
+use crate::manifest::element_id_from_path;
+use crate::symbols::{SymbolKind, SymbolTable};
+use crate::OutputTree;
+
+/// Validate the date or time of `--since`, e.g. `2025-06-01` or `2025-06-01T14:30`. Exported
+/// timestamps are ISO 8601, so it is compared to them as a string.
+pub fn parse_since(value: &str) -> Result<String, String> {
+    let regex = Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}(:\d{2})?)?$").unwrap();
+    match regex.is_match(value) {
+        true => Ok(value.to_string()),
+        false => Err("expected a date like 2025-06-01 or a time like 2025-06-01T14:30".to_string()),
+    }
+}
+
+/// The latest modification recorded on the UUIDs of an exploded element and its children, e.g.
+/// of a table and its fields
+pub fn latest_timestamp(xml: &str) -> Option<&str> {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    let regex = REGEX.get_or_init(|| Regex::new(r#"<UUID [^>]*timestamp="([^"]+)""#).unwrap());
+    regex
+        .captures_iter(xml)
+        .filter_map(|captures| captures.get(1))
+        .map(|timestamp| timestamp.as_str())
+        .max()
+}
+
+/// Whether the exploded catalog item at `file_path` was modified on or after `since`. Its own
+/// timestamps are used, else those of the catalog it is listed in, e.g. the script catalog for the
+/// steps of a script. Items without any timestamp are kept.
+pub fn is_modified_since(
+    file_path: &Path,
+    catalog_type: CatalogType,
+    symbols: Option<&SymbolTable>,
+    since: &str,
+) -> bool {
+    let xml = fs::read_to_string(file_path).unwrap_or_default();
+    if let Some(timestamp) = latest_timestamp(&xml) {
+        return timestamp >= since;
+    }
+    let kind = match catalog_type {
+        CatalogType::Script | CatalogType::StepsForScripts => SymbolKind::Script,
+        CatalogType::Layout => SymbolKind::Layout,
+        CatalogType::BaseTable | CatalogType::FieldsForTables => SymbolKind::BaseTable,
+        CatalogType::TableOccurrence => SymbolKind::TableOccurrence,
+        CatalogType::ValueList | CatalogType::OptionsForValueLists => SymbolKind::ValueList,
+        _ => return true,
+    };
+    let modified_at = element_id_from_path(file_path)
+        .and_then(|id| id.parse().ok())
+        .and_then(|id| symbols?.symbol(kind, id)?.modified_at.as_deref());
+    modified_at.is_none_or(|timestamp| timestamp >= since)
+}
+
+/// Refuse to explode with `--since` over the output of a previous run: the output of a database is
+/// deleted before exploding, so the files of the items not modified since would be lost
+pub fn check_no_previous_output(
+    out_dir: &Path,
+    paths: &[PathBuf],
+    output_tree: &OutputTree,
+) -> Result<(), Error> {
+    for db_name in paths.iter().filter_map(|path| read_db_name(path)) {
+        let exists = match output_tree {
+            OutputTree::Db => out_dir.join(&db_name).exists(),
+            OutputTree::Domain => fs::read_dir(out_dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .any(|entry| entry.path().join(&db_name).exists())
+                })
+                .unwrap_or(false),
+        };
+        if exists {
+            bail!(
+                "{} already holds the output of database '{db_name}', which --since would replace with only the items modified since; explode into an empty folder",
+                out_dir.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2025-06-01").as_deref(), Ok("2025-06-01"));
+        assert!(parse_since("2025-06-01T14:30").is_ok());
+        assert!(parse_since("2025-06-01T14:30:05").is_ok());
+        assert!(parse_since("06/01/2025").is_err());
+        assert!(parse_since("2025-06-01 14:30").is_err());
+    }
+
+    #[test]
+    fn test_latest_timestamp() {
+        let xml = r#"<BaseTable id="129" name="Invoices">
+            <UUID modifications="2" accountName="Admin" timestamp="2025-05-04T09:27:02">A</UUID>
+            <Field id="6" name="Total">
+                <UUID modifications="1" accountName="Admin" timestamp="2025-06-09T10:20:05">B</UUID>
+            </Field>
+        </BaseTable>"#;
+        assert_eq!(latest_timestamp(xml), Some("2025-06-09T10:20:05"));
+        assert_eq!(latest_timestamp("<UUID>A</UUID>"), None);
+    }
+
+    #[test]
+    fn test_check_no_previous_output() {
+        let out_dir = std::env::temp_dir().join(format!("fm-since-test-{}", std::process::id()));
+        let paths = [PathBuf::from(
+            "tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml",
+        )];
+        fs::create_dir_all(out_dir.join("Other")).unwrap();
+        assert!(check_no_previous_output(&out_dir, &paths, &OutputTree::Db).is_ok());
+
+        fs::create_dir_all(out_dir.join("scripts/Ooe")).unwrap();
+        assert!(check_no_previous_output(&out_dir, &paths, &OutputTree::Db).is_ok());
+        assert!(check_no_previous_output(&out_dir, &paths, &OutputTree::Domain).is_err());
+
+        fs::create_dir_all(out_dir.join("Ooe")).unwrap();
+        assert!(check_no_previous_output(&out_dir, &paths, &OutputTree::Db).is_err());
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+
+use crate::config::Flags;
+use crate::utils::write_text_file;
+
+const SANITIZED_SCRIPTS_DIR_NAME: &str = "scripts_sanitized";
+const HEADER_RULE: &str =
+    "================================================================================";
+
+/// Concatenate all sanitized scripts below the output directory into a single text file
+/// Each script is preceded by a header with its path relative to the output directory
+pub fn write_single_file(
+    root_out_dir: &Path,
+    single_file_path: &Path,
+    flags: &Flags,
+) -> Result<usize, Error> {
+    let mut script_paths = Vec::new();
+    collect_sanitized_scripts(root_out_dir, false, &mut script_paths);
+    script_paths.sort();
+
+    let mut content = String::new();
+    for script_path in &script_paths {
+        let script = fs::read_to_string(script_path)
+            .with_context(|| format!("Error reading file {}", script_path.display()))?;
+        let title = script_path
+            .strip_prefix(root_out_dir)
+            .unwrap_or(script_path)
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" / ");
+
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&format!("{HEADER_RULE}\n{title}\n{HEADER_RULE}\n"));
+        content.push_str(script.trim_end_matches(['\r', '\n']));
+        content.push('\n');
+    }
+
+    if let Some(parent) = single_file_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Error creating directory {}", parent.display()))?;
+        }
+    }
+    write_text_file(
+        single_file_path,
+        content.trim_end_matches('\n'),
+        flags,
+        None,
+    )?;
+
+    Ok(script_paths.len())
+}
+
+/// Recursively collect all text files located somewhere below a `scripts_sanitized` folder
+fn collect_sanitized_scripts(current_dir: &Path, in_scripts: bool, paths: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(current_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let is_scripts_dir = in_scripts
+                    || path.file_name().and_then(|s| s.to_str())
+                        == Some(SANITIZED_SCRIPTS_DIR_NAME);
+                collect_sanitized_scripts(&path, is_scripts_dir, paths);
+            } else if in_scripts && path.extension().and_then(|s| s.to_str()) == Some("txt") {
+                paths.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_single_file() {
+        let out_dir = std::env::temp_dir().join("fm_exploder_test_single_file");
+        let _ = fs::remove_dir_all(&out_dir);
+        let scripts_dir = out_dir.join("Db").join(SANITIZED_SCRIPTS_DIR_NAME);
+        fs::create_dir_all(scripts_dir.join("Folder - ID 3")).unwrap();
+        fs::create_dir_all(out_dir.join("Db").join("custom_functions")).unwrap();
+        fs::write(scripts_dir.join("B - ID 2.txt"), "Beep\n").unwrap();
+        fs::write(
+            scripts_dir.join("Folder - ID 3").join("A - ID 1.txt"),
+            "Halt Script\n",
+        )
+        .unwrap();
+        fs::write(
+            out_dir
+                .join("Db")
+                .join("custom_functions")
+                .join("Cf - ID 1.txt"),
+            "1 + 1\n",
+        )
+        .unwrap();
+
+        let single_file_path = out_dir.join("all.txt");
+        let count = write_single_file(&out_dir, &single_file_path, &Flags::default()).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            fs::read_to_string(&single_file_path).unwrap(),
+            format!(
+                "{HEADER_RULE}\nDb / scripts_sanitized / B - ID 2\n{HEADER_RULE}\nBeep\n\n\
+                 {HEADER_RULE}\nDb / scripts_sanitized / Folder - ID 3 / A - ID 1\n{HEADER_RULE}\nHalt Script\n"
+            )
+        );
+
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
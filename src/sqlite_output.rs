@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use rusqlite::{params, Connection, Transaction};
+
+use crate::analysis::broken_refs::step_references;
+use crate::analysis::model::{ExportModel, StepModel};
+use crate::analysis::read_exports;
+use crate::diagnostics::Diagnostics;
+use crate::exit_status::ExitStatus;
+use crate::script_steps::sanitizer::sanitize;
+use crate::symbols::SymbolKind;
+use crate::utils::attributes::parse_unescaped_attribute;
+
+const SCHEMA: &str = "
+CREATE TABLE scripts (db TEXT NOT NULL, id INTEGER NOT NULL, name TEXT NOT NULL,
+    PRIMARY KEY (db, id));
+CREATE TABLE steps (db TEXT NOT NULL, script_id INTEGER NOT NULL, number INTEGER NOT NULL,
+    step_id INTEGER NOT NULL, name TEXT NOT NULL, enabled INTEGER NOT NULL, text TEXT,
+    PRIMARY KEY (db, script_id, number));
+CREATE TABLE step_options (db TEXT NOT NULL, script_id INTEGER NOT NULL, step INTEGER NOT NULL,
+    position INTEGER NOT NULL, type TEXT NOT NULL, value TEXT,
+    PRIMARY KEY (db, script_id, step, position));
+CREATE TABLE step_references (db TEXT NOT NULL, script_id INTEGER NOT NULL,
+    step INTEGER NOT NULL, kind TEXT NOT NULL, target_id INTEGER NOT NULL, name TEXT NOT NULL,
+    table_occurrence_id INTEGER);
+CREATE TABLE layouts (db TEXT NOT NULL, id INTEGER NOT NULL, name TEXT NOT NULL,
+    PRIMARY KEY (db, id));
+CREATE TABLE table_occurrences (db TEXT NOT NULL, id INTEGER NOT NULL, name TEXT NOT NULL,
+    PRIMARY KEY (db, id));
+CREATE TABLE fields (db TEXT NOT NULL, table_id INTEGER NOT NULL, table_name TEXT,
+    id INTEGER NOT NULL, name TEXT NOT NULL, PRIMARY KEY (db, table_id, id));
+CREATE TABLE relationships (db TEXT NOT NULL, id INTEGER NOT NULL,
+    left_table_occurrence TEXT NOT NULL, right_table_occurrence TEXT NOT NULL,
+    PRIMARY KEY (db, id));
+CREATE TABLE join_predicates (db TEXT NOT NULL, relationship_id INTEGER NOT NULL,
+    position INTEGER NOT NULL, left_field TEXT NOT NULL, operator TEXT NOT NULL,
+    right_field TEXT NOT NULL, PRIMARY KEY (db, relationship_id, position));
+CREATE INDEX step_references_target ON step_references (db, kind, target_id);
+";
+
+/// Run `--format sqlite`: write the parsed model of the exports to a new database file
+pub fn run(inputs: &[PathBuf], output_path: &Path) -> Result<ExitStatus, Error> {
+    let models = read_exports(inputs)?;
+    write_database(output_path, &models)?;
+    Ok(ExitStatus::Clean)
+}
+
+/// Write the tables of all exports, replacing an existing database file
+pub fn write_database(output_path: &Path, models: &[ExportModel]) -> Result<(), Error> {
+    if output_path.exists() {
+        fs::remove_file(output_path)
+            .with_context(|| format!("Error removing file {}", output_path.display()))?;
+    }
+    let mut connection = Connection::open(output_path)
+        .with_context(|| format!("Error creating database {}", output_path.display()))?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SCHEMA)?;
+    for model in models {
+        insert_model(&transaction, model)
+            .with_context(|| format!("Error writing {} to the database", model.db_name))?;
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+fn insert_model(transaction: &Transaction, model: &ExportModel) -> Result<(), Error> {
+    let db = &model.db_name;
+    let mut script = transaction.prepare("INSERT INTO scripts VALUES (?1, ?2, ?3)")?;
+    let mut step = transaction.prepare("INSERT INTO steps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+    let mut option =
+        transaction.prepare("INSERT INTO step_options VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    let mut reference =
+        transaction.prepare("INSERT INTO step_references VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+    for script_model in &model.scripts {
+        script.execute(params![db, script_model.id, script_model.name])?;
+        for step_model in &script_model.steps {
+            let text = sanitize(&step_model.id, &step_model.xml, &mut Diagnostics::default())
+                .map(|text| text.replace('\r', "\n"));
+            step.execute(params![
+                db,
+                script_model.id,
+                step_model.number as i64,
+                step_model.id,
+                step_model.name,
+                step_model.enabled,
+                text
+            ])?;
+            for (position, (kind, value)) in step_options(step_model).into_iter().enumerate() {
+                option.execute(params![
+                    db,
+                    script_model.id,
+                    step_model.number as i64,
+                    position as i64 + 1,
+                    kind,
+                    value
+                ])?;
+            }
+            for step_reference in step_references(step_model) {
+                reference.execute(params![
+                    db,
+                    script_model.id,
+                    step_model.number as i64,
+                    serde_json::to_value(step_reference.kind)?.as_str(),
+                    step_reference.id,
+                    step_reference.name,
+                    step_reference.table_occurrence_id
+                ])?;
+            }
+        }
+    }
+
+    let mut layout = transaction.prepare("INSERT INTO layouts VALUES (?1, ?2, ?3)")?;
+    let mut occurrence =
+        transaction.prepare("INSERT INTO table_occurrences VALUES (?1, ?2, ?3)")?;
+    for (kind, id, symbol) in model.symbols.symbols() {
+        match kind {
+            SymbolKind::Layout => layout.execute(params![db, id, symbol.name])?,
+            SymbolKind::TableOccurrence => occurrence.execute(params![db, id, symbol.name])?,
+            _ => 0,
+        };
+    }
+    let mut field = transaction.prepare("INSERT INTO fields VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for (table_id, id, symbol) in model.symbols.fields() {
+        let table = model.symbols.name(SymbolKind::BaseTable, table_id);
+        field.execute(params![db, table_id, table, id, symbol.name])?;
+    }
+
+    let mut relationship =
+        transaction.prepare("INSERT INTO relationships VALUES (?1, ?2, ?3, ?4)")?;
+    let mut predicate =
+        transaction.prepare("INSERT INTO join_predicates VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    for relationship_model in &model.relationships {
+        relationship.execute(params![
+            db,
+            relationship_model.id,
+            relationship_model.left,
+            relationship_model.right
+        ])?;
+        for (position, join) in relationship_model.predicates.iter().enumerate() {
+            predicate.execute(params![
+                db,
+                relationship_model.id,
+                position as i64 + 1,
+                join.left_field,
+                join.operator,
+                join.right_field
+            ])?;
+        }
+    }
+    Ok(())
+}
+
+/// The parameters of a step as type and value. The value is whatever comes first: the text of a
+/// calculation, or the `value` or `name` attribute of an element, e.g. the state of a Boolean
+/// option or the name of a list entry.
+fn step_options(step: &StepModel) -> Vec<(String, Option<String>)> {
+    let mut options: Vec<(String, Option<String>)> = Vec::new();
+    let mut in_parameter = false;
+    let mut reader = Reader::from_str(&step.xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(_) | Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) if e.name().as_ref() == b"Parameter" && !in_parameter => {
+                let kind = parse_unescaped_attribute(&e, "type").unwrap_or_default();
+                options.push((kind, None));
+                in_parameter = true;
+            }
+            Ok(Event::Start(e) | Event::Empty(e)) if in_parameter => {
+                let value = parse_unescaped_attribute(&e, "value")
+                    .or_else(|| parse_unescaped_attribute(&e, "name"));
+                if let (Some((_, current @ None)), Some(value)) = (options.last_mut(), value) {
+                    *current = Some(value);
+                }
+            }
+            Ok(Event::CData(e)) if in_parameter => {
+                if let Some((_, current)) = options.last_mut() {
+                    if current.is_none() {
+                        *current = Some(String::from_utf8_lossy(&e).to_string());
+                    }
+                }
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"Parameter" => in_parameter = false,
+            _ => {}
+        }
+        buf.clear();
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r#"
+        <LayoutCatalog>
+            <Layout id="3" name="Invoice"></Layout>
+        </LayoutCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices"></TableOccurrence>
+            <TableOccurrence id="1065090" name="Lines"></TableOccurrence>
+        </TableOccurrenceCatalog>
+        <RelationshipCatalog>
+            <Relationship id="5">
+                <LeftTable><TableOccurrenceReference id="1065089" name="Invoices"></TableOccurrenceReference></LeftTable>
+                <RightTable><TableOccurrenceReference id="1065090" name="Lines"></TableOccurrenceReference></RightTable>
+                <JoinPredicateList>
+                    <JoinPredicate type="Equal">
+                        <LeftField><FieldReference id="1" name="ID"></FieldReference></LeftField>
+                        <RightField><FieldReference id="2" name="ID_Invoice"></FieldReference></RightField>
+                    </JoinPredicate>
+                </JoinPredicateList>
+            </Relationship>
+        </RelationshipCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="7" name="Print"></ScriptReference>
+                <ObjectList>
+                    <Step id="6" name="Go to Layout" enable="True">
+                        <ParameterValues>
+                            <Parameter type="LayoutReferenceContainer">
+                                <LayoutReferenceContainer value="1">
+                                    <LayoutReference id="3" name="Invoice"></LayoutReference>
+                                </LayoutReferenceContainer>
+                            </Parameter>
+                            <Parameter type="Boolean"><Boolean id="131072" value="True"></Boolean></Parameter>
+                        </ParameterValues>
+                    </Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "#;
+
+    #[test]
+    fn test_write_database() {
+        let model = read_model(&export(CATALOGS));
+        let path = std::env::temp_dir().join("fm_exploder_sqlite_test.db");
+        write_database(&path, &[model]).unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        let query =
+            |sql: &str| -> String { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+        let reference = query(
+            "SELECT l.name FROM step_references r JOIN layouts l
+             ON l.db = r.db AND l.id = r.target_id WHERE r.kind = 'layout'",
+        );
+        let option = query("SELECT value FROM step_options WHERE type = 'Boolean'");
+        let relationship = query(
+            "SELECT left_table_occurrence || '::' || left_field || ' ' || operator || ' '
+             || right_table_occurrence || '::' || right_field
+             FROM relationships JOIN join_predicates ON relationship_id = id",
+        );
+        drop(connection);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(reference, "Invoice");
+        assert_eq!(option, "True");
+        assert_eq!(relationship, "Invoices::ID Equal Lines::ID_Invoice");
+    }
+}
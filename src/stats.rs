@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Error;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::config::CatalogType;
+use crate::exit_status::ExitStatus;
+
+/// Counters collected while exploding; shared between the files processed in parallel
+#[derive(Debug, Default)]
+pub struct RunStats {
+    files: AtomicUsize,
+    parse_failures: AtomicUsize,
+    io_failures: AtomicUsize,
+    scripts: AtomicUsize,
+    layouts: AtomicUsize,
+    custom_functions: AtomicUsize,
+    unknown_steps: AtomicUsize,
+    parse_warnings: AtomicUsize,
+    truncated_exports: AtomicUsize,
+    /// Step ids without a renderer with their name and the scripts using them
+    unknown_step_usages: Mutex<BTreeMap<u32, (String, BTreeSet<String>)>>,
+}
+
+impl RunStats {
+    pub fn record_file<T>(&self, result: &Result<T, Error>) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        if let Err(err) = result {
+            match ExitStatus::from_error(err) {
+                ExitStatus::IoError => &self.io_failures,
+                _ => &self.parse_failures,
+            }
+            .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a catalog item that is not a folder, folder end marker or separator
+    pub fn record_catalog_item(&self, catalog_type: CatalogType) {
+        let counter = match catalog_type {
+            CatalogType::Script => &self.scripts,
+            CatalogType::Layout => &self.layouts,
+            CatalogType::CustomFunctions => &self.custom_functions,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a script step without a renderer, remembering which script uses it
+    pub fn record_unknown_step(&self, step_id: u32, step_name: &str, script: &str) {
+        self.unknown_steps.fetch_add(1, Ordering::Relaxed);
+        self.unknown_step_usages
+            .lock()
+            .unwrap()
+            .entry(step_id)
+            .or_insert_with(|| (step_name.to_string(), BTreeSet::new()))
+            .1
+            .insert(script.to_string());
+    }
+
+    /// All step ids without a renderer, ordered by id
+    pub fn unknown_step_usages(&self) -> Vec<UnknownStepUsage> {
+        self.unknown_step_usages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(step_id, (step_name, scripts))| UnknownStepUsage {
+                step_id: *step_id,
+                step_name: step_name.clone(),
+                scripts: scripts.iter().cloned().collect(),
+            })
+            .collect()
+    }
+
+    /// Count output files that could not be written
+    pub fn record_write_failures(&self, count: usize) {
+        self.io_failures.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Count an output file written right away that could not be written
+    pub fn record_write_result(&self, result: io::Result<()>) {
+        if let Err(err) = result {
+            self.record_write_failures(1);
+            eprintln!("{err}");
+        }
+    }
+
+    pub fn record_parse_warning(&self) {
+        self.parse_warnings.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_truncated_export(&self) {
+        self.truncated_exports.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The exit status of the run, the most severe failure wins
+    pub fn exit_status(&self) -> ExitStatus {
+        if self.io_failures.load(Ordering::Relaxed) > 0 {
+            ExitStatus::IoError
+        } else if self.parse_failures.load(Ordering::Relaxed) > 0 {
+            ExitStatus::ParseError
+        } else if self.truncated_exports.load(Ordering::Relaxed) > 0 {
+            ExitStatus::Truncated
+        } else if self.unknown_steps.load(Ordering::Relaxed) > 0
+            || self.parse_warnings.load(Ordering::Relaxed) > 0
+        {
+            ExitStatus::Warnings
+        } else {
+            ExitStatus::Clean
+        }
+    }
+
+    pub fn summary(&self, elapsed: Duration, output_bytes: u64) -> RunSummary {
+        RunSummary {
+            files: self.files.load(Ordering::Relaxed),
+            failed_files: self.parse_failures.load(Ordering::Relaxed)
+                + self.io_failures.load(Ordering::Relaxed),
+            scripts: self.scripts.load(Ordering::Relaxed),
+            layouts: self.layouts.load(Ordering::Relaxed),
+            custom_functions: self.custom_functions.load(Ordering::Relaxed),
+            unknown_steps: self.unknown_steps.load(Ordering::Relaxed),
+            parse_warnings: self.parse_warnings.load(Ordering::Relaxed),
+            truncated_exports: self.truncated_exports.load(Ordering::Relaxed),
+            elapsed_ms: elapsed.as_millis(),
+            output_bytes,
+            manifest_sha256: None,
+        }
+    }
+}
+
+/// Machine-readable summary of a run, printed as JSON with `--summary-json`
+#[derive(Debug, Default, PartialEq, Serialize, JsonSchema)]
+pub struct RunSummary {
+    pub files: usize,
+    pub failed_files: usize,
+    pub scripts: usize,
+    pub layouts: usize,
+    pub custom_functions: usize,
+    pub unknown_steps: usize,
+    pub parse_warnings: usize,
+    pub truncated_exports: usize,
+    pub elapsed_ms: u128,
+    /// Total size of the files written for all exports
+    pub output_bytes: u64,
+    /// Checksum of the manifest.json written by the run, identifying the snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_sha256: Option<String>,
+}
+
+/// A script step id without a renderer and the scripts using it
+#[derive(Debug, PartialEq)]
+pub struct UnknownStepUsage {
+    pub step_id: u32,
+    pub step_name: String,
+    pub scripts: Vec<String>,
+}
+
+impl Display for UnknownStepUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "step id {} ({}) used in {}",
+            self.step_id,
+            self.step_name,
+            self.scripts.join(", ")
+        )
+    }
+}
+
+/// Total size of all files below the given directory
+pub fn dir_size(dir_path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir_path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or_default()
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_summary_counts() {
+        let stats = RunStats::default();
+        stats.record_file(&Ok(()));
+        stats.record_file::<()>(&Err(anyhow!("Unsupported XML-format")));
+        stats.record_catalog_item(CatalogType::Script);
+        stats.record_catalog_item(CatalogType::Script);
+        stats.record_catalog_item(CatalogType::Layout);
+        stats.record_catalog_item(CatalogType::BaseTable);
+        stats.record_unknown_step(999, "Foo", "Admin / script 'A'");
+        stats.record_parse_warning();
+
+        let summary = stats.summary(Duration::from_millis(42), 1024);
+        assert_eq!(
+            summary,
+            RunSummary {
+                files: 2,
+                failed_files: 1,
+                scripts: 2,
+                layouts: 1,
+                custom_functions: 0,
+                unknown_steps: 1,
+                parse_warnings: 1,
+                truncated_exports: 0,
+                elapsed_ms: 42,
+                output_bytes: 1024,
+                manifest_sha256: None,
+            }
+        );
+        assert_eq!(stats.exit_status(), ExitStatus::ParseError);
+    }
+
+    #[test]
+    fn test_exit_status() {
+        let stats = RunStats::default();
+        assert_eq!(stats.exit_status(), ExitStatus::Clean);
+        stats.record_parse_warning();
+        assert_eq!(stats.exit_status(), ExitStatus::Warnings);
+        stats.record_truncated_export();
+        assert_eq!(stats.exit_status(), ExitStatus::Truncated);
+        stats.record_file::<()>(&Err(std::io::Error::other("disk full").into()));
+        assert_eq!(stats.exit_status(), ExitStatus::IoError);
+    }
+
+    #[test]
+    fn test_unknown_step_usages() {
+        let stats = RunStats::default();
+        stats.record_unknown_step(210, "New Step", "Admin / script 'B'");
+        stats.record_unknown_step(12, "Other Step", "Admin / script 'A'");
+        stats.record_unknown_step(210, "New Step", "Admin / script 'A'");
+        stats.record_unknown_step(210, "New Step", "Admin / script 'A'");
+
+        let usages = stats.unknown_step_usages();
+        assert_eq!(usages.len(), 2);
+        assert_eq!(
+            usages[1].to_string(),
+            "step id 210 (New Step) used in Admin / script 'A', Admin / script 'B'"
+        );
+        assert_eq!(stats.summary(Duration::ZERO, 0).unknown_steps, 4);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+
+use crate::analysis::model::{CalculationOwner, ExportModel};
+use crate::analysis::{read_exports, write_output};
+use crate::exit_status::ExitStatus;
+use crate::symbols::SymbolKind;
+use crate::{AnalysisInput, ReportFormat};
+
+/// Number of elements of each type of one export, or of all exports together
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub db: String,
+    pub scripts: usize,
+    pub steps: usize,
+    pub layouts: usize,
+    pub tables: usize,
+    pub table_occurrences: usize,
+    pub fields: usize,
+    pub relationships: usize,
+    pub value_lists: usize,
+    pub custom_functions: usize,
+    /// Most used first
+    pub steps_by_type: Vec<StepCount>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StepCount {
+    pub id: u32,
+    /// As named by the export, in its language
+    pub name: String,
+    pub count: usize,
+}
+
+/// Run the `stats` subcommand
+pub fn run(input: &AnalysisInput, format: ReportFormat) -> Result<ExitStatus, Error> {
+    let mut summaries: Vec<Summary> = read_exports(&input.inputs)?.iter().map(summary).collect();
+    if summaries.len() > 1 {
+        let total = total(&summaries);
+        summaries.push(total);
+    }
+    write_output(input, &render(&summaries, format)?)?;
+    Ok(ExitStatus::Clean)
+}
+
+pub fn summary(model: &ExportModel) -> Summary {
+    let count = |kind: SymbolKind| {
+        model
+            .symbols
+            .symbols()
+            .filter(|(symbol_kind, _, _)| *symbol_kind == kind)
+            .count()
+    };
+    let custom_functions: HashSet<&str> = model
+        .calculations
+        .iter()
+        .filter_map(|calculation| match &calculation.owner {
+            CalculationOwner::CustomFunction { name } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut steps_by_type: BTreeMap<u32, StepCount> = BTreeMap::new();
+    for step in model.scripts.iter().flat_map(|script| &script.steps) {
+        steps_by_type
+            .entry(step.id)
+            .or_insert_with(|| StepCount {
+                id: step.id,
+                name: step.name.clone(),
+                count: 0,
+            })
+            .count += 1;
+    }
+
+    Summary {
+        db: model.db_name.clone(),
+        scripts: model.scripts.len(),
+        steps: model.scripts.iter().map(|script| script.steps.len()).sum(),
+        layouts: count(SymbolKind::Layout),
+        tables: count(SymbolKind::BaseTable),
+        table_occurrences: count(SymbolKind::TableOccurrence),
+        fields: model.symbols.fields().count(),
+        relationships: model.relationships.len(),
+        value_lists: count(SymbolKind::ValueList),
+        custom_functions: custom_functions.len(),
+        steps_by_type: sorted(steps_by_type),
+    }
+}
+
+/// The sums of all summaries; step names are those of the first export using the step
+fn total(summaries: &[Summary]) -> Summary {
+    let mut total = Summary {
+        db: "Total".to_string(),
+        ..Default::default()
+    };
+    let mut steps_by_type: BTreeMap<u32, StepCount> = BTreeMap::new();
+    for summary in summaries {
+        total.scripts += summary.scripts;
+        total.steps += summary.steps;
+        total.layouts += summary.layouts;
+        total.tables += summary.tables;
+        total.table_occurrences += summary.table_occurrences;
+        total.fields += summary.fields;
+        total.relationships += summary.relationships;
+        total.value_lists += summary.value_lists;
+        total.custom_functions += summary.custom_functions;
+        for step in &summary.steps_by_type {
+            steps_by_type
+                .entry(step.id)
+                .or_insert_with(|| StepCount {
+                    id: step.id,
+                    name: step.name.clone(),
+                    count: 0,
+                })
+                .count += step.count;
+        }
+    }
+    total.steps_by_type = sorted(steps_by_type);
+    total
+}
+
+/// Most used steps first, steps used equally often by id
+fn sorted(steps_by_type: BTreeMap<u32, StepCount>) -> Vec<StepCount> {
+    let mut steps: Vec<StepCount> = steps_by_type.into_values().collect();
+    steps.sort_by_key(|step| std::cmp::Reverse(step.count));
+    steps
+}
+
+pub fn render(summaries: &[Summary], format: ReportFormat) -> Result<String, Error> {
+    match format {
+        ReportFormat::Text => {
+            let mut text = String::new();
+            for summary in summaries {
+                text.push_str(&format!("{}\n", summary.db));
+                for (label, count) in [
+                    ("scripts", summary.scripts),
+                    ("steps", summary.steps),
+                    ("layouts", summary.layouts),
+                    ("tables", summary.tables),
+                    ("table occurrences", summary.table_occurrences),
+                    ("fields", summary.fields),
+                    ("relationships", summary.relationships),
+                    ("value lists", summary.value_lists),
+                    ("custom functions", summary.custom_functions),
+                ] {
+                    text.push_str(&format!("\t{label}: {count}\n"));
+                }
+                if !summary.steps_by_type.is_empty() {
+                    text.push_str("\tsteps by type:\n");
+                }
+                for step in &summary.steps_by_type {
+                    text.push_str(&format!(
+                        "\t\t{} (ID {}): {}\n",
+                        step.name, step.id, step.count
+                    ));
+                }
+            }
+            Ok(text)
+        }
+        ReportFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(summaries)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_fixtures::{export, read_model};
+
+    use super::*;
+
+    const CATALOGS: &str = r##"
+        <BaseTableCatalog>
+            <BaseTable id="129" name="Invoices"></BaseTable>
+        </BaseTableCatalog>
+        <TableOccurrenceCatalog>
+            <TableOccurrence id="1065089" name="Invoices" type="Local"></TableOccurrence>
+            <TableOccurrence id="1065090" name="Invoices Open" type="Local"></TableOccurrence>
+        </TableOccurrenceCatalog>
+        <RelationshipCatalog>
+            <Relationship id="1"></Relationship>
+        </RelationshipCatalog>
+        <FieldsForTables>
+            <FieldCatalog>
+                <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                <ObjectList>
+                    <Field id="1" name="Total"></Field>
+                    <Field id="2" name="Date"></Field>
+                </ObjectList>
+            </FieldCatalog>
+        </FieldsForTables>
+        <LayoutCatalog>
+            <Layout id="1" name="Invoice"></Layout>
+            <Layout id="2" name="-" isSeparatorItem="True"></Layout>
+        </LayoutCatalog>
+        <StepsForScripts>
+            <Script>
+                <ScriptReference id="1" name="Main"></ScriptReference>
+                <ObjectList>
+                    <Step id="89" name="# (comment)" enable="True"></Step>
+                    <Step id="93" name="Beep" enable="True"></Step>
+                    <Step id="89" name="# (comment)" enable="True"></Step>
+                </ObjectList>
+            </Script>
+        </StepsForScripts>
+    "##;
+
+    #[test]
+    fn test_render_text() {
+        let model = read_model(&export(CATALOGS));
+        let expected = "Invoices
+\tscripts: 1
+\tsteps: 3
+\tlayouts: 1
+\ttables: 1
+\ttable occurrences: 2
+\tfields: 2
+\trelationships: 1
+\tvalue lists: 0
+\tcustom functions: 0
+\tsteps by type:
+\t\t# (comment) (ID 89): 2
+\t\tBeep (ID 93): 1
+";
+        assert_eq!(
+            render(&[summary(&model)], ReportFormat::Text).unwrap(),
+            expected
+        );
+    }
+}
@@ -7,7 +7,7 @@ use crate::utils::xml_utils::{
     text_element_to_string, XmlEventType,
 };
 use crate::utils::{build_out_dir_path, create_dir, push_line_to_skeleton, write_xml_file};
-use crate::xml_processor::ProcessingContext;
+use crate::xml_processor::{record_parse_error, ProcessingContext};
 use anyhow::Error;
 
 /// Extract entire element as is into its own file (don't split it into multiple files)
@@ -32,7 +32,7 @@ pub fn process_supporting_element<R: Read + BufRead>(
     loop {
         match context.reader.read_event_into(&mut buf) {
             Err(e) => {
-                println!("Error {e}");
+                record_parse_error(context, None, &e);
                 break;
             }
             Ok(Event::Eof) => break,
@@ -80,7 +80,13 @@ pub fn process_supporting_element<R: Read + BufRead>(
 
                 // Write to file if we're at the end of the catalog
                 if depth == 0 {
-                    write_xml_file(&out_file_path, &result, 1, context.flags);
+                    write_xml_file(
+                        &out_file_path,
+                        &result,
+                        1,
+                        context.flags,
+                        context.run.writer.as_ref(),
+                    )?;
                     context.path_stack.pop();
                     break;
                 }
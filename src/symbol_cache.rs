@@ -0,0 +1,138 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use quick_xml::Reader;
+use sha2::{Digest, Sha256};
+
+use crate::manifest::sha256_hex;
+use crate::symbols::SymbolTable;
+
+/// Read the symbol table of an export in a first pass, from its mapped bytes if given. With a
+/// cache directory (`--cache-dir`) the table is stored by the checksum of the export and read
+/// from there by later runs on the same export, which skip the first pass.
+pub fn read_symbols(
+    path: &Path,
+    mapped: Option<&[u8]>,
+    cache_dir: Option<&Path>,
+) -> Result<SymbolTable, Error> {
+    let build = || match mapped {
+        Some(bytes) => Ok(SymbolTable::from_reader(&mut Reader::from_reader(bytes))),
+        None => SymbolTable::from_export(path),
+    };
+    let Some(cache_dir) = cache_dir else {
+        return build();
+    };
+
+    let checksum = match mapped {
+        Some(bytes) => sha256_hex(bytes),
+        None => file_checksum(path)?,
+    };
+    let cache_path = cache_file_path(cache_dir, &checksum);
+    // An unreadable or outdated cache file is replaced
+    if let Some(symbols) = fs::read(&cache_path)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+    {
+        return Ok(symbols);
+    }
+
+    let symbols = build()?;
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Error creating directory {}", cache_dir.display()))?;
+    // Written under a name of its own and renamed, so runs in parallel never read half a file
+    let temp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temp_path, serde_json::to_vec(&symbols)?)
+        .with_context(|| format!("Error writing file {}", temp_path.display()))?;
+    fs::rename(&temp_path, &cache_path)
+        .with_context(|| format!("Error writing file {}", cache_path.display()))?;
+    Ok(symbols)
+}
+
+/// Tables of other versions of the exploder are kept apart, as their format may differ
+fn cache_file_path(cache_dir: &Path, checksum: &str) -> PathBuf {
+    cache_dir.join(format!(
+        "symbols-{}-{checksum}.json",
+        env!("CARGO_PKG_VERSION")
+    ))
+}
+
+/// SHA-256 of the decoded content of the export, to match the checksum of its mapped bytes
+fn file_checksum(path: &Path) -> Result<String, Error> {
+    let file =
+        File::open(path).with_context(|| format!("Error opening file {}", path.display()))?;
+    let mut reader = encoding_rs_io::DecodeReaderBytes::new(file);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let len = reader
+            .read(&mut buf)
+            .with_context(|| format!("Error reading file {}", path.display()))?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbols::SymbolKind;
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    const EXPORT: &str = r#"<FMSaveAsXML><Structure><AddAction><ScriptCatalog>
+        <Script id="1" name="Hello world"></Script>
+    </ScriptCatalog></AddAction></Structure></FMSaveAsXML>"#;
+
+    #[test]
+    fn test_read_symbols_from_cache() {
+        let dir = ScratchDir::create("symbol-cache-test").unwrap();
+        let cache_dir = dir.path.join("cache");
+        let export = dir.path.join("Hello.xml");
+        fs::write(&export, EXPORT).unwrap();
+
+        let symbols = read_symbols(&export, None, Some(&cache_dir)).unwrap();
+        assert_eq!(
+            symbols.name(SymbolKind::Script, 1).as_deref(),
+            Some("Hello world")
+        );
+        let cache_path = cache_file_path(&cache_dir, &sha256_hex(EXPORT.as_bytes()));
+        assert!(cache_path.is_file());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        // A cached table is read instead of the export, also for the mapped export
+        let mut cached: serde_json::Value =
+            serde_json::from_slice(&fs::read(&cache_path).unwrap()).unwrap();
+        cached["symbols"] = serde_json::json!([[["script", 1], {"name": "Cached", "uuid": null}]]);
+        fs::write(&cache_path, serde_json::to_vec(&cached).unwrap()).unwrap();
+        let symbols = read_symbols(&export, Some(EXPORT.as_bytes()), Some(&cache_dir)).unwrap();
+        assert_eq!(
+            symbols.name(SymbolKind::Script, 1).as_deref(),
+            Some("Cached")
+        );
+        let sections = &serde_json::to_value(&symbols).unwrap()["sections"];
+        assert_eq!(sections[0]["path"], "Structure");
+        assert_eq!(sections[1]["path"], "Structure/AddAction/ScriptCatalog");
+        assert_eq!(
+            sections[1]["start"],
+            EXPORT.find("<ScriptCatalog>").unwrap()
+        );
+        assert_eq!(sections[1]["end"], EXPORT.find("</AddAction>").unwrap());
+
+        // A broken cache file is replaced
+        fs::write(&cache_path, "{").unwrap();
+        let symbols = read_symbols(&export, None, Some(&cache_dir)).unwrap();
+        assert_eq!(
+            symbols.name(SymbolKind::Script, 1).as_deref(),
+            Some("Hello world")
+        );
+    }
+}
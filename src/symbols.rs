@@ -0,0 +1,518 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Error};
+use encoding_rs_io::DecodeReaderBytes;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::sha256_hex;
+use crate::utils::attributes::{parse_unescaped_attribute, unescaped_attribute};
+use crate::utils::xml_utils::text_to_string;
+
+/// Kinds of catalog elements that script steps refer to by id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Script,
+    Layout,
+    BaseTable,
+    TableOccurrence,
+    ValueList,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub uuid: Option<String>,
+    /// Account of the last modification, where the export records it on the UUID
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// Time of the last modification as exported, e.g. `2025-06-09T10:20:05`
+    #[serde(default)]
+    pub modified_at: Option<String>,
+}
+
+/// Names of the scripts, layouts, tables, fields and value lists of one export by internal id,
+/// read from its catalogs in a first pass
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SymbolTable {
+    #[serde(with = "entries")]
+    symbols: HashMap<(SymbolKind, u32), Symbol>,
+    /// Fields by base table id and field id
+    #[serde(with = "entries")]
+    fields: HashMap<(u32, u32), Symbol>,
+    /// Base table id of each local table occurrence
+    #[serde(with = "entries")]
+    base_tables_of_occurrences: HashMap<u32, u32>,
+    /// Table occurrences of tables in other files, whose fields are unknown
+    external_occurrences: HashSet<u32>,
+    /// The top-level sections and catalogs in the order of the export
+    sections: Vec<SectionSpan>,
+}
+
+/// Where a top-level section like `Structure` or a catalog like `Structure/AddAction/ScriptCatalog`
+/// is found in the export, as byte offsets of the decoded input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionSpan {
+    pub path: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Maps with tuple keys serialized as lists of key value pairs, as JSON only has string keys
+mod entries {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K: Serialize, V: Serialize, S: Serializer>(
+        map: &HashMap<K, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// The catalog item being read, until its end tag
+struct Item {
+    depth: usize,
+    kind: Option<SymbolKind>,
+    id: u32,
+    symbol: Symbol,
+    external: bool,
+}
+
+thread_local! {
+    static SYMBOLS: RefCell<Option<Arc<SymbolTable>>> = const { RefCell::new(None) };
+}
+
+/// Resolve references without a name with this table in all steps rendered by `f` on this thread
+pub fn with_symbols<T>(symbols: Option<Arc<SymbolTable>>, f: impl FnOnce() -> T) -> T {
+    let previous = SYMBOLS.with(|cell| cell.replace(symbols));
+    let result = f();
+    SYMBOLS.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+fn resolve(f: impl FnOnce(&SymbolTable) -> Option<String>) -> Option<String> {
+    SYMBOLS.with(|cell| cell.borrow().as_deref().and_then(f))
+}
+
+pub fn id_attribute(e: &BytesStart) -> Option<u32> {
+    unescaped_attribute(e, "id")?.parse().ok()
+}
+
+/// The name of a reference like `<ScriptReference id="3" name="…">`,
+/// looked up by its id if the export left the name empty
+pub fn reference_name(e: &BytesStart, kind: SymbolKind) -> Option<String> {
+    let name = parse_unescaped_attribute(e, "name");
+    if name.as_ref().is_some_and(|name| !name.is_empty()) {
+        return name;
+    }
+    id_attribute(e)
+        .and_then(|id| resolve(|symbols| symbols.name(kind, id)))
+        .or(name)
+}
+
+/// The name of a field of a table occurrence, both given by id
+pub fn field_name(table_occurrence_id: u32, field_id: u32) -> Option<String> {
+    resolve(|symbols| symbols.field_name(table_occurrence_id, field_id))
+}
+
+impl SymbolTable {
+    pub fn name(&self, kind: SymbolKind, id: u32) -> Option<String> {
+        self.symbols
+            .get(&(kind, id))
+            .map(|symbol| symbol.name.clone())
+    }
+
+    pub fn field_name(&self, table_occurrence_id: u32, field_id: u32) -> Option<String> {
+        let base_table_id = self.base_tables_of_occurrences.get(&table_occurrence_id)?;
+        self.fields
+            .get(&(*base_table_id, field_id))
+            .map(|symbol| symbol.name.clone())
+    }
+
+    pub fn symbol(&self, kind: SymbolKind, id: u32) -> Option<&Symbol> {
+        self.symbols.get(&(kind, id))
+    }
+
+    pub fn contains(&self, kind: SymbolKind, id: u32) -> bool {
+        self.symbols.contains_key(&(kind, id))
+    }
+
+    /// Whether the field exists in the table of the occurrence; `None` if this can't be told,
+    /// e.g. for a table occurrence of another file
+    pub fn contains_field(&self, table_occurrence_id: u32, field_id: u32) -> Option<bool> {
+        let base_table_id = self.base_tables_of_occurrences.get(&table_occurrence_id)?;
+        Some(self.fields.contains_key(&(*base_table_id, field_id)))
+    }
+
+    /// The base table of a table occurrence of this file
+    pub fn base_table_of(&self, table_occurrence_id: u32) -> Option<u32> {
+        self.base_tables_of_occurrences
+            .get(&table_occurrence_id)
+            .copied()
+    }
+
+    pub fn is_external_occurrence(&self, table_occurrence_id: u32) -> bool {
+        self.external_occurrences.contains(&table_occurrence_id)
+    }
+
+    /// All scripts, layouts, tables, table occurrences and value lists
+    pub fn symbols(&self) -> impl Iterator<Item = (SymbolKind, u32, &Symbol)> {
+        self.symbols
+            .iter()
+            .map(|((kind, id), symbol)| (*kind, *id, symbol))
+    }
+
+    /// All fields with the id of their base table and their own id
+    pub fn fields(&self) -> impl Iterator<Item = (u32, u32, &Symbol)> {
+        self.fields
+            .iter()
+            .map(|((base_table_id, id), symbol)| (*base_table_id, *id, symbol))
+    }
+
+    /// Checksum of the names references are resolved with, independent of the order of the maps
+    /// and of modification times, so an incremental run only reuses scripts rendered with the same names
+    pub fn checksum(&self) -> String {
+        let mut lines =
+            self.symbols()
+                .map(|(kind, id, symbol)| format!("{kind:?} {id} {}", symbol.name))
+                .chain(self.fields().map(|(base_table_id, id, symbol)| {
+                    format!("Field {base_table_id} {id} {}", symbol.name)
+                }))
+                .chain(self.base_tables_of_occurrences.iter().map(
+                    |(occurrence_id, base_table_id)| {
+                        format!("Occurrence {occurrence_id} {base_table_id}")
+                    },
+                ))
+                .chain(
+                    self.external_occurrences
+                        .iter()
+                        .map(|occurrence_id| format!("External {occurrence_id}")),
+                )
+                .collect::<Vec<_>>();
+        lines.sort_unstable();
+        sha256_hex(lines.join("\n").as_bytes())
+    }
+
+    /// Read the catalogs of an export file
+    pub fn from_export(path: &Path) -> Result<SymbolTable, Error> {
+        let file =
+            File::open(path).with_context(|| format!("Error opening file {}", path.display()))?;
+        let mut reader = Reader::from_reader(BufReader::new(DecodeReaderBytes::new(file)));
+        Ok(SymbolTable::from_reader(&mut reader))
+    }
+
+    /// Read the catalogs from an export; parse errors end the pass with the symbols read so far,
+    /// they are reported by the main pass
+    pub fn from_reader<R: BufRead>(reader: &mut Reader<R>) -> SymbolTable {
+        let mut table = SymbolTable::default();
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut item: Option<Item> = None;
+        // Base table of the field catalog being read
+        let mut base_table_id: Option<u32> = None;
+        let mut in_uuid = false;
+        // Indexes of the sections not yet ended
+        let mut open_sections: Vec<usize> = Vec::new();
+
+        let mut buf = Vec::new();
+        loop {
+            let offset = reader.buffer_position();
+            match reader.read_event_into(&mut buf) {
+                Err(_) | Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => {
+                    let name = e.name().as_ref().to_vec();
+                    // Top-level sections and the catalogs of their actions
+                    if path.len() == 1 || path.len() == 3 {
+                        let mut section_path = path[1..]
+                            .iter()
+                            .map(|name| String::from_utf8_lossy(name))
+                            .collect::<Vec<_>>()
+                            .join("/");
+                        if !section_path.is_empty() {
+                            section_path.push('/');
+                        }
+                        section_path.push_str(&String::from_utf8_lossy(&name));
+                        open_sections.push(table.sections.len());
+                        table.sections.push(SectionSpan {
+                            path: section_path,
+                            start: offset,
+                            end: offset,
+                        });
+                    }
+                    let parent = path.last().map(Vec::as_slice).unwrap_or_default();
+                    let item_depth = item.as_ref().map(|item| item.depth);
+                    match (&item, name.as_slice()) {
+                        (None, _) => {
+                            item =
+                                SymbolTable::start_item(&e, parent, base_table_id, path.len() + 1);
+                        }
+                        (Some(_), b"UUID") if item_depth == Some(path.len()) => {
+                            in_uuid = true;
+                            if let Some(current) = item.as_mut() {
+                                current.symbol.modified_by =
+                                    parse_unescaped_attribute(&e, "accountName");
+                                current.symbol.modified_at =
+                                    parse_unescaped_attribute(&e, "timestamp");
+                            }
+                        }
+                        (Some(current), b"BaseTableReference")
+                            if current.kind == Some(SymbolKind::TableOccurrence)
+                                && !current.external =>
+                        {
+                            if let Some(base_table) = id_attribute(&e) {
+                                table
+                                    .base_tables_of_occurrences
+                                    .insert(current.id, base_table);
+                            }
+                        }
+                        _ => {}
+                    }
+                    if name == b"BaseTableReference" && parent == b"FieldCatalog" {
+                        base_table_id = id_attribute(&e);
+                    }
+                    path.push(name);
+                }
+                Ok(Event::Text(e)) if in_uuid => {
+                    if let Some(current) = item.as_mut() {
+                        current.symbol.uuid = Some(text_to_string(&e).trim().to_string());
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    in_uuid = false;
+                    if path.len() == 2 || path.len() == 4 {
+                        if let Some(index) = open_sections.pop() {
+                            table.sections[index].end = reader.buffer_position();
+                        }
+                    }
+                    if item.as_ref().map(|item| item.depth) == Some(path.len()) {
+                        if let Some(finished) = item.take() {
+                            table.insert(finished, base_table_id);
+                        }
+                    }
+                    if path.pop().as_deref() == Some(b"FieldCatalog") {
+                        base_table_id = None;
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+        table
+    }
+
+    fn start_item(
+        e: &BytesStart,
+        parent: &[u8],
+        base_table_id: Option<u32>,
+        depth: usize,
+    ) -> Option<Item> {
+        let kind = match (e.name().as_ref(), parent) {
+            (b"Script", b"ScriptCatalog" | b"Script") => Some(SymbolKind::Script),
+            (b"Layout", b"LayoutCatalog" | b"Layout") => Some(SymbolKind::Layout),
+            (b"BaseTable", b"BaseTableCatalog") => Some(SymbolKind::BaseTable),
+            (b"TableOccurrence", b"TableOccurrenceCatalog") => Some(SymbolKind::TableOccurrence),
+            (b"ValueList", b"ValueListCatalog") => Some(SymbolKind::ValueList),
+            // Fields have no kind of their own, they are looked up by base table
+            (b"Field", b"ObjectList") if base_table_id.is_some() => None,
+            _ => return None,
+        };
+        // Folders and separators of the script and layout catalogs
+        if ["isFolder", "isSeparatorItem"]
+            .iter()
+            .any(|name| parse_unescaped_attribute(e, name).is_some_and(|value| value != "False"))
+        {
+            return None;
+        }
+        Some(Item {
+            depth,
+            kind,
+            id: id_attribute(e)?,
+            symbol: Symbol {
+                name: parse_unescaped_attribute(e, "name").unwrap_or_default(),
+                uuid: None,
+                modified_by: None,
+                modified_at: None,
+            },
+            external: parse_unescaped_attribute(e, "type").as_deref() == Some("External"),
+        })
+    }
+
+    fn insert(&mut self, item: Item, base_table_id: Option<u32>) {
+        if item.external && item.kind == Some(SymbolKind::TableOccurrence) {
+            self.external_occurrences.insert(item.id);
+        }
+        match (item.kind, base_table_id) {
+            (Some(kind), _) => {
+                self.symbols.insert((kind, item.id), item.symbol);
+            }
+            (None, Some(base_table_id)) => {
+                self.fields.insert((base_table_id, item.id), item.symbol);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<FMSaveAsXML>
+        <Structure>
+            <AddAction>
+                <BaseTableCatalog>
+                    <BaseTable id="129" name="Invoices"><UUID>BT-UUID</UUID></BaseTable>
+                </BaseTableCatalog>
+                <TableOccurrenceCatalog>
+                    <TableOccurrence id="1065089" name="Invoices_Open" type="Local">
+                        <UUID>TO-UUID</UUID>
+                        <BaseTableSourceReference type="BaseTableReference">
+                            <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                        </BaseTableSourceReference>
+                    </TableOccurrence>
+                    <TableOccurrence id="1065090" name="Archive_Invoices" type="External">
+                        <BaseTableSourceReference type="BaseTableReference">
+                            <DataSourceReference id="1" name="Archive"></DataSourceReference>
+                            <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                        </BaseTableSourceReference>
+                    </TableOccurrence>
+                </TableOccurrenceCatalog>
+                <FieldsForTables>
+                    <FieldCatalog>
+                        <BaseTableReference id="129" name="Invoices"></BaseTableReference>
+                        <ObjectList membercount="1">
+                            <Field id="6" name="Total" fieldtype="Normal">
+                                <UUID>FIELD-UUID</UUID>
+                            </Field>
+                        </ObjectList>
+                    </FieldCatalog>
+                </FieldsForTables>
+                <ScriptCatalog>
+                    <Script id="14" name="Reports" isFolder="True">
+                        <UUID>FOLDER-UUID</UUID>
+                        <Script id="15" name="Print invoice">
+                            <UUID modifications="4" userName="Jo" accountName="Admin" timestamp="2025-06-09T10:20:05">SCRIPT-UUID</UUID>
+                            <ScriptReference id="99" name="Inner"></ScriptReference>
+                        </Script>
+                        <Script id="16" name="-" isSeparatorItem="True"></Script>
+                    </Script>
+                </ScriptCatalog>
+                <LayoutCatalog>
+                    <Layout id="2" name="Invoice">
+                        <TableOccurrenceReference id="1065089" name="Invoices_Open"></TableOccurrenceReference>
+                        <Object><Field id="6" name="Total"></Field></Object>
+                    </Layout>
+                </LayoutCatalog>
+            </AddAction>
+        </Structure>
+    </FMSaveAsXML>"#;
+
+    fn table() -> SymbolTable {
+        SymbolTable::from_reader(&mut Reader::from_str(EXPORT))
+    }
+
+    #[test]
+    fn test_catalogs() {
+        let table = table();
+        assert_eq!(
+            table.name(SymbolKind::Script, 15).as_deref(),
+            Some("Print invoice")
+        );
+        assert_eq!(table.name(SymbolKind::Script, 14), None);
+        assert_eq!(table.name(SymbolKind::Script, 16), None);
+        assert_eq!(table.name(SymbolKind::Script, 99), None);
+        assert_eq!(
+            table.name(SymbolKind::Layout, 2).as_deref(),
+            Some("Invoice")
+        );
+        assert_eq!(
+            table.name(SymbolKind::TableOccurrence, 1065089).as_deref(),
+            Some("Invoices_Open")
+        );
+        assert_eq!(table.field_name(1065089, 6).as_deref(), Some("Total"));
+        assert_eq!(table.field_name(1065090, 6), None);
+        assert_eq!(table.contains_field(1065089, 6), Some(true));
+        assert_eq!(table.contains_field(1065089, 7), Some(false));
+        assert_eq!(table.contains_field(1065090, 6), None);
+        assert!(table.is_external_occurrence(1065090));
+        assert_eq!(
+            table.symbols[&(SymbolKind::Script, 15)].uuid.as_deref(),
+            Some("SCRIPT-UUID")
+        );
+        let script = table.symbol(SymbolKind::Script, 15).unwrap();
+        assert_eq!(script.modified_by.as_deref(), Some("Admin"));
+        assert_eq!(script.modified_at.as_deref(), Some("2025-06-09T10:20:05"));
+        assert_eq!(
+            table.symbols[&(SymbolKind::BaseTable, 129)].modified_at,
+            None
+        );
+        assert_eq!(
+            table.symbols[&(SymbolKind::BaseTable, 129)].uuid.as_deref(),
+            Some("BT-UUID")
+        );
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(table().checksum(), table().checksum());
+        // Modifications of a script don't change its name
+        let modified = EXPORT.replace("2025-06-09T10:20:05", "2025-07-01T08:00:00");
+        let modified = SymbolTable::from_reader(&mut Reader::from_str(&modified));
+        assert_eq!(modified.checksum(), table().checksum());
+        let renamed = EXPORT.replace(r#"name="Total""#, r#"name="Sum""#);
+        let renamed = SymbolTable::from_reader(&mut Reader::from_str(&renamed));
+        assert_ne!(renamed.checksum(), table().checksum());
+    }
+
+    #[test]
+    fn test_reference_name() {
+        let mut reader = Reader::from_str(
+            r#"<ScriptReference id="15" name=""></ScriptReference><LayoutReference id="2" name="Old"></LayoutReference>"#,
+        );
+        let Ok(Event::Start(script)) = reader.read_event() else {
+            panic!("Wrong read event")
+        };
+        let script = script.into_owned();
+        reader.read_event().unwrap();
+        let Ok(Event::Start(layout)) = reader.read_event() else {
+            panic!("Wrong read event")
+        };
+
+        assert_eq!(
+            reference_name(&script, SymbolKind::Script).as_deref(),
+            Some("")
+        );
+        with_symbols(Some(Arc::new(table())), || {
+            assert_eq!(
+                reference_name(&script, SymbolKind::Script).as_deref(),
+                Some("Print invoice")
+            );
+            assert_eq!(
+                reference_name(&layout, SymbolKind::Layout).as_deref(),
+                Some("Old")
+            );
+            assert_eq!(field_name(1065089, 6).as_deref(), Some("Total"));
+        });
+    }
+}
@@ -1,138 +1,524 @@
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::io::Read;
-    use std::path::{Path, PathBuf};
-
-    use insta::assert_snapshot;
-    use similar_asserts::assert_eq;
-    use walkdir::WalkDir;
-
-    use crate::config::Flags;
-    use crate::utils::file_utils::escape_filename;
-    use crate::xml_processor::explode_xml;
-    use crate::OutputTree;
-
-    #[test]
-    fn test_escape_filename() {
-        assert_eq!(escape_filename("filename.xml"), "filename.xml");
-        assert_eq!(escape_filename("file/name.xml"), "file_name.xml");
-        assert_eq!(escape_filename("file|name.xml"), "file_name.xml");
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use insta::assert_snapshot;
+use similar_asserts::assert_eq;
+use walkdir::WalkDir;
+
+use crate::config::Flags;
+use crate::exit_status::ExitStatus;
+use crate::inputs::resolve_inputs;
+use crate::utils::file_utils::{escape_filename, ScratchDir};
+use crate::writer_pool::WriterPool;
+use crate::xml_processor::{explode_xml, RunContext};
+use crate::{gen_sample, OutputTree, SampleArgs, Section};
+
+/// Held by tests that parse command line arguments, as these read process wide environment variables
+static ARGS_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_escape_filename() {
+    assert_eq!(escape_filename("filename.xml"), "filename.xml");
+    assert_eq!(escape_filename("file/name.xml"), "file_name.xml");
+    assert_eq!(escape_filename("file|name.xml"), "file_name.xml");
+}
+
+#[test]
+fn snapshot_test_lossless() {
+    snapshot_test_with_mode(true, OutputTree::Db, "snapshots_db_lossless");
+}
+
+#[test]
+fn snapshot_test_lossy_domain() {
+    snapshot_test_with_mode(false, OutputTree::Domain, "snapshots_domain_lossy");
+}
+
+fn snapshot_test_with_mode(is_lossless: bool, output_tree: OutputTree, snapshot_folder_name: &str) {
+    let path_str = format!("./tests/{}", snapshot_folder_name);
+    let snapshot_dir = Path::new(&path_str);
+    let input_dir = Path::new("./tests/xml");
+    // Make output_dir mode-specific to avoid interference from prior or parallel test runs
+    let output_tree_str = match output_tree {
+        OutputTree::Db => "db",
+        OutputTree::Domain => "domain",
+    };
+    let path_str = if is_lossless {
+        format!("./tests/out_{output_tree_str}_lossless")
+    } else {
+        format!("./tests/out_{output_tree_str}_lossy")
+    };
+    let output_dir = Path::new(&path_str);
+    let flags = Flags {
+        lossless: is_lossless,
+        output_tree,
+        ..Default::default()
+    };
+    let _ = fs::remove_dir_all(output_dir);
+
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_path(snapshot_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let paths = fs::read_dir(input_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|e| e.file_name().unwrap() != ".DS_Store")
+        .collect::<Vec<_>>();
+
+    for path in paths {
+        explode_xml(&path, output_dir, &flags, &RunContext::default())
+            .unwrap_or_else(|_| panic!("Error processing file '{}'", path.display()));
+    }
+
+    let output_files: Vec<PathBuf> = WalkDir::new(output_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| e.path().is_file())
+        .filter(|e| e.file_name().to_str().unwrap() != ".DS_Store")
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    for output_file in &output_files {
+        let output_content = String::from_utf8(read_file(output_file)).unwrap();
+        let output_file_slug = output_file.strip_prefix(output_dir).unwrap();
+        let path_str = format!("../tests/{}", snapshot_folder_name);
+        let snapshot_path = Path::new(&path_str)
+            .join(output_file_slug)
+            .parent()
+            .unwrap()
+            .to_path_buf();
+
+        // Use fresh Settings scope for each snapshot to avoid interference from prior or parallel test runs
+        insta::with_settings!({
+            snapshot_path => snapshot_path,
+            prepend_module_to_snapshot => false,
+        }, {
+            assert_snapshot!(output_file.file_name().unwrap().to_str(), output_content);
+        });
+    }
+
+    let mut output_file_paths = output_files
+        .iter()
+        .map(|file| file.strip_prefix(output_dir).unwrap())
+        .map(|file| file.to_string_lossy())
+        .collect::<Vec<_>>();
+    output_file_paths.sort();
+
+    let snapshot_files: Vec<PathBuf> = WalkDir::new(snapshot_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| e.path().is_file())
+        .filter(|e| e.file_name().to_str().unwrap().ends_with(".snap"))
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    let mut snapshot_file_paths = snapshot_files
+        .iter()
+        .map(|file| file.strip_prefix(snapshot_dir).unwrap())
+        .map(|file| {
+            file.to_string_lossy()
+                .strip_suffix(".snap")
+                .unwrap()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    snapshot_file_paths.sort();
+
+    assert_eq!(snapshot_file_paths.join("\n"), output_file_paths.join("\n"));
+}
+
+/// Explode every fixture in `tests/fixtures` and compare its whole output tree with a single snapshot
+#[test]
+fn fixture_test() {
+    let mut fixtures = fs::read_dir("./tests/fixtures")
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    for fixture in fixtures {
+        fixture_test_for(&fixture);
     }
+}
+
+fn fixture_test_for(fixture: &Path) {
+    use clap::Parser;
 
-    #[test]
-    fn snapshot_test_lossless() {
-        snapshot_test_with_mode(true, OutputTree::Db, "snapshots_db_lossless");
+    let name = fixture.file_name().unwrap().to_string_lossy().to_string();
+    let output_dir = Path::new("./tests/out_fixtures").join(&name);
+    let _ = fs::remove_dir_all(&output_dir);
+
+    // Command line options of the fixture, one per line
+    let mut args = vec![
+        "fm-xml-export-exploder".to_string(),
+        fixture.to_string_lossy().to_string(),
+        output_dir.to_string_lossy().to_string(),
+    ];
+    if let Ok(options) = fs::read_to_string(fixture.join("args")) {
+        args.extend(
+            options
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from),
+        );
     }
+    let flags = {
+        let _lock = ARGS_LOCK.lock().unwrap();
+        crate::Args::try_parse_from(&args)
+            .unwrap_or_else(|err| panic!("Invalid args of fixture '{name}': {err}"))
+            .flags()
+    };
 
-    #[test]
-    fn snapshot_test_lossy_domain() {
-        snapshot_test_with_mode(false, OutputTree::Domain, "snapshots_domain_lossy");
+    let run = RunContext::default();
+    for path in resolve_inputs(&[fixture.to_path_buf()]).unwrap() {
+        explode_xml(&path, &output_dir, &flags, &run)
+            .unwrap_or_else(|_| panic!("Error processing file '{}'", path.display()));
     }
+    let diagnostics = run.diagnostics.sorted();
+    assert!(diagnostics.is_empty(), "{name}: {diagnostics:#?}");
 
-    fn snapshot_test_with_mode(
-        is_lossless: bool,
-        output_tree: OutputTree,
-        snapshot_folder_name: &str,
-    ) {
-        let path_str = format!("./tests/{}", snapshot_folder_name);
-        let snapshot_dir = Path::new(&path_str);
-        let input_dir = Path::new("./tests/xml");
-        // Make output_dir mode-specific to avoid interference from prior or parallel test runs
-        let output_tree_str = match output_tree {
-            OutputTree::Db => "db",
-            OutputTree::Domain => "domain",
-        };
-        let path_str = if is_lossless {
-            format!("./tests/out_{output_tree_str}_lossless")
-        } else {
-            format!("./tests/out_{output_tree_str}_lossy")
-        };
-        let output_dir = Path::new(&path_str);
-        let flags = Flags {
-            parse_all_lines: false,
-            lossless: is_lossless,
-            output_tree,
-        };
-        let _ = fs::remove_dir_all(output_dir);
-
-        let mut settings = insta::Settings::clone_current();
-        settings.set_snapshot_path(snapshot_dir);
-        settings.set_prepend_module_to_snapshot(false);
-
-        let paths = fs::read_dir(input_dir)
-            .unwrap()
-            .filter_map(|entry| entry.ok().map(|e| e.path()))
-            .filter(|path| path.is_file())
-            .filter(|e| e.file_name().unwrap() != ".DS_Store")
-            .collect::<Vec<_>>();
-
-        for path in paths {
-            explode_xml(&path, output_dir, &flags)
-                .unwrap_or_else(|_| panic!("Error processing file '{}'", path.display()));
+    let mut output_files: Vec<PathBuf> = WalkDir::new(&output_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    output_files.sort();
+
+    let mut tree = String::new();
+    for output_file in &output_files {
+        let slug = output_file.strip_prefix(&output_dir).unwrap();
+        let content = String::from_utf8(read_file(output_file)).unwrap();
+        if slug.extension().unwrap_or_default() == "xml" {
+            assert_well_formed(&content)
+                .unwrap_or_else(|err| panic!("{}: {err}", output_file.display()));
         }
+        tree.push_str(&format!("=== {} ===\n{content}\n", slug.to_string_lossy()));
+    }
 
-        let output_files: Vec<PathBuf> = WalkDir::new(output_dir)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|e| e.path().is_file())
-            .filter(|e| e.file_name().to_str().unwrap() != ".DS_Store")
-            .map(|e| e.path().to_path_buf())
-            .collect();
-
-        for output_file in &output_files {
-            let output_content = String::from_utf8(read_file(output_file)).unwrap();
-            let output_file_slug = output_file.strip_prefix(output_dir).unwrap();
-            let path_str = format!("../tests/{}", snapshot_folder_name);
-            let snapshot_path = Path::new(&path_str)
-                .join(output_file_slug)
-                .parent()
-                .unwrap()
-                .to_path_buf();
-
-            // Use fresh Settings scope for each snapshot to avoid interference from prior or parallel test runs
-            insta::with_settings!({
-                snapshot_path => snapshot_path,
-                prepend_module_to_snapshot => false,
-            }, {
-                assert_snapshot!(output_file.file_name().unwrap().to_str(), output_content);
-            });
+    insta::with_settings!({
+        snapshot_path => Path::new("../tests/fixtures").join(&name),
+        prepend_module_to_snapshot => false,
+    }, {
+        assert_snapshot!("output_tree", tree);
+    });
+}
+
+/// Written XML files must be readable again, e.g. by diff tools or a later re-import
+fn assert_well_formed(xml: &str) -> Result<(), quick_xml::Error> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.config_mut().check_end_names = true;
+    loop {
+        if let quick_xml::events::Event::Eof = reader.read_event()? {
+            return Ok(());
         }
+    }
+}
 
-        let mut output_file_paths = output_files
-            .iter()
-            .map(|file| file.strip_prefix(output_dir).unwrap())
-            .map(|file| file.to_string_lossy())
-            .collect::<Vec<_>>();
-        output_file_paths.sort();
-
-        let snapshot_files: Vec<PathBuf> = WalkDir::new(snapshot_dir)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|e| e.path().is_file())
-            .filter(|e| e.file_name().to_str().unwrap().ends_with(".snap"))
-            .map(|e| e.path().to_path_buf())
-            .collect();
-
-        let mut snapshot_file_paths = snapshot_files
-            .iter()
-            .map(|file| file.strip_prefix(snapshot_dir).unwrap())
-            .map(|file| {
-                file.to_string_lossy()
-                    .strip_suffix(".snap")
-                    .unwrap()
-                    .to_string()
-            })
-            .collect::<Vec<_>>();
-        snapshot_file_paths.sort();
-
-        assert_eq!(snapshot_file_paths.join("\n"), output_file_paths.join("\n"));
+fn read_file(file_path: &PathBuf) -> Vec<u8> {
+    let mut file = fs::File::open(file_path).expect("Failed to open file");
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).expect("Failed to read file");
+    content
+}
+
+#[test]
+fn test_only_selected_sections() {
+    let output_dir = Path::new("./tests/out_only_scripts");
+    let _ = fs::remove_dir_all(output_dir);
+    let flags = Flags {
+        only: vec![Section::Scripts],
+        ..Default::default()
+    };
+    let path = Path::new("./tests/xml/Admin API Tool - saxml_v_2_2_1_0.xml").to_path_buf();
+    explode_xml(&path, output_dir, &flags, &RunContext::default()).unwrap();
+
+    let mut folders = fs::read_dir(output_dir.join("Admin API Tool"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    folders.sort();
+    let _ = fs::remove_dir_all(output_dir);
+    assert_eq!(
+        folders,
+        vec!["script_stubs", "scripts", "scripts_sanitized"]
+    );
+}
+
+#[test]
+fn test_args_from_env() {
+    use clap::{CommandFactory, Parser};
+
+    crate::Args::command().debug_assert();
+
+    // Setting process wide variables doesn't interfere with other tests holding the lock
+    let _lock = ARGS_LOCK.lock().unwrap();
+    std::env::set_var("FM_EXPLODER_SOURCE", "./tests/xml");
+    std::env::set_var("FM_EXPLODER_TARGET", "./tests/out_env");
+    std::env::set_var("FM_EXPLODER_LINE_ENDINGS", "crlf");
+    std::env::set_var("FM_EXPLODER_QUIET", "true");
+    std::env::set_var("FM_EXPLODER_ONLY", "scripts,layouts");
+    let args = crate::Args::try_parse_from(["fm-xml-export-exploder", "--all-lines"]);
+    for name in ["SOURCE", "TARGET", "LINE_ENDINGS", "QUIET", "ONLY"] {
+        std::env::remove_var(format!("FM_EXPLODER_{name}"));
     }
 
-    fn read_file(file_path: &PathBuf) -> Vec<u8> {
-        let mut file = fs::File::open(file_path).expect("Failed to open file");
-        let mut content = Vec::new();
-        file.read_to_end(&mut content).expect("Failed to read file");
-        content
+    let args = args.unwrap();
+    assert_eq!(args.source, Some(PathBuf::from("./tests/xml")));
+    assert_eq!(args.target, Some(PathBuf::from("./tests/out_env")));
+    assert_eq!(args.line_endings, crate::LineEnding::Crlf);
+    assert!(args.quiet);
+    assert!(args.all_lines);
+    assert_eq!(args.only, vec![Section::Scripts, Section::Layouts]);
+}
+
+#[test]
+fn test_summary_json_to_stdout_conflicts() {
+    use clap::Parser;
+
+    let _lock = ARGS_LOCK.lock().unwrap();
+    let args = crate::Args::try_parse_from([
+        "fm-xml-export-exploder",
+        "./tests/xml",
+        "--format",
+        "jsonl",
+        "--output",
+        "-",
+        "--summary-json",
+    ])
+    .unwrap();
+    let err = args.validate().unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+    let args = crate::Args::try_parse_from([
+        "fm-xml-export-exploder",
+        "./tests/xml",
+        "-",
+        "--format",
+        "jsonl",
+        "--summary-json",
+    ])
+    .unwrap();
+    assert!(args.validate().is_err());
+}
+
+#[test]
+fn test_parse_error_location() {
+    let dir = std::env::temp_dir().join(format!("fm-parse-error-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Broken.xml");
+    fs::write(
+        &path,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<FMSaveAsXML version="2.2.1.0" Source="21.0.1" File="Broken.fmp12">
+	<Structure membercount="1">
+		<AddAction membercount="1">
+			<ScriptCatalog membercount="1">
+				<Script id="1" name="Hello">
+					<Options>1</Option>
+				</Script>
+			</ScriptCatalog>
+		</AddAction>
+	</Structure>
+</FMSaveAsXML>
+"#,
+    )
+    .unwrap();
+
+    let run = RunContext::default();
+    let result = explode_xml(&path, &dir.join("out"), &Flags::default(), &run);
+    let _ = fs::remove_dir_all(&dir);
+    assert!(result.is_ok());
+
+    let diagnostics = run.diagnostics.sorted();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].context, "Broken.xml / Script 'Hello'");
+    assert_eq!(diagnostics[0].location.as_deref(), Some("Broken.xml:7:16"));
+}
+
+#[test]
+fn test_truncated_export() {
+    let dir = std::env::temp_dir().join(format!("fm-truncated-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Truncated.xml");
+    fs::write(
+        &path,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<FMSaveAsXML version="2.2.1.0" Source="21.0.1" File="Truncated.fmp12">
+	<Structure membercount="1">
+		<AddAction membercount="1">
+			<ScriptCatalog membercount="2">
+				<Script id="1" name="Hello">
+					<Options>1</Options>
+				</Script>
+				<Script id="2" name="World">
+"#,
+    )
+    .unwrap();
+
+    let run = RunContext::default();
+    let result = explode_xml(&path, &dir.join("out"), &Flags::default(), &run);
+    let written = dir
+        .join("out/Truncated/script_stubs/Hello - ID 1.xml")
+        .is_file();
+    let _ = fs::remove_dir_all(&dir);
+    assert!(result.is_ok());
+    assert!(written);
+
+    let diagnostics = run.diagnostics.sorted();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].context, "Truncated.xml");
+    assert_eq!(run.stats.exit_status(), ExitStatus::Truncated);
+}
+
+#[test]
+fn test_index() {
+    let output_dir = std::env::temp_dir().join(format!("fm-index-test-{}", std::process::id()));
+    let path = Path::new("./tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml").to_path_buf();
+    let flags = Flags::default();
+    let db_name = explode_xml(&path, &output_dir, &flags, &RunContext::default()).unwrap();
+    let symbols = crate::symbols::SymbolTable::from_export(&path).unwrap();
+    let index = crate::index::Index::build(&output_dir, &[(db_name, symbols)], &flags).unwrap();
+    let _ = fs::remove_dir_all(&output_dir);
+
+    let database = &index.databases[0];
+    assert_eq!(database.name, "Ooe");
+    let script = database.scripts.iter().find(|entry| entry.id == 1).unwrap();
+    assert_eq!(script.name, "Hello world");
+    assert!(script.uuid.is_some());
+    assert_eq!(
+        script.paths,
+        vec![
+            "Ooe/script_stubs/Hello world - ID 1.xml",
+            "Ooe/scripts/Hello world - ID 1.xml",
+            "Ooe/scripts_sanitized/Hello world - ID 1.txt",
+        ]
+    );
+    let field = database
+        .fields
+        .iter()
+        .find(|entry| entry.table_id == Some(129))
+        .unwrap();
+    assert_eq!(
+        field.paths,
+        vec![
+            "Ooe/table_stubs/TestTable - ID 129.xml",
+            "Ooe/tables/TestTable - ID 129.xml",
+            "Ooe/tables__modify_action/TestTable - ID 129.xml",
+        ]
+    );
+}
+
+/// All files exploded from the export with the run, by their path below the output folder
+fn exploded_files(path: &Path, name: &str, run: &RunContext) -> Vec<(String, Vec<u8>)> {
+    let output_dir = std::env::temp_dir().join(format!("fm-{name}-test-{}", std::process::id()));
+    explode_xml(&path.to_path_buf(), &output_dir, &Flags::default(), run).unwrap();
+    if let Some(writer) = &run.writer {
+        writer.finish();
     }
+    let mut files: Vec<(String, Vec<u8>)> = WalkDir::new(&output_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| {
+            let slug = e.path().strip_prefix(&output_dir).unwrap();
+            (
+                slug.to_string_lossy().to_string(),
+                read_file(&e.path().to_path_buf()),
+            )
+        })
+        .collect();
+    files.sort();
+    let _ = fs::remove_dir_all(&output_dir);
+    files
+}
+
+#[test]
+fn test_mmap_matches_buffered_reads() {
+    let path = Path::new("./tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml");
+    let buffered = exploded_files(path, "buffered", &RunContext::default());
+    assert!(!buffered.is_empty());
+    let run = RunContext {
+        mmap: true,
+        ..Default::default()
+    };
+    assert_eq!(exploded_files(path, "mmap", &run), buffered);
+}
+
+#[test]
+fn test_writer_pool_matches_direct_writes() {
+    let path = Path::new("./tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml");
+    let direct = exploded_files(path, "direct-writes", &RunContext::default());
+    assert!(!direct.is_empty());
+    let run = RunContext {
+        writer: Some(WriterPool::new(3)),
+        ..Default::default()
+    };
+    assert_eq!(exploded_files(path, "writer-pool", &run), direct);
+}
+
+#[test]
+fn test_sample_export_exits_cleanly() {
+    // Steps rendered by the generic renderer are no unknown steps, so CI can rely on exit code 0
+    let path = Path::new("./tests/xml/Ooe - 2025-06-23 - saxml_v2_2_3_0.xml");
+    let run = RunContext::default();
+    exploded_files(path, "exit-status", &run);
+    assert!(run.stats.unknown_step_usages().is_empty());
+    assert_eq!(run.stats.exit_status(), ExitStatus::Clean);
+}
+
+#[test]
+fn test_gen_sample_explodes_cleanly() {
+    let dir = ScratchDir::create("gen-sample-test").unwrap();
+    let args = SampleArgs {
+        scripts: 120,
+        steps: 11,
+        layouts: 3,
+        tables: 2,
+        fields: 4,
+        utf16: true,
+        output: dir.path.join("Sample.xml"),
+    };
+    gen_sample::run(&args).unwrap();
+
+    let run = RunContext::default();
+    let files = exploded_files(&args.output, "gen-sample", &run);
+    assert!(run.diagnostics.sorted().is_empty());
+    assert_eq!(run.stats.exit_status(), ExitStatus::Clean);
+    let count = |folder: &str| {
+        files
+            .iter()
+            .filter(|(path, _)| path.starts_with(&format!("Sample/{folder}/")))
+            .count()
+    };
+    assert_eq!(count("scripts_sanitized"), 120);
+    assert_eq!(count("layouts"), 3);
+    assert_eq!(count("tables"), 2);
+
+    let (_, script) = files
+        .iter()
+        .find(|(path, _)| path.ends_with("Folder 2 - ID 123/Script 101 - ID 101.txt"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(script),
+        r#"# Step 1 of script 101: keep the totals of "Table 2" up to date
+Set Variable [ $total1 ; Table 1::Field 3 + 1 ]
+If [ Table 2::Field 4 > 0 ]
+	Set Field [ Table 1::Field 1 ; Table 1::Field 1 * 2 ]
+End If
+Go to Layout [ Layout: "Layout 2" ; Animation: None ]
+Perform Script [ From list ; "Script 108" ]
+# Step 8 of script 101: keep the totals of "Table 1" up to date
+# Step 9 of script 101: keep the totals of "Table 2" up to date
+Set Variable [ $total9 ; Table 1::Field 3 + 9 ]
+# Step 11 of script 101: keep the totals of "Table 2" up to date
+
+"#
+    );
 }
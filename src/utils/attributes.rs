@@ -3,8 +3,33 @@ use std::borrow::Cow;
 use quick_xml::events::BytesStart;
 use quick_xml::name::QName;
 
+use crate::utils::xml_utils::unescape_entities;
+
+/// The value of an attribute with entities and character references resolved
 pub fn parse_unescaped_attribute(e: &BytesStart, attribute: &str) -> Option<String> {
-    get_attribute(e, attribute).map(|text| quick_xml::escape::unescape(&text).unwrap().to_string())
+    unescaped_attribute(e, attribute).map(Cow::into_owned)
+}
+
+/// Like `parse_unescaped_attribute`, but borrowing the value from the element unless it has
+/// entities to resolve, for values that are only compared or parsed
+pub fn unescaped_attribute<'a>(e: &'a BytesStart, attribute: &str) -> Option<Cow<'a, str>> {
+    match attribute_value(e, attribute)? {
+        Cow::Borrowed(value) if value.contains('&') => Some(Cow::Owned(unescape_entities(value))),
+        Cow::Owned(value) if value.contains('&') => Some(Cow::Owned(unescape_entities(&value))),
+        value => Some(value),
+    }
+}
+
+/// The raw value of an attribute, borrowed from the element if it's valid UTF-8
+fn attribute_value<'a>(e: &'a BytesStart, attribute: &str) -> Option<Cow<'a, str>> {
+    let attr = e
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == attribute.as_bytes())?;
+    Some(match attr.value {
+        Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+        Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+    })
 }
 
 pub fn key_to_string(key: QName) -> String {
@@ -33,16 +58,13 @@ pub fn get_attributes(e: &BytesStart) -> Option<Vec<(String, String)>> {
 }
 
 pub fn get_attribute(element: &BytesStart, attribute_name: &str) -> Option<String> {
-    for attr in get_attributes(element).unwrap() {
-        if attr.0 == attribute_name {
-            return Some(attr.1);
-        }
-    }
-    None
+    attribute_value(element, attribute_name).map(Cow::into_owned)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use quick_xml::events::Event;
     use quick_xml::Reader;
 
@@ -60,4 +82,21 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_unescaped_attribute_borrows_unless_unescaped() {
+        let mut reader = Reader::from_str(r#"<Boolean type="Pause" name="A &amp; B">"#);
+        let Ok(Event::Start(e)) = reader.read_event() else {
+            panic!("Wrong read event")
+        };
+        assert!(matches!(
+            utils::attributes::unescaped_attribute(&e, "type"),
+            Some(Cow::Borrowed("Pause"))
+        ));
+        assert_eq!(
+            utils::attributes::unescaped_attribute(&e, "name").as_deref(),
+            Some("A & B")
+        );
+        assert_eq!(utils::attributes::unescaped_attribute(&e, "value"), None);
+    }
 }
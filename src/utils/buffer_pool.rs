@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+/// Buffers kept per thread; the step parsers nest only a few levels deep
+const MAX_POOLED_BUFFERS: usize = 16;
+/// Larger buffers are dropped instead of kept, so one huge element doesn't pin its memory
+const MAX_POOLED_CAPACITY: usize = 64 * 1024;
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An event buffer of `Reader::read_event_into` taken from a pool of the current thread and
+/// returned to it on drop, so the parser of each step and parameter doesn't allocate and grow
+/// a buffer of its own
+pub struct PooledBuffer(Vec<u8>);
+
+impl PooledBuffer {
+    pub fn take() -> PooledBuffer {
+        PooledBuffer(
+            POOL.with(|pool| pool.borrow_mut().pop())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.0);
+        if buf.capacity() == 0 || buf.capacity() > MAX_POOLED_CAPACITY {
+            return;
+        }
+        buf.clear();
+        // The pool is gone while the thread shuts down
+        let _ = POOL.try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < MAX_POOLED_BUFFERS {
+                pool.push(buf);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let mut buf = PooledBuffer::take();
+        buf.extend_from_slice(b"<Step/>");
+        let capacity = buf.capacity();
+        let ptr = buf.as_ptr();
+        drop(buf);
+
+        let buf = PooledBuffer::take();
+        assert!(buf.is_empty());
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf.as_ptr(), ptr);
+        // A nested parser gets a buffer of its own
+        assert_ne!(PooledBuffer::take().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_large_buffers_are_dropped() {
+        let mut buf = PooledBuffer::take();
+        buf.reserve(MAX_POOLED_CAPACITY + 1);
+        let ptr = buf.as_ptr();
+        drop(buf);
+        assert_ne!(PooledBuffer::take().as_ptr(), ptr);
+    }
+}
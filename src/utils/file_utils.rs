@@ -1,14 +1,20 @@
-use anyhow::{anyhow, Error, Result};
-use std::path::PathBuf;
+use anyhow::{Context, Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Validate that a path exists and is a directory
 pub fn valid_dir_or_throw(dir_path: &PathBuf) -> Result<(), Error> {
     let metadata = std::fs::metadata(dir_path)
-        .map_err(|_| anyhow!("Path '{}' not exists", dir_path.display()))?;
+        .with_context(|| format!("Path '{}' not exists", dir_path.display()))?;
 
     match metadata.is_dir() {
         true => Ok(()),
-        false => Err(anyhow!("Path '{}' is not a directory", dir_path.display())),
+        false => Err(std::io::Error::other(format!(
+            "Path '{}' is not a directory",
+            dir_path.display()
+        ))
+        .into()),
     }
 }
 
@@ -50,3 +56,49 @@ pub fn should_skip_line(line: &str) -> bool {
 
     false
 }
+
+/// Recursively collect all files below a directory
+pub fn collect_files(current_dir: &Path, paths: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(current_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_files(&path, paths);
+            } else if path.is_file() {
+                paths.push(path);
+            }
+        }
+    }
+}
+
+/// Number of the next scratch directory of this process
+static SCRATCH_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary directory removed when dropped, e.g. for the exports of a call or the staging
+/// directory of a run. Each has a name of its own, so runs in parallel don't share one.
+pub struct ScratchDir {
+    pub path: PathBuf,
+}
+
+impl ScratchDir {
+    /// Create a new, empty directory; `purpose` becomes part of its name
+    pub fn create(purpose: &str) -> Result<Self, Error> {
+        let path = std::env::temp_dir().join(format!(
+            "fm-xml-export-exploder-{purpose}-{}-{}",
+            std::process::id(),
+            SCRATCH_DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        if path.exists() {
+            fs::remove_dir_all(&path)?;
+        }
+        fs::create_dir_all(&path)
+            .with_context(|| format!("Error creating directory {}", path.display()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Error, Result};
@@ -8,17 +9,21 @@ use quick_xml::events::{BytesStart, Event};
 use regex::Regex;
 
 use crate::config::{CatalogType, Flags};
+use crate::profile::timed_write;
+use crate::redact::{redact_api_keys, redact_xml};
+use crate::stats::RunStats;
 use crate::utils::attributes::get_attributes;
 use crate::utils::file_utils::{escape_filename, join_scope_id_and_name, should_skip_line};
 use crate::utils::xml_utils::{
-    element_to_string, encode_xml_special_characters, end_element_to_string,
-    extract_values_from_xml_paths, general_ref_to_string, start_element_to_string,
-    text_element_to_string, XmlEventType,
+    element_to_string, end_element_to_string, extract_values_from_xml_paths, general_ref_to_string,
+    start_element_to_string, text_element_to_string, unescape_entities, XmlEventType,
 };
+use crate::writer_pool::WriterPool;
 use crate::xml_processor::{Action, ProcessingContext, Qualifier, TopLevelSection};
-use crate::{OutputTree, Skeleton};
+use crate::{LineEnding, OutputTree, Section, Skeleton};
 
 pub(crate) mod attributes;
+pub(crate) mod buffer_pool;
 pub(crate) mod file_utils;
 pub(crate) mod xml_utils;
 
@@ -63,12 +68,8 @@ impl Entity {
         for attr in get_attributes(e).unwrap() {
             match attr.0.as_str() {
                 "id" => self.id = attr.1.to_string(),
-                "name" => {
-                    if self.name.is_empty() {
-                        self.name = encode_xml_special_characters(attr.1.to_string())
-                    }
-                }
-                "Display" => self.name = encode_xml_special_characters(attr.1.to_string()),
+                "name" if self.name.is_empty() => self.name = unescape_entities(&attr.1),
+                "Display" => self.name = unescape_entities(&attr.1),
                 _ => {}
             }
         }
@@ -167,6 +168,7 @@ pub fn write_rest_of_element_to_file<R: Read + BufRead>(
         &entity,
         remove_indent_count,
         context.flags,
+        &context.run.stats,
     )
 }
 
@@ -175,17 +177,21 @@ pub fn write_entity_to_file(
     entity: &Entity,
     remove_indent_count: usize,
     flags: &Flags,
+    stats: &RunStats,
 ) -> PathBuf {
     let filename = join_scope_id_and_name(entity.id.as_str(), entity.name.as_str());
     let filename = escape_filename(&filename);
 
     let output_file_path = output_dir.join(format!("{filename}.xml"));
-    write_xml_file(
+    let result = write_xml_file(
         &output_file_path,
         &entity.content,
         remove_indent_count,
         flags,
+        // Written right away, as catalog items are read back to be renamed and moved
+        None,
     );
+    stats.record_write_result(result);
     output_file_path
 }
 
@@ -203,9 +209,8 @@ pub fn build_out_dir_path<R: Read + BufRead>(
     };
 
     let domain = match qualifier {
-        Some(Qualifier::SanitizedScripts) => "scripts_sanitized".to_string(),
-        Some(Qualifier::SanitizedCustomFunctions) => "custom_functions".to_string(),
-        _ => {
+        Some(qualifier) => qualifier.folder_name().to_string(),
+        None => {
             match context.top_level_section {
                 Some(TopLevelSection::Structure) => {
                     let mut domain_base = if let Some(catalog_type) = &context.catalog_type {
@@ -231,7 +236,7 @@ pub fn build_out_dir_path<R: Read + BufRead>(
                     }
                     domain_base
                 }
-                _ => "_".to_string(),
+                _ => METADATA_FOLDER_NAME.to_string(),
             }
         }
     };
@@ -266,15 +271,62 @@ pub fn _delete_then_create_dir(out_dir_path: &Path) {
     });
 }
 
+/// Output folder of the metadata and DDR info outside the structure catalogs
+pub const METADATA_FOLDER_NAME: &str = "_";
+
+/// The `--only` section an output folder like `scripts_sanitized` belongs to
+fn domain_section(domain: &str) -> Option<Section> {
+    // Folders like `layouts__modify_action` hold the same elements for another action
+    let domain = domain.split("__").next().unwrap_or(domain);
+    if domain == METADATA_FOLDER_NAME {
+        return Some(Section::Metadata);
+    }
+    if let Some(qualifier) = Qualifier::ALL.iter().find(|q| q.folder_name() == domain) {
+        return Some(qualifier.section());
+    }
+    CatalogType::ALL
+        .iter()
+        .find(|catalog_type| catalog_type.get_config().out_folder_name == domain)
+        .map(CatalogType::section)
+}
+
+/// The `--only` section of a file given by its path relative to the output root
+pub fn path_section(relative_path: &str, output_tree: &OutputTree) -> Option<Section> {
+    let mut folders = relative_path.split('/');
+    let domain = match output_tree {
+        OutputTree::Db => folders.nth(1)?,
+        OutputTree::Domain => folders.next()?,
+    };
+    domain_section(domain)
+}
+
+/// Whether the output folder of a domain is rewritten by this run. With `--only`, the folders of
+/// the other sections are kept.
+fn is_domain_cleared(flags: &Flags, domain: &Path) -> bool {
+    if flags.only.is_empty() {
+        return true;
+    }
+    let domain = domain.file_name().unwrap_or_default().to_string_lossy();
+    domain_section(&domain).is_some_and(|section| flags.includes(section))
+}
+
 pub fn delete_output_directory(context: &ProcessingContext<'_, impl BufRead>) -> Result<(), Error> {
     let db_name = context.db_name.as_ref().unwrap();
 
     match context.flags.output_tree {
         OutputTree::Db => {
-            // Delete ./db_name/
             let dir_to_delete = context.root_out_dir.join(db_name);
-            if dir_to_delete.exists() {
+            if dir_to_delete.exists() && context.flags.only.is_empty() {
+                // Delete ./db_name/
                 fs::remove_dir_all(&dir_to_delete)?;
+            } else if let Ok(entries) = fs::read_dir(&dir_to_delete) {
+                // Delete the directories ./db_name/*/ of the selected sections
+                for entry in entries.flatten() {
+                    let entry_path = entry.path();
+                    if entry_path.is_dir() && is_domain_cleared(context.flags, &entry_path) {
+                        fs::remove_dir_all(&entry_path)?;
+                    }
+                }
             }
         }
         OutputTree::Domain => {
@@ -282,7 +334,7 @@ pub fn delete_output_directory(context: &ProcessingContext<'_, impl BufRead>) ->
             if let Ok(entries) = fs::read_dir(&context.root_out_dir) {
                 for entry in entries.flatten() {
                     let entry_path = entry.path();
-                    if entry_path.is_dir() {
+                    if entry_path.is_dir() && is_domain_cleared(context.flags, &entry_path) {
                         let target_dir = entry_path.join(db_name);
                         if target_dir.exists() {
                             fs::remove_dir_all(&target_dir)?;
@@ -306,7 +358,12 @@ pub fn write_xml_file(
     content: &str,
     remove_indent_count: usize,
     flags: &Flags,
-) {
+    writer: Option<&WriterPool>,
+) -> io::Result<()> {
+    let content = match flags.redact {
+        true => redact_xml(content),
+        false => Cow::Borrowed(content),
+    };
     let mut file_content = String::new();
     let reader = BufReader::new(content.as_bytes());
     for line in reader.lines() {
@@ -321,10 +378,15 @@ pub fn write_xml_file(
         file_content.push('\n');
     }
 
-    write_file(output_file_path, &file_content);
+    write_file(output_file_path, &file_content, flags, writer)
 }
 
-pub fn write_text_file(output_file_path: &Path, content: &str) {
+pub fn write_text_file(
+    output_file_path: &Path,
+    content: &str,
+    flags: &Flags,
+    writer: Option<&WriterPool>,
+) -> io::Result<()> {
     let mut file_content = String::new();
     let regex = Regex::new(r"\r\n|\n\r|\r|\n").unwrap();
     for line in regex.split(content) {
@@ -332,25 +394,58 @@ pub fn write_text_file(output_file_path: &Path, content: &str) {
         file_content.push('\n');
     }
 
-    write_file(output_file_path, &file_content);
+    write_file(output_file_path, &file_content, flags, writer)
 }
 
-fn write_file(output_file_path: &Path, file_content: &String) {
-    match File::create(output_file_path) {
-        Ok(ref mut output_file) => {
-            write!(output_file, "{file_content}").expect("Failed to write to file");
-            output_file.flush().expect("Failed to flush output file");
-        }
-        Err(err) => {
-            eprintln!(
-                "Error creating file {}: {}",
-                output_file_path.display(),
-                err
-            );
+/// Apply the configured line ending and trailing newline policy to `\n` separated content
+pub fn apply_line_policy(content: &str, flags: &Flags) -> String {
+    let content = if flags.trailing_newline {
+        content
+    } else {
+        content.trim_end_matches('\n')
+    };
+    match flags.line_endings {
+        LineEnding::Lf => content.to_string(),
+        LineEnding::Crlf => content.replace('\n', "\r\n"),
+    }
+}
+
+/// Write the file on the threads of the writer pool if there is one, or else right away,
+/// with literal API keys masked by `--redact`. Failed writes of the pool are counted by the pool.
+fn write_file(
+    output_file_path: &Path,
+    file_content: &str,
+    flags: &Flags,
+    writer: Option<&WriterPool>,
+) -> io::Result<()> {
+    let file_content = match flags.redact {
+        true => redact_api_keys(file_content),
+        false => Cow::Borrowed(file_content),
+    };
+    let file_content = apply_line_policy(&file_content, flags);
+    match writer {
+        Some(writer) => {
+            writer.write(output_file_path.to_path_buf(), file_content);
+            Ok(())
         }
+        None => write_to_disk(output_file_path, &file_content),
     }
 }
 
+pub fn write_to_disk(output_file_path: &Path, file_content: &str) -> io::Result<()> {
+    timed_write(file_content.len(), || {
+        let mut output_file = File::create(output_file_path)?;
+        output_file.write_all(file_content.as_bytes())?;
+        output_file.flush()
+    })
+    .map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("Error writing file {}: {}", output_file_path.display(), err),
+        )
+    })
+}
+
 /// Push a line to the skeleton
 /// But do this so that <tag>value</tag> is pushed as a single line instead of <tag>\nvalue\n</tag>
 pub fn push_line_to_skeleton(
@@ -420,11 +515,155 @@ pub fn version_string_to_number(version: &str) -> u64 {
     major * 1_000_000_000 + minor * 1_000_000 + patch * 1_000 + build
 }
 
+// Some catalog items derive their name differently from the standard method which builds the name using id and name attributes in the catalog item tag
+pub fn rename_file_if_necessary(file_path: &Path, path_stack: &[Vec<u8>], tag_name: &[u8]) {
+    // Check if path_stack has the required structure: second value should be "Structure" and third should be "AddAction"
+    let has_structure_add_action = path_stack.len() >= 3
+        && path_stack.get(1).is_some_and(|v| v == b"Structure")
+        && path_stack.get(2).is_some_and(|v| v == b"AddAction");
+    let has_structure_modify_action = path_stack.len() >= 3
+        && path_stack.get(1).is_some_and(|v| v == b"Structure")
+        && path_stack.get(2).is_some_and(|v| v == b"ModifyAction");
+
+    let results = match tag_name {
+        b"Account" if has_structure_add_action => {
+            let paths = vec!["Account/Authentication/AccountName", "Account/@id"];
+            Some(extract_values_from_xml_paths(file_path, &paths))
+        }
+        b"Authorization" if has_structure_add_action => {
+            let paths = vec!["Authorization/Display", "Authorization/@id"];
+            Some(extract_values_from_xml_paths(file_path, &paths))
+        }
+        b"BinaryData" if has_structure_add_action => {
+            let paths = vec![
+                "BinaryData/LibraryReference/@key",
+                "BinaryData/LibraryReference/@id",
+            ];
+            Some(extract_values_from_xml_paths(file_path, &paths))
+        }
+        b"Layout" if has_structure_modify_action => {
+            let paths = vec!["Layout/LayoutReference/@name", "Layout/LayoutReference/@id"];
+            Some(extract_values_from_xml_paths(file_path, &paths))
+        }
+        b"Relationship" if has_structure_add_action => {
+            let paths = vec![
+                "Relationship/LeftTable/TableOccurrenceReference/@name",
+                "Relationship/RightTable/TableOccurrenceReference/@name",
+                "Relationship/@id",
+            ];
+            Some(extract_values_from_xml_paths(file_path, &paths))
+        }
+        _ => None,
+    };
+
+    if let Some(Ok(results)) = results {
+        // Get the ID from the last element
+        if let Some(Some(id)) = results.last() {
+            // Join all names (except the last element which is the ID)
+            let names: Vec<_> = results[..results.len() - 1]
+                .iter()
+                .filter_map(|r| r.as_ref())
+                .collect();
+
+            let name_part = if names.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "{} - ",
+                    names
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" - ")
+                )
+            };
+
+            let new_name = format!("{name_part}ID {id}.xml");
+            let _ = rename_file(file_path, &new_name);
+        }
+    }
+}
+
+/// Move a file to a subfolder if the subfolder path is not empty
+pub fn move_to_subfolder(file_path: &Path, subfolder_dir_path: &Path) -> Result<PathBuf, String> {
+    if subfolder_dir_path.to_string_lossy().is_empty() {
+        return Ok(file_path.to_path_buf());
+    }
+
+    // Create the subfolder directory if it doesn't exist
+    fs::create_dir_all(subfolder_dir_path)
+        .map_err(|e| format!("Failed to create subfolder directory: {e}"))?;
+
+    // Get the filename from the original path
+    let filename = file_path
+        .file_name()
+        .ok_or_else(|| "File path has no filename".to_string())?;
+
+    // Create the new path in the subfolder
+    let new_path = subfolder_dir_path.join(filename);
+
+    // Move the file to the new location
+    fs::rename(file_path, &new_path)
+        .map_err(|e| format!("Failed to move file to subfolder: {e}"))?;
+
+    Ok(new_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_apply_line_policy() {
+        let content = "<a>\n\t<b></b>\n</a>\n";
+        assert_eq!(apply_line_policy(content, &Flags::default()), content);
+
+        let flags = Flags {
+            line_endings: LineEnding::Crlf,
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_line_policy(content, &flags),
+            "<a>\r\n\t<b></b>\r\n</a>\r\n"
+        );
+
+        let flags = Flags {
+            line_endings: LineEnding::Crlf,
+            trailing_newline: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_line_policy(content, &flags),
+            "<a>\r\n\t<b></b>\r\n</a>"
+        );
+    }
+
+    #[test]
+    fn test_is_domain_cleared() {
+        let flags = Flags {
+            only: vec![Section::Scripts],
+            ..Default::default()
+        };
+        assert!(is_domain_cleared(&flags, Path::new("Db/scripts_sanitized")));
+        assert!(is_domain_cleared(
+            &flags,
+            Path::new("Db/scripts__modify_action")
+        ));
+        assert!(!is_domain_cleared(&flags, Path::new("Db/layouts")));
+        assert!(!is_domain_cleared(&flags, Path::new("Db/_")));
+        assert!(is_domain_cleared(
+            &Flags::default(),
+            Path::new("Db/layouts")
+        ));
+    }
+
+    #[test]
+    fn test_write_to_disk_failure() {
+        let err = write_to_disk(Path::new("/nonexistent-fm-dir/file.txt"), "content").unwrap_err();
+        assert!(err.to_string().contains("/nonexistent-fm-dir/file.txt"));
+    }
+
     #[test]
     fn test_rename_file() {
         // Create a temporary file
@@ -580,97 +819,3 @@ mod tests {
         fs::remove_file(&temp_file).unwrap();
     }
 }
-
-// Some catalog items derive their name differently from the standard method which builds the name using id and name attributes in the catalog item tag
-pub fn rename_file_if_necessary(file_path: &Path, path_stack: &[Vec<u8>], tag_name: &[u8]) {
-    // Check if path_stack has the required structure: second value should be "Structure" and third should be "AddAction"
-    let has_structure_add_action = path_stack.len() >= 3
-        && path_stack.get(1).is_some_and(|v| v == b"Structure")
-        && path_stack.get(2).is_some_and(|v| v == b"AddAction");
-    let has_structure_modify_action = path_stack.len() >= 3
-        && path_stack.get(1).is_some_and(|v| v == b"Structure")
-        && path_stack.get(2).is_some_and(|v| v == b"ModifyAction");
-
-    let results = match tag_name {
-        b"Account" if has_structure_add_action => {
-            let paths = vec!["Account/Authentication/AccountName", "Account/@id"];
-            Some(extract_values_from_xml_paths(file_path, &paths))
-        }
-        b"Authorization" if has_structure_add_action => {
-            let paths = vec!["Authorization/Display", "Authorization/@id"];
-            Some(extract_values_from_xml_paths(file_path, &paths))
-        }
-        b"BinaryData" if has_structure_add_action => {
-            let paths = vec![
-                "BinaryData/LibraryReference/@key",
-                "BinaryData/LibraryReference/@id",
-            ];
-            Some(extract_values_from_xml_paths(file_path, &paths))
-        }
-        b"Layout" if has_structure_modify_action => {
-            let paths = vec!["Layout/LayoutReference/@name", "Layout/LayoutReference/@id"];
-            Some(extract_values_from_xml_paths(file_path, &paths))
-        }
-        b"Relationship" if has_structure_add_action => {
-            let paths = vec![
-                "Relationship/LeftTable/TableOccurrenceReference/@name",
-                "Relationship/RightTable/TableOccurrenceReference/@name",
-                "Relationship/@id",
-            ];
-            Some(extract_values_from_xml_paths(file_path, &paths))
-        }
-        _ => None,
-    };
-
-    if let Some(Ok(results)) = results {
-        // Get the ID from the last element
-        if let Some(Some(id)) = results.last() {
-            // Join all names (except the last element which is the ID)
-            let names: Vec<_> = results[..results.len() - 1]
-                .iter()
-                .filter_map(|r| r.as_ref())
-                .collect();
-
-            let name_part = if names.is_empty() {
-                String::new()
-            } else {
-                format!(
-                    "{} - ",
-                    names
-                        .iter()
-                        .map(|s| s.as_str())
-                        .collect::<Vec<_>>()
-                        .join(" - ")
-                )
-            };
-
-            let new_name = format!("{name_part}ID {id}.xml");
-            let _ = rename_file(file_path, &new_name);
-        }
-    }
-}
-
-/// Move a file to a subfolder if the subfolder path is not empty
-pub fn move_to_subfolder(file_path: &Path, subfolder_dir_path: &Path) -> Result<PathBuf, String> {
-    if subfolder_dir_path.to_string_lossy().is_empty() {
-        return Ok(file_path.to_path_buf());
-    }
-
-    // Create the subfolder directory if it doesn't exist
-    fs::create_dir_all(subfolder_dir_path)
-        .map_err(|e| format!("Failed to create subfolder directory: {e}"))?;
-
-    // Get the filename from the original path
-    let filename = file_path
-        .file_name()
-        .ok_or_else(|| "File path has no filename".to_string())?;
-
-    // Create the new path in the subfolder
-    let new_path = subfolder_dir_path.join(filename);
-
-    // Move the file to the new location
-    fs::rename(file_path, &new_path)
-        .map_err(|e| format!("Failed to move file to subfolder: {e}"))?;
-
-    Ok(new_path)
-}
@@ -5,11 +5,11 @@ use std::path::Path;
 
 use anyhow::Result;
 use quick_xml::events::{BytesCData, BytesEnd, BytesRef, BytesStart, BytesText, Event};
-use quick_xml::Reader;
+use quick_xml::{Reader, Writer};
 
-use crate::utils::attributes::get_attributes;
+use crate::utils::attributes::{get_attribute, get_attributes};
 use crate::utils::push_line_to_skeleton;
-use crate::xml_processor::ProcessingContext;
+use crate::xml_processor::{record_parse_error, ProcessingContext};
 use crate::{Flags, Skeleton};
 
 #[derive(Debug, Default, PartialEq)]
@@ -129,13 +129,60 @@ pub fn general_ref_to_string(e: &BytesRef, escape: bool) -> String {
     }
 }
 
-pub fn encode_xml_special_characters(input: String) -> String {
-    input
-        .replace("&amp;", "&")
-        .replace("&quot;", "\"")
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&apos;", "'")
+/// Resolve predefined entities and character references, e.g. `&amp;`, `&quot;`, `&#13;` or `&#x41;`
+/// Unknown or malformed references are kept as they are
+pub fn unescape_entities(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let resolved = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let resolved = match entity.strip_prefix('#') {
+                Some(char_ref) => match char_ref.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => char_ref.parse::<u32>().ok(),
+                }
+                .and_then(char::from_u32)
+                .map(String::from),
+                None => quick_xml::escape::resolve_predefined_entity(entity).map(str::to_string),
+            };
+            resolved.map(|resolved| (resolved, end + 1))
+        });
+        match resolved {
+            Some((resolved, len)) => {
+                unescaped.push_str(&resolved);
+                rest = &rest[len..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Re-indent an XML fragment with two spaces per level, dropping whitespace-only text
+/// Returns the trimmed input if it is not well-formed
+pub fn pretty_print_xml(xml: &str) -> String {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => {
+                if writer.write_event(event).is_err() {
+                    return xml.trim().to_string();
+                }
+            }
+            Err(_) => return xml.trim().to_string(),
+        }
+    }
+    String::from_utf8(writer.into_inner()).unwrap_or_else(|_| xml.trim().to_string())
 }
 
 fn decode_xml_special_characters(input: String) -> String {
@@ -170,6 +217,112 @@ pub fn skip_rest_of_element<R: Read + BufRead>(reader: &mut Reader<R>, _: &Bytes
     }
 }
 
+/// Skip the rest of an element like `skip_rest_of_element`, but scan its raw bytes for tags instead
+/// of reading them as events, for sections deselected via `--only`. The reader checks each end tag
+/// against the start tags it has read, so the ends of elements started by events are read as
+/// events too, as are tags cut off by the end of the buffered bytes while no raw element is open.
+pub fn skip_rest_of_element_raw<R: BufRead>(reader: &mut Reader<R>) {
+    // For each element started by an event, the number of raw elements open inside it
+    let mut open = vec![0_usize];
+    let mut state = RawState::Text;
+    let mut buf: Vec<u8> = Vec::new();
+    while let Some(nested) = open.last_mut() {
+        let mut stream = reader.stream();
+        let bytes = match stream.fill_buf() {
+            Ok(bytes) if !bytes.is_empty() => bytes,
+            _ => return,
+        };
+        let (len, next_state) = match state {
+            RawState::Text => match memchr::memchr(b'<', bytes) {
+                None => (bytes.len(), RawState::Text),
+                Some(index)
+                    if *nested == 0 && matches!(bytes.get(index + 1), None | Some(b'/')) =>
+                {
+                    stream.consume(index);
+                    buf.clear();
+                    match reader.read_event_into(&mut buf) {
+                        Ok(Event::Start(_)) => open.push(0),
+                        Ok(Event::End(_)) => {
+                            open.pop();
+                        }
+                        Ok(Event::Eof) => return,
+                        _ => {}
+                    }
+                    continue;
+                }
+                Some(index) => (index + 1, RawState::Markup),
+            },
+            RawState::Markup => match bytes[0] {
+                b'/' => {
+                    *nested -= 1;
+                    (1, RawState::Text)
+                }
+                b'!' => (1, RawState::Bang),
+                b'?' => (1, RawState::Until(b"?>", 0)),
+                _ => (0, RawState::StartTag(None, false)),
+            },
+            RawState::Bang => match bytes[0] {
+                b'[' => (1, RawState::Until(b"]]>", 0)),
+                _ => (1, RawState::Until(b"-->", 0)),
+            },
+            RawState::StartTag(Some(quote), _) => match memchr::memchr(quote, bytes) {
+                None => (bytes.len(), state),
+                Some(index) => (index + 1, RawState::StartTag(None, false)),
+            },
+            RawState::StartTag(None, slash) => match memchr::memchr3(b'>', b'"', b'\'', bytes) {
+                None => (bytes.len(), RawState::StartTag(None, bytes.ends_with(b"/"))),
+                Some(index) if bytes[index] == b'>' => {
+                    let is_empty = match index {
+                        0 => slash,
+                        _ => bytes[index - 1] == b'/',
+                    };
+                    if !is_empty {
+                        *nested += 1;
+                    }
+                    (index + 1, RawState::Text)
+                }
+                Some(index) => (index + 1, RawState::StartTag(Some(bytes[index]), false)),
+            },
+            RawState::Until(end, mut matched) => {
+                let mut len = bytes.len();
+                for (index, &byte) in bytes.iter().enumerate() {
+                    matched = match byte {
+                        _ if byte == end[matched] => matched + 1,
+                        // The ends are runs of the same byte followed by another, e.g. `]]>`
+                        _ if byte == end[0] => matched.max(1),
+                        _ => 0,
+                    };
+                    if matched == end.len() {
+                        len = index + 1;
+                        break;
+                    }
+                }
+                match matched == end.len() {
+                    true => (len, RawState::Text),
+                    false => (len, RawState::Until(end, matched)),
+                }
+            }
+        };
+        stream.consume(len);
+        state = next_state;
+    }
+}
+
+/// Where the raw bytes of a skipped element are being scanned, kept across buffer refills
+#[derive(Clone, Copy)]
+enum RawState {
+    Text,
+    /// After a `<`
+    Markup,
+    /// After a `<!`, starting a comment or CDATA
+    Bang,
+    /// In a start tag, with the quote of the attribute value being read and whether the last
+    /// byte was a `/`
+    StartTag(Option<u8>, bool),
+    /// In a comment, CDATA or processing instruction, with the bytes of its end matched so far
+    Until(&'static [u8], usize),
+}
+
 pub fn push_rest_of_element_to_skeleton<R: Read + BufRead>(
     reader: &mut Reader<R>,
     _: &BytesStart,
@@ -266,7 +419,15 @@ pub fn element_to_string<R: Read + BufRead>(
     let mut buf: Vec<u8> = Vec::new();
     loop {
         match context.reader.read_event_into(&mut buf) {
-            Err(_) => continue,
+            Err(e) => {
+                let enclosing = format!(
+                    "{} '{}'",
+                    local_name_to_string(start_tag.name().as_ref()),
+                    get_attribute(start_tag, "name").unwrap_or_default()
+                );
+                record_parse_error(context, Some(enclosing), &e);
+                continue;
+            }
             Ok(Event::Eof) => break,
             Ok(Event::Start(e)) => {
                 depth += 1;
@@ -295,19 +456,6 @@ pub fn element_to_string<R: Read + BufRead>(
     content
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_decode_xml_special_characters() {
-        assert_eq!(
-            decode_xml_special_characters("This & that \"test\" <tag>".to_string()),
-            "This &amp; that &quot;test&quot; &lt;tag&gt;"
-        );
-    }
-}
-
 /// Extract content from XML file using multiple XPath-like expressions
 ///
 /// # Arguments
@@ -319,7 +467,7 @@ mod tests {
 /// * `Err(String)` - If there's an error reading the file or parsing XML
 ///
 /// # Examples
-/// ```
+/// ```ignore
 /// let paths = vec![
 ///     "Account/Authentication/AccountName",
 ///     "Account/@id"
@@ -484,3 +632,67 @@ pub fn extract_values_from_xml_paths(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_xml_special_characters() {
+        assert_eq!(
+            decode_xml_special_characters("This & that \"test\" <tag>".to_string()),
+            "This &amp; that &quot;test&quot; &lt;tag&gt;"
+        );
+    }
+
+    #[test]
+    fn test_unescape_entities() {
+        assert_eq!(
+            unescape_entities("Replace &amp; Find &lt;unknown&gt; &quot;a&apos;b&quot;"),
+            "Replace & Find <unknown> \"a'b\""
+        );
+        assert_eq!(unescape_entities("a&#13;b&#x41;&#65;"), "a\rbAA");
+        assert_eq!(unescape_entities("&amp;lt;"), "&lt;");
+        assert_eq!(
+            unescape_entities("Tom & Jerry &nbsp; &#xZZ; &"),
+            "Tom & Jerry &nbsp; &#xZZ; &"
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_xml() {
+        let xml = "<Step id=\"1\">\n\t\t\t<Options>4</Options>\n\t\t\t<A><B/></A>\n\t\t</Step>";
+        assert_eq!(
+            pretty_print_xml(xml),
+            "<Step id=\"1\">\n  <Options>4</Options>\n  <A>\n    <B/>\n  </A>\n</Step>"
+        );
+        assert_eq!(pretty_print_xml(" <Step> "), "<Step>");
+    }
+
+    #[test]
+    fn test_skip_rest_of_element_raw() {
+        let xml = concat!(
+            r#"<Root><Skip a="x>y"><Skip/><!-- </Skip> --><![CDATA[</Skip>]]]]><?pi </Skip>?>"#,
+            r#"<Skip id="2" b='/>'><Other>t &amp; u</Other></Skip ><SkipMore/></Skip><Next/></Root>"#
+        );
+        // Tags cut off by the end of the buffered bytes are read as events
+        for capacity in 1..=xml.len() {
+            let mut reader =
+                Reader::from_reader(BufReader::with_capacity(capacity, xml.as_bytes()));
+            let mut buf = Vec::new();
+            reader.read_event_into(&mut buf).unwrap();
+            reader.read_event_into(&mut buf).unwrap();
+            skip_rest_of_element_raw(&mut reader);
+            assert_eq!(
+                reader.read_event_into(&mut buf).unwrap(),
+                Event::Empty(BytesStart::new("Next")),
+                "capacity {capacity}"
+            );
+            assert_eq!(
+                reader.read_event_into(&mut buf).unwrap(),
+                Event::End(BytesEnd::new("Root"))
+            );
+            assert_eq!(reader.buffer_position(), xml.len() as u64);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::utils::write_to_disk;
+
+/// Files queued per writer thread before the rendering threads wait for the disk
+const QUEUE_CAPACITY_PER_THREAD: usize = 64;
+
+struct WriteJob {
+    path: PathBuf,
+    content: String,
+}
+
+/// Writes the rendered output files on threads of its own (`--writers`), so rendering doesn't
+/// wait for the disk. All files have been written once `finish` returns.
+pub struct WriterPool {
+    sender: Mutex<Option<SyncSender<WriteJob>>>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+    failures: Arc<AtomicUsize>,
+}
+
+impl WriterPool {
+    pub fn new(thread_count: usize) -> WriterPool {
+        let (sender, receiver) = sync_channel(thread_count * QUEUE_CAPACITY_PER_THREAD);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let failures = Arc::new(AtomicUsize::new(0));
+        let threads = (0..thread_count)
+            .map(|_| {
+                let receiver = receiver.clone();
+                let failures = failures.clone();
+                thread::spawn(move || write_jobs(&receiver, &failures))
+            })
+            .collect();
+        WriterPool {
+            sender: Mutex::new(Some(sender)),
+            threads: Mutex::new(threads),
+            failures,
+        }
+    }
+
+    /// Queue a file, written right away if the pool is finished
+    pub fn write(&self, path: PathBuf, content: String) {
+        let sender = self.sender.lock().unwrap().clone();
+        let Some(sender) = sender else {
+            return write_job(&path, &content, &self.failures);
+        };
+        // Without threads left to receive it the file is written by the caller
+        if let Err(err) = sender.send(WriteJob { path, content }) {
+            write_job(&err.0.path, &err.0.content, &self.failures);
+        }
+    }
+
+    /// Files of this pool that could not be written
+    pub fn failures(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Write the queued files and stop the threads, rethrowing the panic of a thread
+    pub fn finish(&self) {
+        self.sender.lock().unwrap().take();
+        for thread in self.threads.lock().unwrap().drain(..) {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl Drop for WriterPool {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.finish();
+        }
+    }
+}
+
+fn write_jobs(receiver: &Mutex<Receiver<WriteJob>>, failures: &AtomicUsize) {
+    loop {
+        let job = receiver.lock().unwrap().recv();
+        let Ok(WriteJob { path, content }) = job else {
+            return;
+        };
+        write_job(&path, &content, failures);
+    }
+}
+
+fn write_job(path: &Path, content: &str, failures: &AtomicUsize) {
+    if let Err(err) = write_to_disk(path, content) {
+        failures.fetch_add(1, Ordering::Relaxed);
+        eprintln!("{err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::utils::file_utils::ScratchDir;
+
+    use super::*;
+
+    #[test]
+    fn test_finish() {
+        let dir = ScratchDir::create("writer-pool-test").unwrap();
+        let pool = WriterPool::new(2);
+        for index in 0..200 {
+            pool.write(dir.path.join(format!("{index}.txt")), index.to_string());
+        }
+        pool.finish();
+        assert_eq!(fs::read_to_string(dir.path.join("199.txt")).unwrap(), "199");
+        assert_eq!(fs::read_dir(&dir.path).unwrap().count(), 200);
+
+        // Files of a finished pool are written right away
+        pool.write(dir.path.join("late.txt"), "late".to_string());
+        assert_eq!(
+            fs::read_to_string(dir.path.join("late.txt")).unwrap(),
+            "late"
+        );
+        assert_eq!(pool.failures(), 0);
+    }
+
+    #[test]
+    fn test_failures() {
+        let pool = WriterPool::new(2);
+        pool.write(PathBuf::from("/nonexistent-fm-dir/a.txt"), String::new());
+        pool.write(PathBuf::from("/nonexistent-fm-dir/b.txt"), String::new());
+        pool.finish();
+        assert_eq!(pool.failures(), 2);
+
+        // Each pool counts its own failures
+        assert_eq!(WriterPool::new(1).failures(), 0);
+    }
+}
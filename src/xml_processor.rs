@@ -1,22 +1,40 @@
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{fs::File, time::Instant};
 
-use anyhow::{bail, Context, Error, Result};
+use anyhow::{anyhow, bail, Context, Error, Result};
+use clap::ValueEnum;
 use encoding_rs_io::DecodeReaderBytes;
+use indicatif::ProgressBar;
 use quick_xml::events::{BytesEnd, BytesStart, Event};
 use quick_xml::reader::Reader;
 
 use crate::catalog::xml_explode_catalog;
 use crate::config::{CatalogType, Flags};
 use crate::custom_function_sanitizer::create_sanitized_custom_functions;
-use crate::script_sanitizer::create_sanitized_scripts;
+use crate::diagnostics::{line_column_at, Diagnostic, DiagnosticsCollector};
+use crate::incremental::Incremental;
+use crate::jsonl::JsonlSink;
+use crate::manifest::RenderReports;
+use crate::mapped_export::MappedExport;
+use crate::profile::Profile;
+use crate::progress::Progress;
+use crate::redact::with_redaction;
+use crate::script_sanitizer::{create_sanitized_scripts, ScriptExport};
+use crate::script_template::ScriptTemplate;
+use crate::stats::RunStats;
 use crate::supporting::process_supporting_element;
+use crate::symbol_cache::read_symbols;
+use crate::symbols::SymbolTable;
 use crate::utils::attributes::get_attribute;
-use crate::utils::xml_utils::{end_element_to_string, start_element_to_string, XmlEventType};
+use crate::utils::xml_utils::{
+    end_element_to_string, skip_rest_of_element_raw, start_element_to_string, XmlEventType,
+};
 use crate::utils::{build_out_dir_path, delete_output_directory, write_xml_file, FolderStructure};
 use crate::utils::{create_dir, push_line_to_skeleton};
-use crate::Skeleton;
+use crate::writer_pool::WriterPool;
+use crate::{Section, Skeleton};
 
 pub enum TopLevelSection {
     Structure,
@@ -31,14 +49,43 @@ pub enum Action {
     Delete,
 }
 
+#[derive(Clone, Copy)]
 pub enum Qualifier {
     SanitizedScripts,
     SanitizedCustomFunctions,
+    CalcComments,
+}
+
+impl Qualifier {
+    pub const ALL: [Qualifier; 3] = [
+        Self::SanitizedScripts,
+        Self::SanitizedCustomFunctions,
+        Self::CalcComments,
+    ];
+
+    /// The output folder of the files derived from a catalog
+    pub fn folder_name(&self) -> &'static str {
+        match self {
+            Self::SanitizedScripts => "scripts_sanitized",
+            Self::SanitizedCustomFunctions => "custom_functions",
+            Self::CalcComments => "calc_comments",
+        }
+    }
+
+    /// The section selectable via `--only` these files belong to
+    pub fn section(&self) -> Section {
+        match self {
+            Self::SanitizedScripts | Self::CalcComments => Section::Scripts,
+            Self::SanitizedCustomFunctions => Section::CustomFunctions,
+        }
+    }
 }
 
 /// Context for XML catalog processing
 pub struct ProcessingContext<'a, R: Read + BufRead> {
     pub reader: &'a mut Reader<R>,
+    /// The export being read, to locate parse errors
+    pub source_path: &'a Path,
     pub path_stack: &'a mut Vec<Vec<u8>>,
     pub root_out_dir: PathBuf,
     pub saxml_version: Option<String>,
@@ -49,21 +96,169 @@ pub struct ProcessingContext<'a, R: Read + BufRead> {
     pub current_out_dir: PathBuf,
     pub skeleton: &'a mut Skeleton,
     pub flags: &'a Flags,
+    pub progress: &'a ProgressBar,
+    pub run: &'a RunContext,
+    /// Set by parse errors other than the input ending unexpectedly, e.g. mismatched tags
+    pub ill_formed: bool,
+    /// Names of the catalog elements of the export by id, read before the main pass
+    pub symbols: Option<Arc<SymbolTable>>,
+}
+
+/// State shared by all exports processed in parallel during one run
+#[derive(Default)]
+pub struct RunContext {
+    pub progress: Progress,
+    pub stats: RunStats,
+    pub incremental: Option<Incremental>,
+    pub jsonl: Option<JsonlSink>,
+    pub template: Option<ScriptTemplate>,
+    pub diagnostics: DiagnosticsCollector,
+    /// Parse the exports from memory maps instead of buffered reads (`--mmap`)
+    pub mmap: bool,
+    /// Where symbol tables are cached by the checksum of their export (`--cache-dir`)
+    pub cache_dir: Option<PathBuf>,
+    /// Timings per section and script, reported with `--profile`
+    pub profile: Option<Profile>,
+    /// Writes the output files on threads of its own (`--writers`); `None` writes them right away
+    pub writer: Option<WriterPool>,
+    /// Symbol checksums and reports of the rendered scripts, for the manifest
+    pub reports: RenderReports,
+}
+
+/// Record a parse error of the export, located by line and column and the enclosing element,
+/// e.g. the script being read (default: the path of open elements)
+pub fn record_parse_error<R: Read + BufRead>(
+    context: &mut ProcessingContext<'_, R>,
+    enclosing: Option<String>,
+    err: &quick_xml::Error,
+) {
+    context.run.stats.record_parse_warning();
+    // Syntax errors are all caused by the input ending within markup
+    if !matches!(err, quick_xml::Error::Syntax(_)) {
+        context.ill_formed = true;
+    }
+    let file_name = context.source_path.file_name().unwrap_or_default();
+    let enclosing = enclosing.unwrap_or_else(|| {
+        context
+            .path_stack
+            .iter()
+            .map(|name| String::from_utf8_lossy(name))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    });
+    let offset = context.reader.error_position();
+    context.run.diagnostics.push(Diagnostic {
+        context: format!("{} / {enclosing}", file_name.to_string_lossy()),
+        offset,
+        message: format!("Error parsing XML: {err}"),
+        location: locate(context, offset),
+    });
+}
+
+/// Record an export that ended before all elements were closed, e.g. because it was interrupted
+fn record_truncated_export<R: Read + BufRead>(context: &ProcessingContext<'_, R>) {
+    context.run.stats.record_truncated_export();
+    let file_name = context.source_path.file_name().unwrap_or_default();
+    let offset = context.reader.buffer_position();
+    context.run.diagnostics.push(Diagnostic {
+        context: file_name.to_string_lossy().to_string(),
+        offset,
+        message: "Export ended unexpectedly, everything read up to here was written".to_string(),
+        location: locate(context, offset),
+    });
+}
+
+/// The offset in the export as `file:line:column`
+fn locate<R: Read + BufRead>(context: &ProcessingContext<'_, R>, offset: u64) -> Option<String> {
+    let file_name = context.source_path.file_name().unwrap_or_default();
+    File::open(context.source_path)
+        .and_then(|file| line_column_at(DecodeReaderBytes::new(file), offset))
+        .ok()
+        .map(|(line, column)| format!("{}:{line}:{column}", file_name.to_string_lossy()))
 }
 
 /// Process a single XML file and explode it into individual files
+/// Returns the name of the exploded database
 pub fn explode_xml(
     fm_export_file_path: &PathBuf,
     root_out_dir: &Path,
     flags: &Flags,
-) -> Result<(), Error> {
+    run: &RunContext,
+) -> Result<String, Error> {
     let start = Instant::now();
     let fm_export_file_name = fm_export_file_path.file_name().unwrap().to_str().unwrap();
 
     // Open XML file
     let file = File::open(fm_export_file_path)
         .with_context(|| format!("Error opening file {}", fm_export_file_path.display(),))?;
+    let file_len = file.metadata().map(|m| m.len()).unwrap_or_default();
+    let progress_bar = run.progress.file_bar(fm_export_file_name, file_len);
+    progress_bar.set_message("parsing header");
+
+    let mapped = match run.mmap {
+        true => MappedExport::open(fm_export_file_path)?,
+        false => None,
+    };
+
+    // Scripts refer to other elements by id, so read the catalogs first
+    let symbols_start = Instant::now();
+    let symbols = match flags.includes(Section::Scripts) {
+        true => Some(Arc::new(read_symbols(
+            fm_export_file_path,
+            mapped.as_ref().map(MappedExport::bytes),
+            run.cache_dir.as_deref(),
+        )?)),
+        false => None,
+    };
+    if let (Some(profile), Some(_)) = (&run.profile, &symbols) {
+        profile.record_section("symbols", symbols_start.elapsed(), file_len);
+    }
+
+    // Steps are written before their entries in the DDR info, which are redacted with them
+    let db_name = with_redaction(|| match &mapped {
+        Some(mapped) => explode_events(
+            &mut Reader::from_reader(progress_bar.wrap_read(mapped.bytes())),
+            fm_export_file_path,
+            root_out_dir,
+            flags,
+            run,
+            &progress_bar,
+            symbols,
+        ),
+        None => explode_events(
+            &mut Reader::from_reader(BufReader::new(DecodeReaderBytes::new(
+                progress_bar.wrap_read(file),
+            ))),
+            fm_export_file_path,
+            root_out_dir,
+            flags,
+            run,
+            &progress_bar,
+            symbols,
+        ),
+    })?;
+
+    progress_bar.finish_and_clear();
+    run.progress.println(format!(
+        "→ {} finished in {} ms.",
+        fm_export_file_name,
+        start.elapsed().as_millis()
+    ));
+
+    db_name.ok_or_else(|| anyhow!("Missing db name in {}", fm_export_file_name))
+}
 
+/// Explode the events of an export read from the file or the mapped bytes,
+/// returning the name of its database
+fn explode_events<R: Read + BufRead>(
+    reader: &mut Reader<R>,
+    source_path: &Path,
+    root_out_dir: &Path,
+    flags: &Flags,
+    run: &RunContext,
+    progress_bar: &ProgressBar,
+    symbols: Option<Arc<SymbolTable>>,
+) -> Result<Option<String>, Error> {
     // Initialize variables
     let mut depth = 0;
     let mut cf_folder_structure: Option<FolderStructure> = None;
@@ -72,7 +267,8 @@ pub fn explode_xml(
 
     // Instantiate processing context which will be passed around to various functions
     let mut context = ProcessingContext {
-        reader: &mut Reader::from_reader(BufReader::new(DecodeReaderBytes::new(file))),
+        reader,
+        source_path,
         path_stack: &mut Vec::new(),
         root_out_dir: root_out_dir.to_path_buf(),
         saxml_version: None,
@@ -83,13 +279,17 @@ pub fn explode_xml(
         current_out_dir: PathBuf::new(),
         skeleton: &mut Skeleton::default(),
         flags,
+        progress: progress_bar,
+        run,
+        ill_formed: false,
+        symbols,
     };
 
     // Iterate over XML events
     loop {
         match context.reader.read_event_into(&mut buf) {
             Err(e) => {
-                println!("Error in {}: {}", fm_export_file_path.display(), e);
+                record_parse_error(&mut context, None, &e);
                 break;
             }
             Ok(Event::Eof) => break,
@@ -108,8 +308,14 @@ pub fn explode_xml(
                         delete_output_directory(&context)?;
                     }
                     1 => {
+                        let section_start = SectionStart::now(&context);
                         let was_xml_element_consumed =
                             process_top_level_section(&mut context, &start_tag)?;
+                        if let Some(TopLevelSection::Metadata | TopLevelSection::DdrInfo) =
+                            context.top_level_section
+                        {
+                            section_start.record(&context, "metadata");
+                        }
                         if was_xml_element_consumed {
                             continue;
                         }
@@ -124,12 +330,20 @@ pub fn explode_xml(
                         };
                     }
                     3 => {
+                        let section_start = SectionStart::now(&context);
                         let is_supported_catalog = process_catalog_elements(
                             &mut context,
                             &start_tag,
                             &mut cf_folder_structure,
                             &mut script_folder_structure,
                         )?;
+                        if let Some(section) = CatalogType::from_bytes(start_tag.name().as_ref())
+                            .map(|catalog_type| catalog_type.section())
+                            .filter(|section| context.flags.includes(*section))
+                        {
+                            let section = section.to_possible_value().unwrap();
+                            section_start.record(&context, section.get_name());
+                        }
                         if is_supported_catalog {
                             continue;
                         }
@@ -148,18 +362,18 @@ pub fn explode_xml(
         buf.clear()
     }
 
+    // Elements still open at the end of the input mean the export is incomplete,
+    // unless they are the result of ill-formed XML
+    if !context.path_stack.is_empty() && !context.ill_formed {
+        record_truncated_export(&context);
+    }
+
     // Write skeleton file if in lossless mode
     if flags.lossless {
         write_skeleton_file(&context)?;
     }
 
-    println!(
-        "→ {} finished in {} ms.",
-        fm_export_file_name,
-        start.elapsed().as_millis()
-    );
-
-    Ok(())
+    Ok(context.db_name)
 }
 
 fn process_root_element<R: Read + BufRead>(
@@ -191,6 +405,11 @@ fn process_top_level_section<R: Read + BufRead>(
 ) -> Result<bool, Error> {
     let mut was_xml_element_consumed = false;
     match start_tag.name().as_ref() {
+        b"Metadata" | b"DDR_INFO" if !context.flags.includes(Section::Metadata) => {
+            context.top_level_section = None;
+            skip_catalog_or_section(context);
+            was_xml_element_consumed = true;
+        }
         b"Structure" => {
             context.top_level_section = Some(TopLevelSection::Structure);
         }
@@ -222,6 +441,10 @@ fn process_catalog_elements<R: Read + BufRead>(
             return Ok(false);
         }
     };
+    if !context.flags.includes(catalog_type.section()) {
+        skip_catalog_or_section(context);
+        return Ok(true);
+    }
     context.catalog_type = Some(catalog_type);
     // Handle special cases that need folder structures
     let folder_structure = match catalog_type {
@@ -249,23 +472,73 @@ fn process_catalog_elements<R: Read + BufRead>(
 
     // Handle post-processing for StepsForScripts
     if catalog_type == CatalogType::StepsForScripts {
+        context.progress.set_message("writing sanitized scripts");
         let sanitized_scripts_dir_path =
             build_out_dir_path(context, Some(Qualifier::SanitizedScripts))?;
-        create_sanitized_scripts(
-            &xml_out_dir_path,
-            &sanitized_scripts_dir_path,
-            context.flags,
-        );
+        let calc_comments_dir_path = match context.flags.calc_comments {
+            true => Some(build_out_dir_path(context, Some(Qualifier::CalcComments))?),
+            false => None,
+        };
+        let export = ScriptExport {
+            db_name: context.db_name.as_ref().unwrap(),
+            xml_out_dir_path: &xml_out_dir_path,
+            text_out_dir_path: &sanitized_scripts_dir_path,
+            calc_comments_out_dir_path: calc_comments_dir_path.as_deref(),
+            symbols: context.symbols.clone(),
+            symbols_checksum: context
+                .symbols
+                .as_ref()
+                .map(|symbols| symbols.checksum())
+                .unwrap_or_default(),
+        };
+        create_sanitized_scripts(&export, context.flags, context.run);
     }
 
     if catalog_type == CatalogType::CalcsForCustomFunctions {
+        context
+            .progress
+            .set_message("writing sanitized custom functions");
         let sanitized_cf_dir_path =
             build_out_dir_path(context, Some(Qualifier::SanitizedCustomFunctions))?;
-        create_sanitized_custom_functions(&xml_out_dir_path, &sanitized_cf_dir_path);
+        create_sanitized_custom_functions(
+            context.db_name.as_ref().unwrap(),
+            &xml_out_dir_path,
+            &sanitized_cf_dir_path,
+            context.flags,
+            context.run,
+        );
     }
     Ok(true) // is_supported_catalog
 }
 
+/// Where and when a section of the export started, to record its time and bytes with `--profile`
+struct SectionStart {
+    instant: Instant,
+    position: u64,
+}
+
+impl SectionStart {
+    fn now<R: Read + BufRead>(context: &ProcessingContext<'_, R>) -> SectionStart {
+        SectionStart {
+            instant: Instant::now(),
+            position: context.reader.buffer_position(),
+        }
+    }
+
+    fn record<R: Read + BufRead>(&self, context: &ProcessingContext<'_, R>, section: &str) {
+        if let Some(profile) = &context.run.profile {
+            let bytes = context.reader.buffer_position() - self.position;
+            profile.record_section(section, self.instant.elapsed(), bytes);
+        }
+    }
+}
+
+/// Skip an element deselected via `--only`, including all its children
+fn skip_catalog_or_section<R: Read + BufRead>(context: &mut ProcessingContext<'_, R>) {
+    skip_rest_of_element_raw(context.reader);
+    context.path_stack.pop();
+}
+
 fn write_skeleton_file<R: Read + BufRead>(context: &ProcessingContext<'_, R>) -> Result<(), Error> {
     let skeleton_dir_path = build_out_dir_path(context, None)?;
     let skeleton_file_path = skeleton_dir_path.join("skeleton.xml");
@@ -274,7 +547,8 @@ fn write_skeleton_file<R: Read + BufRead>(context: &ProcessingContext<'_, R>) ->
         &context.skeleton.content,
         0,
         context.flags,
-    );
+        context.run.writer.as_ref(),
+    )?;
     Ok(())
 }
 
@@ -0,0 +1,76 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Error, Result};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::utils::file_utils::collect_files;
+
+/// Pack all files below the staging directory into a zip archive, using paths relative to the staging directory
+pub fn write_zip(staging_dir: &Path, zip_file_path: &Path) -> Result<usize, Error> {
+    let mut file_paths = Vec::new();
+    collect_files(staging_dir, &mut file_paths);
+    file_paths.sort();
+
+    if let Some(parent) = zip_file_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Error creating directory {}", parent.display()))?;
+        }
+    }
+    let zip_file = File::create(zip_file_path)
+        .with_context(|| format!("Error creating file {}", zip_file_path.display()))?;
+    let mut zip = ZipWriter::new(BufWriter::new(zip_file));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file_path in &file_paths {
+        let entry_name = file_path
+            .strip_prefix(staging_dir)
+            .unwrap_or(file_path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let content = fs::read(file_path)
+            .with_context(|| format!("Error reading file {}", file_path.display()))?;
+        zip.start_file(entry_name, options)?;
+        zip.write_all(&content)?;
+    }
+
+    zip.finish()?.flush()?;
+    Ok(file_paths.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::utils::file_utils::ScratchDir;
+
+    #[test]
+    fn test_write_zip() {
+        let base_dir = ScratchDir::create("zip-test").unwrap();
+        let staging_dir = base_dir.path.join("staging");
+        fs::create_dir_all(staging_dir.join("Db").join("scripts")).unwrap();
+        fs::write(
+            staging_dir.join("Db").join("scripts").join("A - ID 1.xml"),
+            "<Script></Script>\n",
+        )
+        .unwrap();
+
+        let zip_file_path = base_dir.path.join("out.zip");
+        assert_eq!(write_zip(&staging_dir, &zip_file_path).unwrap(), 1);
+
+        let mut archive = zip::ZipArchive::new(File::open(&zip_file_path).unwrap()).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name("Db/scripts/A - ID 1.xml")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "<Script></Script>\n");
+    }
+}